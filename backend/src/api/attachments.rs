@@ -0,0 +1,94 @@
+use crate::api::blocking;
+use crate::domain::services::filament_service::FilamentService;
+use crate::infrastructure::attachments::{image_content_type, thumbnail};
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+
+// Phone photos are much bigger than the default 256 KiB payload limit
+const MAX_ATTACHMENT_BYTES: usize = 32 * 1024 * 1024;
+
+// Expects `web::Data<FilamentService>` configured `with_attachments`
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("/api/rolls/{id}/attachments")
+            .app_data(web::PayloadConfig::new(MAX_ATTACHMENT_BYTES))
+            .route(web::post().to(upload)),
+    )
+    .route(
+        "/api/rolls/{id}/attachments/{attachment_id}",
+        web::get().to(download),
+    )
+    .route(
+        "/api/rolls/{id}/attachments/{attachment_id}",
+        web::delete().to(remove),
+    )
+    .route(
+        "/api/rolls/{id}/attachments/{attachment_id}/thumbnail",
+        web::get().to(download_thumbnail),
+    );
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UploadQuery {
+    pub file_name: String,
+}
+
+// Takes the image as the raw request body, e.g.
+// `POST /api/rolls/{id}/attachments?file_name=label.jpg`
+async fn upload(
+    service: web::Data<FilamentService>,
+    path: web::Path<String>,
+    query: web::Query<UploadQuery>,
+    body: web::Bytes,
+) -> Result<HttpResponse, actix_web::Error> {
+    let roll_id = path.into_inner();
+    let file_name = query.into_inner().file_name;
+    let attachment = blocking(move || {
+        let content_type = image_content_type(&body)?;
+        let thumbnail = thumbnail(&body)?;
+        service.add_attachment(&roll_id, &file_name, content_type, &body, &thumbnail)
+    })
+    .await?;
+
+    Ok(HttpResponse::Created().json(attachment))
+}
+
+async fn download(
+    service: web::Data<FilamentService>,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (roll_id, attachment_id) = path.into_inner();
+    let (attachment, content) =
+        blocking(move || service.attachment_content(&roll_id, &attachment_id)).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(attachment.content_type())
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Inline,
+            parameters: vec![DispositionParam::Filename(
+                attachment.file_name().to_string(),
+            )],
+        })
+        .body(content))
+}
+
+async fn download_thumbnail(
+    service: web::Data<FilamentService>,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (roll_id, attachment_id) = path.into_inner();
+    let png = blocking(move || service.attachment_thumbnail(&roll_id, &attachment_id)).await?;
+
+    Ok(HttpResponse::Ok().content_type("image/png").body(png))
+}
+
+async fn remove(
+    service: web::Data<FilamentService>,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (roll_id, attachment_id) = path.into_inner();
+    let roll = blocking(move || service.remove_attachment(&roll_id, &attachment_id)).await?;
+
+    Ok(HttpResponse::Ok().json(roll))
+}
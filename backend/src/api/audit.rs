@@ -0,0 +1,27 @@
+use crate::api::blocking;
+use crate::domain::audit::AuditLog;
+use actix_web::{web, HttpResponse};
+
+// Expects `web::Data<dyn AuditLog>`
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.route("/api/audit", web::get().to(list_entries))
+        .route("/api/rolls/{id}/audit", web::get().to(roll_entries));
+}
+
+// Every recorded change, oldest first
+async fn list_entries(
+    audit_log: web::Data<dyn AuditLog>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let entries = blocking(move || audit_log.find_all()).await?;
+    Ok(HttpResponse::Ok().json(entries))
+}
+
+// A roll's history, oldest first. Deleted rolls keep theirs.
+async fn roll_entries(
+    audit_log: web::Data<dyn AuditLog>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let roll_id = path.into_inner();
+    let entries = blocking(move || audit_log.find_by_roll(&roll_id)).await?;
+    Ok(HttpResponse::Ok().json(entries))
+}
@@ -0,0 +1,25 @@
+use crate::domain::error::FilamentError;
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct ErrorBody {
+    message: String,
+}
+
+impl ResponseError for FilamentError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            FilamentError::NotFound(_) | FilamentError::Missing(_) => StatusCode::NOT_FOUND,
+            FilamentError::InvalidData(_) => StatusCode::BAD_REQUEST,
+            FilamentError::RepositoryError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).json(ErrorBody {
+            message: self.to_string(),
+        })
+    }
+}
@@ -0,0 +1,59 @@
+use crate::api::blocking;
+use crate::domain::feasibility::FilamentRequirement;
+use crate::domain::services::filament_service::FilamentService;
+use crate::infrastructure::slicer::{parse_3mf, parse_gcode};
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+use std::io::Cursor;
+
+// Sliced files are much bigger than the default 256 KiB payload limit
+const MAX_UPLOAD_BYTES: usize = 256 * 1024 * 1024;
+
+// 3MF files are zip archives
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+// Expects `web::Data<FilamentService>` to be registered on the app
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/feasibility")
+            .route("", web::post().to(check))
+            .service(
+                web::resource("/upload")
+                    .app_data(web::PayloadConfig::new(MAX_UPLOAD_BYTES))
+                    .route(web::post().to(check_upload)),
+            ),
+    );
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FeasibilityRequest {
+    pub requirements: Vec<FilamentRequirement>,
+}
+
+async fn check(
+    service: web::Data<FilamentService>,
+    body: web::Json<FeasibilityRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let requirements = body.into_inner().requirements;
+    let report = blocking(move || service.check_feasibility(&requirements)).await?;
+
+    Ok(HttpResponse::Ok().json(report))
+}
+
+// Takes a G-code or 3MF file as the raw request body
+async fn check_upload(
+    service: web::Data<FilamentService>,
+    body: web::Bytes,
+) -> Result<HttpResponse, actix_web::Error> {
+    let report = blocking(move || {
+        let requirements = if body.starts_with(ZIP_MAGIC) {
+            parse_3mf(Cursor::new(body))?
+        } else {
+            parse_gcode(body.as_ref())?
+        };
+        service.check_feasibility(&requirements)
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(report))
+}
@@ -0,0 +1,121 @@
+use crate::api::blocking;
+use crate::domain::error::FilamentError;
+use crate::domain::filament::FilamentRepository;
+use crate::infrastructure::import::ImportMode;
+use crate::infrastructure::inventory_csv::{self, ColumnMapping, InventoryField};
+use crate::infrastructure::spoolman::import::{import_csv, import_json};
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+// Exports of a few thousand spools stay well below this
+const MAX_IMPORT_BYTES: usize = 16 * 1024 * 1024;
+
+// Bulk moves of rolls into and out of the inventory. Expects
+// `web::Data<dyn FilamentRepository>` to be registered on the app.
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("/api/import/spoolman")
+            .app_data(web::PayloadConfig::new(MAX_IMPORT_BYTES))
+            .route(web::post().to(import_spoolman)),
+    )
+    .service(
+        web::resource("/api/inventory/import")
+            .app_data(web::PayloadConfig::new(MAX_IMPORT_BYTES))
+            .route(web::post().to(import_inventory)),
+    )
+    .route("/api/inventory/export", web::get().to(export_inventory));
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SpoolmanImportQuery {
+    // json (default) or csv
+    #[serde(default)]
+    pub format: Option<String>,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+// Body is a Spoolman export as is. Rows that fail validation are reported
+// rather than stopping the import.
+async fn import_spoolman(
+    repository: web::Data<dyn FilamentRepository>,
+    query: web::Query<SpoolmanImportQuery>,
+    body: String,
+) -> Result<HttpResponse, actix_web::Error> {
+    let SpoolmanImportQuery { format, dry_run } = query.into_inner();
+    let mode = if dry_run {
+        ImportMode::DryRun
+    } else {
+        ImportMode::Apply
+    };
+    let report = blocking(move || match format.as_deref().unwrap_or("json") {
+        "json" => import_json(repository.as_ref(), &body, mode),
+        "csv" => import_csv(repository.as_ref(), body.as_bytes(), mode),
+        other => Err(FilamentError::InvalidData(format!(
+            "Unknown import format '{}'; expected json or csv",
+            other
+        ))),
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(report))
+}
+
+async fn export_inventory(
+    repository: web::Data<dyn FilamentRepository>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let csv = blocking(move || {
+        let mut csv = Vec::new();
+        inventory_csv::export_csv(repository.as_ref(), &mut csv)?;
+        Ok(csv)
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/csv")
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename("inventory.csv".to_string())],
+        })
+        .body(csv))
+}
+
+// Besides `dry_run`, each query parameter maps a field to the spreadsheet
+// column holding it, e.g. `?remaining_weight=Grams left`
+async fn import_inventory(
+    repository: web::Data<dyn FilamentRepository>,
+    query: web::Query<HashMap<String, String>>,
+    body: web::Bytes,
+) -> Result<HttpResponse, actix_web::Error> {
+    let mut mode = ImportMode::Apply;
+    let mut mapping = ColumnMapping::new();
+    for (name, value) in query.into_inner() {
+        if name == "dry_run" {
+            mode = match value.as_str() {
+                "true" => ImportMode::DryRun,
+                "false" => ImportMode::Apply,
+                _ => {
+                    return Err(FilamentError::InvalidData(format!(
+                        "Invalid dry_run '{}'; expected true or false",
+                        value
+                    ))
+                    .into())
+                }
+            };
+            continue;
+        }
+        let field = InventoryField::from_header(&name).ok_or_else(|| {
+            FilamentError::InvalidData(format!("Unknown inventory field '{}'", name))
+        })?;
+        mapping = mapping.map(field, &value);
+    }
+
+    let report = blocking(move || {
+        inventory_csv::import_csv(repository.as_ref(), body.as_ref(), &mapping, mode)
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(report))
+}
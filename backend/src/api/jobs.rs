@@ -0,0 +1,104 @@
+use crate::api::blocking;
+use crate::domain::error::FilamentError;
+use crate::domain::print_job::PrintStatus;
+use crate::domain::services::print_job_service::PrintJobService;
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+
+// Expects `web::Data<PrintJobService>` to be registered on the app
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/jobs")
+            .route("", web::get().to(list_jobs))
+            .route("", web::post().to(start_job))
+            .route("/{id}", web::get().to(get_job))
+            .route("/{id}/finish", web::post().to(finish_job)),
+    )
+    .route("/api/reports/waste", web::get().to(waste_report));
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Grams {
+    pub roll_id: String,
+    pub grams: f32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StartJobRequest {
+    pub file_name: String,
+    pub printer_id: String,
+    #[serde(default)]
+    pub planned: Vec<Grams>,
+}
+
+// Either measured usage per roll or how far the print got
+#[derive(Debug, Deserialize)]
+pub struct FinishJobRequest {
+    pub status: PrintStatus,
+    pub actual: Option<Vec<Grams>>,
+    pub percent_complete: Option<f32>,
+}
+
+fn pairs(grams: Vec<Grams>) -> Vec<(String, f32)> {
+    grams.into_iter().map(|g| (g.roll_id, g.grams)).collect()
+}
+
+async fn list_jobs(service: web::Data<PrintJobService>) -> Result<HttpResponse, actix_web::Error> {
+    let jobs = blocking(move || service.list_jobs()).await?;
+
+    Ok(HttpResponse::Ok().json(jobs))
+}
+
+async fn start_job(
+    service: web::Data<PrintJobService>,
+    body: web::Json<StartJobRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let request = body.into_inner();
+    let job = blocking(move || {
+        service.start_job(
+            &request.file_name,
+            &request.printer_id,
+            &pairs(request.planned),
+        )
+    })
+    .await?;
+
+    Ok(HttpResponse::Created().json(job))
+}
+
+async fn get_job(
+    service: web::Data<PrintJobService>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let id = path.into_inner();
+    let job = blocking(move || service.get_job(&id)).await?;
+
+    Ok(HttpResponse::Ok().json(job))
+}
+
+async fn finish_job(
+    service: web::Data<PrintJobService>,
+    path: web::Path<String>,
+    body: web::Json<FinishJobRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let id = path.into_inner();
+    let request = body.into_inner();
+    let job = blocking(move || match (request.actual, request.percent_complete) {
+        (Some(actual), None) => service.finish_job(&id, request.status, &pairs(actual)),
+        (None, Some(percent)) => service.finish_job_at_progress(&id, request.status, percent),
+        _ => Err(FilamentError::InvalidData(
+            "Specify exactly one of actual or percent_complete".to_string(),
+        )),
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(job))
+}
+
+async fn waste_report(
+    service: web::Data<PrintJobService>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let waste = blocking(move || service.waste_by_material()).await?;
+
+    Ok(HttpResponse::Ok().json(waste))
+}
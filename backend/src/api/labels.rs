@@ -0,0 +1,260 @@
+use crate::api::blocking;
+use crate::domain::error::FilamentError;
+use crate::domain::roll_filter::RollFilter;
+use crate::domain::services::filament_service::FilamentService;
+use crate::infrastructure::labels::raster::{render_bitmap, Bitmap, DEFAULT_DPI};
+use crate::infrastructure::labels::sheet::{render_sheets, SheetTemplate};
+use crate::infrastructure::labels::svg::render_svg;
+use crate::infrastructure::labels::thermal::{send_raw, ThermalFormat};
+use crate::infrastructure::labels::{LabelLayout, LabelSettings, LabelSize};
+use actix_web::{web, HttpResponse};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+const PRINTER_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Every label is rendered in memory before anything is returned or sent
+pub const MAX_LABELS: usize = 500;
+
+// Expects `web::Data<FilamentService>` and `web::Data<LabelSettings>`
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.route("/api/rolls/{id}/label.svg", web::get().to(svg_label))
+        .route("/api/rolls/{id}/label.png", web::get().to(png_label))
+        // zpl, escpos or brother
+        .route(
+            "/api/rolls/{id}/label.{format}",
+            web::get().to(thermal_label),
+        )
+        .service(
+            web::scope("/api/labels")
+                .route("", web::post().to(thermal_batch))
+                .route("/print", web::post().to(print_batch))
+                .route("/sheet", web::post().to(sheet)),
+        );
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LabelQuery {
+    // `WIDTHxHEIGHT` in mm, 62x29 by default
+    pub size: Option<String>,
+    pub dpi: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchLabelRequest {
+    pub roll_ids: Vec<String>,
+    pub format: ThermalFormat,
+    pub size: Option<String>,
+    // Defaults to the format's usual resolution
+    pub dpi: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PrintLabelsRequest {
+    #[serde(flatten)]
+    pub labels: BatchLabelRequest,
+    // Id of a printer in `LabelSettings::printers`
+    pub printer: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct PrintedLabels {
+    pub labels: usize,
+    pub bytes: usize,
+}
+
+// An Avery product code such as `L7160`, or a custom grid
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum SheetChoice {
+    Avery(String),
+    Custom(SheetTemplate),
+}
+
+// Labels for either the listed rolls or every roll matching `filter`
+#[derive(Debug, Deserialize)]
+pub struct SheetRequest {
+    #[serde(default)]
+    pub roll_ids: Vec<String>,
+    pub filter: Option<RollFilter>,
+    pub template: SheetChoice,
+    // Slots already used on the first sheet
+    #[serde(default)]
+    pub skip: usize,
+}
+
+fn layout(
+    service: &FilamentService,
+    settings: &LabelSettings,
+    roll_id: &str,
+    size: Option<&str>,
+) -> Result<LabelLayout, FilamentError> {
+    let size = match size {
+        Some(size) => LabelSize::parse(size)?,
+        None => LabelSize::BROTHER_62,
+    };
+    let roll = service.get_roll(roll_id)?;
+    LabelLayout::for_roll(&roll, size, settings.base_url.as_deref())
+}
+
+fn check_label_count(count: usize) -> Result<(), FilamentError> {
+    if count > MAX_LABELS {
+        return Err(FilamentError::InvalidData(format!(
+            "At most {} labels can be made at once, got {}",
+            MAX_LABELS, count
+        )));
+    }
+    Ok(())
+}
+
+fn thermal_job(
+    service: &FilamentService,
+    settings: &LabelSettings,
+    request: &BatchLabelRequest,
+) -> Result<Vec<u8>, FilamentError> {
+    check_label_count(request.roll_ids.len())?;
+    let dpi = request.dpi.unwrap_or(request.format.default_dpi());
+    let bitmaps = request
+        .roll_ids
+        .iter()
+        .map(|id| {
+            render_bitmap(
+                &layout(service, settings, id, request.size.as_deref())?,
+                dpi,
+            )
+        })
+        .collect::<Result<Vec<Bitmap>, FilamentError>>()?;
+    request.format.encode(&bitmaps)
+}
+
+async fn svg_label(
+    service: web::Data<FilamentService>,
+    settings: web::Data<LabelSettings>,
+    path: web::Path<String>,
+    query: web::Query<LabelQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let roll_id = path.into_inner();
+    let query = query.into_inner();
+    let svg = blocking(move || {
+        let layout = layout(&service, &settings, &roll_id, query.size.as_deref())?;
+        Ok(render_svg(&layout))
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().content_type("image/svg+xml").body(svg))
+}
+
+async fn png_label(
+    service: web::Data<FilamentService>,
+    settings: web::Data<LabelSettings>,
+    path: web::Path<String>,
+    query: web::Query<LabelQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let roll_id = path.into_inner();
+    let query = query.into_inner();
+    let png = blocking(move || {
+        let layout = layout(&service, &settings, &roll_id, query.size.as_deref())?;
+        render_bitmap(&layout, query.dpi.unwrap_or(DEFAULT_DPI))?.to_png()
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().content_type("image/png").body(png))
+}
+
+async fn thermal_label(
+    service: web::Data<FilamentService>,
+    settings: web::Data<LabelSettings>,
+    path: web::Path<(String, String)>,
+    query: web::Query<LabelQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (roll_id, format) = path.into_inner();
+    let format: ThermalFormat = format.parse()?;
+    let query = query.into_inner();
+    let request = BatchLabelRequest {
+        roll_ids: vec![roll_id],
+        format,
+        size: query.size,
+        dpi: query.dpi,
+    };
+    let job = blocking(move || thermal_job(&service, &settings, &request)).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .body(job))
+}
+
+async fn thermal_batch(
+    service: web::Data<FilamentService>,
+    settings: web::Data<LabelSettings>,
+    body: web::Json<BatchLabelRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let request = body.into_inner();
+    let content_type = request.format.content_type();
+    let job = blocking(move || thermal_job(&service, &settings, &request)).await?;
+
+    Ok(HttpResponse::Ok().content_type(content_type).body(job))
+}
+
+async fn print_batch(
+    service: web::Data<FilamentService>,
+    settings: web::Data<LabelSettings>,
+    body: web::Json<PrintLabelsRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let request = body.into_inner();
+    let printed = blocking(move || {
+        // Only printers configured on the server can be reached
+        let address = settings.printer_address(&request.printer)?;
+        let job = thermal_job(&service, &settings, &request.labels)?;
+        send_raw(address, &job, PRINTER_TIMEOUT)?;
+        Ok(PrintedLabels {
+            labels: request.labels.roll_ids.len(),
+            bytes: job.len(),
+        })
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(printed))
+}
+
+async fn sheet(
+    service: web::Data<FilamentService>,
+    settings: web::Data<LabelSettings>,
+    body: web::Json<SheetRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let request = body.into_inner();
+    let pdf = blocking(move || {
+        let template = match request.template {
+            SheetChoice::Avery(code) => SheetTemplate::avery(&code)?,
+            SheetChoice::Custom(template) => template,
+        };
+        let size = template.label_size()?;
+
+        check_label_count(request.roll_ids.len())?;
+        let rolls = match (request.roll_ids.is_empty(), request.filter) {
+            (false, None) => request
+                .roll_ids
+                .iter()
+                .map(|id| service.get_roll(id))
+                .collect::<Result<Vec<_>, _>>()?,
+            (true, Some(filter)) => service.find_rolls(&filter)?,
+            _ => {
+                return Err(FilamentError::InvalidData(
+                    "Give either roll_ids or a filter".to_string(),
+                ))
+            }
+        };
+        check_label_count(rolls.len())?;
+        let layouts = rolls
+            .iter()
+            .map(|roll| LabelLayout::for_roll(roll, size, settings.base_url.as_deref()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        render_sheets(&layouts, &template, request.skip)
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/pdf")
+        .insert_header(("Content-Disposition", "attachment; filename=\"labels.pdf\""))
+        .body(pdf))
+}
@@ -0,0 +1,129 @@
+use crate::api::blocking;
+use crate::domain::error::FilamentError;
+use crate::domain::filament::FilamentRoll;
+use crate::domain::lot::DefectiveLot;
+use crate::domain::services::filament_service::FilamentService;
+use actix_web::{web, HttpResponse};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+// Expects `web::Data<FilamentService>` configured `with_defective_lots`
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.route("/api/rolls/{id}/lot", web::put().to(set_lot))
+        .service(
+            web::scope("/api/lots")
+                .route("/defective", web::get().to(defective_lots))
+                .route("/{lot_number}/rolls", web::get().to(lot_rolls))
+                .route("/{lot_number}/defective", web::put().to(mark_defective))
+                .route("/{lot_number}/defective", web::delete().to(clear_defective)),
+        );
+}
+
+// A roll with the flag on its lot, if any
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct LotRoll {
+    #[serde(flatten)]
+    pub roll: FilamentRoll,
+    pub defective_lot: Option<DefectiveLot>,
+}
+
+// Pairs each roll with the defective lot covering it, keeping the order
+pub(crate) fn flag_defective(
+    service: &FilamentService,
+    rolls: Vec<FilamentRoll>,
+) -> Result<Vec<LotRoll>, FilamentError> {
+    let defective_lots = service.defective_lots()?;
+    Ok(rolls
+        .into_iter()
+        .map(|roll| LotRoll {
+            defective_lot: defective_lots.iter().find(|lot| lot.covers(&roll)).cloned(),
+            roll,
+        })
+        .collect())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetLotRequest {
+    #[serde(default)]
+    pub lot_number: String,
+    pub manufactured_on: Option<NaiveDate>,
+    pub received_on: Option<NaiveDate>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MarkDefectiveRequest {
+    pub manufacturer: String,
+    #[serde(default)]
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LotManufacturer {
+    pub manufacturer: String,
+}
+
+async fn set_lot(
+    service: web::Data<FilamentService>,
+    path: web::Path<String>,
+    body: web::Json<SetLotRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let roll_id = path.into_inner();
+    let request = body.into_inner();
+    let roll = blocking(move || {
+        service.set_lot(
+            &roll_id,
+            &request.lot_number,
+            request.manufactured_on,
+            request.received_on,
+        )
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(roll))
+}
+
+async fn defective_lots(
+    service: web::Data<FilamentService>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let lots = blocking(move || service.defective_lots()).await?;
+
+    Ok(HttpResponse::Ok().json(lots))
+}
+
+async fn lot_rolls(
+    service: web::Data<FilamentService>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let lot_number = path.into_inner();
+    let rolls =
+        blocking(move || flag_defective(&service, service.rolls_in_lot(&lot_number)?)).await?;
+
+    Ok(HttpResponse::Ok().json(rolls))
+}
+
+async fn mark_defective(
+    service: web::Data<FilamentService>,
+    path: web::Path<String>,
+    body: web::Json<MarkDefectiveRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let lot_number = path.into_inner();
+    let request = body.into_inner();
+    let lot = blocking(move || {
+        service.mark_lot_defective(&request.manufacturer, &lot_number, &request.reason)
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(lot))
+}
+
+async fn clear_defective(
+    service: web::Data<FilamentService>,
+    path: web::Path<String>,
+    query: web::Query<LotManufacturer>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let lot_number = path.into_inner();
+    let manufacturer = query.into_inner().manufacturer;
+    blocking(move || service.clear_defective_lot(&manufacturer, &lot_number)).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
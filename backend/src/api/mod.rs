@@ -0,0 +1,28 @@
+use crate::domain::error::FilamentError;
+use actix_web::web;
+
+pub mod attachments;
+pub mod audit;
+pub mod error;
+pub mod feasibility;
+pub mod inventory;
+pub mod jobs;
+pub mod labels;
+pub mod lots;
+pub mod octoprint;
+pub mod printers;
+pub mod products;
+pub mod reservations;
+pub mod rolls;
+pub mod scan;
+pub mod spoolman;
+pub mod tags;
+
+// Runs a synchronous service call on the blocking thread pool
+pub(crate) async fn blocking<T, F>(operation: F) -> Result<T, actix_web::Error>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, FilamentError> + Send + 'static,
+{
+    web::block(operation).await?.map_err(Into::into)
+}
@@ -0,0 +1,257 @@
+use crate::api::blocking;
+use crate::domain::assignment::RollAssignments;
+use crate::domain::error::FilamentError;
+use crate::domain::filament::FilamentRoll;
+use crate::domain::lot::DefectiveLot;
+use crate::domain::material::{density_for, length_to_grams};
+use crate::domain::printer::DIAMETER_TOLERANCE;
+use crate::domain::services::filament_service::FilamentService;
+use crate::domain::unit_of_work::UnitOfWork;
+use actix_web::{web, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+// Most tools listed for a printer whose tool count is not known
+const MAX_TOOLS: u32 = 16;
+
+// Routes for the OctoPrint plugin. Expects `web::Data<FilamentService>` and
+// `web::Data<dyn RollAssignments>` to be registered on the app.
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/octoprint")
+            .route("/rolls", web::get().to(compatible_rolls))
+            .route("/printers/{printer}/tools", web::get().to(active_rolls))
+            .route(
+                "/printers/{printer}/tools/{tool}",
+                web::put().to(select_roll),
+            )
+            .route(
+                "/printers/{printer}/tools/{tool}",
+                web::delete().to(clear_roll),
+            )
+            .route("/printers/{printer}/usage", web::post().to(report_usage)),
+    );
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct RollSummary {
+    pub id: String,
+    pub name: String,
+    pub material: String,
+    pub color: String,
+    pub diameter: f32,
+    pub manufacturer: String,
+    pub remaining_weight: f32,
+    pub percentage_remaining: f32,
+    pub storage_location: String,
+    pub lot_number: String,
+    pub tags: Vec<String>,
+    // Set when the roll's lot has been flagged; the plugin warns before printing
+    pub defective_lot: bool,
+}
+
+impl From<&FilamentRoll> for RollSummary {
+    fn from(roll: &FilamentRoll) -> Self {
+        RollSummary {
+            id: roll.id().to_string(),
+            name: roll.name().to_string(),
+            material: roll.material().to_string(),
+            color: roll.color().to_string(),
+            diameter: roll.diameter(),
+            manufacturer: roll.manufacturer().to_string(),
+            remaining_weight: roll.remaining_weight(),
+            percentage_remaining: roll.percentage_remaining(),
+            storage_location: roll.storage_location().to_string(),
+            lot_number: roll.lot_number().to_string(),
+            tags: roll.tags().to_vec(),
+            defective_lot: false,
+        }
+    }
+}
+
+impl RollSummary {
+    fn flagged(roll: &FilamentRoll, defective_lots: &[DefectiveLot]) -> Self {
+        RollSummary {
+            defective_lot: defective_lots.iter().any(|lot| lot.covers(roll)),
+            ..RollSummary::from(roll)
+        }
+    }
+}
+
+// Filament properties of the printer profile loaded in OctoPrint
+#[derive(Debug, Deserialize)]
+pub struct ProfileQuery {
+    pub diameter: f32,
+    pub material: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ToolsQuery {
+    // How many tools to list, from tool 0
+    pub tools: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct ToolSelection {
+    pub tool: u32,
+    pub roll: Option<RollSummary>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SelectRollRequest {
+    pub roll_id: String,
+}
+
+// What one tool used during a print; OctoPrint reports length, some slicers grams
+#[derive(Debug, Deserialize)]
+pub struct ToolUsage {
+    pub tool: u32,
+    pub grams: Option<f32>,
+    pub length_mm: Option<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UsageReport {
+    pub tools: Vec<ToolUsage>,
+}
+
+async fn compatible_rolls(
+    service: web::Data<FilamentService>,
+    query: web::Query<ProfileQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let profile = query.into_inner();
+    let rolls = blocking(move || {
+        let defective_lots = service.defective_lots()?;
+        let mut rolls: Vec<RollSummary> = service
+            .list_rolls()?
+            .iter()
+            .filter(|roll| (roll.diameter() - profile.diameter).abs() < DIAMETER_TOLERANCE)
+            .filter(|roll| {
+                profile
+                    .material
+                    .as_ref()
+                    .is_none_or(|m| roll.material().eq_ignore_ascii_case(m))
+            })
+            .filter(|roll| roll.remaining_weight() > 0.0)
+            .map(|roll| RollSummary::flagged(roll, &defective_lots))
+            .collect();
+        rolls.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+        Ok(rolls)
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(rolls))
+}
+
+// Selected rolls for the first `tools` tools (default 1), so single-tool
+// printers need no query. Never lists more tools than the printer has.
+async fn active_rolls(
+    service: web::Data<FilamentService>,
+    assignments: web::Data<dyn RollAssignments>,
+    path: web::Path<String>,
+    query: web::Query<ToolsQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let printer = path.into_inner();
+    let requested = query.into_inner().tools.unwrap_or(1);
+    let selections = blocking(move || {
+        let tools = requested.min(assignments.tool_count(&printer)?.unwrap_or(MAX_TOOLS));
+        let defective_lots = service.defective_lots()?;
+        (0..tools)
+            .map(|tool| {
+                let roll = match assignments.loaded_roll(&printer, tool)? {
+                    Some(roll_id) => Some(RollSummary::flagged(
+                        &service.get_roll(&roll_id)?,
+                        &defective_lots,
+                    )),
+                    None => None,
+                };
+                Ok(ToolSelection { tool, roll })
+            })
+            .collect::<Result<Vec<_>, FilamentError>>()
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(selections))
+}
+
+async fn select_roll(
+    service: web::Data<FilamentService>,
+    assignments: web::Data<dyn RollAssignments>,
+    path: web::Path<(String, u32)>,
+    body: web::Json<SelectRollRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (printer, tool) = path.into_inner();
+    let roll_id = body.into_inner().roll_id;
+    let selection = blocking(move || {
+        let roll = service.get_roll(&roll_id)?;
+        assignments.load_roll(&printer, tool, roll.id())?;
+        Ok(ToolSelection {
+            tool,
+            roll: Some(RollSummary::flagged(&roll, &service.defective_lots()?)),
+        })
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(selection))
+}
+
+async fn clear_roll(
+    assignments: web::Data<dyn RollAssignments>,
+    path: web::Path<(String, u32)>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (printer, tool) = path.into_inner();
+    blocking(move || assignments.unload(&printer, tool)).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+// Deducts usage at print end as one unit of work, so one over-consuming tool
+// rejects the whole report and nothing is half applied
+async fn report_usage(
+    service: web::Data<FilamentService>,
+    assignments: web::Data<dyn RollAssignments>,
+    path: web::Path<String>,
+    body: web::Json<UsageReport>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let printer = path.into_inner();
+    let report = body.into_inner();
+    let updated = blocking(move || {
+        let mut unit = UnitOfWork::new();
+
+        for usage in &report.tools {
+            let roll_id = assignments
+                .loaded_roll(&printer, usage.tool)?
+                .ok_or_else(|| {
+                    FilamentError::InvalidData(format!(
+                        "No roll selected for tool {} on printer '{}'",
+                        usage.tool, printer
+                    ))
+                })?;
+
+            let grams = match (usage.grams, usage.length_mm) {
+                (Some(grams), None) => grams,
+                (None, Some(length)) => {
+                    let roll = service.get_roll(&roll_id)?;
+                    length_to_grams(length, roll.diameter(), density_for(roll.material()))
+                }
+                _ => {
+                    return Err(FilamentError::InvalidData(format!(
+                        "Tool {}: specify exactly one of grams or length_mm",
+                        usage.tool
+                    )))
+                }
+            };
+
+            unit = unit.consume(&roll_id, grams);
+        }
+
+        let defective_lots = service.defective_lots()?;
+        Ok(service
+            .commit(&unit)?
+            .iter()
+            .map(|roll| RollSummary::flagged(roll, &defective_lots))
+            .collect::<Vec<_>>())
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(updated))
+}
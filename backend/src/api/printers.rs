@@ -0,0 +1,100 @@
+use crate::api::blocking;
+use crate::domain::error::FilamentError;
+use crate::domain::printer::{Printer, Toolhead};
+use crate::domain::services::printer_service::PrinterService;
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+
+// Expects `web::Data<PrinterService>` to be registered on the app
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/printers")
+            .route("", web::get().to(list_printers))
+            .route("", web::post().to(register_printer))
+            .route("/{id}", web::get().to(get_printer))
+            .route("/{id}/tools/{tool}", web::put().to(load_roll))
+            .route("/{id}/tools/{tool}", web::delete().to(unload_roll)),
+    );
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewToolhead {
+    pub name: String,
+    pub filament_diameter: f32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewPrinter {
+    pub id: Option<String>,
+    pub name: String,
+    pub toolheads: Vec<NewToolhead>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LoadRollRequest {
+    pub roll_id: String,
+}
+
+async fn list_printers(
+    service: web::Data<PrinterService>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let mut printers = blocking(move || service.list_printers()).await?;
+    printers.sort_by(|a, b| a.id().cmp(b.id()));
+
+    Ok(HttpResponse::Ok().json(printers))
+}
+
+async fn register_printer(
+    service: web::Data<PrinterService>,
+    body: web::Json<NewPrinter>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let request = body.into_inner();
+    let printer = blocking(move || {
+        let toolheads = request
+            .toolheads
+            .iter()
+            .map(|t| Toolhead::new(&t.name, t.filament_diameter))
+            .collect::<Result<Vec<_>, FilamentError>>()?;
+        let printer = match &request.id {
+            Some(id) => Printer::with_id(id, &request.name, toolheads)?,
+            None => Printer::new(&request.name, toolheads)?,
+        };
+        service.register_printer(&printer)?;
+        Ok(printer)
+    })
+    .await?;
+
+    Ok(HttpResponse::Created().json(printer))
+}
+
+async fn get_printer(
+    service: web::Data<PrinterService>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let id = path.into_inner();
+    let printer = blocking(move || service.get_printer(&id)).await?;
+
+    Ok(HttpResponse::Ok().json(printer))
+}
+
+async fn load_roll(
+    service: web::Data<PrinterService>,
+    path: web::Path<(String, u32)>,
+    body: web::Json<LoadRollRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (id, tool) = path.into_inner();
+    let roll_id = body.into_inner().roll_id;
+    let printer = blocking(move || service.load_roll(&id, tool, &roll_id)).await?;
+
+    Ok(HttpResponse::Ok().json(printer))
+}
+
+async fn unload_roll(
+    service: web::Data<PrinterService>,
+    path: web::Path<(String, u32)>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (id, tool) = path.into_inner();
+    let printer = blocking(move || service.unload_roll(&id, tool)).await?;
+
+    Ok(HttpResponse::Ok().json(printer))
+}
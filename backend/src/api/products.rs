@@ -0,0 +1,140 @@
+use crate::api::blocking;
+use crate::domain::error::FilamentError;
+use crate::domain::product::{Product, ProductCatalogue};
+use crate::domain::services::product_service::ProductService;
+use crate::infrastructure::import::ImportMode;
+use crate::infrastructure::spoolman::catalogue::import_products;
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+
+// The compiled SpoolmanDB is several megabytes
+const MAX_IMPORT_BYTES: usize = 64 * 1024 * 1024;
+
+// Expects `web::Data<ProductService>`, and `web::Data<dyn ProductCatalogue>`
+// for imports
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/products")
+            .route("", web::get().to(list_products))
+            .route("", web::post().to(add_product))
+            .service(
+                web::resource("/import")
+                    .app_data(web::PayloadConfig::new(MAX_IMPORT_BYTES))
+                    .route(web::post().to(import)),
+            )
+            .route("/{id}", web::get().to(get_product))
+            .route("/{id}/rolls", web::post().to(create_roll)),
+    );
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewProduct {
+    pub manufacturer: String,
+    pub product_line: Option<String>,
+    pub name: String,
+    pub material: String,
+    pub color: String,
+    pub diameter: f32,
+    pub net_weight: f32,
+    pub spool_weight: Option<f32>,
+    pub ean: Option<String>,
+}
+
+impl NewProduct {
+    fn into_product(self) -> Result<Product, FilamentError> {
+        let mut product = Product::new(
+            &self.manufacturer,
+            &self.name,
+            &self.material,
+            &self.color,
+            self.diameter,
+            self.net_weight,
+        )?;
+        if let Some(product_line) = &self.product_line {
+            product = product.with_product_line(product_line);
+        }
+        if let Some(spool_weight) = self.spool_weight {
+            product = product.with_spool_weight(spool_weight)?;
+        }
+        if let Some(ean) = &self.ean {
+            product = product.with_ean(ean)?;
+        }
+        Ok(product)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportQuery {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewRollRequest {
+    #[serde(default)]
+    pub storage_location: String,
+    // Full when left out
+    pub remaining_weight: Option<f32>,
+}
+
+async fn list_products(
+    service: web::Data<ProductService>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let products = blocking(move || service.list_products()).await?;
+
+    Ok(HttpResponse::Ok().json(products))
+}
+
+async fn get_product(
+    service: web::Data<ProductService>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let id = path.into_inner();
+    let product = blocking(move || service.get_product(&id)).await?;
+
+    Ok(HttpResponse::Ok().json(product))
+}
+
+async fn add_product(
+    service: web::Data<ProductService>,
+    body: web::Json<NewProduct>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let product = blocking(move || {
+        let product = body.into_inner().into_product()?;
+        service.add_product(&product)?;
+        Ok(product)
+    })
+    .await?;
+
+    Ok(HttpResponse::Created().json(product))
+}
+
+// Body is a SpoolmanDB file as is
+async fn import(
+    catalogue: web::Data<dyn ProductCatalogue>,
+    query: web::Query<ImportQuery>,
+    body: String,
+) -> Result<HttpResponse, actix_web::Error> {
+    let mode = if query.dry_run {
+        ImportMode::DryRun
+    } else {
+        ImportMode::Apply
+    };
+    let report = blocking(move || import_products(catalogue.as_ref(), &body, mode)).await?;
+
+    Ok(HttpResponse::Ok().json(report))
+}
+
+async fn create_roll(
+    service: web::Data<ProductService>,
+    path: web::Path<String>,
+    body: web::Json<NewRollRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let product_id = path.into_inner();
+    let roll = blocking(move || {
+        service.create_roll(&product_id, &body.storage_location, body.remaining_weight)
+    })
+    .await?;
+
+    Ok(HttpResponse::Created().json(roll))
+}
@@ -0,0 +1,99 @@
+use crate::api::blocking;
+use crate::domain::reservation::Reservation;
+use crate::domain::services::filament_service::FilamentService;
+use actix_web::{web, HttpResponse};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// Expects `web::Data<FilamentService>` configured `with_reservations`
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.route(
+        "/api/rolls/{id}/reservations",
+        web::get().to(roll_reservations),
+    )
+    .route("/api/rolls/{id}/reservations", web::post().to(reserve))
+    .service(
+        web::scope("/api/reservations")
+            .route("/{id}", web::delete().to(release))
+            .route("/{id}/consume", web::post().to(consume)),
+    );
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct RollAvailability {
+    pub roll_id: String,
+    pub available_weight: f32,
+    pub reservations: Vec<Reservation>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReserveRequest {
+    pub grams: f32,
+    #[serde(default)]
+    pub purpose: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConsumeRequest {
+    pub grams: f32,
+}
+
+async fn roll_reservations(
+    service: web::Data<FilamentService>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let roll_id = path.into_inner();
+    let availability = blocking(move || {
+        Ok(RollAvailability {
+            available_weight: service.available_weight(&roll_id)?,
+            reservations: service.reservations_for(&roll_id)?,
+            roll_id,
+        })
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(availability))
+}
+
+async fn reserve(
+    service: web::Data<FilamentService>,
+    path: web::Path<String>,
+    body: web::Json<ReserveRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let roll_id = path.into_inner();
+    let request = body.into_inner();
+    let reservation = blocking(move || {
+        service.reserve(
+            &roll_id,
+            request.grams,
+            &request.purpose,
+            request.expires_at,
+        )
+    })
+    .await?;
+
+    Ok(HttpResponse::Created().json(reservation))
+}
+
+async fn release(
+    service: web::Data<FilamentService>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let id = path.into_inner();
+    blocking(move || service.release_reservation(&id)).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+async fn consume(
+    service: web::Data<FilamentService>,
+    path: web::Path<String>,
+    body: web::Json<ConsumeRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let id = path.into_inner();
+    let grams = body.into_inner().grams;
+    let roll = blocking(move || service.consume_reservation(&id, grams)).await?;
+
+    Ok(HttpResponse::Ok().json(roll))
+}
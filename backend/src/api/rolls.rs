@@ -0,0 +1,112 @@
+use crate::api::blocking;
+use crate::api::lots::flag_defective;
+use crate::domain::custom_field::CustomValue;
+use crate::domain::roll_filter::RollFilter;
+use crate::domain::services::filament_service::FilamentService;
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+
+// Expects `web::Data<FilamentService>` to be registered on the app
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    // Plain routes rather than a scope, which would hide the other modules'
+    // `/api/rolls/{id}/...` routes
+    cfg.route("/api/rolls/search", web::post().to(search))
+        .route("/api/rolls/{id}", web::delete().to(delete_roll))
+        .route("/api/rolls/{id}/tags", web::put().to(set_tags))
+        .route("/api/rolls/{id}/fields/{name}", web::put().to(set_field))
+        .route(
+            "/api/rolls/{id}/fields/{name}",
+            web::delete().to(remove_field),
+        )
+        .route("/api/rolls/{id}/notes", web::post().to(add_note))
+        .route(
+            "/api/rolls/{id}/notes/{note_id}",
+            web::delete().to(remove_note),
+        );
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NoteRequest {
+    pub text: String,
+}
+
+// Takes a `RollFilter`; tags and custom fields need a body rather than a query.
+// Each roll comes back as a `LotRoll`, so rolls from defective lots stand out.
+async fn search(
+    service: web::Data<FilamentService>,
+    body: web::Json<RollFilter>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let filter = body.into_inner();
+    let rolls = blocking(move || flag_defective(&service, service.find_rolls(&filter)?)).await?;
+
+    Ok(HttpResponse::Ok().json(rolls))
+}
+
+// Replaces every tag with the list in the body
+async fn set_tags(
+    service: web::Data<FilamentService>,
+    path: web::Path<String>,
+    body: web::Json<Vec<String>>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let roll_id = path.into_inner();
+    let tags = body.into_inner();
+    let roll = blocking(move || service.set_tags(&roll_id, &tags)).await?;
+
+    Ok(HttpResponse::Ok().json(roll))
+}
+
+// Body is a typed value, e.g. `{"type": "bool", "value": true}`
+async fn set_field(
+    service: web::Data<FilamentService>,
+    path: web::Path<(String, String)>,
+    body: web::Json<CustomValue>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (roll_id, name) = path.into_inner();
+    let value = body.into_inner();
+    let roll = blocking(move || service.set_custom_field(&roll_id, &name, value)).await?;
+
+    Ok(HttpResponse::Ok().json(roll))
+}
+
+async fn remove_field(
+    service: web::Data<FilamentService>,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (roll_id, name) = path.into_inner();
+    let roll = blocking(move || service.remove_custom_field(&roll_id, &name)).await?;
+
+    Ok(HttpResponse::Ok().json(roll))
+}
+
+// Attachment files go with the roll; loaded or reserved rolls are refused
+async fn delete_roll(
+    service: web::Data<FilamentService>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let roll_id = path.into_inner();
+    blocking(move || service.delete_roll(&roll_id)).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+async fn add_note(
+    service: web::Data<FilamentService>,
+    path: web::Path<String>,
+    body: web::Json<NoteRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let roll_id = path.into_inner();
+    let text = body.into_inner().text;
+    let note = blocking(move || service.add_note(&roll_id, &text)).await?;
+
+    Ok(HttpResponse::Created().json(note))
+}
+
+async fn remove_note(
+    service: web::Data<FilamentService>,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (roll_id, note_id) = path.into_inner();
+    let roll = blocking(move || service.remove_note(&roll_id, &note_id)).await?;
+
+    Ok(HttpResponse::Ok().json(roll))
+}
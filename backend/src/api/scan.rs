@@ -0,0 +1,55 @@
+use crate::api::blocking;
+use crate::domain::scan::RollDraft;
+use crate::domain::services::filament_service::Registration;
+use crate::domain::services::scan_service::ScanService;
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+
+// Expects `web::Data<ScanService>`
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/scan")
+            .route("", web::post().to(scan))
+            .route("/register", web::post().to(register)),
+    );
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScanRequest {
+    // Exactly what the scanner read
+    pub payload: String,
+}
+
+// A draft from `/api/scan`, possibly edited, and where the spool is going
+#[derive(Debug, Deserialize)]
+pub struct RegisterRequest {
+    #[serde(flatten)]
+    pub draft: RollDraft,
+    #[serde(default)]
+    pub storage_location: String,
+}
+
+async fn scan(
+    service: web::Data<ScanService>,
+    body: web::Json<ScanRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let result = blocking(move || service.resolve(&body.payload)).await?;
+
+    Ok(HttpResponse::Ok().json(result))
+}
+
+async fn register(
+    service: web::Data<ScanService>,
+    body: web::Json<RegisterRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let RegisterRequest {
+        draft,
+        storage_location,
+    } = body.into_inner();
+    let registration = blocking(move || service.register(draft, &storage_location)).await?;
+
+    Ok(match registration {
+        Registration::Created(roll) => HttpResponse::Created().json(roll),
+        Registration::AlreadyRegistered(roll) => HttpResponse::Ok().json(roll),
+    })
+}
@@ -0,0 +1,281 @@
+use crate::api::blocking;
+use crate::domain::error::FilamentError;
+use crate::domain::filament::FilamentRoll;
+use crate::domain::lot::DefectiveLot;
+use crate::domain::material::{density_for, length_to_grams};
+use crate::domain::services::filament_service::FilamentService;
+use crate::infrastructure::spoolman::ids::SpoolmanIds;
+use crate::infrastructure::spoolman::models::{
+    PatchSpoolRequest, SpoolmanFilament, SpoolmanSpool, SpoolmanVendor, UseSpoolRequest,
+};
+use actix_web::{web, HttpResponse};
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+
+// Spoolman release whose v1 API this layer mirrors
+pub const SPOOLMAN_API_VERSION: &str = "0.22.1";
+
+// Routes mirroring the Spoolman v1 REST API. Expects `web::Data<FilamentService>`
+// and `web::Data<SpoolmanIds>` to be registered on the app.
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/v1")
+            .route("/health", web::get().to(health))
+            .route("/info", web::get().to(info))
+            .route("/vendor", web::get().to(list_vendors))
+            .route("/vendor/{id}", web::get().to(get_vendor))
+            .route("/filament", web::get().to(list_filaments))
+            .route("/filament/{id}", web::get().to(get_filament))
+            .route("/spool", web::get().to(list_spools))
+            .route("/spool/{id}", web::get().to(get_spool))
+            .route("/spool/{id}", web::patch().to(patch_spool))
+            .route("/spool/{id}/use", web::put().to(use_spool)),
+    );
+}
+
+async fn health() -> HttpResponse {
+    HttpResponse::Ok().json(json!({ "status": "healthy" }))
+}
+
+async fn info() -> HttpResponse {
+    HttpResponse::Ok().json(json!({
+        "version": SPOOLMAN_API_VERSION,
+        "debug_mode": false,
+        "automatic_backups": false,
+    }))
+}
+
+async fn list_vendors(
+    service: web::Data<FilamentService>,
+    ids: web::Data<SpoolmanIds>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let vendors = blocking(move || {
+        let mut vendors = BTreeMap::new();
+        for roll in load_rolls(&service, &ids)? {
+            let vendor = SpoolmanVendor::from_manufacturer(roll.manufacturer(), &ids)?;
+            vendors.insert(vendor.id, vendor);
+        }
+        Ok(vendors.into_values().collect::<Vec<_>>())
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(vendors))
+}
+
+async fn get_vendor(
+    service: web::Data<FilamentService>,
+    ids: web::Data<SpoolmanIds>,
+    path: web::Path<u32>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let vendor_id = path.into_inner();
+    let vendor = blocking(move || {
+        for roll in load_rolls(&service, &ids)? {
+            let vendor = SpoolmanVendor::from_manufacturer(roll.manufacturer(), &ids)?;
+            if vendor.id == vendor_id {
+                return Ok(vendor);
+            }
+        }
+        Err(FilamentError::Missing(format!(
+            "Vendor {} not found",
+            vendor_id
+        )))
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(vendor))
+}
+
+async fn list_filaments(
+    service: web::Data<FilamentService>,
+    ids: web::Data<SpoolmanIds>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let filaments = blocking(move || {
+        let mut filaments = BTreeMap::new();
+        for roll in load_rolls(&service, &ids)? {
+            let filament = SpoolmanFilament::from_roll(&roll, &ids)?;
+            filaments.insert(filament.id, filament);
+        }
+        Ok(filaments.into_values().collect::<Vec<_>>())
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(filaments))
+}
+
+async fn get_filament(
+    service: web::Data<FilamentService>,
+    ids: web::Data<SpoolmanIds>,
+    path: web::Path<u32>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let filament_id = path.into_inner();
+    let filament = blocking(move || {
+        for roll in load_rolls(&service, &ids)? {
+            let filament = SpoolmanFilament::from_roll(&roll, &ids)?;
+            if filament.id == filament_id {
+                return Ok(filament);
+            }
+        }
+        Err(FilamentError::Missing(format!(
+            "Filament {} not found",
+            filament_id
+        )))
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(filament))
+}
+
+// Supports Spoolman's `filament.material`, `location` and `lot_nr` filters.
+// Spools from defective lots carry the reason in `extra`.
+async fn list_spools(
+    service: web::Data<FilamentService>,
+    ids: web::Data<SpoolmanIds>,
+    query: web::Query<HashMap<String, String>>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let query = query.into_inner();
+    let spools = blocking(move || {
+        let defective_lots = service.defective_lots()?;
+        load_rolls(&service, &ids)?
+            .iter()
+            .filter(|roll| {
+                query
+                    .get("filament.material")
+                    .is_none_or(|m| roll.material().eq_ignore_ascii_case(m))
+            })
+            .filter(|roll| {
+                query
+                    .get("location")
+                    .is_none_or(|l| roll.storage_location() == l)
+            })
+            .filter(|roll| {
+                query
+                    .get("lot_nr")
+                    .is_none_or(|lot| roll.lot_number() == lot)
+            })
+            .map(|roll| flagged_spool(roll, &ids, &defective_lots))
+            .collect::<Result<Vec<_>, FilamentError>>()
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(spools))
+}
+
+async fn get_spool(
+    service: web::Data<FilamentService>,
+    ids: web::Data<SpoolmanIds>,
+    path: web::Path<u32>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let spool_id = path.into_inner();
+    let spool = blocking(move || {
+        let roll = find_spool(&service, &ids, spool_id)?;
+        flagged_spool(&roll, &ids, &service.defective_lots()?)
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(spool))
+}
+
+async fn patch_spool(
+    service: web::Data<FilamentService>,
+    ids: web::Data<SpoolmanIds>,
+    path: web::Path<u32>,
+    body: web::Json<PatchSpoolRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let spool_id = path.into_inner();
+    let patch = body.into_inner();
+    let spool = blocking(move || {
+        let roll_id = find_spool(&service, &ids, spool_id)?.id().to_string();
+
+        // One write for the whole patch, so a half-applied patch is never stored
+        let roll = service.update_roll(&roll_id, |roll| {
+            let remaining_weight = match (patch.remaining_weight, patch.used_weight) {
+                (Some(remaining), _) => Some(remaining),
+                (None, Some(used)) => Some(roll.weight() - used),
+                (None, None) => None,
+            };
+            if let Some(remaining_weight) = remaining_weight {
+                roll.update_remaining_weight(remaining_weight)?;
+            }
+            if let Some(location) = &patch.location {
+                roll.move_to(location);
+            }
+            Ok(())
+        })?;
+
+        flagged_spool(&roll, &ids, &service.defective_lots()?)
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(spool))
+}
+
+async fn use_spool(
+    service: web::Data<FilamentService>,
+    ids: web::Data<SpoolmanIds>,
+    path: web::Path<u32>,
+    body: web::Json<UseSpoolRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let spool_id = path.into_inner();
+    let usage = body.into_inner();
+    let spool = blocking(move || {
+        let roll = find_spool(&service, &ids, spool_id)?;
+
+        let grams = match (usage.use_weight, usage.use_length) {
+            (Some(weight), None) => weight,
+            (None, Some(length)) => {
+                length_to_grams(length, roll.diameter(), density_for(roll.material()))
+            }
+            _ => {
+                return Err(FilamentError::InvalidData(
+                    "Specify exactly one of use_weight or use_length".to_string(),
+                ))
+            }
+        };
+
+        let updated = service.consume(roll.id(), grams)?;
+        flagged_spool(&updated, &ids, &service.defective_lots()?)
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(spool))
+}
+
+// Spools from defective lots carry the reason in `extra`
+fn flagged_spool(
+    roll: &FilamentRoll,
+    ids: &SpoolmanIds,
+    defective_lots: &[DefectiveLot],
+) -> Result<SpoolmanSpool, FilamentError> {
+    let mut spool = SpoolmanSpool::from_roll(roll, ids)?;
+    if let Some(lot) = defective_lots.iter().find(|lot| lot.covers(roll)) {
+        spool.flag_defective_lot(lot);
+    }
+    Ok(spool)
+}
+
+fn load_rolls(
+    service: &FilamentService,
+    ids: &SpoolmanIds,
+) -> Result<Vec<FilamentRoll>, FilamentError> {
+    let mut rolls = service.list_rolls()?;
+    ids.register_all(&mut rolls)?;
+    Ok(rolls)
+}
+
+fn find_spool(
+    service: &FilamentService,
+    ids: &SpoolmanIds,
+    spool_id: u32,
+) -> Result<FilamentRoll, FilamentError> {
+    let roll_id = match ids.roll_id(spool_id)? {
+        Some(roll_id) => roll_id,
+        // Spool ids are only assigned once a roll has been listed
+        None => {
+            load_rolls(service, ids)?;
+            ids.roll_id(spool_id)?
+                .ok_or_else(|| FilamentError::Missing(format!("Spool {} not found", spool_id)))?
+        }
+    };
+
+    service.get_roll(&roll_id)
+}
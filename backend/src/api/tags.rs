@@ -0,0 +1,54 @@
+use crate::api::blocking;
+use crate::domain::services::filament_service::FilamentService;
+use crate::infrastructure::nfc::tigertag::TigerTagIds;
+use crate::infrastructure::nfc::TagFormat;
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+
+// Expects `web::Data<FilamentService>` and `web::Data<TigerTagIds>`
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    // openspool, ndef or tigertag; bambu is only read
+    cfg.route("/api/rolls/{id}/tag.{format}", web::get().to(write_tag))
+        .route("/api/tags/read", web::post().to(read_tag));
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReadTagQuery {
+    // Detected from the data when left out
+    pub format: Option<String>,
+}
+
+// What to write to a roll's tag
+async fn write_tag(
+    service: web::Data<FilamentService>,
+    ids: web::Data<TigerTagIds>,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (roll_id, format) = path.into_inner();
+    let format: TagFormat = format.parse()?;
+    let tag = blocking(move || format.encode(&service.get_roll(&roll_id)?, &ids)).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .body(tag))
+}
+
+// Turns what a reader got off a tag into a draft for `/api/scan/register`
+async fn read_tag(
+    ids: web::Data<TigerTagIds>,
+    query: web::Query<ReadTagQuery>,
+    body: web::Bytes,
+) -> Result<HttpResponse, actix_web::Error> {
+    let format = query.into_inner().format;
+    // Dumps are parsed, and may be large, so keep them off the async workers
+    let draft = blocking(move || {
+        let format = match format {
+            Some(format) => format.parse()?,
+            None => TagFormat::detect(&body),
+        };
+        format.decode(&body, &ids)
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(draft))
+}
@@ -0,0 +1,13 @@
+use crate::domain::error::FilamentError;
+
+// Which roll is loaded on each printer tool. Tools are numbered from 0, so a
+// single-extruder printer only uses tool 0.
+pub trait RollAssignments: Send + Sync {
+    fn loaded_roll(&self, printer: &str, tool: u32) -> Result<Option<String>, FilamentError>;
+    fn load_roll(&self, printer: &str, tool: u32, roll_id: &str) -> Result<(), FilamentError>;
+    fn unload(&self, printer: &str, tool: u32) -> Result<(), FilamentError>;
+    // Whether any tool of any printer holds the roll
+    fn is_loaded(&self, roll_id: &str) -> Result<bool, FilamentError>;
+    // None when the store does not know the printer's tools
+    fn tool_count(&self, printer: &str) -> Result<Option<u32>, FilamentError>;
+}
@@ -0,0 +1,20 @@
+use crate::domain::error::FilamentError;
+use crate::domain::filament::FilamentRoll;
+use async_trait::async_trait;
+
+// Async counterpart of `FilamentRepository` for use from actix-web handlers.
+// Database-backed repositories implement this natively; synchronous ones can
+// be wrapped in `BlockingRepositoryAdapter`.
+#[async_trait]
+pub trait AsyncFilamentRepository: Send + Sync {
+    async fn save(&self, filament: &FilamentRoll) -> Result<(), FilamentError>;
+    async fn find_by_id(&self, id: &str) -> Result<FilamentRoll, FilamentError>;
+    async fn update_remaining_weight(
+        &self,
+        id: &str,
+        remaining_weight: f32,
+    ) -> Result<FilamentRoll, FilamentError>;
+    async fn find_all(&self) -> Result<Vec<FilamentRoll>, FilamentError>;
+    async fn find_by_material(&self, material: &str) -> Result<Vec<FilamentRoll>, FilamentError>;
+    async fn delete(&self, id: &str) -> Result<(), FilamentError>;
+}
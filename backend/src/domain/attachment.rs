@@ -0,0 +1,121 @@
+use crate::domain::error::FilamentError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// Holds the files behind a roll's attachments. Files are addressed by roll id
+// and a name unique within that roll, so everything a roll owns can be removed
+// together.
+pub trait AttachmentStorage: Send + Sync {
+    fn put(&self, roll_id: &str, name: &str, content: &[u8]) -> Result<(), FilamentError>;
+
+    // Missing when nothing is stored under that name
+    fn get(&self, roll_id: &str, name: &str) -> Result<Vec<u8>, FilamentError>;
+
+    // Deleting a file that is already gone is not an error
+    fn delete(&self, roll_id: &str, name: &str) -> Result<(), FilamentError>;
+
+    // Every file stored for the roll
+    fn delete_roll(&self, roll_id: &str) -> Result<(), FilamentError>;
+}
+
+// Free-text note on a roll, e.g. "Stringy above 230 °C"
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct RollNote {
+    id: String,
+    text: String,
+    created_at: DateTime<Utc>,
+}
+
+impl RollNote {
+    pub fn new(text: &str) -> Result<Self, FilamentError> {
+        let text = text.trim();
+
+        if text.is_empty() {
+            return Err(FilamentError::InvalidData(
+                "Note cannot be empty".to_string(),
+            ));
+        }
+
+        Ok(RollNote {
+            id: Uuid::new_v4().to_string(),
+            text: text.to_string(),
+            created_at: Utc::now(),
+        })
+    }
+
+    // Getters
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+}
+
+// An image attached to a roll, e.g. a photo of the spool label. Only the
+// metadata lives on the roll; the image and its thumbnail are in
+// `AttachmentStorage`.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct Attachment {
+    id: String,
+    file_name: String,
+    content_type: String,
+    size: u64,
+    uploaded_at: DateTime<Utc>,
+}
+
+impl Attachment {
+    pub fn new(file_name: &str, content_type: &str, size: u64) -> Result<Self, FilamentError> {
+        let file_name = file_name.trim();
+
+        if file_name.is_empty() {
+            return Err(FilamentError::InvalidData(
+                "File name cannot be empty".to_string(),
+            ));
+        }
+
+        Ok(Attachment {
+            id: Uuid::new_v4().to_string(),
+            file_name: file_name.to_string(),
+            content_type: content_type.to_string(),
+            size,
+            uploaded_at: Utc::now(),
+        })
+    }
+
+    // Storage names of the uploaded file and its thumbnail
+    pub fn content_name(&self) -> String {
+        self.id.clone()
+    }
+
+    pub fn thumbnail_name(&self) -> String {
+        format!("{}.thumbnail.png", self.id)
+    }
+
+    // Getters
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    pub fn content_type(&self) -> &str {
+        &self.content_type
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn uploaded_at(&self) -> DateTime<Utc> {
+        self.uploaded_at
+    }
+}
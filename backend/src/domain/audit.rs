@@ -0,0 +1,238 @@
+use crate::domain::error::FilamentError;
+use crate::domain::filament::FilamentRoll;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub trait AuditLog: Send + Sync {
+    fn record(&self, entry: &AuditEntry) -> Result<(), FilamentError>;
+
+    // Entries are returned oldest first
+    fn find_by_roll(&self, roll_id: &str) -> Result<Vec<AuditEntry>, FilamentError>;
+    fn find_all(&self) -> Result<Vec<AuditEntry>, FilamentError>;
+}
+
+// Lets one log be written by the repository and read by the API
+impl<L: AuditLog + ?Sized> AuditLog for std::sync::Arc<L> {
+    fn record(&self, entry: &AuditEntry) -> Result<(), FilamentError> {
+        (**self).record(entry)
+    }
+
+    fn find_by_roll(&self, roll_id: &str) -> Result<Vec<AuditEntry>, FilamentError> {
+        (**self).find_by_roll(roll_id)
+    }
+
+    fn find_all(&self) -> Result<Vec<AuditEntry>, FilamentError> {
+        (**self).find_all()
+    }
+}
+
+// Where a change originated
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeSource {
+    Api,
+    Cli,
+    Import,
+    System,
+}
+
+// Who is making changes and through which channel
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct AuditContext {
+    actor: String,
+    source: ChangeSource,
+}
+
+impl AuditContext {
+    pub fn new(actor: &str, source: ChangeSource) -> Self {
+        AuditContext {
+            actor: actor.to_string(),
+            source,
+        }
+    }
+
+    pub fn system() -> Self {
+        Self::new("system", ChangeSource::System)
+    }
+
+    pub fn actor(&self) -> &str {
+        &self.actor
+    }
+
+    pub fn source(&self) -> ChangeSource {
+        self.source
+    }
+}
+
+// A single field's value before and after a change.
+// `before` is None when the roll was first registered or a custom field was
+// added, `after` is None when a custom field was removed.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct AuditEntry {
+    id: String,
+    roll_id: String,
+    timestamp: DateTime<Utc>,
+    actor: String,
+    source: ChangeSource,
+    changes: Vec<FieldChange>,
+}
+
+impl AuditEntry {
+    pub fn new(roll_id: &str, context: &AuditContext, changes: Vec<FieldChange>) -> Self {
+        AuditEntry {
+            id: Uuid::new_v4().to_string(),
+            roll_id: roll_id.to_string(),
+            timestamp: Utc::now(),
+            actor: context.actor.clone(),
+            source: context.source,
+            changes,
+        }
+    }
+
+    // Getters
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn roll_id(&self) -> &str {
+        &self.roll_id
+    }
+
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    pub fn actor(&self) -> &str {
+        &self.actor
+    }
+
+    pub fn source(&self) -> ChangeSource {
+        self.source
+    }
+
+    pub fn changes(&self) -> &[FieldChange] {
+        &self.changes
+    }
+}
+
+// Field-level differences between two versions of a roll.
+// Passing None for `before` lists every field as newly set.
+pub fn diff_rolls(before: Option<&FilamentRoll>, after: &FilamentRoll) -> Vec<FieldChange> {
+    let after_fields = roll_fields(after);
+
+    match before {
+        None => after_fields
+            .into_iter()
+            .map(|(field, value)| FieldChange {
+                field,
+                before: None,
+                after: Some(value),
+            })
+            .collect(),
+        Some(before) => {
+            let before_fields = roll_fields(before);
+            let value_in = |fields: &[(String, String)], field: &str| {
+                fields
+                    .iter()
+                    .find(|(name, _)| name == field)
+                    .map(|(_, value)| value.clone())
+            };
+
+            let mut changes: Vec<FieldChange> = after_fields
+                .iter()
+                .filter_map(|(field, new)| {
+                    let old = value_in(&before_fields, field);
+                    (old.as_ref() != Some(new)).then(|| FieldChange {
+                        field: field.clone(),
+                        before: old,
+                        after: Some(new.clone()),
+                    })
+                })
+                .collect();
+            // Custom fields, notes and attachments that were removed
+            changes.extend(
+                before_fields
+                    .iter()
+                    .filter(|(field, _)| value_in(&after_fields, field).is_none())
+                    .map(|(field, old)| FieldChange {
+                        field: field.clone(),
+                        before: Some(old.clone()),
+                        after: None,
+                    }),
+            );
+            changes
+        }
+    }
+}
+
+// Every field of a deleted roll, listed as cleared
+pub fn deleted_fields(before: &FilamentRoll) -> Vec<FieldChange> {
+    roll_fields(before)
+        .into_iter()
+        .map(|(field, value)| FieldChange {
+            field,
+            before: Some(value),
+            after: None,
+        })
+        .collect()
+}
+
+// Fixed fields in a set order, then custom fields as `custom_fields.<name>`,
+// notes as `notes.<id>` and attachments as `attachments.<id>`
+fn roll_fields(roll: &FilamentRoll) -> Vec<(String, String)> {
+    let mut fields: Vec<(String, String)> = [
+        ("name", roll.name().to_string()),
+        ("material", roll.material().to_string()),
+        ("color", roll.color().to_string()),
+        ("diameter", roll.diameter().to_string()),
+        ("weight", roll.weight().to_string()),
+        ("remaining_weight", roll.remaining_weight().to_string()),
+        ("manufacturer", roll.manufacturer().to_string()),
+        ("storage_location", roll.storage_location().to_string()),
+        (
+            "spool_weight",
+            roll.spool_weight()
+                .map(|w| w.to_string())
+                .unwrap_or_default(),
+        ),
+        ("lot_number", roll.lot_number().to_string()),
+        ("manufactured_on", date_field(roll.manufactured_on())),
+        ("received_on", date_field(roll.received_on())),
+        ("tags", roll.tags().join("; ")),
+    ]
+    .into_iter()
+    .map(|(field, value)| (field.to_string(), value))
+    .collect();
+
+    fields.extend(roll.custom_fields().iter().map(|(name, value)| {
+        (
+            format!("custom_fields.{}", name),
+            format!("{} ({})", value, value.kind()),
+        )
+    }));
+    fields.extend(
+        roll.notes()
+            .iter()
+            .map(|note| (format!("notes.{}", note.id()), note.text().to_string())),
+    );
+    fields.extend(roll.attachments().iter().map(|attachment| {
+        (
+            format!("attachments.{}", attachment.id()),
+            attachment.file_name().to_string(),
+        )
+    }));
+    fields
+}
+
+// `YYYY-MM-DD`, or empty when unknown
+fn date_field(date: Option<NaiveDate>) -> String {
+    date.map(|date| date.to_string()).unwrap_or_default()
+}
@@ -0,0 +1,78 @@
+use crate::domain::error::FilamentError;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Dates are ISO 8601 calendar dates wherever they are written as text
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+// Value of a user-defined field on a roll, e.g. `{"type": "number", "value": 0.4}`
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum CustomValue {
+    String(String),
+    Number(f64),
+    Bool(bool),
+    Date(NaiveDate),
+}
+
+impl CustomValue {
+    // `string`, `number`, `bool` or `date`, as used in exports
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CustomValue::String(_) => "string",
+            CustomValue::Number(_) => "number",
+            CustomValue::Bool(_) => "bool",
+            CustomValue::Date(_) => "date",
+        }
+    }
+
+    // Reads `text` as a value of `kind`, the inverse of `kind` and `to_string`
+    pub fn parse(kind: &str, text: &str) -> Result<Self, FilamentError> {
+        let text = text.trim();
+        let invalid = || FilamentError::InvalidData(format!("Invalid {} value '{}'", kind, text));
+
+        match kind.trim().to_lowercase().as_str() {
+            "string" => Ok(CustomValue::String(text.to_string())),
+            "number" => text
+                .parse()
+                .ok()
+                .filter(|n: &f64| n.is_finite())
+                .map(CustomValue::Number)
+                .ok_or_else(invalid),
+            "bool" => match text.to_lowercase().as_str() {
+                "true" | "yes" | "1" => Ok(CustomValue::Bool(true)),
+                "false" | "no" | "0" => Ok(CustomValue::Bool(false)),
+                _ => Err(invalid()),
+            },
+            "date" => NaiveDate::parse_from_str(text, DATE_FORMAT)
+                .map(CustomValue::Date)
+                .map_err(|_| invalid()),
+            _ => Err(FilamentError::InvalidData(format!(
+                "Unknown custom field type '{}'; expected string, number, bool or date",
+                kind
+            ))),
+        }
+    }
+
+    // Same type and value; strings ignore case and surrounding whitespace
+    pub fn matches(&self, other: &CustomValue) -> bool {
+        match (self, other) {
+            (CustomValue::String(a), CustomValue::String(b)) => {
+                a.trim().eq_ignore_ascii_case(b.trim())
+            }
+            _ => self == other,
+        }
+    }
+}
+
+impl fmt::Display for CustomValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CustomValue::String(value) => write!(f, "{}", value),
+            CustomValue::Number(value) => write!(f, "{}", value),
+            CustomValue::Bool(value) => write!(f, "{}", value),
+            CustomValue::Date(value) => write!(f, "{}", value.format(DATE_FORMAT)),
+        }
+    }
+}
@@ -3,6 +3,8 @@ use std::fmt;
 #[derive(Debug)]
 pub enum FilamentError {
     NotFound(String),
+    // Anything other than a roll that does not exist; the message says what
+    Missing(String),
     InvalidData(String),
     RepositoryError(String),
 }
@@ -13,6 +15,7 @@ impl fmt::Display for FilamentError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             FilamentError::NotFound(id) => write!(f, "Filament with id '{}' not found", id),
+            FilamentError::Missing(msg) => write!(f, "{}", msg),
             FilamentError::InvalidData(msg) => write!(f, "Invalid filament data: {}", msg),
             FilamentError::RepositoryError(msg) => write!(f, "Repository error: {}", msg),
         }
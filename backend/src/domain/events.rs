@@ -0,0 +1,148 @@
+use crate::domain::error::FilamentError;
+use crate::domain::filament::FilamentRoll;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+pub trait EventStore: Send + Sync {
+    // Appends events in order and returns them with their assigned sequence numbers
+    fn append(&self, events: &[FilamentEvent]) -> Result<Vec<RecordedEvent>, FilamentError>;
+
+    // Events with a sequence number greater than `after_sequence`, oldest first
+    fn load_since(&self, after_sequence: u64) -> Result<Vec<RecordedEvent>, FilamentError>;
+}
+
+pub trait SnapshotStore: Send + Sync {
+    fn save(&self, snapshot: &Snapshot) -> Result<(), FilamentError>;
+    fn load_latest(&self) -> Result<Option<Snapshot>, FilamentError>;
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FilamentEvent {
+    RollRegistered {
+        roll: FilamentRoll,
+    },
+    FilamentConsumed {
+        roll_id: String,
+        grams: f32,
+        remaining_weight: f32,
+    },
+    // Remaining weight went up, e.g. a re-weigh after a bad reading
+    WeightCorrected {
+        roll_id: String,
+        remaining_weight: f32,
+    },
+    RollMoved {
+        roll_id: String,
+        storage_location: String,
+    },
+    // Any other attribute correction; carries the full revised roll
+    RollRevised {
+        roll: FilamentRoll,
+    },
+    RollArchived {
+        roll_id: String,
+    },
+}
+
+impl FilamentEvent {
+    pub fn roll_id(&self) -> &str {
+        match self {
+            FilamentEvent::RollRegistered { roll } | FilamentEvent::RollRevised { roll } => {
+                roll.id()
+            }
+            FilamentEvent::FilamentConsumed { roll_id, .. }
+            | FilamentEvent::WeightCorrected { roll_id, .. }
+            | FilamentEvent::RollMoved { roll_id, .. }
+            | FilamentEvent::RollArchived { roll_id } => roll_id,
+        }
+    }
+
+    // Events needed to take a roll from `before` to `after`
+    pub fn for_change(before: Option<&FilamentRoll>, after: &FilamentRoll) -> Vec<FilamentEvent> {
+        let before = match before {
+            None => {
+                return vec![FilamentEvent::RollRegistered {
+                    roll: after.clone(),
+                }]
+            }
+            Some(before) => before,
+        };
+
+        let mut events = Vec::new();
+
+        // Compare everything except weight and location, which have their own events
+        let mut revised = before.clone();
+        revised.move_to(after.storage_location());
+        if revised
+            .update_remaining_weight(after.remaining_weight())
+            .is_err()
+            || revised != *after
+        {
+            events.push(FilamentEvent::RollRevised {
+                roll: after.clone(),
+            });
+            return events;
+        }
+
+        if after.remaining_weight() < before.remaining_weight() {
+            events.push(FilamentEvent::FilamentConsumed {
+                roll_id: after.id().to_string(),
+                grams: before.remaining_weight() - after.remaining_weight(),
+                remaining_weight: after.remaining_weight(),
+            });
+        } else if after.remaining_weight() > before.remaining_weight() {
+            events.push(FilamentEvent::WeightCorrected {
+                roll_id: after.id().to_string(),
+                remaining_weight: after.remaining_weight(),
+            });
+        }
+
+        if after.storage_location() != before.storage_location() {
+            events.push(FilamentEvent::RollMoved {
+                roll_id: after.id().to_string(),
+                storage_location: after.storage_location().to_string(),
+            });
+        }
+
+        events
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct RecordedEvent {
+    sequence: u64,
+    recorded_at: DateTime<Utc>,
+    event: FilamentEvent,
+}
+
+impl RecordedEvent {
+    pub fn new(sequence: u64, event: FilamentEvent) -> Self {
+        RecordedEvent {
+            sequence,
+            recorded_at: Utc::now(),
+            event,
+        }
+    }
+
+    // Getters
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    pub fn recorded_at(&self) -> DateTime<Utc> {
+        self.recorded_at
+    }
+
+    pub fn event(&self) -> &FilamentEvent {
+        &self.event
+    }
+}
+
+// Folded state as of `version`, the sequence number of the last applied event
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct Snapshot {
+    pub version: u64,
+    pub rolls: Vec<FilamentRoll>,
+    pub archived: Vec<FilamentRoll>,
+}
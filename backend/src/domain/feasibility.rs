@@ -0,0 +1,197 @@
+use crate::domain::filament::FilamentRoll;
+use crate::domain::printer::DIAMETER_TOLERANCE;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// Filament one extruder needs for a print, from slicer output or typed in
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct FilamentRequirement {
+    pub extruder: u32,
+    pub material: String,
+    // `#RRGGBB`; None accepts any colour
+    pub color: Option<String>,
+    // None accepts any diameter
+    pub diameter: Option<f32>,
+    pub grams: f32,
+}
+
+impl FilamentRequirement {
+    pub fn matches(&self, roll: &FilamentRoll) -> bool {
+        roll.material().eq_ignore_ascii_case(&self.material)
+            && self
+                .color
+                .as_ref()
+                .is_none_or(|color| normalize_color(color) == normalize_color(roll.color()))
+            && self
+                .diameter
+                .is_none_or(|diameter| (diameter - roll.diameter()).abs() < DIAMETER_TOLERANCE)
+    }
+}
+
+// A roll that matches a requirement, with what it has left after earlier
+// requirements of the same print were allocated
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct Candidate {
+    pub roll_id: String,
+    pub name: String,
+    pub available_weight: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct Allocation {
+    pub roll_id: String,
+    pub grams: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Fulfilment {
+    SingleRoll {
+        allocation: Allocation,
+    },
+    // No one roll has enough; swap rolls mid-print in this order
+    Swap {
+        allocations: Vec<Allocation>,
+    },
+    // Every matching roll together is not enough
+    Shortfall {
+        missing_grams: f32,
+        allocations: Vec<Allocation>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct RequirementCheck {
+    pub requirement: FilamentRequirement,
+    pub candidates: Vec<Candidate>,
+    pub fulfilment: Fulfilment,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct FeasibilityReport {
+    pub checks: Vec<RequirementCheck>,
+}
+
+impl FeasibilityReport {
+    pub fn is_feasible(&self) -> bool {
+        self.shortfall() == 0.0
+    }
+
+    // Grams missing across all requirements
+    pub fn shortfall(&self) -> f32 {
+        self.checks
+            .iter()
+            .map(|check| match &check.fulfilment {
+                Fulfilment::Shortfall { missing_grams, .. } => *missing_grams,
+                _ => 0.0,
+            })
+            .sum()
+    }
+}
+
+// Allocates `rolls`, each paired with its available weight, to the
+// requirements in order. A roll used by one requirement is not offered to the
+// next, so two extruders cannot both count on the same filament.
+pub fn check_requirements(
+    requirements: &[FilamentRequirement],
+    rolls: &[(FilamentRoll, f32)],
+) -> FeasibilityReport {
+    let mut available: HashMap<&str, f32> = rolls
+        .iter()
+        .map(|(roll, weight)| (roll.id(), *weight))
+        .collect();
+
+    let checks = requirements
+        .iter()
+        .map(|requirement| {
+            let mut candidates: Vec<Candidate> = rolls
+                .iter()
+                .filter(|(roll, _)| requirement.matches(roll))
+                .map(|(roll, _)| Candidate {
+                    roll_id: roll.id().to_string(),
+                    name: roll.name().to_string(),
+                    available_weight: available[roll.id()],
+                })
+                .filter(|c| c.available_weight > 0.0)
+                .collect();
+            // Largest first, so a swap needs as few rolls as possible
+            candidates.sort_by(|a, b| {
+                b.available_weight
+                    .total_cmp(&a.available_weight)
+                    .then_with(|| a.roll_id.cmp(&b.roll_id))
+            });
+
+            let fulfilment = fulfil(requirement.grams, &candidates);
+            let allocations = match &fulfilment {
+                Fulfilment::SingleRoll { allocation } => std::slice::from_ref(allocation),
+                Fulfilment::Swap { allocations } | Fulfilment::Shortfall { allocations, .. } => {
+                    allocations.as_slice()
+                }
+            };
+            for allocation in allocations {
+                if let Some(left) = available.get_mut(allocation.roll_id.as_str()) {
+                    *left -= allocation.grams;
+                }
+            }
+
+            RequirementCheck {
+                requirement: requirement.clone(),
+                candidates,
+                fulfilment,
+            }
+        })
+        .collect();
+
+    FeasibilityReport { checks }
+}
+
+// `candidates` must be sorted largest first
+fn fulfil(grams: f32, candidates: &[Candidate]) -> Fulfilment {
+    // The smallest roll that suffices, keeping fuller rolls for bigger prints
+    if let Some(single) = candidates
+        .iter()
+        .rev()
+        .find(|c| c.available_weight >= grams)
+    {
+        return Fulfilment::SingleRoll {
+            allocation: Allocation {
+                roll_id: single.roll_id.clone(),
+                grams,
+            },
+        };
+    }
+
+    let mut allocations = Vec::new();
+    let mut still_needed = grams;
+    for candidate in candidates {
+        if still_needed <= 0.0 {
+            break;
+        }
+        let take = candidate.available_weight.min(still_needed);
+        allocations.push(Allocation {
+            roll_id: candidate.roll_id.clone(),
+            grams: take,
+        });
+        still_needed -= take;
+    }
+
+    if still_needed > 0.0 {
+        Fulfilment::Shortfall {
+            missing_grams: still_needed,
+            allocations,
+        }
+    } else {
+        Fulfilment::Swap { allocations }
+    }
+}
+
+// Compares colours as `#RRGGBB`, ignoring case and a trailing alpha byte
+pub(crate) fn normalize_color(color: &str) -> String {
+    let hex = color.trim().trim_start_matches('#').to_uppercase();
+    // Colours come from users, so only slice what is known to be ASCII
+    if hex.len() == 8 && hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        hex[..6].to_string()
+    } else {
+        hex
+    }
+}
@@ -1,8 +1,16 @@
+use crate::domain::attachment::{Attachment, RollNote};
+use crate::domain::custom_field::CustomValue;
 use crate::domain::error::FilamentError;
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
-pub trait FilamentRepository {
+// Define what "low inventory" means - less than 20% remaining
+pub const LOW_INVENTORY_THRESHOLD: f32 = 20.0;
+
+// Send + Sync so one repository can be shared across actix-web workers
+pub trait FilamentRepository: Send + Sync {
     // Add error handling to save operation
     fn save(&self, filament: &FilamentRoll) -> Result<(), FilamentError>;
 
@@ -17,6 +25,28 @@ pub trait FilamentRepository {
     // Return Results for collection methods too - they could fail
     fn find_all(&self) -> Result<Vec<FilamentRoll>, FilamentError>;
     fn find_by_material(&self, material: &str) -> Result<Vec<FilamentRoll>, FilamentError>;
+
+    // NotFound when there is no such roll
+    fn delete(&self, id: &str) -> Result<(), FilamentError>;
+
+    // Applies `change` to the stored roll and writes it back in one step, so
+    // no other write can land in between and be lost. Nothing is written
+    // when `change` fails.
+    fn update(
+        &self,
+        id: &str,
+        change: &mut dyn FnMut(&mut FilamentRoll) -> Result<(), FilamentError>,
+    ) -> Result<FilamentRoll, FilamentError>;
+
+    // Every roll from one manufacturing lot, whoever made it
+    fn find_by_lot(&self, lot_number: &str) -> Result<Vec<FilamentRoll>, FilamentError> {
+        let lot_number = lot_number.trim();
+        Ok(self
+            .find_all()?
+            .into_iter()
+            .filter(|roll| !lot_number.is_empty() && roll.lot_number() == lot_number)
+            .collect())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
@@ -33,6 +63,31 @@ pub struct FilamentRoll {
 
     // Optional attributes
     storage_location: Option<String>,
+    // Weight of the empty spool in grams, so a weighed spool can be converted
+    // to filament left; absent from rolls saved before it was recorded
+    #[serde(default)]
+    spool_weight: Option<f32>,
+
+    // Batch tracking; absent from rolls saved before lots were recorded
+    #[serde(default)]
+    lot_number: Option<String>,
+    #[serde(default)]
+    manufactured_on: Option<NaiveDate>,
+    #[serde(default)]
+    received_on: Option<NaiveDate>,
+
+    // User-defined labels, e.g. `customer-supplied`, in the order first added
+    #[serde(default)]
+    tags: Vec<String>,
+    // User-defined typed fields, by name
+    #[serde(default)]
+    custom_fields: BTreeMap<String, CustomValue>,
+
+    // Timestamped notes and attached images, oldest first
+    #[serde(default)]
+    notes: Vec<RollNote>,
+    #[serde(default)]
+    attachments: Vec<Attachment>,
 }
 
 // Builder pattern for FilamentRoll construction
@@ -46,6 +101,14 @@ pub struct FilamentRollBuilder {
     remaining_weight: Option<f32>,
     manufacturer: String,
     storage_location: Option<String>,
+    spool_weight: Option<f32>,
+    lot_number: Option<String>,
+    manufactured_on: Option<NaiveDate>,
+    received_on: Option<NaiveDate>,
+    tags: Vec<String>,
+    custom_fields: Vec<(String, CustomValue)>,
+    notes: Vec<RollNote>,
+    attachments: Vec<Attachment>,
 }
 
 impl FilamentRollBuilder {
@@ -67,6 +130,14 @@ impl FilamentRollBuilder {
             remaining_weight: None,
             manufacturer,
             storage_location: None,
+            spool_weight: None,
+            lot_number: None,
+            manufactured_on: None,
+            received_on: None,
+            tags: Vec::new(),
+            custom_fields: Vec::new(),
+            notes: Vec::new(),
+            attachments: Vec::new(),
         }
     }
 
@@ -85,6 +156,46 @@ impl FilamentRollBuilder {
         self
     }
 
+    pub fn with_spool_weight(mut self, spool_weight: f32) -> Self {
+        self.spool_weight = Some(spool_weight);
+        self
+    }
+
+    pub fn with_lot_number(mut self, lot_number: &str) -> Self {
+        self.lot_number = Some(lot_number.to_string());
+        self
+    }
+
+    pub fn with_manufactured_on(mut self, manufactured_on: NaiveDate) -> Self {
+        self.manufactured_on = Some(manufactured_on);
+        self
+    }
+
+    pub fn with_received_on(mut self, received_on: NaiveDate) -> Self {
+        self.received_on = Some(received_on);
+        self
+    }
+
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+
+    pub fn with_custom_field(mut self, name: &str, value: CustomValue) -> Self {
+        self.custom_fields.push((name.to_string(), value));
+        self
+    }
+
+    pub fn with_note(mut self, note: RollNote) -> Self {
+        self.notes.push(note);
+        self
+    }
+
+    pub fn with_attachment(mut self, attachment: Attachment) -> Self {
+        self.attachments.push(attachment);
+        self
+    }
+
     pub fn build(self) -> Result<FilamentRoll, FilamentError> {
         // Validate required fields
         if self.name.is_empty() {
@@ -142,6 +253,19 @@ impl FilamentRollBuilder {
             ));
         }
 
+        if self.spool_weight.is_some_and(|w| w < 0.0) {
+            return Err(FilamentError::InvalidData(
+                "Spool weight cannot be negative".to_string(),
+            ));
+        }
+
+        check_lot_dates(self.manufactured_on, self.received_on)?;
+        let tags = normalize_tags(&self.tags)?;
+        let mut custom_fields = BTreeMap::new();
+        for (name, value) in self.custom_fields {
+            custom_fields.insert(field_name(&name)?, value);
+        }
+
         Ok(FilamentRoll {
             id,
             name: self.name,
@@ -152,6 +276,17 @@ impl FilamentRollBuilder {
             remaining_weight,
             manufacturer: self.manufacturer,
             storage_location: self.storage_location,
+            spool_weight: self.spool_weight,
+            lot_number: self
+                .lot_number
+                .map(|lot| lot.trim().to_string())
+                .filter(|lot| !lot.is_empty()),
+            manufactured_on: self.manufactured_on,
+            received_on: self.received_on,
+            tags,
+            custom_fields,
+            notes: self.notes,
+            attachments: self.attachments,
         })
     }
 }
@@ -169,6 +304,7 @@ impl FilamentRoll {
         FilamentRollBuilder::new(name, material, color, diameter, weight, manufacturer).build()
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn with_id(
         id: &str,
         name: &str,
@@ -211,6 +347,81 @@ impl FilamentRoll {
         Ok(())
     }
 
+    pub fn move_to(&mut self, storage_location: &str) {
+        self.storage_location = if storage_location.is_empty() {
+            None
+        } else {
+            Some(storage_location.to_string())
+        };
+    }
+
+    // Replaces the batch details; an empty lot number clears it
+    pub fn set_lot(
+        &mut self,
+        lot_number: &str,
+        manufactured_on: Option<NaiveDate>,
+        received_on: Option<NaiveDate>,
+    ) -> Result<(), FilamentError> {
+        check_lot_dates(manufactured_on, received_on)?;
+
+        let lot_number = lot_number.trim();
+        self.lot_number = if lot_number.is_empty() {
+            None
+        } else {
+            Some(lot_number.to_string())
+        };
+        self.manufactured_on = manufactured_on;
+        self.received_on = received_on;
+        Ok(())
+    }
+
+    // Replaces every tag; duplicates differing only in case are dropped
+    pub fn set_tags(&mut self, tags: &[String]) -> Result<(), FilamentError> {
+        self.tags = normalize_tags(tags)?;
+        Ok(())
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag.trim()))
+    }
+
+    pub fn set_custom_field(
+        &mut self,
+        name: &str,
+        value: CustomValue,
+    ) -> Result<(), FilamentError> {
+        self.custom_fields.insert(field_name(name)?, value);
+        Ok(())
+    }
+
+    // The value that was removed, if the field was set
+    pub fn remove_custom_field(&mut self, name: &str) -> Option<CustomValue> {
+        self.custom_fields.remove(name.trim())
+    }
+
+    pub fn add_note(&mut self, note: RollNote) {
+        self.notes.push(note);
+    }
+
+    // The note that was removed, if there was one with that id
+    pub fn remove_note(&mut self, note_id: &str) -> Option<RollNote> {
+        let index = self.notes.iter().position(|n| n.id() == note_id)?;
+        Some(self.notes.remove(index))
+    }
+
+    pub fn add_attachment(&mut self, attachment: Attachment) {
+        self.attachments.push(attachment);
+    }
+
+    // The attachment that was removed; its files are left for the caller
+    pub fn remove_attachment(&mut self, attachment_id: &str) -> Option<Attachment> {
+        let index = self
+            .attachments
+            .iter()
+            .position(|a| a.id() == attachment_id)?;
+        Some(self.attachments.remove(index))
+    }
+
     pub fn percentage_remaining(&self) -> f32 {
         // Guard against division by zero
         if self.weight == 0.0 {
@@ -256,4 +467,91 @@ impl FilamentRoll {
     pub fn storage_location(&self) -> &str {
         self.storage_location.as_deref().unwrap_or("")
     }
+
+    pub fn spool_weight(&self) -> Option<f32> {
+        self.spool_weight
+    }
+
+    pub fn lot_number(&self) -> &str {
+        self.lot_number.as_deref().unwrap_or("")
+    }
+
+    pub fn manufactured_on(&self) -> Option<NaiveDate> {
+        self.manufactured_on
+    }
+
+    pub fn received_on(&self) -> Option<NaiveDate> {
+        self.received_on
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn custom_fields(&self) -> &BTreeMap<String, CustomValue> {
+        &self.custom_fields
+    }
+
+    pub fn custom_field(&self, name: &str) -> Option<&CustomValue> {
+        self.custom_fields.get(name.trim())
+    }
+
+    pub fn notes(&self) -> &[RollNote] {
+        &self.notes
+    }
+
+    pub fn attachments(&self) -> &[Attachment] {
+        &self.attachments
+    }
+
+    pub fn attachment(&self, attachment_id: &str) -> Option<&Attachment> {
+        self.attachments.iter().find(|a| a.id() == attachment_id)
+    }
+}
+
+// Tags are listed in exports separated by semicolons, so cannot contain one
+fn normalize_tags(tags: &[String]) -> Result<Vec<String>, FilamentError> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim();
+        if tag.is_empty() {
+            return Err(FilamentError::InvalidData(
+                "Tags cannot be empty".to_string(),
+            ));
+        }
+        if tag.contains(';') {
+            return Err(FilamentError::InvalidData(format!(
+                "Tag '{}' cannot contain ';'",
+                tag
+            )));
+        }
+        if !normalized.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            normalized.push(tag.to_string());
+        }
+    }
+    Ok(normalized)
+}
+
+fn field_name(name: &str) -> Result<String, FilamentError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(FilamentError::InvalidData(
+            "Custom field name cannot be empty".to_string(),
+        ));
+    }
+    Ok(name.to_string())
+}
+
+fn check_lot_dates(
+    manufactured_on: Option<NaiveDate>,
+    received_on: Option<NaiveDate>,
+) -> Result<(), FilamentError> {
+    match (manufactured_on, received_on) {
+        (Some(manufactured), Some(received)) if manufactured > received => {
+            Err(FilamentError::InvalidData(
+                "Manufacturing date cannot be after the received date".to_string(),
+            ))
+        }
+        _ => Ok(()),
+    }
 }
@@ -0,0 +1,77 @@
+use crate::domain::error::FilamentError;
+use crate::domain::filament::FilamentRoll;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// Lots are identified by manufacturer and lot number, as different
+// manufacturers can print the same lot code
+pub trait DefectiveLotRepository: Send + Sync {
+    // Replaces any earlier flag on the same lot
+    fn save(&self, lot: &DefectiveLot) -> Result<(), FilamentError>;
+    fn find_all(&self) -> Result<Vec<DefectiveLot>, FilamentError>;
+    fn delete(&self, manufacturer: &str, lot_number: &str) -> Result<(), FilamentError>;
+}
+
+// A manufacturing lot whose rolls should not be printed with
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct DefectiveLot {
+    manufacturer: String,
+    lot_number: String,
+    // What is wrong with it, e.g. "Colour off" or "Brittle"
+    reason: String,
+    flagged_at: DateTime<Utc>,
+}
+
+impl DefectiveLot {
+    pub fn new(manufacturer: &str, lot_number: &str, reason: &str) -> Result<Self, FilamentError> {
+        let manufacturer = manufacturer.trim();
+        let lot_number = lot_number.trim();
+
+        if manufacturer.is_empty() {
+            return Err(FilamentError::InvalidData(
+                "Manufacturer cannot be empty".to_string(),
+            ));
+        }
+
+        if lot_number.is_empty() {
+            return Err(FilamentError::InvalidData(
+                "Lot number cannot be empty".to_string(),
+            ));
+        }
+
+        Ok(DefectiveLot {
+            manufacturer: manufacturer.to_string(),
+            lot_number: lot_number.to_string(),
+            reason: reason.trim().to_string(),
+            flagged_at: Utc::now(),
+        })
+    }
+
+    // Whether `roll` came from this lot
+    pub fn covers(&self, roll: &FilamentRoll) -> bool {
+        self.is_lot(roll.manufacturer(), roll.lot_number())
+    }
+
+    // Manufacturer names are compared ignoring case; lot numbers exactly
+    pub fn is_lot(&self, manufacturer: &str, lot_number: &str) -> bool {
+        self.manufacturer.eq_ignore_ascii_case(manufacturer.trim())
+            && self.lot_number == lot_number.trim()
+    }
+
+    // Getters
+    pub fn manufacturer(&self) -> &str {
+        &self.manufacturer
+    }
+
+    pub fn lot_number(&self) -> &str {
+        &self.lot_number
+    }
+
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+
+    pub fn flagged_at(&self) -> DateTime<Utc> {
+        self.flagged_at
+    }
+}
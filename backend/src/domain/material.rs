@@ -0,0 +1,66 @@
+use std::f32::consts::PI;
+
+// Fallback when a material is not in the table; PLA is the most common
+pub const DEFAULT_DENSITY: f32 = 1.24;
+
+// Typical densities in g/cm³
+const DENSITIES: &[(&str, f32)] = &[
+    ("PLA", 1.24),
+    ("PETG", 1.27),
+    ("ABS", 1.04),
+    ("ASA", 1.07),
+    ("TPU", 1.21),
+    ("PA", 1.14),
+    ("NYLON", 1.14),
+    ("PC", 1.20),
+    ("PVA", 1.23),
+    ("HIPS", 1.04),
+];
+
+// Typical nozzle temperature ranges in °C, as written to NFC spool tags
+const PRINT_TEMPERATURES: &[(&str, u16, u16)] = &[
+    ("PLA", 190, 220),
+    ("PETG", 220, 250),
+    ("ABS", 230, 260),
+    ("ASA", 240, 260),
+    ("TPU", 210, 230),
+    ("PA", 250, 280),
+    ("NYLON", 250, 280),
+    ("PC", 260, 290),
+    ("PVA", 190, 220),
+    ("HIPS", 230, 250),
+];
+
+pub fn density_for(material: &str) -> f32 {
+    let material = material.trim().to_uppercase();
+    DENSITIES
+        .iter()
+        .find(|(name, _)| *name == material)
+        .map(|(_, density)| *density)
+        .unwrap_or(DEFAULT_DENSITY)
+}
+
+// Minimum and maximum nozzle temperature, for materials in the table
+pub fn print_temperatures(material: &str) -> Option<(u16, u16)> {
+    let material = material.trim().to_uppercase();
+    PRINT_TEMPERATURES
+        .iter()
+        .find(|(name, _, _)| *name == material)
+        .map(|&(_, min, max)| (min, max))
+}
+
+// Grams of filament in `length_mm` of strand
+pub fn length_to_grams(length_mm: f32, diameter_mm: f32, density: f32) -> f32 {
+    let radius_cm = diameter_mm / 20.0;
+    let volume_cm3 = PI * radius_cm * radius_cm * (length_mm / 10.0);
+    volume_cm3 * density
+}
+
+// Length in mm of a strand weighing `grams`
+pub fn grams_to_length(grams: f32, diameter_mm: f32, density: f32) -> f32 {
+    let grams_per_mm = length_to_grams(1.0, diameter_mm, density);
+    if grams_per_mm == 0.0 {
+        return 0.0;
+    }
+    grams / grams_per_mm
+}
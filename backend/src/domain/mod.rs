@@ -1,3 +1,20 @@
+pub mod assignment;
+pub mod async_repository;
+pub mod attachment;
+pub mod audit;
+pub mod custom_field;
 pub mod error;
+pub mod events;
+pub mod feasibility;
 pub mod filament;
+pub mod lot;
+pub mod material;
+pub mod print_job;
+pub mod printer;
+pub mod product;
+pub mod projections;
+pub mod reservation;
+pub mod roll_filter;
+pub mod scan;
 pub mod services;
+pub mod unit_of_work;
@@ -0,0 +1,174 @@
+use crate::domain::error::FilamentError;
+use crate::domain::filament::FilamentRoll;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub trait PrintJobRepository: Send + Sync {
+    fn save(&self, job: &PrintJob) -> Result<(), FilamentError>;
+    fn find_by_id(&self, id: &str) -> Result<PrintJob, FilamentError>;
+    fn find_all(&self) -> Result<Vec<PrintJob>, FilamentError>;
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum PrintStatus {
+    InProgress,
+    Success,
+    Failed,
+    Cancelled,
+}
+
+impl PrintStatus {
+    pub fn is_finished(&self) -> bool {
+        *self != PrintStatus::InProgress
+    }
+}
+
+// Filament one roll contributes to a job. The material is copied from the
+// roll so reports stay correct after the roll is used up or edited.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct RollUsage {
+    pub roll_id: String,
+    pub material: String,
+    // From slicer metadata
+    pub planned_grams: f32,
+    // Set when the job finishes
+    pub actual_grams: Option<f32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct PrintJob {
+    id: String,
+    file_name: String,
+    printer_id: String,
+    started_at: DateTime<Utc>,
+    ended_at: Option<DateTime<Utc>>,
+    status: PrintStatus,
+    usage: Vec<RollUsage>,
+}
+
+impl PrintJob {
+    pub fn start(file_name: &str, printer_id: &str) -> Result<Self, FilamentError> {
+        if file_name.is_empty() {
+            return Err(FilamentError::InvalidData(
+                "File name cannot be empty".to_string(),
+            ));
+        }
+
+        Ok(PrintJob {
+            id: Uuid::new_v4().to_string(),
+            file_name: file_name.to_string(),
+            printer_id: printer_id.to_string(),
+            started_at: Utc::now(),
+            ended_at: None,
+            status: PrintStatus::InProgress,
+            usage: Vec::new(),
+        })
+    }
+
+    // Adds the slicer's estimate for `roll`. Planning the same roll twice
+    // adds to its estimate, e.g. for two objects printed from one roll.
+    pub fn plan(&mut self, roll: &FilamentRoll, grams: f32) -> Result<(), FilamentError> {
+        if grams < 0.0 {
+            return Err(FilamentError::InvalidData(
+                "Planned grams cannot be negative".to_string(),
+            ));
+        }
+
+        match self.usage.iter_mut().find(|u| u.roll_id == roll.id()) {
+            Some(usage) => usage.planned_grams += grams,
+            None => self.usage.push(RollUsage {
+                roll_id: roll.id().to_string(),
+                material: roll.material().to_string(),
+                planned_grams: grams,
+                actual_grams: None,
+            }),
+        }
+        Ok(())
+    }
+
+    // Ends the job with measured usage per roll. Rolls the job did not plan
+    // for are added without an estimate; planned rolls left out used nothing.
+    pub fn finish(
+        &mut self,
+        status: PrintStatus,
+        actual: &[(FilamentRoll, f32)],
+    ) -> Result<(), FilamentError> {
+        if self.status.is_finished() {
+            return Err(FilamentError::InvalidData(format!(
+                "Print job '{}' has already finished",
+                self.id
+            )));
+        }
+
+        if !status.is_finished() {
+            return Err(FilamentError::InvalidData(
+                "A print job must finish as success, failed or cancelled".to_string(),
+            ));
+        }
+
+        if actual.iter().any(|(_, grams)| *grams < 0.0) {
+            return Err(FilamentError::InvalidData(
+                "Actual grams cannot be negative".to_string(),
+            ));
+        }
+
+        for (roll, grams) in actual {
+            let index = match self.usage.iter().position(|u| u.roll_id == roll.id()) {
+                Some(index) => index,
+                None => {
+                    self.plan(roll, 0.0)?;
+                    self.usage.len() - 1
+                }
+            };
+            let usage = &mut self.usage[index];
+            usage.actual_grams = Some(usage.actual_grams.unwrap_or(0.0) + grams);
+        }
+
+        for usage in &mut self.usage {
+            usage.actual_grams.get_or_insert(0.0);
+        }
+
+        self.status = status;
+        self.ended_at = Some(Utc::now());
+        Ok(())
+    }
+
+    pub fn planned_grams(&self) -> f32 {
+        self.usage.iter().map(|u| u.planned_grams).sum()
+    }
+
+    pub fn actual_grams(&self) -> f32 {
+        self.usage.iter().filter_map(|u| u.actual_grams).sum()
+    }
+
+    // Getters
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    pub fn printer_id(&self) -> &str {
+        &self.printer_id
+    }
+
+    pub fn started_at(&self) -> DateTime<Utc> {
+        self.started_at
+    }
+
+    pub fn ended_at(&self) -> Option<DateTime<Utc>> {
+        self.ended_at
+    }
+
+    pub fn status(&self) -> PrintStatus {
+        self.status
+    }
+
+    pub fn usage(&self) -> &[RollUsage] {
+        &self.usage
+    }
+}
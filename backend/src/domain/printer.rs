@@ -0,0 +1,159 @@
+use crate::domain::error::FilamentError;
+use crate::domain::filament::FilamentRoll;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// Filament diameters closer than this are treated as the same nominal size
+pub const DIAMETER_TOLERANCE: f32 = 0.05;
+
+pub trait PrinterRepository: Send + Sync {
+    fn save(&self, printer: &Printer) -> Result<(), FilamentError>;
+    fn find_by_id(&self, id: &str) -> Result<Printer, FilamentError>;
+    fn find_all(&self) -> Result<Vec<Printer>, FilamentError>;
+}
+
+// One place a roll can be loaded: an extruder, or a slot of an AMS/MMU unit
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct Toolhead {
+    name: String,
+    filament_diameter: f32,
+    loaded_roll: Option<String>,
+}
+
+impl Toolhead {
+    pub fn new(name: &str, filament_diameter: f32) -> Result<Self, FilamentError> {
+        if name.is_empty() {
+            return Err(FilamentError::InvalidData(
+                "Toolhead name cannot be empty".to_string(),
+            ));
+        }
+
+        if filament_diameter <= 0.0 {
+            return Err(FilamentError::InvalidData(
+                "Filament diameter must be positive".to_string(),
+            ));
+        }
+
+        Ok(Toolhead {
+            name: name.to_string(),
+            filament_diameter,
+            loaded_roll: None,
+        })
+    }
+
+    // Getters
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn filament_diameter(&self) -> f32 {
+        self.filament_diameter
+    }
+
+    pub fn loaded_roll(&self) -> Option<&str> {
+        self.loaded_roll.as_deref()
+    }
+}
+
+// A printer and its toolheads. Toolheads are addressed by position, so tool 0
+// is the first one listed.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct Printer {
+    id: String,
+    name: String,
+    toolheads: Vec<Toolhead>,
+}
+
+impl Printer {
+    pub fn new(name: &str, toolheads: Vec<Toolhead>) -> Result<Self, FilamentError> {
+        Self::with_id(&Uuid::new_v4().to_string(), name, toolheads)
+    }
+
+    pub fn with_id(id: &str, name: &str, toolheads: Vec<Toolhead>) -> Result<Self, FilamentError> {
+        if id.is_empty() {
+            return Err(FilamentError::InvalidData(
+                "Printer id cannot be empty".to_string(),
+            ));
+        }
+
+        if name.is_empty() {
+            return Err(FilamentError::InvalidData(
+                "Printer name cannot be empty".to_string(),
+            ));
+        }
+
+        if toolheads.is_empty() {
+            return Err(FilamentError::InvalidData(
+                "Printer needs at least one toolhead".to_string(),
+            ));
+        }
+
+        Ok(Printer {
+            id: id.to_string(),
+            name: name.to_string(),
+            toolheads,
+        })
+    }
+
+    // Loads `roll` on `tool`, replacing whatever was there.
+    // Rejects rolls whose diameter does not fit the toolhead.
+    pub fn load(&mut self, tool: u32, roll: &FilamentRoll) -> Result<(), FilamentError> {
+        let printer_id = self.id.clone();
+        let toolhead = self.toolhead_mut(tool)?;
+
+        if (toolhead.filament_diameter - roll.diameter()).abs() >= DIAMETER_TOLERANCE {
+            return Err(FilamentError::InvalidData(format!(
+                "Roll '{}' is {} mm filament but tool {} on printer '{}' takes {} mm",
+                roll.id(),
+                roll.diameter(),
+                tool,
+                printer_id,
+                toolhead.filament_diameter
+            )));
+        }
+
+        toolhead.loaded_roll = Some(roll.id().to_string());
+        Ok(())
+    }
+
+    pub fn unload(&mut self, tool: u32) -> Result<(), FilamentError> {
+        self.toolhead_mut(tool)?.loaded_roll = None;
+        Ok(())
+    }
+
+    // The tool `roll_id` is loaded on, if any
+    pub fn tool_holding(&self, roll_id: &str) -> Option<u32> {
+        self.toolheads
+            .iter()
+            .position(|t| t.loaded_roll() == Some(roll_id))
+            .map(|index| index as u32)
+    }
+
+    // Getters
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn toolheads(&self) -> &[Toolhead] {
+        &self.toolheads
+    }
+
+    pub fn toolhead(&self, tool: u32) -> Result<&Toolhead, FilamentError> {
+        self.toolheads
+            .get(tool as usize)
+            .ok_or_else(|| self.unknown_tool(tool))
+    }
+
+    fn toolhead_mut(&mut self, tool: u32) -> Result<&mut Toolhead, FilamentError> {
+        let error = self.unknown_tool(tool);
+        self.toolheads.get_mut(tool as usize).ok_or(error)
+    }
+
+    fn unknown_tool(&self, tool: u32) -> FilamentError {
+        FilamentError::Missing(format!("Printer '{}' has no tool {}", self.id, tool))
+    }
+}
@@ -0,0 +1,189 @@
+use crate::domain::error::FilamentError;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub trait ProductCatalogue: Send + Sync {
+    fn save(&self, product: &Product) -> Result<(), FilamentError>;
+    fn find_by_id(&self, id: &str) -> Result<Product, FilamentError>;
+    // `ean` is expected in the form `normalize_barcode` returns
+    fn find_by_ean(&self, ean: &str) -> Result<Product, FilamentError>;
+    fn find_all(&self) -> Result<Vec<Product>, FilamentError>;
+}
+
+// A filament as sold, used to fill in new rolls when their barcode is scanned
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct Product {
+    id: String,
+    manufacturer: String,
+    // e.g. `PolyLite PLA`, when the name does not make it obvious
+    product_line: Option<String>,
+    name: String,
+    material: String,
+    color: String,
+    diameter: f32,
+    // Filament on a full spool, in grams
+    net_weight: f32,
+    // The empty spool, in grams, for working out what is left by weighing
+    spool_weight: Option<f32>,
+    ean: Option<String>,
+}
+
+impl Product {
+    pub fn new(
+        manufacturer: &str,
+        name: &str,
+        material: &str,
+        color: &str,
+        diameter: f32,
+        net_weight: f32,
+    ) -> Result<Self, FilamentError> {
+        // Everything a roll needs, so rolls can always be made from products
+        for (field, value) in [
+            ("Manufacturer", manufacturer),
+            ("Name", name),
+            ("Material", material),
+            ("Color", color),
+        ] {
+            if value.trim().is_empty() {
+                return Err(FilamentError::InvalidData(format!(
+                    "{} cannot be empty",
+                    field
+                )));
+            }
+        }
+
+        if diameter <= 0.0 {
+            return Err(FilamentError::InvalidData(
+                "Diameter must be positive".to_string(),
+            ));
+        }
+
+        if net_weight <= 0.0 {
+            return Err(FilamentError::InvalidData(
+                "Net weight must be positive".to_string(),
+            ));
+        }
+
+        Ok(Product {
+            id: Uuid::new_v4().to_string(),
+            manufacturer: manufacturer.to_string(),
+            product_line: None,
+            name: name.to_string(),
+            material: material.to_string(),
+            color: color.to_string(),
+            diameter,
+            net_weight,
+            spool_weight: None,
+            ean: None,
+        })
+    }
+
+    // Catalogue imports keep their source's ids, so importing again updates
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.id = id.to_string();
+        self
+    }
+
+    pub fn with_product_line(mut self, product_line: &str) -> Self {
+        self.product_line = Some(product_line.to_string());
+        self
+    }
+
+    pub fn with_spool_weight(mut self, spool_weight: f32) -> Result<Self, FilamentError> {
+        if spool_weight < 0.0 {
+            return Err(FilamentError::InvalidData(
+                "Spool weight cannot be negative".to_string(),
+            ));
+        }
+        self.spool_weight = Some(spool_weight);
+        Ok(self)
+    }
+
+    // Stored normalised, so EAN-13 and UPC-A prints of one code match
+    pub fn with_ean(mut self, ean: &str) -> Result<Self, FilamentError> {
+        let ean = normalize_barcode(ean).ok_or_else(|| {
+            FilamentError::InvalidData(format!("'{}' is not a valid EAN or UPC barcode", ean))
+        })?;
+        self.ean = Some(ean);
+        Ok(self)
+    }
+
+    // Getters
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn manufacturer(&self) -> &str {
+        &self.manufacturer
+    }
+
+    pub fn product_line(&self) -> Option<&str> {
+        self.product_line.as_deref()
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn material(&self) -> &str {
+        &self.material
+    }
+
+    pub fn color(&self) -> &str {
+        &self.color
+    }
+
+    pub fn diameter(&self) -> f32 {
+        self.diameter
+    }
+
+    pub fn net_weight(&self) -> f32 {
+        self.net_weight
+    }
+
+    pub fn spool_weight(&self) -> Option<f32> {
+        self.spool_weight
+    }
+
+    pub fn ean(&self) -> Option<&str> {
+        self.ean.as_deref()
+    }
+}
+
+// Checks the digits of an EAN-13, UPC-A, EAN-8 or GTIN-14 barcode. UPC-A and
+// GTIN-14 codes with a leading zero come back as the equivalent EAN-13;
+// anything else that is not a valid code gives `None`.
+pub fn normalize_barcode(code: &str) -> Option<String> {
+    let digits: String = code
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect();
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let normalized = match digits.len() {
+        8 | 13 => digits,
+        12 => format!("0{}", digits),
+        14 if digits.starts_with('0') => digits[1..].to_string(),
+        _ => return None,
+    };
+
+    // Weights alternate 3, 1, ... leftwards from the digit before the check digit
+    let values: Vec<u32> = normalized.bytes().map(|b| (b - b'0') as u32).collect();
+    let (check, payload) = values.split_last()?;
+    let sum: u32 = payload
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, digit)| {
+            if i.is_multiple_of(2) {
+                digit * 3
+            } else {
+                *digit
+            }
+        })
+        .sum();
+
+    ((10 - sum % 10) % 10 == *check).then_some(normalized)
+}
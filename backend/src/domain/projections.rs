@@ -0,0 +1,180 @@
+use crate::domain::error::FilamentError;
+use crate::domain::events::{FilamentEvent, RecordedEvent, Snapshot};
+use crate::domain::filament::{FilamentRoll, LOW_INVENTORY_THRESHOLD};
+use std::collections::{BTreeSet, HashMap};
+
+// A read model built by folding the event stream
+pub trait Projection {
+    fn apply(&mut self, event: &RecordedEvent) -> Result<(), FilamentError>;
+}
+
+// Current state of every roll; the write model of the event-sourced repository
+#[derive(Debug, Default, Clone)]
+pub struct RollProjection {
+    version: u64,
+    rolls: HashMap<String, FilamentRoll>,
+    archived: HashMap<String, FilamentRoll>,
+}
+
+impl RollProjection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_snapshot(snapshot: Snapshot) -> Self {
+        RollProjection {
+            version: snapshot.version,
+            rolls: snapshot
+                .rolls
+                .into_iter()
+                .map(|r| (r.id().to_string(), r))
+                .collect(),
+            archived: snapshot
+                .archived
+                .into_iter()
+                .map(|r| (r.id().to_string(), r))
+                .collect(),
+        }
+    }
+
+    pub fn to_snapshot(&self) -> Snapshot {
+        Snapshot {
+            version: self.version,
+            rolls: self.rolls.values().cloned().collect(),
+            archived: self.archived.values().cloned().collect(),
+        }
+    }
+
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    pub fn get(&self, id: &str) -> Option<&FilamentRoll> {
+        self.rolls.get(id)
+    }
+
+    pub fn rolls(&self) -> impl Iterator<Item = &FilamentRoll> {
+        self.rolls.values()
+    }
+
+    pub fn archived(&self) -> impl Iterator<Item = &FilamentRoll> {
+        self.archived.values()
+    }
+
+    fn roll_mut(&mut self, id: &str) -> Result<&mut FilamentRoll, FilamentError> {
+        self.rolls
+            .get_mut(id)
+            .ok_or_else(|| FilamentError::NotFound(id.to_string()))
+    }
+}
+
+impl Projection for RollProjection {
+    fn apply(&mut self, event: &RecordedEvent) -> Result<(), FilamentError> {
+        // Events at or below the snapshot version are already folded in
+        if event.sequence() <= self.version {
+            return Ok(());
+        }
+
+        match event.event() {
+            FilamentEvent::RollRegistered { roll } | FilamentEvent::RollRevised { roll } => {
+                // Registering an archived id brings it back as a live roll
+                self.archived.remove(roll.id());
+                self.rolls.insert(roll.id().to_string(), roll.clone());
+            }
+            FilamentEvent::FilamentConsumed {
+                roll_id,
+                remaining_weight,
+                ..
+            }
+            | FilamentEvent::WeightCorrected {
+                roll_id,
+                remaining_weight,
+            } => {
+                self.roll_mut(roll_id)?
+                    .update_remaining_weight(*remaining_weight)?;
+            }
+            FilamentEvent::RollMoved {
+                roll_id,
+                storage_location,
+            } => {
+                self.roll_mut(roll_id)?.move_to(storage_location);
+            }
+            FilamentEvent::RollArchived { roll_id } => {
+                let roll = self
+                    .rolls
+                    .remove(roll_id)
+                    .ok_or_else(|| FilamentError::NotFound(roll_id.to_string()))?;
+                self.archived.insert(roll_id.clone(), roll);
+            }
+        }
+
+        self.version = event.sequence();
+        Ok(())
+    }
+}
+
+// Ids of active rolls below the low-inventory threshold
+#[derive(Debug, Default, Clone)]
+pub struct LowInventoryProjection {
+    // Total weight per active roll
+    weights: HashMap<String, f32>,
+    low: BTreeSet<String>,
+}
+
+impl LowInventoryProjection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn roll_ids(&self) -> Vec<String> {
+        self.low.iter().cloned().collect()
+    }
+
+    fn track(&mut self, roll_id: &str, weight: f32, remaining_weight: f32) {
+        self.weights.insert(roll_id.to_string(), weight);
+
+        let percentage = if weight == 0.0 {
+            0.0
+        } else {
+            (remaining_weight / weight) * 100.0
+        };
+
+        if percentage < LOW_INVENTORY_THRESHOLD {
+            self.low.insert(roll_id.to_string());
+        } else {
+            self.low.remove(roll_id);
+        }
+    }
+}
+
+impl Projection for LowInventoryProjection {
+    fn apply(&mut self, event: &RecordedEvent) -> Result<(), FilamentError> {
+        match event.event() {
+            FilamentEvent::RollRegistered { roll } | FilamentEvent::RollRevised { roll } => {
+                self.track(roll.id(), roll.weight(), roll.remaining_weight());
+            }
+            FilamentEvent::FilamentConsumed {
+                roll_id,
+                remaining_weight,
+                ..
+            }
+            | FilamentEvent::WeightCorrected {
+                roll_id,
+                remaining_weight,
+            } => {
+                let weight = self
+                    .weights
+                    .get(roll_id)
+                    .copied()
+                    .ok_or_else(|| FilamentError::NotFound(roll_id.clone()))?;
+                self.track(roll_id, weight, *remaining_weight);
+            }
+            FilamentEvent::RollMoved { .. } => {}
+            FilamentEvent::RollArchived { roll_id } => {
+                self.weights.remove(roll_id);
+                self.low.remove(roll_id);
+            }
+        }
+        Ok(())
+    }
+}
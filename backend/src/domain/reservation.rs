@@ -0,0 +1,84 @@
+use crate::domain::error::FilamentError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub trait ReservationRepository: Send + Sync {
+    fn save(&self, reservation: &Reservation) -> Result<(), FilamentError>;
+    fn find_by_id(&self, id: &str) -> Result<Reservation, FilamentError>;
+    // Includes expired reservations; callers filter with `is_active`
+    fn find_by_roll(&self, roll_id: &str) -> Result<Vec<Reservation>, FilamentError>;
+    fn delete(&self, id: &str) -> Result<(), FilamentError>;
+}
+
+// Grams set aside on a roll for a queued job until `expires_at`
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct Reservation {
+    id: String,
+    roll_id: String,
+    grams: f32,
+    // What the filament is reserved for, e.g. a job or file name
+    purpose: String,
+    created_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+}
+
+impl Reservation {
+    pub fn new(
+        roll_id: &str,
+        grams: f32,
+        purpose: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Self, FilamentError> {
+        if grams <= 0.0 {
+            return Err(FilamentError::InvalidData(
+                "Reserved grams must be positive".to_string(),
+            ));
+        }
+
+        let created_at = Utc::now();
+        if expires_at <= created_at {
+            return Err(FilamentError::InvalidData(
+                "Reservation must expire in the future".to_string(),
+            ));
+        }
+
+        Ok(Reservation {
+            id: Uuid::new_v4().to_string(),
+            roll_id: roll_id.to_string(),
+            grams,
+            purpose: purpose.to_string(),
+            created_at,
+            expires_at,
+        })
+    }
+
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at > now
+    }
+
+    // Getters
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn roll_id(&self) -> &str {
+        &self.roll_id
+    }
+
+    pub fn grams(&self) -> f32 {
+        self.grams
+    }
+
+    pub fn purpose(&self) -> &str {
+        &self.purpose
+    }
+
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        self.expires_at
+    }
+}
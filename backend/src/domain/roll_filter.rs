@@ -0,0 +1,54 @@
+use crate::domain::custom_field::CustomValue;
+use crate::domain::feasibility::normalize_color;
+use crate::domain::filament::FilamentRoll;
+use crate::domain::printer::DIAMETER_TOLERANCE;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// Criteria for picking rolls out of the inventory; unset fields match anything
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+pub struct RollFilter {
+    pub material: Option<String>,
+    pub manufacturer: Option<String>,
+    pub location: Option<String>,
+    // `#RRGGBB`, alpha ignored
+    pub color: Option<String>,
+    pub diameter: Option<f32>,
+    pub lot_number: Option<String>,
+    // Rolls must carry every one of these tags, ignoring case
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // Rolls must have each field set to a matching value of the same type
+    #[serde(default)]
+    pub custom_fields: BTreeMap<String, CustomValue>,
+}
+
+impl RollFilter {
+    pub fn matches(&self, roll: &FilamentRoll) -> bool {
+        let same = |wanted: &Option<String>, actual: &str| {
+            wanted
+                .as_ref()
+                .is_none_or(|wanted| wanted.trim().eq_ignore_ascii_case(actual.trim()))
+        };
+
+        same(&self.material, roll.material())
+            && same(&self.manufacturer, roll.manufacturer())
+            && same(&self.location, roll.storage_location())
+            && self
+                .color
+                .as_ref()
+                .is_none_or(|color| normalize_color(color) == normalize_color(roll.color()))
+            && self
+                .diameter
+                .is_none_or(|diameter| (diameter - roll.diameter()).abs() < DIAMETER_TOLERANCE)
+            && self
+                .lot_number
+                .as_ref()
+                .is_none_or(|lot| lot.trim() == roll.lot_number())
+            && self.tags.iter().all(|tag| roll.has_tag(tag))
+            && self.custom_fields.iter().all(|(name, wanted)| {
+                roll.custom_field(name)
+                    .is_some_and(|value| value.matches(wanted))
+            })
+    }
+}
@@ -0,0 +1,122 @@
+use crate::domain::filament::{FilamentRoll, FilamentRollBuilder};
+use crate::domain::product::{normalize_barcode, Product};
+use serde::{Deserialize, Serialize};
+
+// Printed on labels when no public URL is configured
+pub const ROLL_URI_PREFIX: &str = "filament-tracker:roll:";
+
+// What a roll's QR code encodes: a link into the web UI when a public URL is
+// configured, otherwise the roll id behind `ROLL_URI_PREFIX`
+pub fn roll_payload(roll_id: &str, base_url: Option<&str>) -> String {
+    match base_url {
+        Some(base) => format!("{}/rolls/{}", base.trim_end_matches('/'), roll_id),
+        None => format!("{}{}", ROLL_URI_PREFIX, roll_id),
+    }
+}
+
+// What a scanner read, before anything is looked up
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ScanCode {
+    // One of our labels, in either form `roll_payload` prints
+    Roll(String),
+    // A manufacturer EAN or UPC, normalised
+    Barcode(String),
+    // Anything else, tried as a roll id
+    Text(String),
+}
+
+impl ScanCode {
+    // Links are recognised by their `/rolls/{id}` path whatever the host, so
+    // labels keep working after the public URL changes
+    pub fn parse(payload: &str) -> Self {
+        let payload = payload.trim();
+
+        if let Some(id) = payload.strip_prefix(ROLL_URI_PREFIX) {
+            return ScanCode::Roll(id.trim().to_string());
+        }
+
+        if payload.starts_with("http://") || payload.starts_with("https://") {
+            let path = payload
+                .split(['?', '#'])
+                .next()
+                .unwrap_or_default()
+                .trim_end_matches('/');
+            if let Some((_, id)) = path.rsplit_once("/rolls/") {
+                if !id.is_empty() && !id.contains('/') {
+                    return ScanCode::Roll(id.to_string());
+                }
+            }
+        }
+
+        match normalize_barcode(payload) {
+            Some(barcode) => ScanCode::Barcode(barcode),
+            None => ScanCode::Text(payload.to_string()),
+        }
+    }
+}
+
+// The details of a roll about to be registered, for the user to confirm or
+// correct before it is saved
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct RollDraft {
+    pub name: String,
+    pub material: String,
+    pub color: String,
+    pub diameter: f32,
+    pub weight: f32,
+    pub manufacturer: String,
+    // Set when the tag identifies the spool itself, e.g. a Bambu tray UUID,
+    // so registering the same spool twice finds the roll made the first time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    // Weight of the empty spool, when the catalogue or tag gives it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spool_weight: Option<f32>,
+}
+
+impl RollDraft {
+    pub fn into_builder(self) -> FilamentRollBuilder {
+        let builder = FilamentRollBuilder::new(
+            self.name,
+            self.material,
+            self.color,
+            self.diameter,
+            self.weight,
+            self.manufacturer,
+        );
+        let builder = match self.spool_weight {
+            Some(spool_weight) => builder.with_spool_weight(spool_weight),
+            None => builder,
+        };
+        match self.id {
+            Some(id) => builder.with_id(&id),
+            None => builder,
+        }
+    }
+}
+
+impl From<&Product> for RollDraft {
+    fn from(product: &Product) -> Self {
+        RollDraft {
+            name: product.name().to_string(),
+            material: product.material().to_string(),
+            color: product.color().to_string(),
+            diameter: product.diameter(),
+            weight: product.net_weight(),
+            manufacturer: product.manufacturer().to_string(),
+            id: None,
+            spool_weight: product.spool_weight(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ScanResult {
+    // A roll already in the inventory
+    Roll { roll: FilamentRoll },
+    // A new spool of a catalogued product, ready to register
+    Product { product: Product, draft: RollDraft },
+    // A valid barcode the catalogue does not know
+    UnknownBarcode { barcode: String },
+}
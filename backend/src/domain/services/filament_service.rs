@@ -1,28 +1,569 @@
+use crate::domain::assignment::RollAssignments;
+use crate::domain::attachment::{Attachment, AttachmentStorage, RollNote};
+use crate::domain::custom_field::CustomValue;
 use crate::domain::error::FilamentError;
+use crate::domain::feasibility::{check_requirements, FeasibilityReport, FilamentRequirement};
 use crate::domain::filament::{FilamentRepository, FilamentRoll};
+// Kept reachable here for existing callers
+pub use crate::domain::filament::LOW_INVENTORY_THRESHOLD;
+use crate::domain::lot::{DefectiveLot, DefectiveLotRepository};
+use crate::domain::reservation::{Reservation, ReservationRepository};
+use crate::domain::roll_filter::RollFilter;
+use crate::domain::unit_of_work::{TransactionalFilamentRepository, UnitOfWork};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::sync::{Arc, Mutex};
 
-pub struct FilamentService<'a> {
-    repository: &'a dyn FilamentRepository,
+// What `register_roll` did with a roll
+#[derive(Debug, PartialEq, Clone)]
+pub enum Registration {
+    Created(FilamentRoll),
+    // The id was taken; holds the roll stored under it, unchanged
+    AlreadyRegistered(FilamentRoll),
 }
 
-impl<'a> FilamentService<'a> {
-    pub fn new(repository: &'a dyn FilamentRepository) -> Self {
-        FilamentService { repository }
+impl Registration {
+    pub fn roll(&self) -> &FilamentRoll {
+        match self {
+            Registration::Created(roll) | Registration::AlreadyRegistered(roll) => roll,
+        }
     }
+}
 
-    pub fn get_low_inventory(&self) -> Result<Vec<FilamentRoll>, FilamentError> {
-        // Define what "low inventory" means - less than 20% remaining
-        const LOW_INVENTORY_THRESHOLD: f32 = 20.0;
+// Cheap to clone; every clone shares the same repository
+#[derive(Clone)]
+pub struct FilamentService {
+    repository: Arc<dyn FilamentRepository>,
+    // The same repository, when it can commit several rolls at once
+    transactions: Option<Arc<dyn TransactionalFilamentRepository>>,
+    reservations: Option<Arc<dyn ReservationRepository>>,
+    defective_lots: Option<Arc<dyn DefectiveLotRepository>>,
+    attachments: Option<Arc<dyn AttachmentStorage>>,
+    assignments: Option<Arc<dyn RollAssignments>>,
+    // Serialises reserving so two jobs cannot both claim the last grams
+    reserving: Arc<Mutex<()>>,
+    // Serialises registering so two scans of one tag cannot both create it
+    registering: Arc<Mutex<()>>,
+}
 
-        // Get all filaments from the repository
-        let all_filaments = self.repository.find_all()?;
+impl FilamentService {
+    pub fn new(repository: Arc<dyn FilamentRepository>) -> Self {
+        FilamentService {
+            repository,
+            transactions: None,
+            reservations: None,
+            defective_lots: None,
+            attachments: None,
+            assignments: None,
+            reserving: Arc::new(Mutex::new(())),
+            registering: Arc::new(Mutex::new(())),
+        }
+    }
+
+    // Also allows `commit`, which needs every change to land together
+    pub fn transactional(repository: Arc<dyn TransactionalFilamentRepository>) -> Self {
+        FilamentService {
+            transactions: Some(repository.clone()),
+            ..Self::new(repository)
+        }
+    }
+
+    // Without reservations every gram remaining on a roll counts as available
+    pub fn with_reservations(mut self, reservations: Arc<dyn ReservationRepository>) -> Self {
+        self.reservations = Some(reservations);
+        self
+    }
+
+    // Without it no lot can be flagged, so every roll counts as usable
+    pub fn with_defective_lots(mut self, defective_lots: Arc<dyn DefectiveLotRepository>) -> Self {
+        self.defective_lots = Some(defective_lots);
+        self
+    }
+
+    // Without it rolls can still have notes, but not attachments
+    pub fn with_attachments(mut self, attachments: Arc<dyn AttachmentStorage>) -> Self {
+        self.attachments = Some(attachments);
+        self
+    }
 
-        // Filter for filaments with less than 20% remaining
-        let low_inventory = all_filaments
+    // Without it a roll loaded on a printer can be deleted from under it
+    pub fn with_assignments(mut self, assignments: Arc<dyn RollAssignments>) -> Self {
+        self.assignments = Some(assignments);
+        self
+    }
+
+    pub fn get_roll(&self, id: &str) -> Result<FilamentRoll, FilamentError> {
+        self.repository.find_by_id(id)
+    }
+
+    // Saves a new roll. A roll whose id is already stored is left as it is.
+    pub fn register_roll(&self, roll: &FilamentRoll) -> Result<Registration, FilamentError> {
+        let _guard = self.registering.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        match self.repository.find_by_id(roll.id()) {
+            Ok(existing) => Ok(Registration::AlreadyRegistered(existing)),
+            Err(FilamentError::NotFound(_)) => {
+                self.repository.save(roll)?;
+                Ok(Registration::Created(roll.clone()))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn list_rolls(&self) -> Result<Vec<FilamentRoll>, FilamentError> {
+        self.repository.find_all()
+    }
+
+    // Sorted by name, then id, so repeated queries list rolls in the same order
+    pub fn find_rolls(&self, filter: &RollFilter) -> Result<Vec<FilamentRoll>, FilamentError> {
+        let mut rolls: Vec<FilamentRoll> = self
+            .repository
+            .find_all()?
             .into_iter()
-            .filter(|filament| filament.percentage_remaining() < LOW_INVENTORY_THRESHOLD)
+            .filter(|roll| filter.matches(roll))
             .collect();
+        rolls.sort_by(|a, b| a.name().cmp(b.name()).then_with(|| a.id().cmp(b.id())));
+        Ok(rolls)
+    }
+
+    // Every roll from one lot, sorted like `find_rolls`
+    pub fn rolls_in_lot(&self, lot_number: &str) -> Result<Vec<FilamentRoll>, FilamentError> {
+        let mut rolls = self.repository.find_by_lot(lot_number)?;
+        rolls.sort_by(|a, b| a.name().cmp(b.name()).then_with(|| a.id().cmp(b.id())));
+        Ok(rolls)
+    }
+
+    pub fn set_lot(
+        &self,
+        id: &str,
+        lot_number: &str,
+        manufactured_on: Option<NaiveDate>,
+        received_on: Option<NaiveDate>,
+    ) -> Result<FilamentRoll, FilamentError> {
+        self.update_roll(id, |filament| {
+            filament.set_lot(lot_number, manufactured_on, received_on)
+        })
+    }
+
+    pub fn set_tags(&self, id: &str, tags: &[String]) -> Result<FilamentRoll, FilamentError> {
+        self.update_roll(id, |filament| filament.set_tags(tags))
+    }
+
+    pub fn set_custom_field(
+        &self,
+        id: &str,
+        name: &str,
+        value: CustomValue,
+    ) -> Result<FilamentRoll, FilamentError> {
+        self.update_roll(id, |filament| {
+            filament.set_custom_field(name, value.clone())
+        })
+    }
+
+    pub fn remove_custom_field(&self, id: &str, name: &str) -> Result<FilamentRoll, FilamentError> {
+        self.update_roll(id, |filament| match filament.remove_custom_field(name) {
+            Some(_) => Ok(()),
+            None => Err(FilamentError::Missing(format!(
+                "Roll '{}' has no custom field '{}'",
+                id, name
+            ))),
+        })
+    }
+
+    // Removes the roll along with every attachment file stored for it. A roll
+    // that is loaded on a printer or reserved for a job is kept; expired
+    // reservations go with it.
+    pub fn delete_roll(&self, id: &str) -> Result<(), FilamentError> {
+        // Held so no reservation can be made between the check and the delete
+        let _guard = self.reserving.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        self.repository.find_by_id(id)?;
+        if let Some(assignments) = &self.assignments {
+            if assignments.is_loaded(id)? {
+                return Err(FilamentError::InvalidData(format!(
+                    "Roll '{}' is loaded on a printer; unload it before deleting",
+                    id
+                )));
+            }
+        }
+        if let Some(reservations) = &self.reservations {
+            let now = Utc::now();
+            let stored = reservations.find_by_roll(id)?;
+            if stored.iter().any(|r| r.is_active(now)) {
+                return Err(FilamentError::InvalidData(format!(
+                    "Roll '{}' is reserved for a job; release the reservation before deleting",
+                    id
+                )));
+            }
+            for reservation in stored {
+                reservations.delete(reservation.id())?;
+            }
+        }
+
+        self.repository.delete(id)?;
+
+        match &self.attachments {
+            Some(storage) => storage.delete_roll(id),
+            None => Ok(()),
+        }
+    }
+
+    pub fn add_note(&self, id: &str, text: &str) -> Result<RollNote, FilamentError> {
+        let note = RollNote::new(text)?;
+        self.update_roll(id, |filament| {
+            filament.add_note(note.clone());
+            Ok(())
+        })?;
+        Ok(note)
+    }
+
+    pub fn remove_note(&self, id: &str, note_id: &str) -> Result<FilamentRoll, FilamentError> {
+        self.update_roll(id, |filament| match filament.remove_note(note_id) {
+            Some(_) => Ok(()),
+            None => Err(FilamentError::Missing(format!(
+                "Roll '{}' has no note '{}'",
+                id, note_id
+            ))),
+        })
+    }
+
+    // Stores the file and its thumbnail, then records the attachment on the roll
+    pub fn add_attachment(
+        &self,
+        id: &str,
+        file_name: &str,
+        content_type: &str,
+        content: &[u8],
+        thumbnail: &[u8],
+    ) -> Result<Attachment, FilamentError> {
+        let storage = self.attachment_storage()?;
+        // Nothing is stored for a roll that does not exist
+        self.repository.find_by_id(id)?;
+        let attachment = Attachment::new(file_name, content_type, content.len() as u64)?;
+
+        let stored = storage
+            .put(id, &attachment.content_name(), content)
+            .and_then(|_| storage.put(id, &attachment.thumbnail_name(), thumbnail))
+            .and_then(|_| {
+                self.update_roll(id, |filament| {
+                    filament.add_attachment(attachment.clone());
+                    Ok(())
+                })
+            });
+        if let Err(e) = stored {
+            // Best effort: the original error matters more than a leftover file
+            let _ = storage.delete(id, &attachment.content_name());
+            let _ = storage.delete(id, &attachment.thumbnail_name());
+            return Err(e);
+        }
+
+        Ok(attachment)
+    }
+
+    pub fn attachment_content(
+        &self,
+        id: &str,
+        attachment_id: &str,
+    ) -> Result<(Attachment, Vec<u8>), FilamentError> {
+        let attachment = self.find_attachment(id, attachment_id)?;
+        let content = self
+            .attachment_storage()?
+            .get(id, &attachment.content_name())?;
+        Ok((attachment, content))
+    }
+
+    // Always a PNG
+    pub fn attachment_thumbnail(
+        &self,
+        id: &str,
+        attachment_id: &str,
+    ) -> Result<Vec<u8>, FilamentError> {
+        let attachment = self.find_attachment(id, attachment_id)?;
+        self.attachment_storage()?
+            .get(id, &attachment.thumbnail_name())
+    }
+
+    pub fn remove_attachment(
+        &self,
+        id: &str,
+        attachment_id: &str,
+    ) -> Result<FilamentRoll, FilamentError> {
+        let storage = self.attachment_storage()?;
+        let mut removed = None;
+        let filament = self.update_roll(id, |filament| {
+            let attachment = filament.remove_attachment(attachment_id).ok_or_else(|| {
+                FilamentError::Missing(format!(
+                    "Roll '{}' has no attachment '{}'",
+                    id, attachment_id
+                ))
+            })?;
+            removed = Some(attachment);
+            Ok(())
+        })?;
+
+        // Files go only once the roll no longer refers to them
+        if let Some(attachment) = removed {
+            storage.delete(id, &attachment.content_name())?;
+            storage.delete(id, &attachment.thumbnail_name())?;
+        }
+        Ok(filament)
+    }
+
+    pub fn mark_lot_defective(
+        &self,
+        manufacturer: &str,
+        lot_number: &str,
+        reason: &str,
+    ) -> Result<DefectiveLot, FilamentError> {
+        let lot = DefectiveLot::new(manufacturer, lot_number, reason)?;
+        self.defective_lot_repository()?.save(&lot)?;
+        Ok(lot)
+    }
+
+    pub fn clear_defective_lot(
+        &self,
+        manufacturer: &str,
+        lot_number: &str,
+    ) -> Result<(), FilamentError> {
+        self.defective_lot_repository()?
+            .delete(manufacturer, lot_number)
+    }
+
+    // Sorted by manufacturer, then lot number
+    pub fn defective_lots(&self) -> Result<Vec<DefectiveLot>, FilamentError> {
+        let mut lots = match &self.defective_lots {
+            Some(defective_lots) => defective_lots.find_all()?,
+            None => Vec::new(),
+        };
+        lots.sort_by(|a, b| {
+            a.manufacturer()
+                .cmp(b.manufacturer())
+                .then_with(|| a.lot_number().cmp(b.lot_number()))
+        });
+        Ok(lots)
+    }
+
+    // The flag on the roll's lot, if it has been marked defective
+    pub fn defective_lot_of(
+        &self,
+        roll: &FilamentRoll,
+    ) -> Result<Option<DefectiveLot>, FilamentError> {
+        Ok(self
+            .defective_lots()?
+            .into_iter()
+            .find(|lot| lot.covers(roll)))
+    }
+
+    pub fn set_remaining_weight(
+        &self,
+        id: &str,
+        remaining_weight: f32,
+    ) -> Result<FilamentRoll, FilamentError> {
+        self.repository
+            .update_remaining_weight(id, remaining_weight)
+    }
+
+    // Deducts used filament, rejecting usage that exceeds what is left on the
+    // roll. The check and the write happen in one update, so concurrent
+    // deductions cannot both spend the same grams.
+    pub fn consume(&self, id: &str, grams: f32) -> Result<FilamentRoll, FilamentError> {
+        let unit = UnitOfWork::new().consume(id, grams);
+        self.update_roll(id, |filament| {
+            if let Some(consumed) = unit.apply(|_| Ok(filament.clone()))?.pop() {
+                *filament = consumed;
+            }
+            Ok(())
+        })
+    }
+
+    // Applies every change or none, e.g. usage across the tools of one print
+    pub fn commit(&self, unit: &UnitOfWork) -> Result<Vec<FilamentRoll>, FilamentError> {
+        self.transactions
+            .as_ref()
+            .ok_or_else(|| {
+                FilamentError::RepositoryError("Transactions are not configured".to_string())
+            })?
+            .commit(unit)
+    }
+
+    pub fn move_roll(
+        &self,
+        id: &str,
+        storage_location: &str,
+    ) -> Result<FilamentRoll, FilamentError> {
+        self.update_roll(id, |filament| {
+            filament.move_to(storage_location);
+            Ok(())
+        })
+    }
+
+    // Applies several edits to one roll as a single write, working on the
+    // roll as stored at that moment, so concurrent writes are never undone
+    pub fn update_roll(
+        &self,
+        id: &str,
+        mut change: impl FnMut(&mut FilamentRoll) -> Result<(), FilamentError>,
+    ) -> Result<FilamentRoll, FilamentError> {
+        self.repository.update(id, &mut change)
+    }
+
+    // Sets grams aside on a roll for a queued job
+    pub fn reserve(
+        &self,
+        roll_id: &str,
+        grams: f32,
+        purpose: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Reservation, FilamentError> {
+        let reservations = self.reservation_repository()?;
+        let _guard = self.reserving.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        let reservation = Reservation::new(roll_id, grams, purpose, expires_at)?;
+        let available = self.available_weight(roll_id)?;
+        if grams > available {
+            return Err(FilamentError::InvalidData(format!(
+                "Cannot reserve {} g on roll '{}': only {} g available",
+                grams, roll_id, available
+            )));
+        }
+
+        reservations.save(&reservation)?;
+        Ok(reservation)
+    }
+
+    pub fn release_reservation(&self, reservation_id: &str) -> Result<(), FilamentError> {
+        self.reservation_repository()?.delete(reservation_id)
+    }
+
+    // Active reservations on a roll, soonest to expire first
+    pub fn reservations_for(&self, roll_id: &str) -> Result<Vec<Reservation>, FilamentError> {
+        let mut active = match &self.reservations {
+            Some(reservations) => {
+                let now = Utc::now();
+                reservations
+                    .find_by_roll(roll_id)?
+                    .into_iter()
+                    .filter(|r| r.is_active(now))
+                    .collect()
+            }
+            None => Vec::new(),
+        };
+        active.sort_by_key(|r| r.expires_at());
+        Ok(active)
+    }
+
+    // Consumes filament for the job a reservation was made for and releases it
+    pub fn consume_reservation(
+        &self,
+        reservation_id: &str,
+        grams: f32,
+    ) -> Result<FilamentRoll, FilamentError> {
+        let reservations = self.reservation_repository()?;
+        // Held until the reservation is gone, so it cannot be consumed twice
+        let _guard = self.reserving.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        let reservation = reservations.find_by_id(reservation_id)?;
+        let roll = self.consume(reservation.roll_id(), grams)?;
+        reservations.delete(reservation_id)?;
+        Ok(roll)
+    }
+
+    // Grams left once active reservations are set aside
+    pub fn available_weight(&self, roll_id: &str) -> Result<f32, FilamentError> {
+        let roll = self.repository.find_by_id(roll_id)?;
+        self.available_weight_of(&roll)
+    }
+
+    pub fn get_low_inventory(&self) -> Result<Vec<FilamentRoll>, FilamentError> {
+        // Get all filaments from the repository
+        let all_filaments = self.repository.find_all()?;
+
+        // Filter for filaments with less than 20% available, so reserved
+        // filament does not hide a roll that needs restocking
+        let mut low_inventory = Vec::new();
+        for filament in all_filaments {
+            let available = self.available_weight_of(&filament)?;
+            if filament.weight() == 0.0
+                || available / filament.weight() * 100.0 < LOW_INVENTORY_THRESHOLD
+            {
+                low_inventory.push(filament);
+            }
+        }
 
         Ok(low_inventory)
     }
+
+    // Which rolls could cover each requirement, using available weight so
+    // filament reserved for other jobs is not counted. Rolls from defective
+    // lots are never suggested.
+    pub fn check_feasibility(
+        &self,
+        requirements: &[FilamentRequirement],
+    ) -> Result<FeasibilityReport, FilamentError> {
+        if let Some(requirement) = requirements.iter().find(|r| r.grams < 0.0) {
+            return Err(FilamentError::InvalidData(format!(
+                "Extruder {}: required grams cannot be negative",
+                requirement.extruder
+            )));
+        }
+
+        let defective_lots = self.defective_lots()?;
+        let mut rolls = Vec::new();
+        for roll in self.repository.find_all()? {
+            if defective_lots.iter().any(|lot| lot.covers(&roll)) {
+                continue;
+            }
+            let available = self.available_weight_of(&roll)?;
+            rolls.push((roll, available));
+        }
+
+        Ok(check_requirements(requirements, &rolls))
+    }
+
+    fn available_weight_of(&self, roll: &FilamentRoll) -> Result<f32, FilamentError> {
+        let reserved: f32 = self
+            .reservations_for(roll.id())?
+            .iter()
+            .map(Reservation::grams)
+            .sum();
+        Ok((roll.remaining_weight() - reserved).max(0.0))
+    }
+
+    fn find_attachment(&self, id: &str, attachment_id: &str) -> Result<Attachment, FilamentError> {
+        self.repository
+            .find_by_id(id)?
+            .attachment(attachment_id)
+            .cloned()
+            .ok_or_else(|| {
+                FilamentError::Missing(format!(
+                    "Roll '{}' has no attachment '{}'",
+                    id, attachment_id
+                ))
+            })
+    }
+
+    fn attachment_storage(&self) -> Result<&Arc<dyn AttachmentStorage>, FilamentError> {
+        self.attachments.as_ref().ok_or_else(|| {
+            FilamentError::RepositoryError("Attachments are not configured".to_string())
+        })
+    }
+
+    fn defective_lot_repository(&self) -> Result<&Arc<dyn DefectiveLotRepository>, FilamentError> {
+        self.defective_lots.as_ref().ok_or_else(|| {
+            FilamentError::RepositoryError("Defective lots are not configured".to_string())
+        })
+    }
+
+    fn reservation_repository(&self) -> Result<&Arc<dyn ReservationRepository>, FilamentError> {
+        self.reservations.as_ref().ok_or_else(|| {
+            FilamentError::RepositoryError("Reservations are not configured".to_string())
+        })
+    }
 }
@@ -1 +1,5 @@
 pub mod filament_service;
+pub mod print_job_service;
+pub mod printer_service;
+pub mod product_service;
+pub mod scan_service;
@@ -0,0 +1,171 @@
+use crate::domain::error::FilamentError;
+use crate::domain::filament::FilamentRoll;
+use crate::domain::print_job::{PrintJob, PrintJobRepository, PrintStatus};
+use crate::domain::printer::PrinterRepository;
+use crate::domain::unit_of_work::{TransactionalFilamentRepository, UnitOfWork};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+// Filament lost to failed and cancelled prints of one material
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct MaterialWaste {
+    pub material: String,
+    pub jobs: usize,
+    pub grams: f32,
+}
+
+// Records print jobs and deducts what they actually used. Every roll of a
+// job is deducted in one unit of work, so a rejected roll leaves all unchanged.
+#[derive(Clone)]
+pub struct PrintJobService {
+    jobs: Arc<dyn PrintJobRepository>,
+    rolls: Arc<dyn TransactionalFilamentRepository>,
+    printers: Arc<dyn PrinterRepository>,
+    // Serialises finishing so one job cannot be finished, and deducted, twice
+    finishing: Arc<Mutex<()>>,
+}
+
+impl PrintJobService {
+    pub fn new(
+        jobs: Arc<dyn PrintJobRepository>,
+        rolls: Arc<dyn TransactionalFilamentRepository>,
+        printers: Arc<dyn PrinterRepository>,
+    ) -> Self {
+        PrintJobService {
+            jobs,
+            rolls,
+            printers,
+            finishing: Arc::new(Mutex::new(())),
+        }
+    }
+
+    // Starts a job with the slicer's per-roll estimates
+    pub fn start_job(
+        &self,
+        file_name: &str,
+        printer_id: &str,
+        planned: &[(String, f32)],
+    ) -> Result<PrintJob, FilamentError> {
+        self.printers.find_by_id(printer_id)?;
+        let mut job = PrintJob::start(file_name, printer_id)?;
+        for (roll_id, grams) in planned {
+            job.plan(&self.rolls.find_by_id(roll_id)?, *grams)?;
+        }
+
+        self.jobs.save(&job)?;
+        Ok(job)
+    }
+
+    // Finishes a job and deducts the measured usage. Failed and cancelled
+    // prints deduct what they used before stopping. The job keeps the usage
+    // as measured even where a roll had less left, which is emptied instead.
+    pub fn finish_job(
+        &self,
+        job_id: &str,
+        status: PrintStatus,
+        actual: &[(String, f32)],
+    ) -> Result<PrintJob, FilamentError> {
+        let _guard = self.finishing.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        let started = self.jobs.find_by_id(job_id)?;
+        let actual = actual
+            .iter()
+            .map(|(roll_id, grams)| Ok((self.rolls.find_by_id(roll_id)?, *grams)))
+            .collect::<Result<Vec<(FilamentRoll, f32)>, FilamentError>>()?;
+
+        let mut job = started.clone();
+        job.finish(status, &actual)?;
+
+        let unit = job
+            .usage()
+            .iter()
+            .filter(|u| u.actual_grams.unwrap_or(0.0) > 0.0)
+            .fold(UnitOfWork::new(), |unit, u| {
+                unit.consume_available(&u.roll_id, u.actual_grams.unwrap_or(0.0))
+            });
+
+        // Mark the job finished first so a retry after a crash cannot deduct
+        // twice; put it back if the deduction is rejected
+        self.jobs.save(&job)?;
+        if let Err(e) = self.rolls.commit(&unit) {
+            self.jobs.save(&started)?;
+            return Err(e);
+        }
+
+        Ok(job)
+    }
+
+    // Finishes a job that reports progress rather than measured usage, e.g. a
+    // print that failed at 40% used 40% of each planned amount
+    pub fn finish_job_at_progress(
+        &self,
+        job_id: &str,
+        status: PrintStatus,
+        percent_complete: f32,
+    ) -> Result<PrintJob, FilamentError> {
+        if !(0.0..=100.0).contains(&percent_complete) {
+            return Err(FilamentError::InvalidData(
+                "Progress must be between 0 and 100 percent".to_string(),
+            ));
+        }
+
+        let actual: Vec<(String, f32)> = self
+            .jobs
+            .find_by_id(job_id)?
+            .usage()
+            .iter()
+            .map(|u| {
+                (
+                    u.roll_id.clone(),
+                    u.planned_grams * percent_complete / 100.0,
+                )
+            })
+            .collect();
+
+        self.finish_job(job_id, status, &actual)
+    }
+
+    pub fn get_job(&self, id: &str) -> Result<PrintJob, FilamentError> {
+        self.jobs.find_by_id(id)
+    }
+
+    // Newest first
+    pub fn list_jobs(&self) -> Result<Vec<PrintJob>, FilamentError> {
+        let mut jobs = self.jobs.find_all()?;
+        jobs.sort_by_key(|job| Reverse(job.started_at()));
+        Ok(jobs)
+    }
+
+    // Grams used by failed and cancelled prints, per material, sorted by material
+    pub fn waste_by_material(&self) -> Result<Vec<MaterialWaste>, FilamentError> {
+        let mut waste: BTreeMap<String, MaterialWaste> = BTreeMap::new();
+
+        for job in self.jobs.find_all()? {
+            if !matches!(job.status(), PrintStatus::Failed | PrintStatus::Cancelled) {
+                continue;
+            }
+
+            let mut materials_in_job = Vec::new();
+            for usage in job.usage() {
+                let entry = waste
+                    .entry(usage.material.clone())
+                    .or_insert_with(|| MaterialWaste {
+                        material: usage.material.clone(),
+                        jobs: 0,
+                        grams: 0.0,
+                    });
+                entry.grams += usage.actual_grams.unwrap_or(0.0);
+                if !materials_in_job.contains(&usage.material) {
+                    entry.jobs += 1;
+                    materials_in_job.push(usage.material.clone());
+                }
+            }
+        }
+
+        Ok(waste.into_values().collect())
+    }
+}
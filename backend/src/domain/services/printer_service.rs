@@ -0,0 +1,133 @@
+use crate::domain::assignment::RollAssignments;
+use crate::domain::error::FilamentError;
+use crate::domain::filament::FilamentRepository;
+use crate::domain::printer::{Printer, PrinterRepository};
+use std::sync::{Arc, Mutex};
+
+// Where a roll is currently loaded
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RollLocation {
+    pub printer_id: String,
+    pub tool: u32,
+}
+
+// Loads and unloads rolls on printers. Implements `RollAssignments`, so the
+// OctoPrint and Moonraker integrations get the same checks.
+#[derive(Clone)]
+pub struct PrinterService {
+    printers: Arc<dyn PrinterRepository>,
+    rolls: Arc<dyn FilamentRepository>,
+    // Serialises loads and unloads so two requests cannot put one roll in two places
+    changes: Arc<Mutex<()>>,
+}
+
+impl PrinterService {
+    pub fn new(printers: Arc<dyn PrinterRepository>, rolls: Arc<dyn FilamentRepository>) -> Self {
+        PrinterService {
+            printers,
+            rolls,
+            changes: Arc::new(Mutex::new(())),
+        }
+    }
+
+    // Adds a printer. Its toolheads must start empty; use `load_roll` to fill them.
+    pub fn register_printer(&self, printer: &Printer) -> Result<(), FilamentError> {
+        if printer
+            .toolheads()
+            .iter()
+            .any(|t| t.loaded_roll().is_some())
+        {
+            return Err(FilamentError::InvalidData(
+                "New printers must be registered with empty toolheads".to_string(),
+            ));
+        }
+
+        match self.printers.find_by_id(printer.id()) {
+            Ok(_) => Err(FilamentError::InvalidData(format!(
+                "Printer '{}' already exists",
+                printer.id()
+            ))),
+            Err(FilamentError::Missing(_)) => self.printers.save(printer),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn get_printer(&self, id: &str) -> Result<Printer, FilamentError> {
+        self.printers.find_by_id(id)
+    }
+
+    pub fn list_printers(&self) -> Result<Vec<Printer>, FilamentError> {
+        self.printers.find_all()
+    }
+
+    pub fn load_roll(
+        &self,
+        printer_id: &str,
+        tool: u32,
+        roll_id: &str,
+    ) -> Result<Printer, FilamentError> {
+        let _guard = self.changes.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        let roll = self.rolls.find_by_id(roll_id)?;
+        let mut printer = self.printers.find_by_id(printer_id)?;
+
+        if let Some(location) = self.find_roll_location(roll_id)? {
+            if location.printer_id != printer_id || location.tool != tool {
+                return Err(FilamentError::InvalidData(format!(
+                    "Roll '{}' is already loaded on tool {} of printer '{}'",
+                    roll_id, location.tool, location.printer_id
+                )));
+            }
+        }
+
+        printer.load(tool, &roll)?;
+        self.printers.save(&printer)?;
+        Ok(printer)
+    }
+
+    pub fn unload_roll(&self, printer_id: &str, tool: u32) -> Result<Printer, FilamentError> {
+        let _guard = self.changes.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        let mut printer = self.printers.find_by_id(printer_id)?;
+        printer.unload(tool)?;
+        self.printers.save(&printer)?;
+        Ok(printer)
+    }
+
+    pub fn find_roll_location(&self, roll_id: &str) -> Result<Option<RollLocation>, FilamentError> {
+        Ok(self.printers.find_all()?.iter().find_map(|printer| {
+            printer.tool_holding(roll_id).map(|tool| RollLocation {
+                printer_id: printer.id().to_string(),
+                tool,
+            })
+        }))
+    }
+}
+
+impl RollAssignments for PrinterService {
+    fn loaded_roll(&self, printer: &str, tool: u32) -> Result<Option<String>, FilamentError> {
+        let printer = self.printers.find_by_id(printer)?;
+        Ok(printer.toolhead(tool)?.loaded_roll().map(str::to_string))
+    }
+
+    fn load_roll(&self, printer: &str, tool: u32, roll_id: &str) -> Result<(), FilamentError> {
+        PrinterService::load_roll(self, printer, tool, roll_id).map(|_| ())
+    }
+
+    fn unload(&self, printer: &str, tool: u32) -> Result<(), FilamentError> {
+        self.unload_roll(printer, tool).map(|_| ())
+    }
+
+    fn is_loaded(&self, roll_id: &str) -> Result<bool, FilamentError> {
+        Ok(self.find_roll_location(roll_id)?.is_some())
+    }
+
+    fn tool_count(&self, printer: &str) -> Result<Option<u32>, FilamentError> {
+        let printer = self.printers.find_by_id(printer)?;
+        Ok(Some(printer.toolheads().len() as u32))
+    }
+}
@@ -0,0 +1,92 @@
+use crate::domain::error::FilamentError;
+use crate::domain::filament::FilamentRoll;
+use crate::domain::product::{Product, ProductCatalogue};
+use crate::domain::scan::RollDraft;
+use crate::domain::services::filament_service::FilamentService;
+use std::sync::{Arc, Mutex};
+
+// The catalogue of filament products, and new rolls made from its entries
+#[derive(Clone)]
+pub struct ProductService {
+    products: Arc<dyn ProductCatalogue>,
+    rolls: FilamentService,
+    // Serialises adding so two products cannot claim one id or barcode
+    adding: Arc<Mutex<()>>,
+}
+
+impl ProductService {
+    pub fn new(products: Arc<dyn ProductCatalogue>, rolls: FilamentService) -> Self {
+        ProductService {
+            products,
+            rolls,
+            adding: Arc::new(Mutex::new(())),
+        }
+    }
+
+    // A barcode may only identify one product
+    pub fn add_product(&self, product: &Product) -> Result<(), FilamentError> {
+        let _guard = self.adding.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        match self.products.find_by_id(product.id()) {
+            Ok(_) => {
+                return Err(FilamentError::InvalidData(format!(
+                    "Product '{}' already exists",
+                    product.id()
+                )))
+            }
+            Err(FilamentError::Missing(_)) => {}
+            Err(e) => return Err(e),
+        }
+
+        if let Some(ean) = product.ean() {
+            match self.products.find_by_ean(ean) {
+                Ok(other) => {
+                    return Err(FilamentError::InvalidData(format!(
+                        "Barcode {} already belongs to product '{}'",
+                        ean,
+                        other.id()
+                    )))
+                }
+                Err(FilamentError::Missing(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        self.products.save(product)
+    }
+
+    pub fn get_product(&self, id: &str) -> Result<Product, FilamentError> {
+        self.products.find_by_id(id)
+    }
+
+    // Sorted by manufacturer, then name
+    pub fn list_products(&self) -> Result<Vec<Product>, FilamentError> {
+        let mut products = self.products.find_all()?;
+        products.sort_by(|a, b| {
+            (a.manufacturer(), a.name(), a.id()).cmp(&(b.manufacturer(), b.name(), b.id()))
+        });
+        Ok(products)
+    }
+
+    // A new roll of the product, full unless `remaining_weight` says otherwise,
+    // on the catalogue's spool
+    pub fn create_roll(
+        &self,
+        product_id: &str,
+        storage_location: &str,
+        remaining_weight: Option<f32>,
+    ) -> Result<FilamentRoll, FilamentError> {
+        let product = self.products.find_by_id(product_id)?;
+        let mut builder = RollDraft::from(&product)
+            .into_builder()
+            .with_storage_location(storage_location);
+        if let Some(remaining_weight) = remaining_weight {
+            builder = builder.with_remaining_weight(remaining_weight);
+        }
+
+        let roll = builder.build()?;
+        Ok(self.rolls.register_roll(&roll)?.roll().clone())
+    }
+}
@@ -0,0 +1,62 @@
+use crate::domain::error::FilamentError;
+use crate::domain::product::ProductCatalogue;
+use crate::domain::scan::{RollDraft, ScanCode, ScanResult};
+use crate::domain::services::filament_service::{FilamentService, Registration};
+use std::sync::Arc;
+
+// Turns what a barcode scanner reads into a roll, or into a draft of a new
+// roll that only needs confirming
+#[derive(Clone)]
+pub struct ScanService {
+    rolls: FilamentService,
+    products: Arc<dyn ProductCatalogue>,
+}
+
+impl ScanService {
+    pub fn new(rolls: FilamentService, products: Arc<dyn ProductCatalogue>) -> Self {
+        ScanService { rolls, products }
+    }
+
+    pub fn resolve(&self, payload: &str) -> Result<ScanResult, FilamentError> {
+        if payload.trim().is_empty() {
+            return Err(FilamentError::InvalidData(
+                "Scanned payload is empty".to_string(),
+            ));
+        }
+
+        match ScanCode::parse(payload) {
+            ScanCode::Roll(id) | ScanCode::Text(id) => Ok(ScanResult::Roll {
+                roll: self.rolls.get_roll(&id)?,
+            }),
+            ScanCode::Barcode(barcode) => match self.products.find_by_ean(&barcode) {
+                Ok(product) => Ok(ScanResult::Product {
+                    draft: RollDraft::from(&product),
+                    product,
+                }),
+                // Roll ids may be all digits too, e.g. ones carried over from
+                // another inventory
+                Err(FilamentError::Missing(_)) => match self.rolls.get_roll(payload.trim()) {
+                    Ok(roll) => Ok(ScanResult::Roll { roll }),
+                    Err(FilamentError::NotFound(_)) => Ok(ScanResult::UnknownBarcode { barcode }),
+                    Err(e) => Err(e),
+                },
+                Err(e) => Err(e),
+            },
+        }
+    }
+
+    // The confirm step: saves the draft, as corrected by the user, as a new
+    // full roll. A draft whose id is already registered, e.g. a tag scanned
+    // twice, gives back the stored roll instead.
+    pub fn register(
+        &self,
+        draft: RollDraft,
+        storage_location: &str,
+    ) -> Result<Registration, FilamentError> {
+        let roll = draft
+            .into_builder()
+            .with_storage_location(storage_location)
+            .build()?;
+        self.rolls.register_roll(&roll)
+    }
+}
@@ -0,0 +1,147 @@
+use crate::domain::error::FilamentError;
+use crate::domain::filament::{FilamentRepository, FilamentRoll};
+
+// Repositories that can apply a batch of changes atomically.
+// A SQL backend maps `commit` onto a single database transaction.
+pub trait TransactionalFilamentRepository: FilamentRepository {
+    // Applies every change or none of them; returns the updated rolls
+    fn commit(&self, unit: &UnitOfWork) -> Result<Vec<FilamentRoll>, FilamentError>;
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum WeightChange {
+    SetRemainingWeight {
+        roll_id: String,
+        remaining_weight: f32,
+    },
+    Consume {
+        roll_id: String,
+        grams: f32,
+    },
+    // Deducts up to `grams`, emptying the roll rather than failing when less is left
+    ConsumeAvailable {
+        roll_id: String,
+        grams: f32,
+    },
+}
+
+impl WeightChange {
+    pub fn roll_id(&self) -> &str {
+        match self {
+            WeightChange::SetRemainingWeight { roll_id, .. }
+            | WeightChange::Consume { roll_id, .. }
+            | WeightChange::ConsumeAvailable { roll_id, .. } => roll_id,
+        }
+    }
+}
+
+// A batch of weight changes across rolls, e.g. every extruder of a multi-material print
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct UnitOfWork {
+    changes: Vec<WeightChange>,
+}
+
+impl UnitOfWork {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update_remaining_weight(mut self, roll_id: &str, remaining_weight: f32) -> Self {
+        self.changes.push(WeightChange::SetRemainingWeight {
+            roll_id: roll_id.to_string(),
+            remaining_weight,
+        });
+        self
+    }
+
+    pub fn consume(mut self, roll_id: &str, grams: f32) -> Self {
+        self.changes.push(WeightChange::Consume {
+            roll_id: roll_id.to_string(),
+            grams,
+        });
+        self
+    }
+
+    // For usage that already happened, e.g. a finished print, where a roll
+    // weighing less than tracked should not lose the record of it
+    pub fn consume_available(mut self, roll_id: &str, grams: f32) -> Self {
+        self.changes.push(WeightChange::ConsumeAvailable {
+            roll_id: roll_id.to_string(),
+            grams,
+        });
+        self
+    }
+
+    pub fn changes(&self) -> &[WeightChange] {
+        &self.changes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    // Applies the changes to working copies loaded through `load`, without
+    // touching storage. Returns each affected roll once, in first-touched order,
+    // or the first validation error.
+    pub fn apply<F>(&self, mut load: F) -> Result<Vec<FilamentRoll>, FilamentError>
+    where
+        F: FnMut(&str) -> Result<FilamentRoll, FilamentError>,
+    {
+        let mut working: Vec<FilamentRoll> = Vec::new();
+
+        for change in &self.changes {
+            let index = match working.iter().position(|r| r.id() == change.roll_id()) {
+                Some(index) => index,
+                None => {
+                    working.push(load(change.roll_id())?);
+                    working.len() - 1
+                }
+            };
+            let roll = &mut working[index];
+
+            let new_weight = match change {
+                WeightChange::SetRemainingWeight {
+                    remaining_weight, ..
+                } => *remaining_weight,
+                WeightChange::Consume { grams, .. } => {
+                    if *grams < 0.0 {
+                        return Err(FilamentError::InvalidData(format!(
+                            "Roll '{}': consumed grams cannot be negative",
+                            change.roll_id()
+                        )));
+                    }
+                    if *grams > roll.remaining_weight() {
+                        return Err(FilamentError::InvalidData(format!(
+                            "Cannot consume {} g from roll '{}': only {} g remaining",
+                            grams,
+                            change.roll_id(),
+                            roll.remaining_weight()
+                        )));
+                    }
+                    roll.remaining_weight() - grams
+                }
+                WeightChange::ConsumeAvailable { grams, .. } => {
+                    if *grams < 0.0 {
+                        return Err(FilamentError::InvalidData(format!(
+                            "Roll '{}': consumed grams cannot be negative",
+                            change.roll_id()
+                        )));
+                    }
+                    (roll.remaining_weight() - grams).max(0.0)
+                }
+            };
+
+            // Name the failing roll so a rejected batch is easy to diagnose
+            let roll_id = change.roll_id();
+            roll.update_remaining_weight(new_weight)
+                .map_err(|e| match e {
+                    FilamentError::InvalidData(msg) => {
+                        FilamentError::InvalidData(format!("Roll '{}': {}", roll_id, msg))
+                    }
+                    other => other,
+                })?;
+        }
+
+        Ok(working)
+    }
+}
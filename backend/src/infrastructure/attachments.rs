@@ -0,0 +1,128 @@
+use crate::domain::attachment::AttachmentStorage;
+use crate::domain::error::FilamentError;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader};
+use std::fs;
+use std::io::{Cursor, ErrorKind};
+use std::path::PathBuf;
+
+// Longest side of a generated thumbnail, in pixels
+pub const THUMBNAIL_SIZE: u32 = 256;
+
+// Keeps each roll's files in its own directory under `root`
+pub struct LocalAttachmentStorage {
+    root: PathBuf,
+}
+
+impl LocalAttachmentStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        LocalAttachmentStorage { root: root.into() }
+    }
+
+    fn roll_dir(&self, roll_id: &str) -> Result<PathBuf, FilamentError> {
+        Ok(self.root.join(path_segment(roll_id)?))
+    }
+
+    fn file_path(&self, roll_id: &str, name: &str) -> Result<PathBuf, FilamentError> {
+        Ok(self.roll_dir(roll_id)?.join(path_segment(name)?))
+    }
+}
+
+impl AttachmentStorage for LocalAttachmentStorage {
+    fn put(&self, roll_id: &str, name: &str, content: &[u8]) -> Result<(), FilamentError> {
+        let path = self.file_path(roll_id, name)?;
+        fs::create_dir_all(self.roll_dir(roll_id)?).map_err(io_error)?;
+
+        // Write then rename so a crash never leaves a truncated file
+        let temp_path = path.with_extension("tmp");
+        fs::write(&temp_path, content).map_err(io_error)?;
+        fs::rename(&temp_path, &path).map_err(io_error)
+    }
+
+    fn get(&self, roll_id: &str, name: &str) -> Result<Vec<u8>, FilamentError> {
+        fs::read(self.file_path(roll_id, name)?).map_err(|e| match e.kind() {
+            ErrorKind::NotFound => FilamentError::Missing(format!(
+                "Roll '{}' has no attachment file '{}'",
+                roll_id, name
+            )),
+            _ => io_error(e),
+        })
+    }
+
+    fn delete(&self, roll_id: &str, name: &str) -> Result<(), FilamentError> {
+        match fs::remove_file(self.file_path(roll_id, name)?) {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(io_error(e)),
+            _ => Ok(()),
+        }
+    }
+
+    fn delete_roll(&self, roll_id: &str) -> Result<(), FilamentError> {
+        match fs::remove_dir_all(self.roll_dir(roll_id)?) {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(io_error(e)),
+            _ => Ok(()),
+        }
+    }
+}
+
+// MIME type of an uploaded image, judged by its content rather than by what
+// the client claims
+pub fn image_content_type(content: &[u8]) -> Result<&'static str, FilamentError> {
+    match image::guess_format(content) {
+        Ok(format @ (ImageFormat::Jpeg | ImageFormat::Png | ImageFormat::WebP)) => {
+            Ok(format.to_mime_type())
+        }
+        _ => Err(FilamentError::InvalidData(
+            "Attachments must be JPEG, PNG or WebP images".to_string(),
+        )),
+    }
+}
+
+// PNG fitting within THUMBNAIL_SIZE on both sides, upright even when a camera
+// recorded the rotation in EXIF instead of the pixels
+pub fn thumbnail(content: &[u8]) -> Result<Vec<u8>, FilamentError> {
+    let unreadable =
+        |e: image::ImageError| FilamentError::InvalidData(format!("Failed to read image: {}", e));
+
+    let mut decoder = ImageReader::new(Cursor::new(content))
+        .with_guessed_format()
+        .map_err(io_error)?
+        .into_decoder()
+        .map_err(unreadable)?;
+    let orientation = decoder.orientation().map_err(unreadable)?;
+    let mut image = DynamicImage::from_decoder(decoder).map_err(unreadable)?;
+    image.apply_orientation(orientation);
+
+    // Small images are kept as they are rather than scaled up
+    if image.width() > THUMBNAIL_SIZE || image.height() > THUMBNAIL_SIZE {
+        image = image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE);
+    }
+
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to encode thumbnail: {}", e))
+        })?;
+    Ok(png)
+}
+
+// Roll ids and names come from users, so everything but ASCII letters, digits,
+// '-' and '_' is percent-encoded; separators and `..` can never reach the path
+fn path_segment(text: &str) -> Result<String, FilamentError> {
+    if text.is_empty() {
+        return Err(FilamentError::InvalidData(
+            "Attachment path cannot be empty".to_string(),
+        ));
+    }
+
+    Ok(text
+        .bytes()
+        .map(|byte| match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect())
+}
+
+fn io_error(e: std::io::Error) -> FilamentError {
+    FilamentError::RepositoryError(format!("I/O error: {}", e))
+}
@@ -0,0 +1,110 @@
+use crate::domain::error::FilamentError;
+use serde_json::Value;
+
+// Each AMS unit has four trays; Bambu numbers trays globally as unit * 4 + tray
+pub const TRAYS_PER_AMS: u32 = 4;
+
+// RFID readers report this when the spool has no Bambu tag
+const EMPTY_TAG_UID: &str = "0000000000000000";
+
+// One AMS tray as reported in `print.ams.ams[].tray[]`
+#[derive(Debug, PartialEq, Clone)]
+pub struct AmsTray {
+    pub ams: u32,
+    pub tray: u32,
+    // None when the tray is empty
+    pub tray_type: Option<String>,
+    // `#RRGGBB`; the alpha byte Bambu appends is dropped
+    pub color: Option<String>,
+    // Percent left, None when the printer cannot tell (non-Bambu spools report -1)
+    pub remain: Option<f32>,
+    pub tag_uid: Option<String>,
+}
+
+impl AmsTray {
+    // The printer-wide tray number, used as the tool for roll assignments
+    pub fn tool(&self) -> u32 {
+        self.ams * TRAYS_PER_AMS + self.tray
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tray_type.is_none()
+    }
+}
+
+// Parses a message from `device/<serial>/report`. Returns None for messages
+// without AMS state, which covers most of the printer's status updates.
+pub fn parse_report(payload: &[u8]) -> Result<Option<Vec<AmsTray>>, FilamentError> {
+    let document: Value = serde_json::from_slice(payload)
+        .map_err(|e| FilamentError::InvalidData(format!("Invalid Bambu report: {}", e)))?;
+
+    let units = match document.pointer("/print/ams/ams").and_then(Value::as_array) {
+        Some(units) => units,
+        None => return Ok(None),
+    };
+
+    let mut trays = Vec::new();
+    for unit in units {
+        let ams = unit
+            .get("id")
+            .and_then(index)
+            .ok_or_else(|| FilamentError::InvalidData("AMS unit without an id".to_string()))?;
+
+        for tray in unit
+            .get("tray")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let tray_index = tray.get("id").and_then(index).ok_or_else(|| {
+                FilamentError::InvalidData(format!("Tray without an id in AMS {}", ams))
+            })?;
+
+            trays.push(AmsTray {
+                ams,
+                tray: tray_index,
+                tray_type: tray.get("tray_type").and_then(text),
+                color: tray
+                    .get("tray_color")
+                    .and_then(text)
+                    .filter(|c| c.len() >= 6)
+                    .map(|c| format!("#{}", c[..6].to_uppercase())),
+                remain: tray
+                    .get("remain")
+                    .and_then(number)
+                    .filter(|remain| *remain >= 0.0),
+                tag_uid: tray
+                    .get("tag_uid")
+                    .and_then(text)
+                    .filter(|uid| uid != EMPTY_TAG_UID),
+            });
+        }
+    }
+
+    Ok(Some(trays))
+}
+
+// Bambu sends ids as strings ("0") and most numbers as either strings or numbers
+fn index(value: &Value) -> Option<u32> {
+    match value {
+        Value::Number(n) => n.as_u64().map(|n| n as u32),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn text(value: &Value) -> Option<String> {
+    value
+        .as_str()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+fn number(value: &Value) -> Option<f32> {
+    match value {
+        Value::Number(n) => n.as_f64().map(|n| n as f32),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
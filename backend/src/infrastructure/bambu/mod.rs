@@ -0,0 +1,5 @@
+pub mod ams;
+pub mod mqtt;
+pub mod reconcile;
+pub mod rfid;
+pub mod tags;
@@ -0,0 +1,204 @@
+use crate::domain::error::FilamentError;
+use crate::infrastructure::bambu::ams::{parse_report, AmsTray};
+use crate::infrastructure::bambu::reconcile::{AmsReconciler, AmsReport};
+use actix_web::rt::task;
+use rumqttc::tokio_rustls::rustls::client::danger::{
+    HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier,
+};
+use rumqttc::tokio_rustls::rustls::crypto::{
+    ring, verify_tls12_signature, verify_tls13_signature, WebPkiSupportedAlgorithms,
+};
+use rumqttc::tokio_rustls::rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rumqttc::tokio_rustls::rustls::{
+    ClientConfig, DigitallySignedStruct, Error as TlsError, SignatureScheme,
+};
+use rumqttc::{
+    AsyncClient, Event, EventLoop, MqttOptions, Packet, QoS, TlsConfiguration, Transport,
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+// Bambu printers accept the LAN access code as the password for this user
+const BAMBU_USERNAME: &str = "bblp";
+
+// The printer's MQTT over TLS port
+pub const BAMBU_MQTT_PORT: u16 = 8883;
+
+// Full status reports can be tens of kilobytes
+const MAX_PACKET_SIZE: usize = 256 * 1024;
+
+// Asks the printer to publish its full state instead of waiting for changes
+const PUSH_ALL: &str = r#"{"pushing":{"sequence_id":"0","command":"pushall"}}"#;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BambuMqttConfig {
+    pub host: String,
+    pub port: u16,
+    pub serial: String,
+    // LAN access code from the printer's screen; None for brokers without auth
+    pub access_code: Option<String>,
+    // The printer only accepts TLS; plain TCP is for a local broker bridging it
+    pub tls: bool,
+}
+
+impl BambuMqttConfig {
+    pub fn report_topic(&self) -> String {
+        format!("device/{}/report", self.serial)
+    }
+
+    pub fn request_topic(&self) -> String {
+        format!("device/{}/request", self.serial)
+    }
+}
+
+// Subscribes to a printer's report topic, either on the printer itself over
+// TLS or on a broker that bridges it.
+pub struct BambuAmsConnector {
+    config: BambuMqttConfig,
+    client: AsyncClient,
+    events: EventLoop,
+}
+
+impl BambuAmsConnector {
+    pub async fn connect(config: BambuMqttConfig) -> Result<Self, FilamentError> {
+        let mut options = MqttOptions::new(
+            format!("filament-tracker-{}", config.serial),
+            config.host.clone(),
+            config.port,
+        );
+        options
+            .set_keep_alive(Duration::from_secs(30))
+            .set_max_packet_size(MAX_PACKET_SIZE, MAX_PACKET_SIZE);
+        if config.tls {
+            options.set_transport(Transport::tls_with_config(TlsConfiguration::Rustls(
+                Arc::new(printer_tls()),
+            )));
+        } else if config.access_code.is_some() {
+            return Err(FilamentError::InvalidData(
+                "The access code is only sent over TLS".to_string(),
+            ));
+        }
+        if let Some(access_code) = &config.access_code {
+            options.set_credentials(BAMBU_USERNAME, access_code.clone());
+        }
+
+        let (client, events) = AsyncClient::new(options, 10);
+        client
+            .subscribe(config.report_topic(), QoS::AtMostOnce)
+            .await
+            .map_err(mqtt_error)?;
+        client
+            .publish(config.request_topic(), QoS::AtMostOnce, false, PUSH_ALL)
+            .await
+            .map_err(mqtt_error)?;
+
+        Ok(BambuAmsConnector {
+            config,
+            client,
+            events,
+        })
+    }
+
+    // Waits for the next report that carries AMS state, skipping the rest
+    pub async fn next_trays(&mut self) -> Result<Vec<AmsTray>, FilamentError> {
+        let topic = self.config.report_topic();
+
+        loop {
+            let event = self.events.poll().await.map_err(mqtt_error)?;
+            if let Event::Incoming(Packet::Publish(publish)) = event {
+                if publish.topic != topic {
+                    continue;
+                }
+                if let Some(trays) = parse_report(&publish.payload)? {
+                    return Ok(trays);
+                }
+            }
+        }
+    }
+
+    // Reconciles every AMS report until the connection fails, handing each
+    // outcome to `on_report`. Reports that cannot be parsed are handed over
+    // as errors and skipped rather than stopping.
+    pub async fn run(
+        mut self,
+        reconciler: AmsReconciler,
+        mut on_report: impl FnMut(Result<AmsReport, FilamentError>),
+    ) -> Result<(), FilamentError> {
+        loop {
+            let trays = match self.next_trays().await {
+                Ok(trays) => trays,
+                Err(e @ FilamentError::InvalidData(_)) => {
+                    on_report(Err(e));
+                    continue;
+                }
+                Err(e) => {
+                    let _ = self.client.disconnect().await;
+                    return Err(e);
+                }
+            };
+
+            let reconciler = reconciler.clone();
+            let report = task::spawn_blocking(move || reconciler.reconcile(&trays))
+                .await
+                .map_err(|e| {
+                    FilamentError::RepositoryError(format!("Blocking task failed: {}", e))
+                })?;
+            on_report(report);
+        }
+    }
+}
+
+// Printers present a certificate signed by Bambu's own CA for their serial
+// number rather than their address, so the chain cannot be checked against
+// public roots. The connection is still encrypted and the handshake signed by
+// the certificate's key.
+fn printer_tls() -> ClientConfig {
+    ClientConfig::builder()
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(PrinterCertificate(
+            ring::default_provider().signature_verification_algorithms,
+        )))
+        .with_no_client_auth()
+}
+
+#[derive(Debug)]
+struct PrinterCertificate(WebPkiSupportedAlgorithms);
+
+impl ServerCertVerifier for PrinterCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, TlsError> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, TlsError> {
+        verify_tls12_signature(message, cert, dss, &self.0)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, TlsError> {
+        verify_tls13_signature(message, cert, dss, &self.0)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.supported_schemes()
+    }
+}
+
+fn mqtt_error(e: impl std::fmt::Display) -> FilamentError {
+    FilamentError::RepositoryError(format!("Bambu MQTT error: {}", e))
+}
@@ -0,0 +1,241 @@
+use crate::domain::assignment::RollAssignments;
+use crate::domain::error::FilamentError;
+use crate::domain::filament::FilamentRoll;
+use crate::domain::services::filament_service::FilamentService;
+use crate::infrastructure::bambu::ams::AmsTray;
+use crate::infrastructure::bambu::tags::{InMemoryTrayTags, TrayTag, TrayTagStore};
+use std::sync::Arc;
+
+// How far, in percent of the roll's weight, the AMS estimate may drift from
+// the tracked weight before it is flagged instead of applied
+pub const DEFAULT_TOLERANCE_PERCENT: f32 = 10.0;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct WeightUpdate {
+    pub tool: u32,
+    pub roll_id: String,
+    pub from: f32,
+    pub to: f32,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum DiscrepancyKind {
+    // The tray holds a different material than the assigned roll
+    MaterialMismatch {
+        tray_type: String,
+        roll_material: String,
+    },
+    // The AMS estimate is further from the tracked weight than the tolerance
+    WeightMismatch {
+        tracked: f32,
+        reported: f32,
+    },
+    // The RFID tag changed, so the spool was probably swapped without
+    // updating the assignment
+    TagChanged {
+        previous: String,
+        current: String,
+    },
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Discrepancy {
+    pub tool: u32,
+    pub roll_id: String,
+    pub kind: DiscrepancyKind,
+}
+
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct AmsReport {
+    pub updated: Vec<WeightUpdate>,
+    pub discrepancies: Vec<Discrepancy>,
+    // Loaded trays with no roll assigned
+    pub unassigned: Vec<AmsTray>,
+}
+
+// Compares AMS tray state with the rolls assigned to the printer's trays.
+// Small drops in the AMS estimate are applied, since a Bambu printer reports
+// no per-job usage otherwise; anything else is reported for a person to check.
+#[derive(Clone)]
+pub struct AmsReconciler {
+    printer: String,
+    service: FilamentService,
+    assignments: Arc<dyn RollAssignments>,
+    tolerance_percent: f32,
+    tags: Arc<dyn TrayTagStore>,
+}
+
+// What one tray's report means for its assigned roll
+enum TrayOutcome {
+    Flagged(DiscrepancyKind),
+    Updated { from: f32, to: f32 },
+    Unchanged,
+}
+
+impl AmsReconciler {
+    pub fn new(
+        printer: &str,
+        service: FilamentService,
+        assignments: Arc<dyn RollAssignments>,
+    ) -> Self {
+        AmsReconciler {
+            printer: printer.to_string(),
+            service,
+            assignments,
+            tolerance_percent: DEFAULT_TOLERANCE_PERCENT,
+            tags: Arc::new(InMemoryTrayTags::new()),
+        }
+    }
+
+    // Keeps accepted tags somewhere that outlives the process, so a swap
+    // flagged before a restart is still flagged after it
+    pub fn with_tag_store(mut self, tags: Arc<dyn TrayTagStore>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    pub fn with_tolerance(mut self, tolerance_percent: f32) -> Self {
+        self.tolerance_percent = tolerance_percent;
+        self
+    }
+
+    pub fn reconcile(&self, trays: &[AmsTray]) -> Result<AmsReport, FilamentError> {
+        let mut report = AmsReport::default();
+
+        for tray in trays.iter().filter(|t| !t.is_empty()) {
+            let tool = tray.tool();
+            let roll_id = match self.assignments.loaded_roll(&self.printer, tool)? {
+                Some(roll_id) => roll_id,
+                None => {
+                    report.unassigned.push(tray.clone());
+                    continue;
+                }
+            };
+            let flag = |kind| Discrepancy {
+                tool,
+                roll_id: roll_id.clone(),
+                kind,
+            };
+
+            // Until the swap is resolved, weights would go to the wrong roll
+            if let Some(previous) = self.remember_tag(tool, &roll_id, tray)? {
+                report.discrepancies.push(flag(DiscrepancyKind::TagChanged {
+                    previous,
+                    current: tray.tag_uid.clone().unwrap_or_default(),
+                }));
+                continue;
+            }
+
+            // Compared with the roll as stored when the weight is written, so
+            // a deduction made meanwhile is never overwritten by the estimate
+            let mut outcome = TrayOutcome::Unchanged;
+            self.service.update_roll(&roll_id, |roll| {
+                outcome = self.compare(tray, roll);
+                if let TrayOutcome::Updated { to, .. } = outcome {
+                    roll.update_remaining_weight(to)?;
+                }
+                Ok(())
+            })?;
+
+            match outcome {
+                TrayOutcome::Flagged(kind) => report.discrepancies.push(flag(kind)),
+                TrayOutcome::Updated { from, to } => report.updated.push(WeightUpdate {
+                    tool,
+                    roll_id,
+                    from,
+                    to,
+                }),
+                TrayOutcome::Unchanged => {}
+            }
+        }
+
+        Ok(report)
+    }
+
+    fn compare(&self, tray: &AmsTray, roll: &FilamentRoll) -> TrayOutcome {
+        let tray_type = tray.tray_type.clone().unwrap_or_default();
+        // Bambu sub-types such as "PLA-S" still count as the base material
+        let base_type = tray_type.split('-').next().unwrap_or_default();
+        if !base_type.eq_ignore_ascii_case(roll.material())
+            && !tray_type.eq_ignore_ascii_case(roll.material())
+        {
+            return TrayOutcome::Flagged(DiscrepancyKind::MaterialMismatch {
+                tray_type,
+                roll_material: roll.material().to_string(),
+            });
+        }
+
+        let remain = match tray.remain {
+            Some(remain) => remain,
+            None => return TrayOutcome::Unchanged,
+        };
+        let tracked = roll.remaining_weight();
+        let reported = roll.weight() * remain / 100.0;
+        let tolerance = roll.weight() * self.tolerance_percent / 100.0;
+
+        if (reported - tracked).abs() > tolerance {
+            TrayOutcome::Flagged(DiscrepancyKind::WeightMismatch { tracked, reported })
+        } else if reported < tracked {
+            TrayOutcome::Updated {
+                from: tracked,
+                to: reported,
+            }
+        } else {
+            // A slightly higher estimate is rounding in the AMS; keep the tracked weight
+            TrayOutcome::Unchanged
+        }
+    }
+
+    // Accepts the tag now in the tray as the assigned roll's, e.g. after
+    // checking that the spool was only re-seated. Returns false when the tray
+    // was not flagged as swapped.
+    pub fn confirm_swap(&self, tool: u32) -> Result<bool, FilamentError> {
+        let mut confirmed = false;
+        self.tags.update(&self.printer, &mut |tags| {
+            confirmed = match tags.get_mut(&tool) {
+                Some(tag) if tag.seen != tag.accepted => {
+                    tag.accepted = tag.seen.clone();
+                    true
+                }
+                _ => false,
+            };
+        })?;
+        Ok(confirmed)
+    }
+
+    // Records the tray's tag and returns the accepted one while they differ.
+    // A new assignment accepts whatever tag is in the tray.
+    fn remember_tag(
+        &self,
+        tool: u32,
+        roll_id: &str,
+        tray: &AmsTray,
+    ) -> Result<Option<String>, FilamentError> {
+        let current = match &tray.tag_uid {
+            Some(current) => current,
+            None => return Ok(None),
+        };
+
+        let mut previous = None;
+        self.tags.update(&self.printer, &mut |tags| {
+            previous = match tags.get_mut(&tool) {
+                Some(tag) if tag.roll_id == roll_id => {
+                    tag.seen = current.clone();
+                    (tag.accepted != *current).then(|| tag.accepted.clone())
+                }
+                _ => {
+                    tags.insert(
+                        tool,
+                        TrayTag {
+                            roll_id: roll_id.to_string(),
+                            accepted: current.clone(),
+                            seen: current.clone(),
+                        },
+                    );
+                    None
+                }
+            };
+        })?;
+        Ok(previous)
+    }
+}
@@ -0,0 +1,270 @@
+use crate::domain::error::FilamentError;
+use crate::domain::filament::FilamentRollBuilder;
+use crate::domain::scan::RollDraft;
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use serde_json::Value;
+
+// Bambu spools carry MIFARE Classic 1K tags; blocks are 16 bytes and every
+// fourth block is a sector trailer holding keys rather than data
+const BLOCK_BYTES: usize = 16;
+// A Mifare Classic 4K tag, the largest a dump can describe
+const MAX_BLOCKS: usize = 256;
+
+// Blocks the filament details are read from
+const UID_BLOCK: usize = 0;
+const MATERIAL_ID_BLOCK: usize = 1;
+const TYPE_BLOCK: usize = 2;
+const DETAILED_TYPE_BLOCK: usize = 4;
+const SPOOL_BLOCK: usize = 5;
+const TEMPERATURE_BLOCK: usize = 6;
+const TRAY_UUID_BLOCK: usize = 9;
+const PRODUCTION_BLOCK: usize = 12;
+
+pub const MANUFACTURER: &str = "Bambu Lab";
+
+// Imported rolls get this prefix on the tray UUID, so scanning the same spool
+// twice finds the same roll
+pub const ROLL_ID_PREFIX: &str = "bambu-";
+
+// What a Bambu spool's tag says about it
+#[derive(Debug, Serialize, PartialEq, Clone)]
+pub struct BambuTag {
+    // The chip's own UID, as the AMS reports it in `tag_uid`
+    pub uid: String,
+    // e.g. `A00-G1`
+    pub variant_id: String,
+    // e.g. `GFA00`
+    pub material_id: String,
+    // e.g. `PLA`
+    pub filament_type: String,
+    // e.g. `PLA Basic`
+    pub detailed_type: String,
+    // `#RRGGBB`, with alpha appended when not opaque
+    pub color: String,
+    // Grams of filament on a full spool
+    pub weight: f32,
+    pub diameter: f32,
+    pub drying_temperature: u16,
+    pub drying_hours: u16,
+    pub bed_temperature: u16,
+    pub min_nozzle_temperature: u16,
+    pub max_nozzle_temperature: u16,
+    // Shared by both tags of a spool, as the AMS reports it in `tray_uuid`
+    pub tray_uuid: String,
+    pub production_date: Option<NaiveDateTime>,
+}
+
+impl BambuTag {
+    // Accepts a raw `.bin` dump, a Flipper `.nfc` file or a Proxmark `.eml`
+    // or `.json` dump
+    pub fn parse(dump: &[u8]) -> Result<Self, FilamentError> {
+        Self::from_blocks(&read_blocks(dump)?)
+    }
+
+    // Blocks in order; None for blocks the reader could not authenticate
+    pub fn from_blocks(blocks: &[Option<[u8; BLOCK_BYTES]>]) -> Result<Self, FilamentError> {
+        let block = |index: usize| {
+            blocks.get(index).copied().flatten().ok_or_else(|| {
+                FilamentError::InvalidData(format!(
+                    "Block {} is missing from the tag dump; it needs to be read with the spool's keys",
+                    index
+                ))
+            })
+        };
+        let u16_at = |block: &[u8; BLOCK_BYTES], offset: usize| {
+            u16::from_le_bytes([block[offset], block[offset + 1]])
+        };
+
+        let uid = block(UID_BLOCK)?;
+        let material = block(MATERIAL_ID_BLOCK)?;
+        let spool = block(SPOOL_BLOCK)?;
+        let temperatures = block(TEMPERATURE_BLOCK)?;
+
+        let filament_type = text(&block(TYPE_BLOCK)?);
+        if filament_type.is_empty() {
+            return Err(FilamentError::InvalidData(
+                "Tag has no filament type; is it from a Bambu spool?".to_string(),
+            ));
+        }
+
+        let [r, g, b, a] = [spool[0], spool[1], spool[2], spool[3]];
+        let color = if a == 0xFF {
+            format!("#{:02X}{:02X}{:02X}", r, g, b)
+        } else {
+            format!("#{:02X}{:02X}{:02X}{:02X}", r, g, b, a)
+        };
+
+        Ok(BambuTag {
+            uid: hex(&uid[..4]),
+            variant_id: text(&material[..8]),
+            material_id: text(&material[8..]),
+            filament_type,
+            detailed_type: text(&block(DETAILED_TYPE_BLOCK)?),
+            color,
+            weight: u16_at(&spool, 4) as f32,
+            diameter: f32::from_le_bytes([spool[8], spool[9], spool[10], spool[11]]),
+            drying_temperature: u16_at(&temperatures, 0),
+            drying_hours: u16_at(&temperatures, 2),
+            bed_temperature: u16_at(&temperatures, 6),
+            max_nozzle_temperature: u16_at(&temperatures, 8),
+            min_nozzle_temperature: u16_at(&temperatures, 10),
+            tray_uuid: hex(&block(TRAY_UUID_BLOCK)?),
+            // e.g. `2024_03_21_14_35`
+            production_date: NaiveDateTime::parse_from_str(
+                &text(&block(PRODUCTION_BLOCK)?),
+                "%Y_%m_%d_%H_%M",
+            )
+            .ok(),
+        })
+    }
+
+    // The details a roll needs, for confirming before it is registered
+    pub fn draft(&self) -> RollDraft {
+        let name = match self.detailed_type.as_str() {
+            "" => self.filament_type.clone(),
+            detailed => detailed.to_string(),
+        };
+
+        RollDraft {
+            name,
+            material: self.filament_type.clone(),
+            color: self.color.clone(),
+            diameter: self.diameter,
+            weight: self.weight,
+            manufacturer: MANUFACTURER.to_string(),
+            id: Some(format!("{}{}", ROLL_ID_PREFIX, self.tray_uuid)),
+            spool_weight: None,
+        }
+    }
+
+    // A full roll identified by the spool's tray UUID
+    pub fn to_builder(&self) -> FilamentRollBuilder {
+        self.draft().into_builder()
+    }
+}
+
+// Whether `dump` looks like a MIFARE Classic dump rather than some other tag
+pub fn is_dump(dump: &[u8]) -> bool {
+    let text = dump_text(dump);
+    if text.starts_with("Filetype: Flipper NFC device") {
+        return text.contains("Mifare Classic");
+    }
+    if text.starts_with('{') {
+        return serde_json::from_str::<Value>(text).is_ok_and(|json| json.get("blocks").is_some());
+    }
+    // 1K and 4K raw dumps
+    dump.len() == 1024 || dump.len() == 4096
+}
+
+// The dump as text, or empty when it is binary. Raw dumps are never read as
+// text, even when their first byte happens to be `{`.
+fn dump_text(dump: &[u8]) -> &str {
+    std::str::from_utf8(dump).map(str::trim).unwrap_or("")
+}
+
+fn read_blocks(dump: &[u8]) -> Result<Vec<Option<[u8; BLOCK_BYTES]>>, FilamentError> {
+    let invalid = |message: String| FilamentError::InvalidData(message);
+
+    let text = dump_text(dump);
+
+    // Flipper: `Block 4: 50 4C 41 ...`, with `??` for bytes it could not read
+    if text.starts_with("Filetype:") {
+        let mut blocks = Vec::new();
+        for line in text.lines() {
+            let Some((label, data)) = line.split_once(':') else {
+                continue;
+            };
+            let Some(index) = label.trim().strip_prefix("Block ") else {
+                continue;
+            };
+            let index: usize = index
+                .trim()
+                .parse()
+                .map_err(|_| invalid(format!("Invalid Flipper block line '{}'", line)))?;
+            set_block(&mut blocks, index, parse_block(&data.replace(' ', "")))?;
+        }
+        return Ok(blocks);
+    }
+
+    // Proxmark JSON: `{"blocks": {"0": "75886D0F...", ...}}`
+    if text.starts_with('{') {
+        let json: Value = serde_json::from_str(text)
+            .map_err(|e| invalid(format!("Invalid Proxmark dump: {}", e)))?;
+        let entries = json
+            .get("blocks")
+            .and_then(Value::as_object)
+            .ok_or_else(|| invalid("Proxmark dump has no blocks".to_string()))?;
+        let mut blocks = Vec::new();
+        for (index, data) in entries {
+            let index: usize = index
+                .parse()
+                .map_err(|_| invalid(format!("Invalid Proxmark block number '{}'", index)))?;
+            set_block(&mut blocks, index, data.as_str().and_then(parse_block))?;
+        }
+        return Ok(blocks);
+    }
+
+    // Proxmark `.eml`: one block per line in hex, `--` for unread bytes
+    let is_eml = !text.is_empty()
+        && text
+            .bytes()
+            .all(|b| b.is_ascii_hexdigit() || b == b'-' || b.is_ascii_whitespace());
+    if is_eml {
+        return Ok(text.lines().map(|line| parse_block(line.trim())).collect());
+    }
+
+    // Raw dumps are the blocks back to back
+    if dump.is_empty() || !dump.len().is_multiple_of(BLOCK_BYTES) {
+        return Err(invalid(format!(
+            "A raw tag dump is whole {} byte blocks, not {} bytes",
+            BLOCK_BYTES,
+            dump.len()
+        )));
+    }
+    Ok(dump
+        .chunks_exact(BLOCK_BYTES)
+        .map(|chunk| chunk.try_into().ok())
+        .collect())
+}
+
+// Block numbers come from the dump, so are checked before growing `blocks`
+fn set_block(
+    blocks: &mut Vec<Option<[u8; BLOCK_BYTES]>>,
+    index: usize,
+    block: Option<[u8; BLOCK_BYTES]>,
+) -> Result<(), FilamentError> {
+    if index >= MAX_BLOCKS {
+        return Err(FilamentError::InvalidData(format!(
+            "Block {} is beyond the {} blocks of a Mifare Classic tag",
+            index, MAX_BLOCKS
+        )));
+    }
+    if blocks.len() <= index {
+        blocks.resize(index + 1, None);
+    }
+    blocks[index] = block;
+    Ok(())
+}
+
+// 32 hex digits; None when any byte is unknown
+fn parse_block(hex: &str) -> Option<[u8; BLOCK_BYTES]> {
+    if hex.len() != BLOCK_BYTES * 2 {
+        return None;
+    }
+    let mut block = [0u8; BLOCK_BYTES];
+    for (i, byte) in block.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(hex.get(i * 2..i * 2 + 2)?, 16).ok()?;
+    }
+    Some(block)
+}
+
+// NUL-padded ASCII
+fn text(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).trim().to_string()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect()
+}
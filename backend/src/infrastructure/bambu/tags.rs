@@ -0,0 +1,125 @@
+use crate::domain::error::FilamentError;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+// RFID tags seen in one tray while a roll is assigned to it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrayTag {
+    pub roll_id: String,
+    // Tag of the spool the assigned roll is known to be
+    pub accepted: String,
+    // Tag in the latest report
+    pub seen: String,
+}
+
+// A printer's tray tags by tool number
+pub type TrayTags = BTreeMap<u32, TrayTag>;
+
+// Remembers which spool each AMS tray's roll was accepted as, per printer
+pub trait TrayTagStore: Send + Sync {
+    // Applies `change` to the printer's tray tags in one step
+    fn update(
+        &self,
+        printer: &str,
+        change: &mut dyn FnMut(&mut TrayTags),
+    ) -> Result<(), FilamentError>;
+}
+
+pub struct InMemoryTrayTags {
+    tags: Arc<Mutex<HashMap<String, TrayTags>>>,
+}
+
+impl Default for InMemoryTrayTags {
+    fn default() -> Self {
+        Self {
+            tags: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+impl InMemoryTrayTags {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl TrayTagStore for InMemoryTrayTags {
+    fn update(
+        &self,
+        printer: &str,
+        change: &mut dyn FnMut(&mut TrayTags),
+    ) -> Result<(), FilamentError> {
+        let mut tags = self.tags.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        change(tags.entry(printer.to_string()).or_default());
+        Ok(())
+    }
+}
+
+// Tray tags kept in a JSON file so a swap flagged before a restart stays
+// flagged after it
+pub struct JsonFileTrayTags {
+    path: PathBuf,
+    tags: Mutex<HashMap<String, TrayTags>>,
+}
+
+impl JsonFileTrayTags {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, FilamentError> {
+        let path = path.as_ref().to_path_buf();
+        let tags = if path.exists() {
+            let json = fs::read(&path).map_err(io_error)?;
+            serde_json::from_slice(&json).map_err(|e| {
+                FilamentError::RepositoryError(format!("Failed to decode tray tags: {}", e))
+            })?
+        } else {
+            HashMap::new()
+        };
+
+        Ok(JsonFileTrayTags {
+            path,
+            tags: Mutex::new(tags),
+        })
+    }
+
+    fn persist(&self, tags: &HashMap<String, TrayTags>) -> Result<(), FilamentError> {
+        let json = serde_json::to_vec_pretty(tags).map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to encode tray tags: {}", e))
+        })?;
+
+        // Write then rename so a crash never leaves a truncated file
+        let temp_path = self.path.with_extension("tmp");
+        fs::write(&temp_path, json).map_err(io_error)?;
+        fs::rename(&temp_path, &self.path).map_err(io_error)
+    }
+}
+
+impl TrayTagStore for JsonFileTrayTags {
+    fn update(
+        &self,
+        printer: &str,
+        change: &mut dyn FnMut(&mut TrayTags),
+    ) -> Result<(), FilamentError> {
+        let mut tags = self.tags.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        let printer_tags = tags.entry(printer.to_string()).or_default();
+        let before = printer_tags.clone();
+        change(printer_tags);
+        // Reports arrive every few seconds and rarely change a tag
+        if *printer_tags == before {
+            return Ok(());
+        }
+
+        self.persist(&tags)
+    }
+}
+
+fn io_error(e: std::io::Error) -> FilamentError {
+    FilamentError::RepositoryError(format!("I/O error: {}", e))
+}
@@ -0,0 +1,51 @@
+use crate::domain::filament::FilamentRoll;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportMode {
+    // Validate every row and report, but write nothing
+    DryRun,
+    Apply,
+}
+
+// A row that was not imported. Rows are numbered from 1, excluding any header.
+#[derive(Debug, Serialize, PartialEq, Clone)]
+pub struct RowError {
+    pub row: usize,
+    pub message: String,
+}
+
+// Outcome of an import of rolls, or of whatever `T` is being imported. Invalid
+// rows are collected rather than aborting the run.
+#[derive(Debug, Serialize, PartialEq, Clone)]
+pub struct ImportReport<T = FilamentRoll> {
+    pub dry_run: bool,
+    // New records written, or that would be written in a dry run
+    pub imported: Vec<T>,
+    // Existing records overwritten by an upsert
+    pub updated: Vec<T>,
+    // Rows deliberately left out, e.g. already imported or archived
+    pub skipped: Vec<RowError>,
+    // Rows that failed validation
+    pub failed: Vec<RowError>,
+}
+
+impl<T> ImportReport<T> {
+    pub fn new(mode: ImportMode) -> Self {
+        ImportReport {
+            dry_run: mode == ImportMode::DryRun,
+            imported: Vec::new(),
+            updated: Vec::new(),
+            skipped: Vec::new(),
+            failed: Vec::new(),
+        }
+    }
+
+    pub fn skip(&mut self, row: usize, message: String) {
+        self.skipped.push(RowError { row, message });
+    }
+
+    pub fn fail(&mut self, row: usize, message: String) {
+        self.failed.push(RowError { row, message });
+    }
+}
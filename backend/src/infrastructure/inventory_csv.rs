@@ -0,0 +1,427 @@
+use crate::domain::custom_field::CustomValue;
+use crate::domain::error::FilamentError;
+use crate::domain::filament::{FilamentRepository, FilamentRoll, FilamentRollBuilder};
+use crate::infrastructure::import::{ImportMode, ImportReport};
+use chrono::NaiveDate;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::io::{Read, Write};
+
+// Dates are written and read as ISO 8601 calendar dates
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+// Custom fields get a column each, headed `custom.<name>:<type>` so values
+// keep their type through a spreadsheet; a header without a type reads as string
+const CUSTOM_FIELD_PREFIX: &str = "custom.";
+
+// Separates tags within the tags column
+const TAG_SEPARATOR: &str = "; ";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InventoryField {
+    Id,
+    Name,
+    Material,
+    Color,
+    Diameter,
+    Weight,
+    RemainingWeight,
+    Manufacturer,
+    StorageLocation,
+    LotNumber,
+    // `YYYY-MM-DD`
+    ManufacturedOn,
+    ReceivedOn,
+    // Separated by `; `
+    Tags,
+    // Computed; written on export and ignored on import
+    PercentageRemaining,
+}
+
+impl InventoryField {
+    pub const ALL: [InventoryField; 14] = [
+        InventoryField::Id,
+        InventoryField::Name,
+        InventoryField::Material,
+        InventoryField::Color,
+        InventoryField::Diameter,
+        InventoryField::Weight,
+        InventoryField::RemainingWeight,
+        InventoryField::Manufacturer,
+        InventoryField::StorageLocation,
+        InventoryField::LotNumber,
+        InventoryField::ManufacturedOn,
+        InventoryField::ReceivedOn,
+        InventoryField::Tags,
+        InventoryField::PercentageRemaining,
+    ];
+
+    // Column header used on export and expected on import by default
+    pub fn header(&self) -> &'static str {
+        match self {
+            InventoryField::Id => "id",
+            InventoryField::Name => "name",
+            InventoryField::Material => "material",
+            InventoryField::Color => "color",
+            InventoryField::Diameter => "diameter",
+            InventoryField::Weight => "weight",
+            InventoryField::RemainingWeight => "remaining_weight",
+            InventoryField::Manufacturer => "manufacturer",
+            InventoryField::StorageLocation => "storage_location",
+            InventoryField::LotNumber => "lot_number",
+            InventoryField::ManufacturedOn => "manufactured_on",
+            InventoryField::ReceivedOn => "received_on",
+            InventoryField::Tags => "tags",
+            InventoryField::PercentageRemaining => "percentage_remaining",
+        }
+    }
+
+    pub fn from_header(header: &str) -> Option<InventoryField> {
+        InventoryField::ALL
+            .into_iter()
+            .find(|field| field.header() == header)
+    }
+
+    fn value(&self, roll: &FilamentRoll) -> String {
+        match self {
+            InventoryField::Id => roll.id().to_string(),
+            InventoryField::Name => roll.name().to_string(),
+            InventoryField::Material => roll.material().to_string(),
+            InventoryField::Color => roll.color().to_string(),
+            InventoryField::Diameter => roll.diameter().to_string(),
+            InventoryField::Weight => roll.weight().to_string(),
+            InventoryField::RemainingWeight => roll.remaining_weight().to_string(),
+            InventoryField::Manufacturer => roll.manufacturer().to_string(),
+            InventoryField::StorageLocation => roll.storage_location().to_string(),
+            InventoryField::LotNumber => roll.lot_number().to_string(),
+            InventoryField::ManufacturedOn => date_value(roll.manufactured_on()),
+            InventoryField::ReceivedOn => date_value(roll.received_on()),
+            InventoryField::Tags => roll.tags().join(TAG_SEPARATOR),
+            InventoryField::PercentageRemaining => roll.percentage_remaining().to_string(),
+        }
+    }
+}
+
+// Which spreadsheet column holds each field. Unmapped fields use their
+// default header.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ColumnMapping {
+    columns: HashMap<InventoryField, String>,
+}
+
+impl ColumnMapping {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn map(mut self, field: InventoryField, header: &str) -> Self {
+        self.columns.insert(field, header.to_string());
+        self
+    }
+
+    pub fn header(&self, field: InventoryField) -> &str {
+        self.columns
+            .get(&field)
+            .map(String::as_str)
+            .unwrap_or_else(|| field.header())
+    }
+}
+
+// Writes every roll, sorted by id, with the default headers followed by a
+// column for each custom field in use, sorted by name
+pub fn export_csv<W: Write>(
+    repository: &dyn FilamentRepository,
+    writer: W,
+) -> Result<(), FilamentError> {
+    let mut rolls = repository.find_all()?;
+    rolls.sort_by(|a, b| a.id().cmp(b.id()));
+
+    let custom_columns: BTreeSet<(&str, &str)> = rolls
+        .iter()
+        .flat_map(|roll| roll.custom_fields())
+        .map(|(name, value)| (name.as_str(), value.kind()))
+        .collect();
+
+    let mut writer = csv::Writer::from_writer(writer);
+    let mut header: Vec<String> = InventoryField::ALL
+        .iter()
+        .map(|f| f.header().to_string())
+        .collect();
+    header.extend(
+        custom_columns
+            .iter()
+            .map(|(name, kind)| format!("{}{}:{}", CUSTOM_FIELD_PREFIX, name, kind)),
+    );
+    writer.write_record(&header).map_err(csv_error)?;
+
+    for roll in &rolls {
+        let mut record: Vec<String> = InventoryField::ALL.iter().map(|f| f.value(roll)).collect();
+        record.extend(custom_columns.iter().map(|(name, kind)| {
+            roll.custom_field(name)
+                .filter(|value| value.kind() == *kind)
+                .map(CustomValue::to_string)
+                .unwrap_or_default()
+        }));
+        writer.write_record(&record).map_err(csv_error)?;
+    }
+
+    writer
+        .flush()
+        .map_err(|e| FilamentError::RepositoryError(format!("Failed to write CSV: {}", e)))
+}
+
+// Creates or updates rolls from a spreadsheet. Rows with an id that already
+// exists update that roll, keeping any field whose column is absent or blank.
+pub fn import_csv<R: Read>(
+    repository: &dyn FilamentRepository,
+    reader: R,
+    mapping: &ColumnMapping,
+    mode: ImportMode,
+) -> Result<ImportReport, FilamentError> {
+    let mut reader = csv::Reader::from_reader(reader);
+    let headers = reader
+        .headers()
+        .map_err(|e| FilamentError::InvalidData(format!("Invalid CSV header: {}", e)))?
+        .clone();
+
+    let mut report = ImportReport::new(mode);
+    let mut seen = HashSet::new();
+
+    for (index, row) in reader.records().enumerate() {
+        let row_number = index + 1;
+
+        let row = match row {
+            Ok(row) => row,
+            Err(e) => {
+                report.fail(row_number, e.to_string());
+                continue;
+            }
+        };
+        let columns: HashMap<&str, &str> = headers.iter().zip(row.iter()).collect();
+        let cells = Cells {
+            columns: &columns,
+            mapping,
+        };
+
+        if let Some(id) = cells.text(InventoryField::Id) {
+            if !seen.insert(id.clone()) {
+                report.fail(row_number, format!("Duplicate id '{}' in file", id));
+                continue;
+            }
+        }
+
+        let existing = match cells.text(InventoryField::Id) {
+            Some(id) => match repository.find_by_id(&id) {
+                Ok(roll) => Some(roll),
+                Err(FilamentError::NotFound(_)) => None,
+                Err(e) => return Err(e),
+            },
+            None => None,
+        };
+
+        let mut roll = match cells.to_roll(existing.as_ref()) {
+            Ok(roll) => roll,
+            Err(e) => {
+                report.fail(row_number, e.to_string());
+                continue;
+            }
+        };
+
+        if mode == ImportMode::Apply {
+            if existing.is_some() {
+                // Merge into the roll as stored now, so changes made since it
+                // was read above are kept for the columns the row leaves blank
+                match repository.update(roll.id(), &mut |current| {
+                    *current = cells.to_roll(Some(current))?;
+                    Ok(())
+                }) {
+                    Ok(merged) => roll = merged,
+                    Err(e @ FilamentError::InvalidData(_)) => {
+                        report.fail(row_number, e.to_string());
+                        continue;
+                    }
+                    Err(e) => return Err(e),
+                }
+            } else {
+                repository.save(&roll)?;
+            }
+        }
+
+        if existing.is_some() {
+            report.updated.push(roll);
+        } else {
+            report.imported.push(roll);
+        }
+    }
+
+    Ok(report)
+}
+
+struct Cells<'a> {
+    columns: &'a HashMap<&'a str, &'a str>,
+    mapping: &'a ColumnMapping,
+}
+
+impl Cells<'_> {
+    fn text(&self, field: InventoryField) -> Option<String> {
+        self.columns
+            .get(self.mapping.header(field))
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    }
+
+    fn number(&self, field: InventoryField) -> Result<Option<f32>, FilamentError> {
+        match self.text(field) {
+            None => Ok(None),
+            Some(value) => value.parse().map(Some).map_err(|_| {
+                FilamentError::InvalidData(format!(
+                    "Invalid number '{}' in column '{}'",
+                    value,
+                    self.mapping.header(field)
+                ))
+            }),
+        }
+    }
+
+    fn date(&self, field: InventoryField) -> Result<Option<NaiveDate>, FilamentError> {
+        match self.text(field) {
+            None => Ok(None),
+            Some(value) => NaiveDate::parse_from_str(&value, DATE_FORMAT)
+                .map(Some)
+                .map_err(|_| {
+                    FilamentError::InvalidData(format!(
+                        "Invalid date '{}' in column '{}'; expected YYYY-MM-DD",
+                        value,
+                        self.mapping.header(field)
+                    ))
+                }),
+        }
+    }
+
+    // Non-blank `custom.` columns, sorted by header
+    fn custom_fields(&self) -> Result<Vec<(String, CustomValue)>, FilamentError> {
+        let mut headers: Vec<&str> = self
+            .columns
+            .keys()
+            .copied()
+            .filter(|header| header.starts_with(CUSTOM_FIELD_PREFIX))
+            .collect();
+        headers.sort_unstable();
+
+        let mut fields = Vec::new();
+        for header in headers {
+            let value = self.columns[header].trim();
+            if value.is_empty() {
+                continue;
+            }
+            let column = &header[CUSTOM_FIELD_PREFIX.len()..];
+            let (name, kind) = column.rsplit_once(':').unwrap_or((column, "string"));
+            let value = CustomValue::parse(kind, value)
+                .map_err(|e| FilamentError::InvalidData(format!("{} in column '{}'", e, header)))?;
+            fields.push((name.to_string(), value));
+        }
+        Ok(fields)
+    }
+
+    fn to_roll(&self, existing: Option<&FilamentRoll>) -> Result<FilamentRoll, FilamentError> {
+        let text = |field: InventoryField, current: Option<&str>| {
+            self.text(field)
+                .or_else(|| current.map(str::to_string))
+                .unwrap_or_default()
+        };
+
+        let weight = self
+            .number(InventoryField::Weight)?
+            .or(existing.map(|r| r.weight()))
+            .unwrap_or(0.0);
+
+        let mut builder = FilamentRollBuilder::new(
+            text(InventoryField::Name, existing.map(|r| r.name())),
+            text(InventoryField::Material, existing.map(|r| r.material())),
+            text(InventoryField::Color, existing.map(|r| r.color())),
+            self.number(InventoryField::Diameter)?
+                .or(existing.map(|r| r.diameter()))
+                .unwrap_or(0.0),
+            weight,
+            text(
+                InventoryField::Manufacturer,
+                existing.map(|r| r.manufacturer()),
+            ),
+        );
+
+        if let Some(id) = self.text(InventoryField::Id) {
+            builder = builder.with_id(&id);
+        }
+        if let Some(remaining_weight) = self
+            .number(InventoryField::RemainingWeight)?
+            .or(existing.map(|r| r.remaining_weight()))
+        {
+            builder = builder.with_remaining_weight(remaining_weight);
+        }
+        let location = text(
+            InventoryField::StorageLocation,
+            existing.map(|r| r.storage_location()),
+        );
+        if !location.is_empty() {
+            builder = builder.with_storage_location(&location);
+        }
+        let lot_number = text(InventoryField::LotNumber, existing.map(|r| r.lot_number()));
+        if !lot_number.is_empty() {
+            builder = builder.with_lot_number(&lot_number);
+        }
+        if let Some(manufactured_on) = self
+            .date(InventoryField::ManufacturedOn)?
+            .or(existing.and_then(|r| r.manufactured_on()))
+        {
+            builder = builder.with_manufactured_on(manufactured_on);
+        }
+        if let Some(received_on) = self
+            .date(InventoryField::ReceivedOn)?
+            .or(existing.and_then(|r| r.received_on()))
+        {
+            builder = builder.with_received_on(received_on);
+        }
+
+        let tags = match self.text(InventoryField::Tags) {
+            Some(tags) => tags
+                .split(TAG_SEPARATOR.trim())
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .map(str::to_string)
+                .collect(),
+            None => existing.map(|r| r.tags().to_vec()).unwrap_or_default(),
+        };
+        for tag in &tags {
+            builder = builder.with_tag(tag);
+        }
+
+        let mut custom_fields = existing
+            .map(|r| r.custom_fields().clone())
+            .unwrap_or_default();
+        custom_fields.extend(self.custom_fields()?);
+        for (name, value) in custom_fields {
+            builder = builder.with_custom_field(&name, value);
+        }
+
+        // Notes and attachments are not exported, so an update keeps them
+        if let Some(existing) = existing {
+            for note in existing.notes() {
+                builder = builder.with_note(note.clone());
+            }
+            for attachment in existing.attachments() {
+                builder = builder.with_attachment(attachment.clone());
+            }
+        }
+
+        builder.build()
+    }
+}
+
+fn date_value(date: Option<NaiveDate>) -> String {
+    date.map(|date| date.format(DATE_FORMAT).to_string())
+        .unwrap_or_default()
+}
+
+fn csv_error(e: csv::Error) -> FilamentError {
+    FilamentError::RepositoryError(format!("Failed to write CSV: {}", e))
+}
@@ -0,0 +1,282 @@
+pub mod raster;
+pub mod sheet;
+pub mod svg;
+pub mod thermal;
+
+use crate::domain::error::FilamentError;
+use crate::domain::filament::FilamentRoll;
+// Defined alongside the scanning code that reads labels back
+pub use crate::domain::scan::{roll_payload, ROLL_URI_PREFIX};
+use qrcode::{Color, QrCode};
+use std::collections::HashMap;
+
+// Modules of white space the QR spec asks for around the code
+const QUIET_ZONE: usize = 4;
+
+// Monospace advance relative to the font size, used to fit text in mm
+pub(crate) const CHAR_WIDTH: f32 = 0.6;
+
+// Largest share of the label's long side the QR code may take
+const QR_SHARE: f32 = 0.45;
+
+// Characters a regular line holds before text is scaled down to fit
+const MIN_CHARS: f32 = 16.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LabelSize {
+    pub width_mm: f32,
+    pub height_mm: f32,
+}
+
+impl LabelSize {
+    // Brother DK-11209 on 62 mm tape
+    pub const BROTHER_62: LabelSize = LabelSize {
+        width_mm: 62.0,
+        height_mm: 29.0,
+    };
+    pub const SMALL: LabelSize = LabelSize {
+        width_mm: 40.0,
+        height_mm: 30.0,
+    };
+
+    const MIN_MM: f32 = 10.0;
+    const MAX_MM: f32 = 150.0;
+
+    pub fn new(width_mm: f32, height_mm: f32) -> Result<Self, FilamentError> {
+        let valid = |mm: f32| (Self::MIN_MM..=Self::MAX_MM).contains(&mm);
+        if !valid(width_mm) || !valid(height_mm) {
+            return Err(FilamentError::InvalidData(format!(
+                "Label sides must be between {} and {} mm",
+                Self::MIN_MM,
+                Self::MAX_MM
+            )));
+        }
+
+        Ok(LabelSize {
+            width_mm,
+            height_mm,
+        })
+    }
+
+    // `62x29`, in millimetres
+    pub fn parse(size: &str) -> Result<Self, FilamentError> {
+        let invalid = || {
+            FilamentError::InvalidData(format!(
+                "Invalid label size '{}', expected WIDTHxHEIGHT in mm",
+                size
+            ))
+        };
+        let (width, height) = size.split_once(['x', 'X', '×']).ok_or_else(invalid)?;
+        let width = width.trim().parse().map_err(|_| invalid())?;
+        let height = height.trim().parse().map_err(|_| invalid())?;
+        Self::new(width, height)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LabelSettings {
+    // Where the web UI is reachable, e.g. `http://printfarm.local:8080`
+    pub base_url: Option<String>,
+    // Label printers jobs can be sent to, by id. Values are `host` or
+    // `host:port` of the printer's raw port; clients only ever name the id.
+    pub printers: HashMap<String, String>,
+}
+
+impl LabelSettings {
+    // `desk=192.168.1.50,shop=labels.local:9100`
+    pub fn parse_printers(spec: &str) -> Result<HashMap<String, String>, FilamentError> {
+        spec.split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| match entry.split_once('=') {
+                Some((id, address)) if !id.trim().is_empty() && !address.trim().is_empty() => {
+                    Ok((id.trim().to_string(), address.trim().to_string()))
+                }
+                _ => Err(FilamentError::InvalidData(format!(
+                    "Invalid label printer '{}', expected ID=HOST[:PORT]",
+                    entry
+                ))),
+            })
+            .collect()
+    }
+
+    pub fn printer_address(&self, id: &str) -> Result<&str, FilamentError> {
+        self.printers.get(id).map(String::as_str).ok_or_else(|| {
+            FilamentError::Missing(format!("No label printer '{}' is configured", id))
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QrBlock {
+    pub x: f32,
+    pub y: f32,
+    pub side: f32,
+    // Modules per side, quiet zone included
+    pub modules: usize,
+    // Row-major, true for dark modules
+    pub dark: Vec<bool>,
+}
+
+impl QrBlock {
+    pub fn is_dark(&self, column: usize, row: usize) -> bool {
+        self.dark[row * self.modules + column]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Swatch {
+    pub x: f32,
+    pub y: f32,
+    pub side: f32,
+    pub rgb: [u8; 3],
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextLine {
+    pub x: f32,
+    // Top of the line
+    pub y: f32,
+    pub size: f32,
+    pub text: String,
+}
+
+// Everything on a label positioned in millimetres, shared by the renderers
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelLayout {
+    pub size: LabelSize,
+    pub qr: QrBlock,
+    pub swatch: Option<Swatch>,
+    pub lines: Vec<TextLine>,
+}
+
+impl LabelLayout {
+    // QR code on the left (on top for portrait labels), details beside it
+    pub fn for_roll(
+        roll: &FilamentRoll,
+        size: LabelSize,
+        base_url: Option<&str>,
+    ) -> Result<Self, FilamentError> {
+        let LabelSize {
+            width_mm,
+            height_mm,
+        } = size;
+        let margin = (width_mm.min(height_mm) * 0.06).max(1.0);
+        let landscape = width_mm >= height_mm;
+
+        let mut qr = qr_block(&roll_payload(roll.id(), base_url))?;
+        let (left, top) = if landscape {
+            qr.side = height_mm.min(width_mm * QR_SHARE);
+            qr.y = (height_mm - qr.side) / 2.0;
+            (qr.side, margin)
+        } else {
+            qr.side = width_mm.min(height_mm * QR_SHARE);
+            qr.x = (width_mm - qr.side) / 2.0;
+            (margin, qr.side)
+        };
+        let width = width_mm - left - margin;
+        let height = height_mm - top - margin;
+
+        // Relative sizes of name, material, location and id; the rest is spacing
+        let weights = [1.4, 1.0, 1.0, 0.7];
+        let total: f32 = weights.iter().sum();
+        let unit = (height / (total * 1.15)).min(width / (MIN_CHARS * CHAR_WIDTH));
+        let gap = unit * total * 0.15 / (weights.len() - 1) as f32;
+        let used = unit * total * 1.15;
+
+        let texts = [
+            roll.name().to_string(),
+            format!("{} {:.2} mm", roll.material(), roll.diameter()),
+            roll.storage_location().to_string(),
+            roll.id().to_string(),
+        ];
+
+        let mut lines = Vec::new();
+        let mut swatch = None;
+        let mut y = top + (height - used) / 2.0;
+        for (index, (text, weight)) in texts.into_iter().zip(weights).enumerate() {
+            let mut line_size = unit * weight;
+            // Long names shrink to regular size before being cut short; half a
+            // character is kept spare against rounding
+            if index == 0 {
+                let fitting = width / ((text.chars().count() as f32 + 0.5) * CHAR_WIDTH);
+                line_size = line_size.min(fitting).max(unit);
+            }
+            let mut x = left;
+            // The colour swatch leads the material line
+            if index == 1 {
+                if let Some(rgb) = parse_rgb(roll.color()) {
+                    swatch = Some(Swatch {
+                        x,
+                        y,
+                        side: line_size,
+                        rgb,
+                    });
+                    x += line_size * 1.4;
+                }
+            }
+            lines.push(TextLine {
+                x,
+                y,
+                size: line_size,
+                text: fit(&text, left + width - x, line_size),
+            });
+            y += line_size + gap;
+        }
+
+        Ok(LabelLayout {
+            size,
+            qr,
+            swatch,
+            lines,
+        })
+    }
+}
+
+// Placed and sized by the caller
+fn qr_block(payload: &str) -> Result<QrBlock, FilamentError> {
+    let code = QrCode::new(payload.as_bytes())
+        .map_err(|e| FilamentError::InvalidData(format!("Cannot encode QR code: {}", e)))?;
+    let width = code.width();
+    let modules = width + 2 * QUIET_ZONE;
+    let colors = code.to_colors();
+
+    let mut dark = vec![false; modules * modules];
+    for row in 0..width {
+        for column in 0..width {
+            dark[(row + QUIET_ZONE) * modules + column + QUIET_ZONE] =
+                colors[row * width + column] == Color::Dark;
+        }
+    }
+
+    Ok(QrBlock {
+        x: 0.0,
+        y: 0.0,
+        side: 0.0,
+        modules,
+        dark,
+    })
+}
+
+// Truncates `text` to what fits in `width` mm at `size`
+fn fit(text: &str, width: f32, size: f32) -> String {
+    let max_chars = (width / (size * CHAR_WIDTH)).floor().max(0.0) as usize;
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    if max_chars <= 3 {
+        return text.chars().take(max_chars).collect();
+    }
+    let kept: String = text.chars().take(max_chars - 3).collect();
+    format!("{}...", kept)
+}
+
+// `#RRGGBB` or `#RRGGBBAA`
+fn parse_rgb(color: &str) -> Option<[u8; 3]> {
+    let hex = color.trim().trim_start_matches('#');
+    if hex.len() != 6 && hex.len() != 8 {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
@@ -0,0 +1,206 @@
+use crate::domain::error::FilamentError;
+use crate::infrastructure::labels::LabelLayout;
+use embedded_graphics::mono_font::iso_8859_1::FONT_10X20;
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::pixelcolor::{Rgb888, RgbColor};
+use embedded_graphics::prelude::{DrawTarget, OriginDimensions, Point, Size};
+use embedded_graphics::text::{Baseline, Text};
+use embedded_graphics::{Drawable, Pixel};
+
+pub const DEFAULT_DPI: u32 = 300;
+const MIN_DPI: u32 = 72;
+const MAX_DPI: u32 = 600;
+
+const MM_PER_INCH: f32 = 25.4;
+
+// An RGB image of a label at a given resolution
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bitmap {
+    width: u32,
+    height: u32,
+    dpi: u32,
+    // Row-major RGB
+    pixels: Vec<u8>,
+}
+
+impl Bitmap {
+    fn new(width: u32, height: u32, dpi: u32) -> Self {
+        Bitmap {
+            width,
+            height,
+            dpi,
+            pixels: vec![0xFF; width as usize * height as usize * 3],
+        }
+    }
+
+    fn fill(&mut self, x: u32, y: u32, width: u32, height: u32, rgb: [u8; 3]) {
+        for row in y..(y + height).min(self.height) {
+            for column in x..(x + width).min(self.width) {
+                self.set(column, row, rgb);
+            }
+        }
+    }
+
+    fn set(&mut self, x: u32, y: u32, rgb: [u8; 3]) {
+        if x < self.width && y < self.height {
+            let offset = (y as usize * self.width as usize + x as usize) * 3;
+            self.pixels[offset..offset + 3].copy_from_slice(&rgb);
+        }
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 3] {
+        let offset = (y as usize * self.width as usize + x as usize) * 3;
+        [
+            self.pixels[offset],
+            self.pixels[offset + 1],
+            self.pixels[offset + 2],
+        ]
+    }
+
+    // For one-colour printers: dark enough to print as black
+    pub fn is_dark(&self, x: u32, y: u32) -> bool {
+        let [r, g, b] = self.pixel(x, y);
+        let luma = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+        luma < 128.0
+    }
+
+    pub fn to_png(&self) -> Result<Vec<u8>, FilamentError> {
+        let encode_error =
+            |e: png::EncodingError| FilamentError::RepositoryError(format!("PNG error: {}", e));
+        let mut bytes = Vec::new();
+
+        let mut encoder = png::Encoder::new(&mut bytes, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        // Lets image viewers and print dialogs keep the label's physical size
+        let dots_per_metre = (self.dpi as f32 / MM_PER_INCH * 1000.0).round() as u32;
+        encoder.set_pixel_dims(Some(png::PixelDimensions {
+            xppu: dots_per_metre,
+            yppu: dots_per_metre,
+            unit: png::Unit::Meter,
+        }));
+        let mut writer = encoder.write_header().map_err(encode_error)?;
+        writer
+            .write_image_data(&self.pixels)
+            .map_err(encode_error)?;
+        writer.finish().map_err(encode_error)?;
+
+        Ok(bytes)
+    }
+
+    // Getters
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn dpi(&self) -> u32 {
+        self.dpi
+    }
+}
+
+pub fn render_bitmap(layout: &LabelLayout, dpi: u32) -> Result<Bitmap, FilamentError> {
+    if !(MIN_DPI..=MAX_DPI).contains(&dpi) {
+        return Err(FilamentError::InvalidData(format!(
+            "Resolution must be between {} and {} dpi",
+            MIN_DPI, MAX_DPI
+        )));
+    }
+    let dots = |mm: f32| (mm * dpi as f32 / MM_PER_INCH).round().max(0.0) as u32;
+    let mut bitmap = Bitmap::new(dots(layout.size.width_mm), dots(layout.size.height_mm), dpi);
+
+    // Whole dots per module keep every module the same size for scanners;
+    // the code is centred in whatever is left over
+    let qr = &layout.qr;
+    let side = dots(qr.side);
+    let module = (side / qr.modules as u32).max(1);
+    let inset = side.saturating_sub(module * qr.modules as u32) / 2;
+    let (qr_x, qr_y) = (dots(qr.x) + inset, dots(qr.y) + inset);
+    for row in 0..qr.modules {
+        for column in 0..qr.modules {
+            if qr.is_dark(column, row) {
+                bitmap.fill(
+                    qr_x + column as u32 * module,
+                    qr_y + row as u32 * module,
+                    module,
+                    module,
+                    [0, 0, 0],
+                );
+            }
+        }
+    }
+
+    if let Some(swatch) = &layout.swatch {
+        let (x, y, side) = (dots(swatch.x), dots(swatch.y), dots(swatch.side));
+        let border = (side / 12).max(1);
+        bitmap.fill(x, y, side, side, [0, 0, 0]);
+        bitmap.fill(
+            x + border,
+            y + border,
+            side.saturating_sub(2 * border),
+            side.saturating_sub(2 * border),
+            swatch.rgb,
+        );
+    }
+
+    let font = FONT_10X20;
+    for line in &layout.lines {
+        // The bitmap font is scaled up in whole steps
+        let scale = (dots(line.size) / font.character_size.height).max(1);
+        let mut target = Scaled {
+            bitmap: &mut bitmap,
+            x: dots(line.x),
+            y: dots(line.y),
+            scale,
+        };
+        let style = MonoTextStyle::new(&font, Rgb888::BLACK);
+        let _ =
+            Text::with_baseline(&line.text, Point::zero(), style, Baseline::Top).draw(&mut target);
+    }
+
+    Ok(bitmap)
+}
+
+// Draws each pixel as a `scale` × `scale` block at an offset
+struct Scaled<'a> {
+    bitmap: &'a mut Bitmap,
+    x: u32,
+    y: u32,
+    scale: u32,
+}
+
+impl OriginDimensions for Scaled<'_> {
+    fn size(&self) -> Size {
+        Size::new(
+            self.bitmap.width.saturating_sub(self.x) / self.scale,
+            self.bitmap.height.saturating_sub(self.y) / self.scale,
+        )
+    }
+}
+
+impl DrawTarget for Scaled<'_> {
+    type Color = Rgb888;
+    type Error = std::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            if point.x < 0 || point.y < 0 {
+                continue;
+            }
+            self.bitmap.fill(
+                self.x + point.x as u32 * self.scale,
+                self.y + point.y as u32 * self.scale,
+                self.scale,
+                self.scale,
+                [color.r(), color.g(), color.b()],
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,232 @@
+use crate::domain::error::FilamentError;
+use crate::infrastructure::labels::{LabelLayout, LabelSize};
+use printpdf::path::PaintMode;
+use printpdf::{
+    BuiltinFont, Color, IndirectFontRef, Mm, PdfDocument, PdfLayerReference, Rect, Rgb,
+};
+use serde::{Deserialize, Serialize};
+
+const PT_PER_MM: f32 = 72.0 / 25.4;
+
+// A grid of labels on a sticker sheet. Offsets are from the top-left corner
+// of the page to the first label; gaps are between neighbouring labels.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct SheetTemplate {
+    pub name: String,
+    pub page_width_mm: f32,
+    pub page_height_mm: f32,
+    pub columns: u32,
+    pub rows: u32,
+    pub label_width_mm: f32,
+    pub label_height_mm: f32,
+    pub left_mm: f32,
+    pub top_mm: f32,
+    #[serde(default)]
+    pub column_gap_mm: f32,
+    #[serde(default)]
+    pub row_gap_mm: f32,
+}
+
+// Code, page width and height, columns, rows, label width and height, left
+// and top offsets and column gap, in mm; rows touch on all of them
+type AveryGrid = (&'static str, f32, f32, u32, u32, f32, f32, f32, f32, f32);
+
+const AVERY: &[AveryGrid] = &[
+    // 21 per A4 sheet
+    ("L7160", 210.0, 297.0, 3, 7, 63.5, 38.1, 7.25, 15.15, 2.54),
+    // 14 per A4 sheet
+    ("L7163", 210.0, 297.0, 2, 7, 99.1, 38.1, 4.65, 15.15, 2.5),
+    // 65 per A4 sheet
+    ("L7651", 210.0, 297.0, 5, 13, 38.1, 21.2, 4.65, 10.7, 2.5),
+    // 30 per US Letter sheet
+    (
+        "5160", 215.9, 279.4, 3, 10, 66.675, 25.4, 4.7625, 12.7, 3.175,
+    ),
+];
+
+impl SheetTemplate {
+    // Avery templates by product code
+    pub fn avery(code: &str) -> Result<Self, FilamentError> {
+        let code = code.trim().to_uppercase();
+        let &(
+            _,
+            page_width_mm,
+            page_height_mm,
+            columns,
+            rows,
+            label_width_mm,
+            label_height_mm,
+            left_mm,
+            top_mm,
+            column_gap_mm,
+        ) = AVERY
+            .iter()
+            .find(|template| template.0 == code)
+            .ok_or_else(|| FilamentError::Missing(format!("No Avery template {}", code)))?;
+
+        Ok(SheetTemplate {
+            name: format!("Avery {}", code),
+            page_width_mm,
+            page_height_mm,
+            columns,
+            rows,
+            label_width_mm,
+            label_height_mm,
+            left_mm,
+            top_mm,
+            column_gap_mm,
+            row_gap_mm: 0.0,
+        })
+    }
+
+    pub fn labels_per_sheet(&self) -> usize {
+        (self.columns * self.rows) as usize
+    }
+
+    // Rejects grids that do not fit on the page
+    pub fn label_size(&self) -> Result<LabelSize, FilamentError> {
+        let used_width = self.left_mm
+            + self.columns as f32 * self.label_width_mm
+            + self.columns.saturating_sub(1) as f32 * self.column_gap_mm;
+        let used_height = self.top_mm
+            + self.rows as f32 * self.label_height_mm
+            + self.rows.saturating_sub(1) as f32 * self.row_gap_mm;
+        // Published templates are specified to a hundredth of a millimetre
+        let slack = 0.01;
+        if self.columns == 0
+            || self.rows == 0
+            || self.left_mm < 0.0
+            || self.top_mm < 0.0
+            || used_width > self.page_width_mm + slack
+            || used_height > self.page_height_mm + slack
+        {
+            return Err(FilamentError::InvalidData(format!(
+                "Label grid of sheet template '{}' does not fit on its page",
+                self.name
+            )));
+        }
+
+        LabelSize::new(self.label_width_mm, self.label_height_mm)
+    }
+
+    // Top-left corner of the label at `slot`, counting across then down
+    fn position(&self, slot: usize) -> (f32, f32) {
+        let column = (slot % self.columns as usize) as f32;
+        let row = (slot / self.columns as usize) as f32;
+        (
+            self.left_mm + column * (self.label_width_mm + self.column_gap_mm),
+            self.top_mm + row * (self.label_height_mm + self.row_gap_mm),
+        )
+    }
+}
+
+// One PDF page per sheet. `skip` leaves the first slots of the first sheet
+// empty so part-used sheets can go back through the printer.
+pub fn render_sheets(
+    layouts: &[LabelLayout],
+    template: &SheetTemplate,
+    skip: usize,
+) -> Result<Vec<u8>, FilamentError> {
+    let pdf_error =
+        |e: printpdf::Error| FilamentError::RepositoryError(format!("PDF error: {}", e));
+    let label_size = template.label_size()?;
+    if layouts.is_empty() {
+        return Err(FilamentError::InvalidData("No labels to print".to_string()));
+    }
+    if let Some(layout) = layouts.iter().find(|l| l.size != label_size) {
+        return Err(FilamentError::InvalidData(format!(
+            "Label of {}x{} mm does not match sheet template '{}'",
+            layout.size.width_mm, layout.size.height_mm, template.name
+        )));
+    }
+    let per_sheet = template.labels_per_sheet();
+    let skip = skip % per_sheet;
+
+    let page_width = Mm(template.page_width_mm);
+    let page_height = Mm(template.page_height_mm);
+    let (document, first_page, first_layer) =
+        PdfDocument::new("Filament labels", page_width, page_height, "Labels");
+    // Courier has the monospace advance the layout assumes
+    let font = document
+        .add_builtin_font(BuiltinFont::Courier)
+        .map_err(pdf_error)?;
+
+    let mut layer = document.get_page(first_page).get_layer(first_layer);
+    for (index, layout) in layouts.iter().enumerate() {
+        let slot = skip + index;
+        if slot > 0 && slot.is_multiple_of(per_sheet) {
+            let (page, page_layer) = document.add_page(page_width, page_height, "Labels");
+            layer = document.get_page(page).get_layer(page_layer);
+        }
+        let (x, y) = template.position(slot % per_sheet);
+        draw_label(&layer, &font, layout, x, y, template.page_height_mm);
+    }
+
+    document.save_to_bytes().map_err(pdf_error)
+}
+
+fn draw_label(
+    layer: &PdfLayerReference,
+    font: &IndirectFontRef,
+    layout: &LabelLayout,
+    left: f32,
+    top: f32,
+    page_height: f32,
+) {
+    // PDF measures up from the bottom of the page
+    let rect = |x: f32, y: f32, width: f32, height: f32| {
+        Rect::new(
+            Mm(left + x),
+            Mm(page_height - top - y - height),
+            Mm(left + x + width),
+            Mm(page_height - top - y),
+        )
+    };
+    let black = Color::Rgb(Rgb::new(0.0, 0.0, 0.0, None));
+
+    // Runs of dark modules in a row are drawn as one rectangle
+    let qr = &layout.qr;
+    let module = qr.side / qr.modules as f32;
+    layer.set_fill_color(black.clone());
+    for row in 0..qr.modules {
+        let mut column = 0;
+        while column < qr.modules {
+            if !qr.is_dark(column, row) {
+                column += 1;
+                continue;
+            }
+            let start = column;
+            while column < qr.modules && qr.is_dark(column, row) {
+                column += 1;
+            }
+            layer.add_rect(rect(
+                qr.x + start as f32 * module,
+                qr.y + row as f32 * module,
+                (column - start) as f32 * module,
+                module,
+            ));
+        }
+    }
+
+    if let Some(swatch) = &layout.swatch {
+        let [r, g, b] = swatch.rgb.map(|channel| channel as f32 / 255.0);
+        layer.set_fill_color(Color::Rgb(Rgb::new(r, g, b, None)));
+        layer.set_outline_color(black.clone());
+        layer.set_outline_thickness(swatch.side * 0.08 * PT_PER_MM);
+        layer.add_rect(
+            rect(swatch.x, swatch.y, swatch.side, swatch.side).with_mode(PaintMode::FillStroke),
+        );
+    }
+
+    layer.set_fill_color(black);
+    for line in &layout.lines {
+        // Positioned by baseline, which sits about 80% down a line
+        layer.use_text(
+            line.text.as_str(),
+            line.size * PT_PER_MM,
+            Mm(left + line.x),
+            Mm(page_height - top - line.y - line.size * 0.8),
+            font,
+        );
+    }
+}
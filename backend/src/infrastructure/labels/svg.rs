@@ -0,0 +1,80 @@
+use crate::infrastructure::labels::LabelLayout;
+use std::fmt::Write;
+
+// Printers without a monospace font substitute one of similar width
+const FONT_FAMILY: &str = "DejaVu Sans Mono, Menlo, Consolas, monospace";
+
+// Sized in millimetres so it prints 1:1
+pub fn render_svg(layout: &LabelLayout) -> String {
+    let width = layout.size.width_mm;
+    let height = layout.size.height_mm;
+    let mut svg = String::new();
+
+    // Writing to a String cannot fail
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}mm" height="{h}mm" viewBox="0 0 {w} {h}">"#,
+        w = width,
+        h = height
+    );
+    let _ = writeln!(
+        svg,
+        "<rect width=\"{}\" height=\"{}\" fill=\"#FFFFFF\"/>",
+        width, height
+    );
+
+    let qr = &layout.qr;
+    let _ = write!(
+        svg,
+        "<path transform=\"translate({} {}) scale({})\" shape-rendering=\"crispEdges\" fill=\"#000000\" d=\"",
+        qr.x,
+        qr.y,
+        qr.side / qr.modules as f32
+    );
+    for row in 0..qr.modules {
+        for column in 0..qr.modules {
+            if qr.is_dark(column, row) {
+                let _ = write!(svg, "M{} {}h1v1h-1z", column, row);
+            }
+        }
+    }
+    svg.push_str("\"/>\n");
+
+    if let Some(swatch) = &layout.swatch {
+        let [r, g, b] = swatch.rgb;
+        let _ = writeln!(
+            svg,
+            "<rect x=\"{}\" y=\"{}\" width=\"{s}\" height=\"{s}\" fill=\"#{:02X}{:02X}{:02X}\" stroke=\"#000000\" stroke-width=\"{}\"/>",
+            swatch.x,
+            swatch.y,
+            r,
+            g,
+            b,
+            swatch.side * 0.08,
+            s = swatch.side
+        );
+    }
+
+    for line in &layout.lines {
+        // Positioned by baseline, which sits about 80% down a line
+        let _ = writeln!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" font-family=\"{}\" font-size=\"{}\" fill=\"#000000\">{}</text>",
+            line.x,
+            line.y + line.size * 0.8,
+            FONT_FAMILY,
+            line.size,
+            escape(&line.text)
+        );
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,215 @@
+use crate::domain::error::FilamentError;
+use crate::infrastructure::labels::raster::Bitmap;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::str::FromStr;
+use std::time::Duration;
+
+// Raw printing port used by Zebra, Brother and most ESC/POS printers
+pub const RAW_PRINT_PORT: u16 = 9100;
+
+// Brother QL heads are 720 dots wide at 300 dpi; each raster line covers all of them
+const BROTHER_HEAD_DOTS: u32 = 720;
+const BROTHER_DPI: u32 = 300;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThermalFormat {
+    // Zebra Programming Language
+    Zpl,
+    // Epson ESC/POS receipt and label printers
+    EscPos,
+    // Brother QL raster
+    Brother,
+}
+
+impl FromStr for ThermalFormat {
+    type Err = FilamentError;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format.to_lowercase().as_str() {
+            "zpl" => Ok(ThermalFormat::Zpl),
+            "escpos" | "esc_pos" => Ok(ThermalFormat::EscPos),
+            "brother" => Ok(ThermalFormat::Brother),
+            _ => Err(FilamentError::InvalidData(format!(
+                "Unknown label printer format '{}'",
+                format
+            ))),
+        }
+    }
+}
+
+impl ThermalFormat {
+    // What printers using the format usually have
+    pub fn default_dpi(&self) -> u32 {
+        match self {
+            ThermalFormat::Zpl | ThermalFormat::EscPos => 203,
+            ThermalFormat::Brother => BROTHER_DPI,
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ThermalFormat::Zpl => "application/vnd.zebra-zpl",
+            ThermalFormat::EscPos | ThermalFormat::Brother => "application/octet-stream",
+        }
+    }
+
+    // One job printing every label in order, cutting after each where the
+    // printer can
+    pub fn encode(&self, labels: &[Bitmap]) -> Result<Vec<u8>, FilamentError> {
+        if labels.is_empty() {
+            return Err(FilamentError::InvalidData("No labels to print".to_string()));
+        }
+
+        match self {
+            ThermalFormat::Zpl => Ok(labels.iter().flat_map(zpl).collect()),
+            ThermalFormat::EscPos => Ok(escpos(labels)),
+            ThermalFormat::Brother => brother(labels),
+        }
+    }
+}
+
+// Rows packed eight dots to a byte, most significant bit first, 1 for black
+fn packed_rows(label: &Bitmap) -> Vec<Vec<u8>> {
+    let bytes_per_row = label.width().div_ceil(8) as usize;
+    (0..label.height())
+        .map(|y| {
+            let mut row = vec![0u8; bytes_per_row];
+            for x in 0..label.width() {
+                if label.is_dark(x, y) {
+                    row[x as usize / 8] |= 0x80 >> (x % 8);
+                }
+            }
+            row
+        })
+        .collect()
+}
+
+// The label as a single ^GF graphic field
+fn zpl(label: &Bitmap) -> Vec<u8> {
+    let rows = packed_rows(label);
+    let bytes_per_row = rows.first().map(Vec::len).unwrap_or_default();
+    let total = bytes_per_row * rows.len();
+
+    let mut hex = String::with_capacity(total * 2);
+    for byte in rows.iter().flatten() {
+        hex.push_str(&format!("{:02X}", byte));
+    }
+
+    format!(
+        "^XA^PW{}^LL{}^FO0,0^GFA,{},{},{},{}^FS^XZ\n",
+        label.width(),
+        label.height(),
+        total,
+        total,
+        bytes_per_row,
+        hex
+    )
+    .into_bytes()
+}
+
+// `GS v 0` raster images, each followed by a feed and partial cut
+fn escpos(labels: &[Bitmap]) -> Vec<u8> {
+    // ESC @: initialise
+    let mut bytes = vec![0x1B, 0x40];
+
+    for label in labels {
+        let rows = packed_rows(label);
+        let bytes_per_row = rows.first().map(Vec::len).unwrap_or_default() as u16;
+        bytes.extend_from_slice(&[0x1D, 0x76, 0x30, 0x00]);
+        bytes.extend_from_slice(&bytes_per_row.to_le_bytes());
+        bytes.extend_from_slice(&(label.height() as u16).to_le_bytes());
+        for row in rows {
+            bytes.extend_from_slice(&row);
+        }
+        // GS V B 0: feed to the cutter and cut
+        bytes.extend_from_slice(&[0x1D, 0x56, 0x42, 0x00]);
+    }
+
+    bytes
+}
+
+// Brother QL raster for continuous tape as wide as the label. Labels are
+// centred across the head; the printer expects each line mirrored.
+fn brother(labels: &[Bitmap]) -> Result<Vec<u8>, FilamentError> {
+    if let Some(label) = labels.iter().find(|l| l.dpi() != BROTHER_DPI) {
+        return Err(FilamentError::InvalidData(format!(
+            "Brother labels must be rendered at {} dpi, not {}",
+            BROTHER_DPI,
+            label.dpi()
+        )));
+    }
+
+    // Invalidate, then ESC @ to initialise and ESC i a 1 for raster mode
+    let mut bytes = vec![0x00; 200];
+    bytes.extend_from_slice(&[0x1B, 0x40, 0x1B, 0x69, 0x61, 0x01]);
+
+    for (page, label) in labels.iter().enumerate() {
+        let tape_mm = (label.width() as f32 * 25.4 / BROTHER_DPI as f32).round() as u8;
+        // ESC i z: media kind and width are valid, continuous tape
+        bytes.extend_from_slice(&[0x1B, 0x69, 0x7A, 0x86, 0x0A, tape_mm, 0x00]);
+        bytes.extend_from_slice(&label.height().to_le_bytes());
+        bytes.extend_from_slice(&[if page == 0 { 0x00 } else { 0x01 }, 0x00]);
+        // ESC i M: auto cut, ESC i A 1: after every label, ESC i K: cut at end
+        bytes.extend_from_slice(&[0x1B, 0x69, 0x4D, 0x40, 0x1B, 0x69, 0x41, 0x01]);
+        bytes.extend_from_slice(&[0x1B, 0x69, 0x4B, 0x08]);
+        // ESC i d: 35 dot feed margin for continuous tape
+        bytes.extend_from_slice(&[0x1B, 0x69, 0x64, 0x23, 0x00]);
+
+        let head_bytes = (BROTHER_HEAD_DOTS / 8) as usize;
+        let offset = (BROTHER_HEAD_DOTS as i64 - label.width() as i64) / 2;
+        for y in 0..label.height() {
+            let mut line = vec![0u8; head_bytes];
+            for x in 0..label.width() {
+                // Mirrored: the first bit on the wire is the rightmost dot
+                let dot = BROTHER_HEAD_DOTS as i64 - 1 - (offset + x as i64);
+                if (0..BROTHER_HEAD_DOTS as i64).contains(&dot) && label.is_dark(x, y) {
+                    line[dot as usize / 8] |= 0x80 >> (dot % 8);
+                }
+            }
+            bytes.extend_from_slice(&[0x67, 0x00, head_bytes as u8]);
+            bytes.extend_from_slice(&line);
+        }
+
+        // FF between labels, Ctrl-Z to print and feed after the last
+        bytes.push(if page + 1 == labels.len() { 0x1A } else { 0x0C });
+    }
+
+    Ok(bytes)
+}
+
+// Sends a job to a printer's raw port. `address` is `host` or `host:port`.
+pub fn send_raw(address: &str, job: &[u8], timeout: Duration) -> Result<(), FilamentError> {
+    let unreachable = |e: std::io::Error| {
+        FilamentError::RepositoryError(format!("Cannot reach label printer {}: {}", address, e))
+    };
+
+    let has_port = address
+        .rsplit_once(':')
+        .is_some_and(|(_, port)| port.parse::<u16>().is_ok());
+    let target = if has_port {
+        address.to_string()
+    } else {
+        format!("{}:{}", address, RAW_PRINT_PORT)
+    };
+    let socket_address = target
+        .to_socket_addrs()
+        .map_err(unreachable)?
+        .next()
+        .ok_or_else(|| {
+            FilamentError::InvalidData(format!("Unknown label printer address '{}'", address))
+        })?;
+
+    let mut stream = TcpStream::connect_timeout(&socket_address, timeout).map_err(unreachable)?;
+    stream
+        .set_write_timeout(Some(timeout))
+        .map_err(unreachable)?;
+    stream.write_all(job).map_err(unreachable)?;
+    stream.flush().map_err(unreachable)?;
+    // Half-close so the printer sees the end of the job
+    stream.shutdown(Shutdown::Write).map_err(unreachable)?;
+
+    Ok(())
+}
@@ -1 +1,10 @@
+pub mod attachments;
+pub mod bambu;
+pub mod import;
+pub mod inventory_csv;
+pub mod labels;
+pub mod moonraker;
+pub mod nfc;
 pub mod repositories;
+pub mod slicer;
+pub mod spoolman;
@@ -0,0 +1,91 @@
+use crate::domain::error::FilamentError;
+use serde::Deserialize;
+
+// Jobs requested per history page
+const PAGE_SIZE: usize = 50;
+
+// One entry of Moonraker's `/server/history/list`
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct MoonrakerJob {
+    pub job_id: String,
+    pub filename: String,
+    // "in_progress", "completed", "cancelled", "error", "klippy_shutdown", ...
+    pub status: String,
+    pub start_time: f64,
+    pub end_time: Option<f64>,
+    // Millimetres of filament extruded
+    pub filament_used: f32,
+}
+
+impl MoonrakerJob {
+    pub fn is_finished(&self) -> bool {
+        self.status != "in_progress"
+    }
+}
+
+#[derive(Deserialize)]
+struct HistoryResponse {
+    result: HistoryResult,
+}
+
+#[derive(Deserialize)]
+struct HistoryResult {
+    jobs: Vec<MoonrakerJob>,
+}
+
+pub struct MoonrakerClient {
+    base_url: String,
+    http: reqwest::Client,
+}
+
+impl MoonrakerClient {
+    pub fn new(base_url: &str) -> Self {
+        MoonrakerClient {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    // Every job started at or after `since` (Unix seconds), oldest first
+    pub async fn job_history(
+        &self,
+        since: Option<f64>,
+    ) -> Result<Vec<MoonrakerJob>, FilamentError> {
+        let mut jobs = Vec::new();
+
+        loop {
+            let mut query = vec![
+                ("order", "asc".to_string()),
+                ("limit", PAGE_SIZE.to_string()),
+                ("start", jobs.len().to_string()),
+            ];
+            if let Some(since) = since {
+                query.push(("since", since.to_string()));
+            }
+
+            let page = self
+                .http
+                .get(format!("{}/server/history/list", self.base_url))
+                .query(&query)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .map_err(request_error)?
+                .json::<HistoryResponse>()
+                .await
+                .map_err(request_error)?
+                .result
+                .jobs;
+
+            let last_page = page.len() < PAGE_SIZE;
+            jobs.extend(page);
+            if last_page {
+                return Ok(jobs);
+            }
+        }
+    }
+}
+
+fn request_error(e: reqwest::Error) -> FilamentError {
+    FilamentError::RepositoryError(format!("Moonraker request failed: {}", e))
+}
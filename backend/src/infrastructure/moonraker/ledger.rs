@@ -0,0 +1,119 @@
+use crate::domain::error::FilamentError;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+// Remembers which print jobs have already been deducted, per printer
+pub trait ProcessedJobLedger: Send + Sync {
+    // Returns false if the job was already recorded
+    fn record(&self, printer: &str, job_id: &str) -> Result<bool, FilamentError>;
+    fn forget(&self, printer: &str, job_id: &str) -> Result<(), FilamentError>;
+}
+
+pub struct InMemoryProcessedJobs {
+    jobs: Arc<Mutex<HashSet<(String, String)>>>,
+}
+
+impl Default for InMemoryProcessedJobs {
+    fn default() -> Self {
+        Self {
+            jobs: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+}
+
+impl InMemoryProcessedJobs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ProcessedJobLedger for InMemoryProcessedJobs {
+    fn record(&self, printer: &str, job_id: &str) -> Result<bool, FilamentError> {
+        let mut jobs = self.jobs.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        Ok(jobs.insert((printer.to_string(), job_id.to_string())))
+    }
+
+    fn forget(&self, printer: &str, job_id: &str) -> Result<(), FilamentError> {
+        let mut jobs = self.jobs.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        jobs.remove(&(printer.to_string(), job_id.to_string()));
+        Ok(())
+    }
+}
+
+// Ledger kept in a JSON file so processed jobs survive restarts
+pub struct JsonFileProcessedJobs {
+    path: PathBuf,
+    jobs: Mutex<HashMap<String, BTreeSet<String>>>,
+}
+
+impl JsonFileProcessedJobs {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, FilamentError> {
+        let path = path.as_ref().to_path_buf();
+        let jobs = if path.exists() {
+            let json = fs::read(&path).map_err(io_error)?;
+            serde_json::from_slice(&json).map_err(|e| {
+                FilamentError::RepositoryError(format!("Failed to decode job ledger: {}", e))
+            })?
+        } else {
+            HashMap::new()
+        };
+
+        Ok(JsonFileProcessedJobs {
+            path,
+            jobs: Mutex::new(jobs),
+        })
+    }
+
+    fn persist(&self, jobs: &HashMap<String, BTreeSet<String>>) -> Result<(), FilamentError> {
+        let json = serde_json::to_vec_pretty(jobs).map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to encode job ledger: {}", e))
+        })?;
+
+        // Write then rename so a crash never leaves a truncated ledger
+        let temp_path = self.path.with_extension("tmp");
+        fs::write(&temp_path, json).map_err(io_error)?;
+        fs::rename(&temp_path, &self.path).map_err(io_error)
+    }
+}
+
+impl ProcessedJobLedger for JsonFileProcessedJobs {
+    fn record(&self, printer: &str, job_id: &str) -> Result<bool, FilamentError> {
+        let mut jobs = self.jobs.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        if !jobs
+            .entry(printer.to_string())
+            .or_default()
+            .insert(job_id.to_string())
+        {
+            return Ok(false);
+        }
+
+        self.persist(&jobs)?;
+        Ok(true)
+    }
+
+    fn forget(&self, printer: &str, job_id: &str) -> Result<(), FilamentError> {
+        let mut jobs = self.jobs.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        if let Some(printer_jobs) = jobs.get_mut(printer) {
+            printer_jobs.remove(job_id);
+        }
+        self.persist(&jobs)
+    }
+}
+
+fn io_error(e: std::io::Error) -> FilamentError {
+    FilamentError::RepositoryError(format!("I/O error: {}", e))
+}
@@ -0,0 +1,145 @@
+use crate::domain::assignment::RollAssignments;
+use crate::domain::error::FilamentError;
+use crate::domain::material::{density_for, length_to_grams};
+use crate::domain::services::filament_service::FilamentService;
+use crate::infrastructure::moonraker::client::{MoonrakerClient, MoonrakerJob};
+use crate::infrastructure::moonraker::ledger::ProcessedJobLedger;
+use actix_web::rt::{task, time};
+use chrono::Utc;
+use std::sync::Arc;
+use std::time::Duration;
+
+// Moonraker reports a single extruder per job, so usage goes to tool 0
+const MOONRAKER_TOOL: u32 = 0;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Deduction {
+    pub job_id: String,
+    pub roll_id: String,
+    pub grams: f32,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct FailedJob {
+    pub job_id: String,
+    pub message: String,
+}
+
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct SyncReport {
+    pub deducted: Vec<Deduction>,
+    pub failed: Vec<FailedJob>,
+}
+
+// Deducts filament used by finished Moonraker jobs from the roll loaded on the
+// printer. Each job is written to the ledger before it is deducted, so a
+// restart never deducts the same job twice. Jobs that cannot be deducted, e.g.
+// because no roll is loaded, are reported and retried on the next poll.
+pub struct MoonrakerSync {
+    printer: String,
+    client: MoonrakerClient,
+    service: FilamentService,
+    assignments: Arc<dyn RollAssignments>,
+    ledger: Arc<dyn ProcessedJobLedger>,
+    // Ignore jobs started before this Unix timestamp. Defaults to when the
+    // sync was created, so old history is never charged to today's roll.
+    since: f64,
+}
+
+impl MoonrakerSync {
+    pub fn new(
+        printer: &str,
+        client: MoonrakerClient,
+        service: FilamentService,
+        assignments: Arc<dyn RollAssignments>,
+        ledger: Arc<dyn ProcessedJobLedger>,
+    ) -> Self {
+        MoonrakerSync {
+            printer: printer.to_string(),
+            client,
+            service,
+            assignments,
+            ledger,
+            since: Utc::now().timestamp() as f64,
+        }
+    }
+
+    pub fn since(mut self, timestamp: f64) -> Self {
+        self.since = timestamp;
+        self
+    }
+
+    pub async fn poll_once(&self) -> Result<SyncReport, FilamentError> {
+        let mut report = SyncReport::default();
+
+        for job in self.client.job_history(Some(self.since)).await? {
+            if !job.is_finished() || !self.ledger.record(&self.printer, &job.job_id)? {
+                continue;
+            }
+
+            match self.deduct(&job).await {
+                Ok(Some(deduction)) => report.deducted.push(deduction),
+                Ok(None) => {}
+                // Storage may recover, so let the next poll retry the job
+                Err(e @ FilamentError::RepositoryError(_)) => {
+                    self.ledger.forget(&self.printer, &job.job_id)?;
+                    return Err(e);
+                }
+                // Report it and retry next time, e.g. once a roll is loaded
+                Err(e) => {
+                    self.ledger.forget(&self.printer, &job.job_id)?;
+                    report.failed.push(FailedJob {
+                        job_id: job.job_id.clone(),
+                        message: e.to_string(),
+                    });
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    // Polls forever, handing every poll's outcome to `on_poll` instead of
+    // stopping at the first failure
+    pub async fn run(
+        self,
+        interval: Duration,
+        mut on_poll: impl FnMut(Result<SyncReport, FilamentError>),
+    ) {
+        loop {
+            on_poll(self.poll_once().await);
+            time::sleep(interval).await;
+        }
+    }
+
+    async fn deduct(&self, job: &MoonrakerJob) -> Result<Option<Deduction>, FilamentError> {
+        if job.filament_used <= 0.0 {
+            return Ok(None);
+        }
+
+        let roll_id = self
+            .assignments
+            .loaded_roll(&self.printer, MOONRAKER_TOOL)?
+            .ok_or_else(|| {
+                FilamentError::InvalidData(format!("No roll loaded on printer '{}'", self.printer))
+            })?;
+
+        let service = self.service.clone();
+        let length = job.filament_used;
+        let id = roll_id.clone();
+        let grams = task::spawn_blocking(move || {
+            let roll = service.get_roll(&id)?;
+            let grams = length_to_grams(length, roll.diameter(), density_for(roll.material()));
+            service.consume(&id, grams)?;
+            Ok::<_, FilamentError>(grams)
+        })
+        .await
+        .map_err(|e| FilamentError::RepositoryError(format!("Blocking task failed: {}", e)))??;
+
+        Ok(Some(Deduction {
+            job_id: job.job_id.clone(),
+            roll_id,
+            grams,
+        }))
+    }
+}
@@ -0,0 +1,228 @@
+pub mod openspool;
+pub mod tigertag;
+
+use crate::domain::error::FilamentError;
+use crate::domain::filament::FilamentRoll;
+use crate::domain::scan::RollDraft;
+use crate::infrastructure::bambu::rfid::{self, BambuTag};
+use crate::infrastructure::nfc::tigertag::TigerTagIds;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+// NDEF record header flags
+const MESSAGE_BEGIN: u8 = 0x80;
+const MESSAGE_END: u8 = 0x40;
+const SHORT_RECORD: u8 = 0x10;
+const ID_LENGTH: u8 = 0x08;
+const TNF_MASK: u8 = 0x07;
+// Type name format for records typed by a MIME type
+const TNF_MIME: u8 = 0x02;
+
+// Type 2 tag TLV blocks, as NTAG chips store NDEF messages
+const TLV_NULL: u8 = 0x00;
+const TLV_NDEF: u8 = 0x03;
+const TLV_TERMINATOR: u8 = 0xFE;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TagFormat {
+    // The OpenSpool JSON payload on its own, for apps that wrap it themselves
+    OpenSpool,
+    // OpenSpool as an NDEF message
+    Ndef,
+    // TigerTag's raw memory layout
+    TigerTag,
+    // A MIFARE Classic dump of a Bambu Lab spool; read only, as Bambu signs them
+    Bambu,
+}
+
+impl FromStr for TagFormat {
+    type Err = FilamentError;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format.to_lowercase().as_str() {
+            "openspool" | "open_spool" => Ok(TagFormat::OpenSpool),
+            "ndef" => Ok(TagFormat::Ndef),
+            "tigertag" | "tiger_tag" => Ok(TagFormat::TigerTag),
+            "bambu" => Ok(TagFormat::Bambu),
+            _ => Err(FilamentError::InvalidData(format!(
+                "Unknown NFC tag format '{}'",
+                format
+            ))),
+        }
+    }
+}
+
+impl TagFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            TagFormat::OpenSpool => openspool::OPENSPOOL_MIME,
+            TagFormat::Ndef | TagFormat::TigerTag | TagFormat::Bambu => "application/octet-stream",
+        }
+    }
+
+    pub fn encode(&self, roll: &FilamentRoll, ids: &TigerTagIds) -> Result<Vec<u8>, FilamentError> {
+        match self {
+            TagFormat::OpenSpool => openspool::to_json(roll).map(String::into_bytes),
+            TagFormat::Ndef => openspool::to_ndef(roll),
+            TagFormat::TigerTag => tigertag::encode(roll, ids),
+            TagFormat::Bambu => Err(FilamentError::InvalidData(
+                "Bambu tags are signed by Bambu Lab and cannot be written".to_string(),
+            )),
+        }
+    }
+
+    pub fn decode(&self, bytes: &[u8], ids: &TigerTagIds) -> Result<RollDraft, FilamentError> {
+        match self {
+            TagFormat::OpenSpool => openspool::from_bytes(bytes),
+            TagFormat::Ndef => openspool::from_ndef(bytes),
+            TagFormat::TigerTag => tigertag::decode(bytes, ids),
+            TagFormat::Bambu => Ok(BambuTag::parse(bytes)?.draft()),
+        }
+    }
+
+    // What a reader returned, when it did not say: MIFARE Classic dumps are
+    // Bambu, JSON is OpenSpool, NDEF with an OpenSpool record is OpenSpool,
+    // anything else is tried as TigerTag
+    pub fn detect(bytes: &[u8]) -> Self {
+        if rfid::is_dump(bytes) {
+            return TagFormat::Bambu;
+        }
+        if bytes.trim_ascii_start().starts_with(b"{") {
+            return TagFormat::OpenSpool;
+        }
+        let has_openspool = mime_records(bytes).is_ok_and(|records| {
+            records
+                .iter()
+                .any(|(mime_type, _)| mime_type.eq_ignore_ascii_case(openspool::OPENSPOOL_MIME))
+        });
+        if has_openspool {
+            TagFormat::Ndef
+        } else {
+            TagFormat::TigerTag
+        }
+    }
+}
+
+// An NDEF message holding one record of `mime_type`, for phone apps and
+// readers that write messages themselves
+pub fn mime_message(mime_type: &str, payload: &[u8]) -> Vec<u8> {
+    let short = payload.len() < 256;
+    let mut header = MESSAGE_BEGIN | MESSAGE_END | TNF_MIME;
+    if short {
+        header |= SHORT_RECORD;
+    }
+
+    let mut message = vec![header, mime_type.len() as u8];
+    if short {
+        message.push(payload.len() as u8);
+    } else {
+        message.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    }
+    message.extend_from_slice(mime_type.as_bytes());
+    message.extend_from_slice(payload);
+    message
+}
+
+// The message in an NDEF TLV, ready to write from the first user page of a tag
+pub fn tlv_wrap(message: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![TLV_NDEF];
+    if message.len() < 0xFF {
+        bytes.push(message.len() as u8);
+    } else {
+        bytes.push(0xFF);
+        bytes.extend_from_slice(&(message.len() as u16).to_be_bytes());
+    }
+    bytes.extend_from_slice(message);
+    bytes.push(TLV_TERMINATOR);
+    bytes
+}
+
+// Payloads of the MIME records in a message, with their types. Accepts a bare
+// message or a tag memory dump starting at the first user page.
+pub fn mime_records(bytes: &[u8]) -> Result<Vec<(String, Vec<u8>)>, FilamentError> {
+    let truncated = || FilamentError::InvalidData("Truncated NDEF message".to_string());
+
+    // A message starts with a record that has MB set, so anything lower is TLV
+    let message = match bytes.first() {
+        Some(&first) if first & MESSAGE_BEGIN == 0 => ndef_tlv(bytes).ok_or_else(|| {
+            FilamentError::InvalidData("No NDEF message found on the tag".to_string())
+        })?,
+        _ => bytes,
+    };
+
+    let mut records = Vec::new();
+    let mut offset = 0;
+    while offset < message.len() {
+        let header = message[offset];
+        let type_length = *message.get(offset + 1).ok_or_else(truncated)? as usize;
+        offset += 2;
+
+        let payload_length = if header & SHORT_RECORD != 0 {
+            let length = *message.get(offset).ok_or_else(truncated)? as usize;
+            offset += 1;
+            length
+        } else {
+            let length = message.get(offset..offset + 4).ok_or_else(truncated)?;
+            offset += 4;
+            u32::from_be_bytes([length[0], length[1], length[2], length[3]]) as usize
+        };
+        let id_length = if header & ID_LENGTH != 0 {
+            let length = *message.get(offset).ok_or_else(truncated)? as usize;
+            offset += 1;
+            length
+        } else {
+            0
+        };
+
+        let record_type = message
+            .get(offset..offset + type_length)
+            .ok_or_else(truncated)?;
+        offset += type_length + id_length;
+        let payload = message
+            .get(offset..offset + payload_length)
+            .ok_or_else(truncated)?;
+        offset += payload_length;
+
+        if header & TNF_MASK == TNF_MIME {
+            records.push((
+                String::from_utf8_lossy(record_type).to_string(),
+                payload.to_vec(),
+            ));
+        }
+        if header & MESSAGE_END != 0 {
+            break;
+        }
+    }
+
+    Ok(records)
+}
+
+// The value of the first NDEF TLV, skipping lock and memory control blocks
+fn ndef_tlv(bytes: &[u8]) -> Option<&[u8]> {
+    let mut offset = 0;
+    loop {
+        let tag = *bytes.get(offset)?;
+        match tag {
+            TLV_NULL => {
+                offset += 1;
+                continue;
+            }
+            TLV_TERMINATOR => return None,
+            _ => {}
+        }
+
+        let (length, header) = match *bytes.get(offset + 1)? {
+            0xFF => {
+                let length = bytes.get(offset + 2..offset + 4)?;
+                (u16::from_be_bytes([length[0], length[1]]) as usize, 4)
+            }
+            length => (length as usize, 2),
+        };
+        let value = bytes.get(offset + header..offset + header + length)?;
+        if tag == TLV_NDEF {
+            return Some(value);
+        }
+        offset += header + length;
+    }
+}
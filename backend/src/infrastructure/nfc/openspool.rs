@@ -0,0 +1,119 @@
+use crate::domain::error::FilamentError;
+use crate::domain::filament::FilamentRoll;
+use crate::domain::material::print_temperatures;
+use crate::domain::scan::RollDraft;
+use crate::infrastructure::nfc::{mime_message, mime_records};
+use serde_json::{json, Map, Value};
+
+// OpenSpool tags hold a single NDEF record of this type
+pub const OPENSPOOL_MIME: &str = "application/json";
+
+const PROTOCOL: &str = "openspool";
+const VERSION: &str = "1.0";
+
+// Assumed when a tag leaves them out; the basic format has neither
+const DEFAULT_DIAMETER: f32 = 1.75;
+const DEFAULT_WEIGHT: f32 = 1000.0;
+
+// The JSON payload. Temperatures are strings, as the format specifies;
+// `diameter` and `weight` come from the extended format.
+pub fn to_json(roll: &FilamentRoll) -> Result<String, FilamentError> {
+    let color = color_hex(roll.color()).ok_or_else(|| {
+        FilamentError::InvalidData(format!(
+            "Colour '{}' must be #RRGGBB to write an OpenSpool tag",
+            roll.color()
+        ))
+    })?;
+
+    let mut payload = json!({
+        "protocol": PROTOCOL,
+        "version": VERSION,
+        "type": roll.material(),
+        "color_hex": color,
+        "brand": roll.manufacturer(),
+        "diameter": roll.diameter(),
+        "weight": roll.weight(),
+    });
+    if let Some((min, max)) = print_temperatures(roll.material()) {
+        payload["min_temp"] = json!(min.to_string());
+        payload["max_temp"] = json!(max.to_string());
+    }
+
+    Ok(payload.to_string())
+}
+
+// The payload as an NDEF message
+pub fn to_ndef(roll: &FilamentRoll) -> Result<Vec<u8>, FilamentError> {
+    Ok(mime_message(OPENSPOOL_MIME, to_json(roll)?.as_bytes()))
+}
+
+// A draft of the roll a tag describes, for confirming before it is registered
+pub fn from_json(json: &str) -> Result<RollDraft, FilamentError> {
+    let invalid = |message: &str| FilamentError::InvalidData(format!("OpenSpool tag {}", message));
+    let payload: Map<String, Value> =
+        serde_json::from_str(json).map_err(|e| invalid(&format!("is not JSON: {}", e)))?;
+
+    let text = |key: &str| {
+        payload
+            .get(key)
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+    };
+    let number = |key: &str| match payload.get(key)? {
+        Value::Number(n) => n.as_f64().map(|n| n as f32),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    };
+
+    if text("protocol").map(str::to_lowercase).as_deref() != Some(PROTOCOL) {
+        return Err(invalid("is missing \"protocol\": \"openspool\""));
+    }
+    let material = text("type").ok_or_else(|| invalid("has no type"))?;
+    let brand = text("brand").unwrap_or("Generic");
+    let color = text("color_hex").ok_or_else(|| invalid("has no color_hex"))?;
+
+    let name = match text("subtype") {
+        Some(subtype) => format!("{} {} {}", brand, material, subtype),
+        None => format!("{} {}", brand, material),
+    };
+
+    Ok(RollDraft {
+        name,
+        material: material.to_string(),
+        color: format!("#{}", color.trim_start_matches('#').to_uppercase()),
+        diameter: number("diameter").unwrap_or(DEFAULT_DIAMETER),
+        weight: number("weight").unwrap_or(DEFAULT_WEIGHT),
+        manufacturer: brand.to_string(),
+        id: None,
+        spool_weight: None,
+    })
+}
+
+// Reads the OpenSpool record from an NDEF message or tag dump
+pub fn from_ndef(bytes: &[u8]) -> Result<RollDraft, FilamentError> {
+    let records = mime_records(bytes)?;
+    let (_, payload) = records
+        .iter()
+        .find(|(mime_type, _)| mime_type.eq_ignore_ascii_case(OPENSPOOL_MIME))
+        .ok_or_else(|| {
+            FilamentError::InvalidData("Tag has no OpenSpool JSON record".to_string())
+        })?;
+    from_bytes(payload)
+}
+
+// The JSON payload as read from a tag
+pub fn from_bytes(payload: &[u8]) -> Result<RollDraft, FilamentError> {
+    let json = std::str::from_utf8(payload).map_err(|_| {
+        FilamentError::InvalidData("OpenSpool tag payload is not UTF-8".to_string())
+    })?;
+
+    from_json(json)
+}
+
+// Six hex digits without '#'; any alpha is dropped
+fn color_hex(color: &str) -> Option<String> {
+    let hex = color.trim().trim_start_matches('#');
+    let valid = (hex.len() == 6 || hex.len() == 8) && hex.chars().all(|c| c.is_ascii_hexdigit());
+    valid.then(|| hex[..6].to_uppercase())
+}
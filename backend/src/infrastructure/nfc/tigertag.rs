@@ -0,0 +1,191 @@
+use crate::domain::error::FilamentError;
+use crate::domain::filament::FilamentRoll;
+use crate::domain::material::print_temperatures;
+use crate::domain::scan::RollDraft;
+use serde::Deserialize;
+
+// TigerTag data starts on the first user page of an NTAG213 and is read and
+// written as raw memory rather than NDEF. Multi-byte fields are big-endian.
+//
+//  0  u32  TigerTag version         16  u8×4 colour, RGBA
+//  4  u32  product id               20  u24  measure (net amount on the spool)
+//  8  u16  material id              23  u8   unit id
+// 10  u8   aspect 1 id              24  u16  nozzle temperature min, °C
+// 11  u8   aspect 2 id              26  u16  nozzle temperature max, °C
+// 12  u8   type id                  28  u8   drying temperature, °C
+// 13  u8   diameter id              29  u8   drying time, hours
+// 14  u16  brand id                 30  u8×2 bed temperature min and max
+//                                   32  u32  timestamp
+pub const TAG_BYTES: usize = 36;
+
+// Product id written for spools that are not in TigerTag's product database
+const NO_PRODUCT: u32 = 0xFFFF_FFFF;
+
+// One entry of TigerTag's published id lists
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct TigerTagId {
+    pub id: u32,
+    pub label: String,
+}
+
+// TigerTag stores ids instead of names, resolved through lists TigerTag
+// publishes. Loaded from one JSON object holding those lists, e.g.
+// `{"version": ..., "materials": [{"id": ..., "label": "PLA"}], ...}`.
+#[derive(Debug, Deserialize, PartialEq, Clone, Default)]
+pub struct TigerTagIds {
+    // Written to the first four bytes of new tags
+    #[serde(default)]
+    pub version: u32,
+    #[serde(default)]
+    pub materials: Vec<TigerTagId>,
+    #[serde(default)]
+    pub brands: Vec<TigerTagId>,
+    // Labels start with the diameter in mm, e.g. `1.75` or `1.75 mm`
+    #[serde(default)]
+    pub diameters: Vec<TigerTagId>,
+    #[serde(default)]
+    pub units: Vec<TigerTagId>,
+}
+
+impl TigerTagIds {
+    pub fn from_json(json: &str) -> Result<Self, FilamentError> {
+        serde_json::from_str(json)
+            .map_err(|e| FilamentError::InvalidData(format!("Invalid TigerTag id lists: {}", e)))
+    }
+
+    fn label(list: &[TigerTagId], id: u32, kind: &str) -> Result<String, FilamentError> {
+        list.iter()
+            .find(|entry| entry.id == id)
+            .map(|entry| entry.label.clone())
+            .ok_or_else(|| {
+                FilamentError::InvalidData(format!("Unknown TigerTag {} id {}", kind, id))
+            })
+    }
+
+    fn id(list: &[TigerTagId], label: &str, kind: &str) -> Result<u32, FilamentError> {
+        list.iter()
+            .find(|entry| entry.label.trim().eq_ignore_ascii_case(label.trim()))
+            .map(|entry| entry.id)
+            .ok_or_else(|| {
+                FilamentError::InvalidData(format!("No TigerTag {} id for '{}'", kind, label))
+            })
+    }
+
+    fn diameter(&self, id: u32) -> Result<f32, FilamentError> {
+        let label = Self::label(&self.diameters, id, "diameter")?;
+        leading_number(&label).ok_or_else(|| {
+            FilamentError::InvalidData(format!("TigerTag diameter '{}' is not a number", label))
+        })
+    }
+
+    fn diameter_id(&self, diameter: f32) -> Result<u32, FilamentError> {
+        self.diameters
+            .iter()
+            .find(|entry| leading_number(&entry.label).is_some_and(|d| (d - diameter).abs() < 0.01))
+            .map(|entry| entry.id)
+            .ok_or_else(|| {
+                FilamentError::InvalidData(format!("No TigerTag diameter id for {} mm", diameter))
+            })
+    }
+}
+
+pub fn encode(roll: &FilamentRoll, ids: &TigerTagIds) -> Result<Vec<u8>, FilamentError> {
+    let rgba = rgba(roll.color()).ok_or_else(|| {
+        FilamentError::InvalidData(format!(
+            "Colour '{}' must be #RRGGBB or #RRGGBBAA to write a TigerTag",
+            roll.color()
+        ))
+    })?;
+    let material = TigerTagIds::id(&ids.materials, roll.material(), "material")?;
+    // Brands TigerTag does not list are written as its generic brand
+    let brand = TigerTagIds::id(&ids.brands, roll.manufacturer(), "brand")
+        .or_else(|_| TigerTagIds::id(&ids.brands, "Generic", "brand"))?;
+    let diameter = ids.diameter_id(roll.diameter())?;
+    let grams = TigerTagIds::id(&ids.units, "g", "unit")?;
+    let (min_temp, max_temp) = print_temperatures(roll.material()).unwrap_or_default();
+
+    let too_large =
+        |_| FilamentError::InvalidData("TigerTag id list has ids too large for a tag".to_string());
+
+    let mut tag = vec![0u8; TAG_BYTES];
+    tag[0..4].copy_from_slice(&ids.version.to_be_bytes());
+    tag[4..8].copy_from_slice(&NO_PRODUCT.to_be_bytes());
+    tag[8..10].copy_from_slice(&u16::try_from(material).map_err(too_large)?.to_be_bytes());
+    tag[13] = u8::try_from(diameter).map_err(too_large)?;
+    tag[14..16].copy_from_slice(&u16::try_from(brand).map_err(too_large)?.to_be_bytes());
+    tag[16..20].copy_from_slice(&rgba);
+    let measure = (roll.weight().round() as u32).min(0xFF_FFFF);
+    tag[20..23].copy_from_slice(&measure.to_be_bytes()[1..]);
+    tag[23] = u8::try_from(grams).map_err(too_large)?;
+    tag[24..26].copy_from_slice(&min_temp.to_be_bytes());
+    tag[26..28].copy_from_slice(&max_temp.to_be_bytes());
+
+    Ok(tag)
+}
+
+// A draft of the roll a tag describes, for confirming before it is registered
+pub fn decode(tag: &[u8], ids: &TigerTagIds) -> Result<RollDraft, FilamentError> {
+    if tag.len() < TAG_BYTES {
+        return Err(FilamentError::InvalidData(format!(
+            "TigerTag data is {} bytes, expected at least {}",
+            tag.len(),
+            TAG_BYTES
+        )));
+    }
+    let u16_at = |offset: usize| u16::from_be_bytes([tag[offset], tag[offset + 1]]) as u32;
+
+    let material = TigerTagIds::label(&ids.materials, u16_at(8), "material")?;
+    let brand = TigerTagIds::label(&ids.brands, u16_at(14), "brand")?;
+    let diameter = ids.diameter(tag[13] as u32)?;
+
+    let measure = u32::from_be_bytes([0, tag[20], tag[21], tag[22]]) as f32;
+    let unit = TigerTagIds::label(&ids.units, tag[23] as u32, "unit")?;
+    let weight = match unit.trim().to_lowercase().as_str() {
+        "g" => measure,
+        "kg" => measure * 1000.0,
+        _ => {
+            return Err(FilamentError::InvalidData(format!(
+                "TigerTag measures in '{}', not by weight",
+                unit
+            )))
+        }
+    };
+
+    let [r, g, b, a] = [tag[16], tag[17], tag[18], tag[19]];
+    let color = if a == 0xFF {
+        format!("#{:02X}{:02X}{:02X}", r, g, b)
+    } else {
+        format!("#{:02X}{:02X}{:02X}{:02X}", r, g, b, a)
+    };
+
+    Ok(RollDraft {
+        name: format!("{} {}", brand, material),
+        material,
+        color,
+        diameter,
+        weight,
+        manufacturer: brand,
+        id: None,
+        spool_weight: None,
+    })
+}
+
+// `#RRGGBB`, opaque, or `#RRGGBBAA`
+fn rgba(color: &str) -> Option<[u8; 4]> {
+    let hex = color.trim().trim_start_matches('#');
+    if hex.len() != 6 && hex.len() != 8 {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    let alpha = if hex.len() == 8 { channel(6)? } else { 0xFF };
+    Some([channel(0)?, channel(2)?, channel(4)?, alpha])
+}
+
+fn leading_number(label: &str) -> Option<f32> {
+    let number: String = label
+        .trim()
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    number.parse().ok()
+}
@@ -0,0 +1,195 @@
+use crate::domain::audit::{
+    deleted_fields, diff_rolls, AuditContext, AuditEntry, AuditLog, FieldChange,
+};
+use crate::domain::error::FilamentError;
+use crate::domain::filament::{FilamentRepository, FilamentRoll};
+use crate::domain::unit_of_work::{TransactionalFilamentRepository, UnitOfWork};
+use std::sync::{Mutex, MutexGuard};
+
+// Wraps a repository and records a field-level audit entry for every write.
+// The trait methods use the default context; the `_as` variants let callers
+// attribute a change to a specific actor and source.
+pub struct AuditedFilamentRepository<R: FilamentRepository, L: AuditLog> {
+    repository: R,
+    audit_log: L,
+    context: AuditContext,
+    // Held from reading the old state until the entry is recorded, so each
+    // entry diffs against the write before it and entries land in order
+    writing: Mutex<()>,
+}
+
+impl<R: FilamentRepository, L: AuditLog> AuditedFilamentRepository<R, L> {
+    pub fn new(repository: R, audit_log: L, context: AuditContext) -> Self {
+        AuditedFilamentRepository {
+            repository,
+            audit_log,
+            context,
+            writing: Mutex::new(()),
+        }
+    }
+
+    pub fn audit_log(&self) -> &L {
+        &self.audit_log
+    }
+
+    pub fn save_as(
+        &self,
+        filament: &FilamentRoll,
+        context: &AuditContext,
+    ) -> Result<(), FilamentError> {
+        let _writing = self.lock_writes()?;
+        let before = self.find_existing(filament.id())?;
+
+        self.repository.save(filament)?;
+
+        self.record(
+            filament.id(),
+            diff_rolls(before.as_ref(), filament),
+            context,
+        )
+    }
+
+    pub fn update_remaining_weight_as(
+        &self,
+        id: &str,
+        remaining_weight: f32,
+        context: &AuditContext,
+    ) -> Result<FilamentRoll, FilamentError> {
+        let _writing = self.lock_writes()?;
+        let before = self.repository.find_by_id(id)?;
+
+        let updated = self
+            .repository
+            .update_remaining_weight(id, remaining_weight)?;
+
+        self.record(id, diff_rolls(Some(&before), &updated), context)?;
+        Ok(updated)
+    }
+
+    pub fn update_as(
+        &self,
+        id: &str,
+        change: &mut dyn FnMut(&mut FilamentRoll) -> Result<(), FilamentError>,
+        context: &AuditContext,
+    ) -> Result<FilamentRoll, FilamentError> {
+        let _writing = self.lock_writes()?;
+        // Taken inside the update, so it is the state the change was applied to
+        let mut before = None;
+        let updated = self.repository.update(id, &mut |roll| {
+            before = Some(roll.clone());
+            change(roll)
+        })?;
+
+        self.record(id, diff_rolls(before.as_ref(), &updated), context)?;
+        Ok(updated)
+    }
+
+    pub fn delete_as(&self, id: &str, context: &AuditContext) -> Result<(), FilamentError> {
+        let _writing = self.lock_writes()?;
+        let before = self.repository.find_by_id(id)?;
+
+        self.repository.delete(id)?;
+
+        self.record(id, deleted_fields(&before), context)
+    }
+
+    pub fn commit_as(
+        &self,
+        unit: &UnitOfWork,
+        context: &AuditContext,
+    ) -> Result<Vec<FilamentRoll>, FilamentError>
+    where
+        R: TransactionalFilamentRepository,
+    {
+        let _writing = self.lock_writes()?;
+        let before = unit
+            .changes()
+            .iter()
+            .map(|c| self.repository.find_by_id(c.roll_id()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let updated = self.repository.commit(unit)?;
+
+        for after in &updated {
+            let previous = before.iter().find(|r| r.id() == after.id());
+            self.record(after.id(), diff_rolls(previous, after), context)?;
+        }
+
+        Ok(updated)
+    }
+
+    fn lock_writes(&self) -> Result<MutexGuard<'_, ()>, FilamentError> {
+        self.writing
+            .lock()
+            .map_err(|e| FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e)))
+    }
+
+    fn find_existing(&self, id: &str) -> Result<Option<FilamentRoll>, FilamentError> {
+        match self.repository.find_by_id(id) {
+            Ok(filament) => Ok(Some(filament)),
+            Err(FilamentError::NotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn record(
+        &self,
+        roll_id: &str,
+        changes: Vec<FieldChange>,
+        context: &AuditContext,
+    ) -> Result<(), FilamentError> {
+        // Saving an unchanged roll is not worth an entry
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        self.audit_log
+            .record(&AuditEntry::new(roll_id, context, changes))
+    }
+}
+
+impl<R: FilamentRepository, L: AuditLog> FilamentRepository for AuditedFilamentRepository<R, L> {
+    fn save(&self, filament: &FilamentRoll) -> Result<(), FilamentError> {
+        self.save_as(filament, &self.context)
+    }
+
+    fn find_by_id(&self, id: &str) -> Result<FilamentRoll, FilamentError> {
+        self.repository.find_by_id(id)
+    }
+
+    fn update_remaining_weight(
+        &self,
+        id: &str,
+        remaining_weight: f32,
+    ) -> Result<FilamentRoll, FilamentError> {
+        self.update_remaining_weight_as(id, remaining_weight, &self.context)
+    }
+
+    fn find_all(&self) -> Result<Vec<FilamentRoll>, FilamentError> {
+        self.repository.find_all()
+    }
+
+    fn find_by_material(&self, material: &str) -> Result<Vec<FilamentRoll>, FilamentError> {
+        self.repository.find_by_material(material)
+    }
+
+    fn delete(&self, id: &str) -> Result<(), FilamentError> {
+        self.delete_as(id, &self.context)
+    }
+
+    fn update(
+        &self,
+        id: &str,
+        change: &mut dyn FnMut(&mut FilamentRoll) -> Result<(), FilamentError>,
+    ) -> Result<FilamentRoll, FilamentError> {
+        self.update_as(id, change, &self.context)
+    }
+}
+
+impl<R: TransactionalFilamentRepository, L: AuditLog> TransactionalFilamentRepository
+    for AuditedFilamentRepository<R, L>
+{
+    fn commit(&self, unit: &UnitOfWork) -> Result<Vec<FilamentRoll>, FilamentError> {
+        self.commit_as(unit, &self.context)
+    }
+}
@@ -0,0 +1,79 @@
+use crate::domain::async_repository::AsyncFilamentRepository;
+use crate::domain::error::FilamentError;
+use crate::domain::filament::{FilamentRepository, FilamentRoll};
+use actix_web::rt::task;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+// Exposes a synchronous repository through `AsyncFilamentRepository` by running
+// each call on the blocking thread pool, keeping file or lock waits off the
+// async workers.
+pub struct BlockingRepositoryAdapter<R> {
+    repository: Arc<R>,
+}
+
+impl<R> Clone for BlockingRepositoryAdapter<R> {
+    fn clone(&self) -> Self {
+        BlockingRepositoryAdapter {
+            repository: Arc::clone(&self.repository),
+        }
+    }
+}
+
+impl<R: FilamentRepository + 'static> BlockingRepositoryAdapter<R> {
+    pub fn new(repository: R) -> Self {
+        Self::from_arc(Arc::new(repository))
+    }
+
+    pub fn from_arc(repository: Arc<R>) -> Self {
+        BlockingRepositoryAdapter { repository }
+    }
+
+    async fn run<T, F>(&self, operation: F) -> Result<T, FilamentError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&R) -> Result<T, FilamentError> + Send + 'static,
+    {
+        let repository = Arc::clone(&self.repository);
+        task::spawn_blocking(move || operation(&repository))
+            .await
+            .map_err(|e| FilamentError::RepositoryError(format!("Blocking task failed: {}", e)))?
+    }
+}
+
+#[async_trait]
+impl<R: FilamentRepository + 'static> AsyncFilamentRepository for BlockingRepositoryAdapter<R> {
+    async fn save(&self, filament: &FilamentRoll) -> Result<(), FilamentError> {
+        let filament = filament.clone();
+        self.run(move |r| r.save(&filament)).await
+    }
+
+    async fn find_by_id(&self, id: &str) -> Result<FilamentRoll, FilamentError> {
+        let id = id.to_string();
+        self.run(move |r| r.find_by_id(&id)).await
+    }
+
+    async fn update_remaining_weight(
+        &self,
+        id: &str,
+        remaining_weight: f32,
+    ) -> Result<FilamentRoll, FilamentError> {
+        let id = id.to_string();
+        self.run(move |r| r.update_remaining_weight(&id, remaining_weight))
+            .await
+    }
+
+    async fn find_all(&self) -> Result<Vec<FilamentRoll>, FilamentError> {
+        self.run(|r| r.find_all()).await
+    }
+
+    async fn find_by_material(&self, material: &str) -> Result<Vec<FilamentRoll>, FilamentError> {
+        let material = material.to_string();
+        self.run(move |r| r.find_by_material(&material)).await
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), FilamentError> {
+        let id = id.to_string();
+        self.run(move |r| r.delete(&id)).await
+    }
+}
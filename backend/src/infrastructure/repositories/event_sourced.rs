@@ -0,0 +1,215 @@
+use crate::domain::error::FilamentError;
+use crate::domain::events::{EventStore, FilamentEvent, Snapshot, SnapshotStore};
+use crate::domain::filament::{FilamentRepository, FilamentRoll};
+use crate::domain::projections::{Projection, RollProjection};
+use crate::domain::unit_of_work::{TransactionalFilamentRepository, UnitOfWork};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+// Persists domain events instead of current state. Current state is a
+// projection rebuilt from the latest snapshot plus the events recorded since.
+pub struct EventSourcedFilamentRepository<E: EventStore, S: SnapshotStore> {
+    events: E,
+    snapshots: S,
+    state: Mutex<RollProjection>,
+    // Take a snapshot after this many events; None disables automatic snapshots
+    snapshot_interval: Option<u64>,
+    // Version of the latest snapshot saved, guarded by `state`'s lock
+    snapshot_version: AtomicU64,
+}
+
+impl<E: EventStore, S: SnapshotStore> EventSourcedFilamentRepository<E, S> {
+    pub fn open(events: E, snapshots: S) -> Result<Self, FilamentError> {
+        let mut state = match snapshots.load_latest()? {
+            Some(snapshot) => RollProjection::from_snapshot(snapshot),
+            None => RollProjection::new(),
+        };
+        let snapshot_version = AtomicU64::new(state.version());
+
+        for event in events.load_since(state.version())? {
+            state.apply(&event)?;
+        }
+
+        Ok(EventSourcedFilamentRepository {
+            events,
+            snapshots,
+            state: Mutex::new(state),
+            snapshot_interval: None,
+            snapshot_version,
+        })
+    }
+
+    pub fn with_snapshot_interval(mut self, interval: u64) -> Self {
+        self.snapshot_interval = Some(interval);
+        self
+    }
+
+    pub fn archive(&self, id: &str) -> Result<(), FilamentError> {
+        let mut state = self.lock_state()?;
+
+        if state.get(id).is_none() {
+            return Err(FilamentError::NotFound(id.to_string()));
+        }
+
+        self.commit(
+            &mut state,
+            vec![FilamentEvent::RollArchived {
+                roll_id: id.to_string(),
+            }],
+        )
+    }
+
+    pub fn find_archived(&self) -> Result<Vec<FilamentRoll>, FilamentError> {
+        Ok(self.lock_state()?.archived().cloned().collect())
+    }
+
+    // Persists the current state so the next `open` only replays newer events
+    pub fn snapshot(&self) -> Result<Snapshot, FilamentError> {
+        let state = self.lock_state()?;
+        let snapshot = state.to_snapshot();
+        self.snapshots.save(&snapshot)?;
+        self.snapshot_version
+            .store(snapshot.version, Ordering::Relaxed);
+        Ok(snapshot)
+    }
+
+    // Replays the full event history into a read model
+    pub fn rebuild<P: Projection>(&self, projection: &mut P) -> Result<(), FilamentError> {
+        for event in self.events.load_since(0)? {
+            projection.apply(&event)?;
+        }
+        Ok(())
+    }
+
+    fn lock_state(&self) -> Result<MutexGuard<'_, RollProjection>, FilamentError> {
+        self.state
+            .lock()
+            .map_err(|e| FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e)))
+    }
+
+    fn commit(
+        &self,
+        state: &mut RollProjection,
+        events: Vec<FilamentEvent>,
+    ) -> Result<(), FilamentError> {
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        for recorded in self.events.append(&events)? {
+            state.apply(&recorded)?;
+        }
+
+        // The events are already durable, so a failed snapshot must not fail
+        // the write; it is retried after the next one instead
+        if let Some(interval) = self.snapshot_interval {
+            if state.version() - self.snapshot_version.load(Ordering::Relaxed) >= interval
+                && self.snapshots.save(&state.to_snapshot()).is_ok()
+            {
+                self.snapshot_version
+                    .store(state.version(), Ordering::Relaxed);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<E: EventStore, S: SnapshotStore> FilamentRepository for EventSourcedFilamentRepository<E, S> {
+    fn save(&self, filament: &FilamentRoll) -> Result<(), FilamentError> {
+        let mut state = self.lock_state()?;
+
+        let events = FilamentEvent::for_change(state.get(filament.id()), filament);
+        self.commit(&mut state, events)
+    }
+
+    fn find_by_id(&self, id: &str) -> Result<FilamentRoll, FilamentError> {
+        self.lock_state()?
+            .get(id)
+            .cloned()
+            .ok_or_else(|| FilamentError::NotFound(id.to_string()))
+    }
+
+    fn update_remaining_weight(
+        &self,
+        id: &str,
+        remaining_weight: f32,
+    ) -> Result<FilamentRoll, FilamentError> {
+        let mut state = self.lock_state()?;
+
+        let current = state
+            .get(id)
+            .ok_or_else(|| FilamentError::NotFound(id.to_string()))?;
+
+        // Use domain entity method for validation before recording anything
+        let mut updated = current.clone();
+        updated.update_remaining_weight(remaining_weight)?;
+
+        let events = FilamentEvent::for_change(Some(current), &updated);
+        self.commit(&mut state, events)?;
+
+        Ok(updated)
+    }
+
+    fn find_all(&self) -> Result<Vec<FilamentRoll>, FilamentError> {
+        Ok(self.lock_state()?.rolls().cloned().collect())
+    }
+
+    fn find_by_material(&self, material: &str) -> Result<Vec<FilamentRoll>, FilamentError> {
+        Ok(self
+            .lock_state()?
+            .rolls()
+            .filter(|f| f.material() == material)
+            .cloned()
+            .collect())
+    }
+
+    // Events are never erased, so a deleted roll is archived
+    fn delete(&self, id: &str) -> Result<(), FilamentError> {
+        self.archive(id)
+    }
+
+    fn update(
+        &self,
+        id: &str,
+        change: &mut dyn FnMut(&mut FilamentRoll) -> Result<(), FilamentError>,
+    ) -> Result<FilamentRoll, FilamentError> {
+        let mut state = self.lock_state()?;
+
+        let current = state
+            .get(id)
+            .ok_or_else(|| FilamentError::NotFound(id.to_string()))?;
+
+        let mut updated = current.clone();
+        change(&mut updated)?;
+
+        let events = FilamentEvent::for_change(Some(current), &updated);
+        self.commit(&mut state, events)?;
+
+        Ok(updated)
+    }
+}
+
+impl<E: EventStore, S: SnapshotStore> TransactionalFilamentRepository
+    for EventSourcedFilamentRepository<E, S>
+{
+    fn commit(&self, unit: &UnitOfWork) -> Result<Vec<FilamentRoll>, FilamentError> {
+        let mut state = self.lock_state()?;
+
+        let updated = unit.apply(|id| {
+            state
+                .get(id)
+                .cloned()
+                .ok_or_else(|| FilamentError::NotFound(id.to_string()))
+        })?;
+
+        // One append for the whole batch, so either all events land or none do
+        let events = updated
+            .iter()
+            .flat_map(|after| FilamentEvent::for_change(state.get(after.id()), after))
+            .collect();
+        EventSourcedFilamentRepository::commit(self, &mut state, events)?;
+
+        Ok(updated)
+    }
+}
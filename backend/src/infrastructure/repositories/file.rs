@@ -0,0 +1,160 @@
+use crate::domain::error::FilamentError;
+use crate::domain::events::{EventStore, FilamentEvent, RecordedEvent, Snapshot, SnapshotStore};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// Append-only event log with one JSON-encoded event per line
+pub struct JsonLinesEventStore {
+    path: PathBuf,
+    // Sequence number of the last event in the file
+    last_sequence: Mutex<u64>,
+}
+
+impl JsonLinesEventStore {
+    // A last line cut short by a crash mid-append was never acknowledged, so
+    // it is dropped from the file
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, FilamentError> {
+        let path = path.as_ref().to_path_buf();
+        if path.exists() {
+            let contents = fs::read(&path).map_err(io_error)?;
+            let intact = intact_length(&contents);
+            if intact < contents.len() {
+                let file = OpenOptions::new()
+                    .write(true)
+                    .open(&path)
+                    .map_err(io_error)?;
+                file.set_len(intact as u64).map_err(io_error)?;
+                file.sync_data().map_err(io_error)?;
+            }
+        }
+
+        let last_sequence = read_events(&path)?
+            .last()
+            .map(|e| e.sequence())
+            .unwrap_or(0);
+
+        Ok(JsonLinesEventStore {
+            path,
+            last_sequence: Mutex::new(last_sequence),
+        })
+    }
+}
+
+impl EventStore for JsonLinesEventStore {
+    fn append(&self, events: &[FilamentEvent]) -> Result<Vec<RecordedEvent>, FilamentError> {
+        let mut last_sequence = self.last_sequence.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        let recorded: Vec<RecordedEvent> = events
+            .iter()
+            .enumerate()
+            .map(|(i, event)| RecordedEvent::new(*last_sequence + 1 + i as u64, event.clone()))
+            .collect();
+
+        // Write the whole batch at once so a batch is never half-recorded
+        let mut buffer = String::new();
+        for event in &recorded {
+            let line = serde_json::to_string(event).map_err(|e| {
+                FilamentError::RepositoryError(format!("Failed to encode event: {}", e))
+            })?;
+            buffer.push_str(&line);
+            buffer.push('\n');
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(io_error)?;
+        let length = file.metadata().map_err(io_error)?.len();
+        let written = file
+            .write_all(buffer.as_bytes())
+            .and_then(|_| file.sync_data());
+        if let Err(e) = written {
+            // Cut off whatever part of the batch made it to disk, so the
+            // file still ends at `last_sequence`
+            let _ = file.set_len(length).and_then(|_| file.sync_data());
+            return Err(io_error(e));
+        }
+
+        *last_sequence += recorded.len() as u64;
+        Ok(recorded)
+    }
+
+    fn load_since(&self, after_sequence: u64) -> Result<Vec<RecordedEvent>, FilamentError> {
+        Ok(read_events(&self.path)?
+            .into_iter()
+            .filter(|e| e.sequence() > after_sequence)
+            .collect())
+    }
+}
+
+// Keeps only the latest snapshot as a single JSON document
+pub struct JsonSnapshotStore {
+    path: PathBuf,
+}
+
+impl JsonSnapshotStore {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        JsonSnapshotStore {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl SnapshotStore for JsonSnapshotStore {
+    fn save(&self, snapshot: &Snapshot) -> Result<(), FilamentError> {
+        let json = serde_json::to_vec(snapshot).map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to encode snapshot: {}", e))
+        })?;
+
+        // Write then rename so a crash never leaves a truncated snapshot
+        let temp_path = self.path.with_extension("tmp");
+        fs::write(&temp_path, json).map_err(io_error)?;
+        fs::rename(&temp_path, &self.path).map_err(io_error)
+    }
+
+    fn load_latest(&self) -> Result<Option<Snapshot>, FilamentError> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+
+        let json = fs::read(&self.path).map_err(io_error)?;
+        serde_json::from_slice(&json).map(Some).map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to decode snapshot: {}", e))
+        })
+    }
+}
+
+fn read_events(path: &Path) -> Result<Vec<RecordedEvent>, FilamentError> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let contents = fs::read_to_string(path).map_err(io_error)?;
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str(line).map_err(|e| {
+                FilamentError::RepositoryError(format!("Failed to decode event: {}", e))
+            })
+        })
+        .collect()
+}
+
+// Length of the log up to the end of its last complete line. Every append
+// ends in a newline, so anything after the last one is a torn write.
+fn intact_length(contents: &[u8]) -> usize {
+    contents
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |i| i + 1)
+}
+
+fn io_error(e: std::io::Error) -> FilamentError {
+    FilamentError::RepositoryError(format!("I/O error: {}", e))
+}
@@ -1,5 +1,16 @@
+use crate::domain::assignment::RollAssignments;
+use crate::domain::async_repository::AsyncFilamentRepository;
+use crate::domain::audit::{AuditEntry, AuditLog};
 use crate::domain::error::FilamentError;
+use crate::domain::events::{EventStore, FilamentEvent, RecordedEvent, Snapshot, SnapshotStore};
 use crate::domain::filament::{FilamentRepository, FilamentRoll};
+use crate::domain::lot::{DefectiveLot, DefectiveLotRepository};
+use crate::domain::print_job::{PrintJob, PrintJobRepository};
+use crate::domain::printer::{Printer, PrinterRepository};
+use crate::domain::product::{Product, ProductCatalogue};
+use crate::domain::reservation::{Reservation, ReservationRepository};
+use crate::domain::unit_of_work::{TransactionalFilamentRepository, UnitOfWork};
+use async_trait::async_trait;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
@@ -80,4 +91,566 @@ impl FilamentRepository for InMemoryFilamentRepository {
             .cloned()
             .collect())
     }
+
+    fn delete(&self, id: &str) -> Result<(), FilamentError> {
+        let mut filaments = self.filaments.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        filaments
+            .remove(id)
+            .map(|_| ())
+            .ok_or_else(|| FilamentError::NotFound(id.to_string()))
+    }
+
+    fn update(
+        &self,
+        id: &str,
+        change: &mut dyn FnMut(&mut FilamentRoll) -> Result<(), FilamentError>,
+    ) -> Result<FilamentRoll, FilamentError> {
+        let mut filaments = self.filaments.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        // Change a copy so a failed change leaves the stored roll untouched
+        let mut filament = filaments
+            .get(id)
+            .cloned()
+            .ok_or_else(|| FilamentError::NotFound(id.to_string()))?;
+        change(&mut filament)?;
+
+        filaments.insert(id.to_string(), filament.clone());
+        Ok(filament)
+    }
+}
+
+// Lock hold times are short and never span an await, so the in-memory
+// repository implements the async trait directly
+#[async_trait]
+impl AsyncFilamentRepository for InMemoryFilamentRepository {
+    async fn save(&self, filament: &FilamentRoll) -> Result<(), FilamentError> {
+        FilamentRepository::save(self, filament)
+    }
+
+    async fn find_by_id(&self, id: &str) -> Result<FilamentRoll, FilamentError> {
+        FilamentRepository::find_by_id(self, id)
+    }
+
+    async fn update_remaining_weight(
+        &self,
+        id: &str,
+        remaining_weight: f32,
+    ) -> Result<FilamentRoll, FilamentError> {
+        FilamentRepository::update_remaining_weight(self, id, remaining_weight)
+    }
+
+    async fn find_all(&self) -> Result<Vec<FilamentRoll>, FilamentError> {
+        FilamentRepository::find_all(self)
+    }
+
+    async fn find_by_material(&self, material: &str) -> Result<Vec<FilamentRoll>, FilamentError> {
+        FilamentRepository::find_by_material(self, material)
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), FilamentError> {
+        FilamentRepository::delete(self, id)
+    }
+}
+
+impl TransactionalFilamentRepository for InMemoryFilamentRepository {
+    fn commit(&self, unit: &UnitOfWork) -> Result<Vec<FilamentRoll>, FilamentError> {
+        // Hold the lock across validation and write so the batch is isolated
+        let mut filaments = self.filaments.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        let updated = unit.apply(|id| {
+            filaments
+                .get(id)
+                .cloned()
+                .ok_or_else(|| FilamentError::NotFound(id.to_string()))
+        })?;
+
+        for filament in &updated {
+            filaments.insert(filament.id().to_string(), filament.clone());
+        }
+
+        Ok(updated)
+    }
+}
+
+pub struct InMemoryAuditLog {
+    entries: Arc<Mutex<Vec<AuditEntry>>>,
+}
+
+impl Default for InMemoryAuditLog {
+    fn default() -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+}
+
+impl InMemoryAuditLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl AuditLog for InMemoryAuditLog {
+    fn record(&self, entry: &AuditEntry) -> Result<(), FilamentError> {
+        let mut entries = self.entries.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        entries.push(entry.clone());
+        Ok(())
+    }
+
+    fn find_by_roll(&self, roll_id: &str) -> Result<Vec<AuditEntry>, FilamentError> {
+        let entries = self.entries.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        Ok(entries
+            .iter()
+            .filter(|e| e.roll_id() == roll_id)
+            .cloned()
+            .collect())
+    }
+
+    fn find_all(&self) -> Result<Vec<AuditEntry>, FilamentError> {
+        let entries = self.entries.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        Ok(entries.clone())
+    }
+}
+
+// Clones share the same underlying event stream
+#[derive(Clone)]
+pub struct InMemoryEventStore {
+    events: Arc<Mutex<Vec<RecordedEvent>>>,
+}
+
+impl Default for InMemoryEventStore {
+    fn default() -> Self {
+        Self {
+            events: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+}
+
+impl InMemoryEventStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl EventStore for InMemoryEventStore {
+    fn append(&self, events: &[FilamentEvent]) -> Result<Vec<RecordedEvent>, FilamentError> {
+        let mut stored = self.events.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        let first = stored.len() as u64 + 1;
+        let recorded: Vec<RecordedEvent> = events
+            .iter()
+            .enumerate()
+            .map(|(i, event)| RecordedEvent::new(first + i as u64, event.clone()))
+            .collect();
+
+        stored.extend(recorded.iter().cloned());
+        Ok(recorded)
+    }
+
+    fn load_since(&self, after_sequence: u64) -> Result<Vec<RecordedEvent>, FilamentError> {
+        let stored = self.events.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        Ok(stored
+            .iter()
+            .filter(|e| e.sequence() > after_sequence)
+            .cloned()
+            .collect())
+    }
+}
+
+#[derive(Clone)]
+pub struct InMemorySnapshotStore {
+    latest: Arc<Mutex<Option<Snapshot>>>,
+}
+
+impl Default for InMemorySnapshotStore {
+    fn default() -> Self {
+        Self {
+            latest: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+impl InMemorySnapshotStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl SnapshotStore for InMemorySnapshotStore {
+    fn save(&self, snapshot: &Snapshot) -> Result<(), FilamentError> {
+        let mut latest = self.latest.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        *latest = Some(snapshot.clone());
+        Ok(())
+    }
+
+    fn load_latest(&self) -> Result<Option<Snapshot>, FilamentError> {
+        let latest = self.latest.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        Ok(latest.clone())
+    }
+}
+
+pub struct InMemoryRollAssignments {
+    assignments: Arc<Mutex<HashMap<(String, u32), String>>>,
+}
+
+impl Default for InMemoryRollAssignments {
+    fn default() -> Self {
+        Self {
+            assignments: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+impl InMemoryRollAssignments {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl RollAssignments for InMemoryRollAssignments {
+    fn loaded_roll(&self, printer: &str, tool: u32) -> Result<Option<String>, FilamentError> {
+        let assignments = self.assignments.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        Ok(assignments.get(&(printer.to_string(), tool)).cloned())
+    }
+
+    fn load_roll(&self, printer: &str, tool: u32, roll_id: &str) -> Result<(), FilamentError> {
+        let mut assignments = self.assignments.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        assignments.insert((printer.to_string(), tool), roll_id.to_string());
+        Ok(())
+    }
+
+    fn unload(&self, printer: &str, tool: u32) -> Result<(), FilamentError> {
+        let mut assignments = self.assignments.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        assignments.remove(&(printer.to_string(), tool));
+        Ok(())
+    }
+
+    fn is_loaded(&self, roll_id: &str) -> Result<bool, FilamentError> {
+        let assignments = self.assignments.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        Ok(assignments.values().any(|loaded| loaded == roll_id))
+    }
+
+    // Any tool number can be loaded
+    fn tool_count(&self, _printer: &str) -> Result<Option<u32>, FilamentError> {
+        Ok(None)
+    }
+}
+
+pub struct InMemoryPrinterRepository {
+    printers: Arc<Mutex<HashMap<String, Printer>>>,
+}
+
+impl Default for InMemoryPrinterRepository {
+    fn default() -> Self {
+        Self {
+            printers: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+impl InMemoryPrinterRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl PrinterRepository for InMemoryPrinterRepository {
+    fn save(&self, printer: &Printer) -> Result<(), FilamentError> {
+        let mut printers = self.printers.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        printers.insert(printer.id().to_string(), printer.clone());
+        Ok(())
+    }
+
+    fn find_by_id(&self, id: &str) -> Result<Printer, FilamentError> {
+        let printers = self.printers.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        printers
+            .get(id)
+            .cloned()
+            .ok_or_else(|| FilamentError::Missing(format!("Printer '{}' not found", id)))
+    }
+
+    fn find_all(&self) -> Result<Vec<Printer>, FilamentError> {
+        let printers = self.printers.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        Ok(printers.values().cloned().collect())
+    }
+}
+
+pub struct InMemoryPrintJobRepository {
+    jobs: Arc<Mutex<HashMap<String, PrintJob>>>,
+}
+
+impl Default for InMemoryPrintJobRepository {
+    fn default() -> Self {
+        Self {
+            jobs: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+impl InMemoryPrintJobRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl PrintJobRepository for InMemoryPrintJobRepository {
+    fn save(&self, job: &PrintJob) -> Result<(), FilamentError> {
+        let mut jobs = self.jobs.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        jobs.insert(job.id().to_string(), job.clone());
+        Ok(())
+    }
+
+    fn find_by_id(&self, id: &str) -> Result<PrintJob, FilamentError> {
+        let jobs = self.jobs.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        jobs.get(id)
+            .cloned()
+            .ok_or_else(|| FilamentError::Missing(format!("Print job '{}' not found", id)))
+    }
+
+    fn find_all(&self) -> Result<Vec<PrintJob>, FilamentError> {
+        let jobs = self.jobs.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        Ok(jobs.values().cloned().collect())
+    }
+}
+
+pub struct InMemoryReservationRepository {
+    reservations: Arc<Mutex<HashMap<String, Reservation>>>,
+}
+
+impl Default for InMemoryReservationRepository {
+    fn default() -> Self {
+        Self {
+            reservations: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+impl InMemoryReservationRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ReservationRepository for InMemoryReservationRepository {
+    fn save(&self, reservation: &Reservation) -> Result<(), FilamentError> {
+        let mut reservations = self.reservations.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        reservations.insert(reservation.id().to_string(), reservation.clone());
+        Ok(())
+    }
+
+    fn find_by_id(&self, id: &str) -> Result<Reservation, FilamentError> {
+        let reservations = self.reservations.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        reservations
+            .get(id)
+            .cloned()
+            .ok_or_else(|| FilamentError::Missing(format!("Reservation '{}' not found", id)))
+    }
+
+    fn find_by_roll(&self, roll_id: &str) -> Result<Vec<Reservation>, FilamentError> {
+        let reservations = self.reservations.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        Ok(reservations
+            .values()
+            .filter(|r| r.roll_id() == roll_id)
+            .cloned()
+            .collect())
+    }
+
+    fn delete(&self, id: &str) -> Result<(), FilamentError> {
+        let mut reservations = self.reservations.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        reservations
+            .remove(id)
+            .map(|_| ())
+            .ok_or_else(|| FilamentError::Missing(format!("Reservation '{}' not found", id)))
+    }
+}
+
+pub struct InMemoryProductCatalogue {
+    products: Arc<Mutex<HashMap<String, Product>>>,
+}
+
+impl Default for InMemoryProductCatalogue {
+    fn default() -> Self {
+        Self {
+            products: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+impl InMemoryProductCatalogue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ProductCatalogue for InMemoryProductCatalogue {
+    fn save(&self, product: &Product) -> Result<(), FilamentError> {
+        let mut products = self.products.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        products.insert(product.id().to_string(), product.clone());
+        Ok(())
+    }
+
+    fn find_by_id(&self, id: &str) -> Result<Product, FilamentError> {
+        let products = self.products.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        products
+            .get(id)
+            .cloned()
+            .ok_or_else(|| FilamentError::Missing(format!("Product '{}' not found", id)))
+    }
+
+    fn find_by_ean(&self, ean: &str) -> Result<Product, FilamentError> {
+        let products = self.products.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        products
+            .values()
+            .find(|p| p.ean() == Some(ean))
+            .cloned()
+            .ok_or_else(|| FilamentError::Missing(format!("No product with barcode {}", ean)))
+    }
+
+    fn find_all(&self) -> Result<Vec<Product>, FilamentError> {
+        let products = self.products.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        Ok(products.values().cloned().collect())
+    }
+}
+
+// Keyed by lower-cased manufacturer and lot number, matching `DefectiveLot::is_lot`
+type LotKey = (String, String);
+
+pub struct InMemoryDefectiveLotRepository {
+    lots: Arc<Mutex<HashMap<LotKey, DefectiveLot>>>,
+}
+
+impl Default for InMemoryDefectiveLotRepository {
+    fn default() -> Self {
+        Self {
+            lots: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+impl InMemoryDefectiveLotRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn key(manufacturer: &str, lot_number: &str) -> LotKey {
+        (
+            manufacturer.trim().to_ascii_lowercase(),
+            lot_number.trim().to_string(),
+        )
+    }
+}
+
+impl DefectiveLotRepository for InMemoryDefectiveLotRepository {
+    fn save(&self, lot: &DefectiveLot) -> Result<(), FilamentError> {
+        let mut lots = self.lots.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        lots.insert(Self::key(lot.manufacturer(), lot.lot_number()), lot.clone());
+        Ok(())
+    }
+
+    fn find_all(&self) -> Result<Vec<DefectiveLot>, FilamentError> {
+        let lots = self.lots.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        Ok(lots.values().cloned().collect())
+    }
+
+    fn delete(&self, manufacturer: &str, lot_number: &str) -> Result<(), FilamentError> {
+        let mut lots = self.lots.lock().map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e))
+        })?;
+
+        lots.remove(&Self::key(manufacturer, lot_number))
+            .map(|_| ())
+            .ok_or_else(|| {
+                FilamentError::Missing(format!(
+                    "Lot '{}' from {} is not flagged as defective",
+                    lot_number, manufacturer
+                ))
+            })
+    }
 }
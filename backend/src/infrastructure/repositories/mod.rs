@@ -1 +1,5 @@
+pub mod audited;
+pub mod blocking;
+pub mod event_sourced;
+pub mod file;
 pub mod memory;
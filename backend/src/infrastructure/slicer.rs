@@ -0,0 +1,172 @@
+use crate::domain::error::FilamentError;
+use crate::domain::feasibility::FilamentRequirement;
+use crate::domain::material::{density_for, length_to_grams};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, BufReader, Read, Seek};
+
+// Used to convert lengths to grams when the G-code does not say
+const DEFAULT_DIAMETER: f32 = 1.75;
+
+// Real slice summaries are a few kilobytes; a compressed entry could expand to
+// far more than the upload it came in
+const MAX_SLICE_INFO_BYTES: u64 = 1024 * 1024;
+
+// Reads the filament summary PrusaSlicer, OrcaSlicer and Bambu Studio write as
+// `; key = value` comments. Per-extruder values are separated by `,` or `;`.
+// Extruders the print does not use are left out.
+pub fn parse_gcode<R: Read>(reader: R) -> Result<Vec<FilamentRequirement>, FilamentError> {
+    let mut settings: HashMap<String, Vec<String>> = HashMap::new();
+
+    for line in BufReader::new(reader).lines() {
+        let line =
+            line.map_err(|e| FilamentError::InvalidData(format!("Invalid G-code: {}", e)))?;
+        let comment = match line.trim().strip_prefix(';') {
+            Some(comment) => comment,
+            None => continue,
+        };
+        if let Some((key, value)) = comment.split_once('=') {
+            let values = value
+                .split([',', ';'])
+                .map(|v| v.trim().to_string())
+                .collect();
+            settings.insert(key.trim().to_string(), values);
+        }
+    }
+
+    let list = |key: &str| settings.get(key).map(Vec::as_slice).unwrap_or_default();
+    // A single value applies to every extruder
+    let value = |key: &str, extruder: usize| {
+        let values = list(key);
+        values
+            .get(extruder)
+            .or(if values.len() == 1 {
+                values.first()
+            } else {
+                None
+            })
+            .filter(|v| !v.is_empty())
+            .cloned()
+    };
+    let number =
+        |key: &str, extruder: usize| value(key, extruder).and_then(|v| v.parse::<f32>().ok());
+
+    let grams_key = "filament used [g]";
+    let length_key = "filament used [mm]";
+    let extruders = list(grams_key).len().max(list(length_key).len());
+    if extruders == 0 {
+        return Err(FilamentError::InvalidData(
+            "G-code has no filament usage summary".to_string(),
+        ));
+    }
+
+    let mut requirements = Vec::new();
+    for extruder in 0..extruders {
+        let material = value("filament_type", extruder).ok_or_else(|| {
+            FilamentError::InvalidData(format!(
+                "G-code has no filament_type for extruder {}",
+                extruder
+            ))
+        })?;
+        let diameter = number("filament_diameter", extruder);
+        let grams = match number(grams_key, extruder).filter(|g| *g > 0.0) {
+            Some(grams) => grams,
+            None => match number(length_key, extruder) {
+                Some(length) => length_to_grams(
+                    length,
+                    diameter.unwrap_or(DEFAULT_DIAMETER),
+                    number("filament_density", extruder)
+                        .filter(|d| *d > 0.0)
+                        .unwrap_or_else(|| density_for(&material)),
+                ),
+                None => 0.0,
+            },
+        };
+
+        if grams > 0.0 {
+            requirements.push(FilamentRequirement {
+                extruder: extruder as u32,
+                material,
+                color: value("filament_colour", extruder),
+                diameter,
+                grams,
+            });
+        }
+    }
+
+    Ok(requirements)
+}
+
+// Reads a sliced 3MF project. Bambu Studio and OrcaSlicer list per-filament
+// usage for each plate in `Metadata/slice_info.config`; plates are added up.
+// Otherwise the first embedded G-code is used.
+pub fn parse_3mf<R: Read + Seek>(reader: R) -> Result<Vec<FilamentRequirement>, FilamentError> {
+    let mut archive = zip::ZipArchive::new(reader)
+        .map_err(|e| FilamentError::InvalidData(format!("Invalid 3MF: {}", e)))?;
+
+    if let Ok(entry) = archive.by_name("Metadata/slice_info.config") {
+        let mut config = String::new();
+        entry
+            .take(MAX_SLICE_INFO_BYTES + 1)
+            .read_to_string(&mut config)
+            .map_err(|e| FilamentError::InvalidData(format!("Invalid 3MF: {}", e)))?;
+        if config.len() as u64 > MAX_SLICE_INFO_BYTES {
+            return Err(FilamentError::InvalidData(format!(
+                "Invalid 3MF: Metadata/slice_info.config is larger than {} bytes",
+                MAX_SLICE_INFO_BYTES
+            )));
+        }
+        let requirements = parse_slice_info(&config);
+        if !requirements.is_empty() {
+            return Ok(requirements);
+        }
+    }
+
+    let gcode = archive
+        .file_names()
+        .filter(|name| name.ends_with(".gcode"))
+        .min()
+        .map(str::to_string)
+        .ok_or_else(|| FilamentError::InvalidData("3MF has not been sliced".to_string()))?;
+    let entry = archive
+        .by_name(&gcode)
+        .map_err(|e| FilamentError::InvalidData(format!("Invalid 3MF: {}", e)))?;
+    parse_gcode(entry)
+}
+
+// `<filament id="1" type="PLA" color="#FFFFFF" used_g="10.05" />`, ids from 1
+fn parse_slice_info(config: &str) -> Vec<FilamentRequirement> {
+    let mut by_extruder: BTreeMap<u32, FilamentRequirement> = BTreeMap::new();
+
+    for tag in config.split("<filament ").skip(1) {
+        let tag = tag.split('>').next().unwrap_or_default();
+        let attribute = |name: &str| {
+            let start = tag.find(&format!("{}=\"", name))? + name.len() + 2;
+            let end = tag[start..].find('"')? + start;
+            Some(tag[start..end].to_string())
+        };
+
+        let (Some(id), Some(material), Some(grams)) = (
+            attribute("id").and_then(|id| id.parse::<u32>().ok()),
+            attribute("type"),
+            attribute("used_g").and_then(|g| g.parse::<f32>().ok()),
+        ) else {
+            continue;
+        };
+        if grams <= 0.0 || id == 0 {
+            continue;
+        }
+
+        by_extruder
+            .entry(id - 1)
+            .or_insert_with(|| FilamentRequirement {
+                extruder: id - 1,
+                material,
+                color: attribute("color"),
+                diameter: None,
+                grams: 0.0,
+            })
+            .grams += grams;
+    }
+
+    by_extruder.into_values().collect()
+}
@@ -0,0 +1,251 @@
+use crate::domain::error::FilamentError;
+use crate::domain::product::{Product, ProductCatalogue};
+use crate::infrastructure::import::{ImportMode, ImportReport};
+use crate::infrastructure::spoolman::import::{list, number, text};
+use serde_json::Value;
+use std::collections::HashSet;
+
+// One SpoolmanDB product: a single colour, spool size and diameter
+#[derive(Debug, Default, Clone, PartialEq)]
+struct ProductRecord {
+    id: Option<String>,
+    manufacturer: Option<String>,
+    product_line: Option<String>,
+    name: Option<String>,
+    material: Option<String>,
+    color_hex: Option<String>,
+    diameter: Option<f32>,
+    weight: Option<f32>,
+    spool_weight: Option<f32>,
+    // Not part of SpoolmanDB, but read when a local copy adds it
+    ean: Option<String>,
+}
+
+// Imports filament products in the open SpoolmanDB format: either the
+// compiled `filaments.json` list, or one manufacturer's source file, whose
+// entries list colours, weights and diameters to be combined. Products keep
+// their SpoolmanDB ids, so importing a newer copy updates them in place.
+pub fn import_products(
+    catalogue: &dyn ProductCatalogue,
+    json: &str,
+    mode: ImportMode,
+) -> Result<ImportReport<Product>, FilamentError> {
+    let document: Value = serde_json::from_str(json)
+        .map_err(|e| FilamentError::InvalidData(format!("Invalid SpoolmanDB JSON: {}", e)))?;
+
+    let records = match &document {
+        Value::Array(filaments) => filaments.iter().map(compiled_record).collect(),
+        Value::Object(file) if file.contains_key("filaments") => source_records(&document),
+        _ => {
+            return Err(FilamentError::InvalidData(
+                "SpoolmanDB JSON must be a list of filaments or a manufacturer file".to_string(),
+            ))
+        }
+    };
+
+    let mut report = ImportReport::new(mode);
+    let mut seen = HashSet::new();
+    let mut seen_eans = HashSet::new();
+
+    for (index, record) in records.into_iter().enumerate() {
+        let row = index + 1;
+
+        let product = match record.and_then(|r| product_from_record(&r).map_err(|e| e.to_string()))
+        {
+            Ok(product) => product,
+            Err(message) => {
+                report.fail(row, message);
+                continue;
+            }
+        };
+
+        if !seen.insert(product.id().to_string()) {
+            report.skip(row, format!("Product '{}' is listed twice", product.id()));
+            continue;
+        }
+
+        if let Some(ean) = product.ean() {
+            if !seen_eans.insert(ean.to_string()) {
+                report.fail(row, format!("Barcode {} is listed twice", ean));
+                continue;
+            }
+            match catalogue.find_by_ean(ean) {
+                Ok(other) if other.id() != product.id() => {
+                    report.fail(
+                        row,
+                        format!(
+                            "Barcode {} already belongs to product '{}'",
+                            ean,
+                            other.id()
+                        ),
+                    );
+                    continue;
+                }
+                Ok(_) | Err(FilamentError::Missing(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        let exists = match catalogue.find_by_id(product.id()) {
+            Ok(_) => true,
+            Err(FilamentError::Missing(_)) => false,
+            Err(e) => return Err(e),
+        };
+
+        if mode == ImportMode::Apply {
+            catalogue.save(&product)?;
+        }
+        if exists {
+            report.updated.push(product);
+        } else {
+            report.imported.push(product);
+        }
+    }
+
+    Ok(report)
+}
+
+fn product_from_record(record: &ProductRecord) -> Result<Product, FilamentError> {
+    let missing = |field: &str| FilamentError::InvalidData(format!("Product has no {}", field));
+    let color = record
+        .color_hex
+        .as_ref()
+        .map(|hex| format!("#{}", hex.trim_start_matches('#').to_uppercase()))
+        .unwrap_or_default();
+
+    let mut product = Product::new(
+        record.manufacturer.as_deref().unwrap_or_default(),
+        record.name.as_deref().unwrap_or_default(),
+        record.material.as_deref().unwrap_or_default(),
+        &color,
+        record.diameter.ok_or_else(|| missing("diameter"))?,
+        record.weight.ok_or_else(|| missing("weight"))?,
+    )?;
+
+    if let Some(id) = &record.id {
+        product = product.with_id(id);
+    }
+    if let Some(product_line) = &record.product_line {
+        product = product.with_product_line(product_line);
+    }
+    if let Some(spool_weight) = record.spool_weight {
+        product = product.with_spool_weight(spool_weight)?;
+    }
+    if let Some(ean) = &record.ean {
+        product = product.with_ean(ean)?;
+    }
+
+    Ok(product)
+}
+
+// An entry of the compiled `filaments.json`
+fn compiled_record(filament: &Value) -> Result<ProductRecord, String> {
+    let field = |name: &str| filament.get(name);
+
+    Ok(ProductRecord {
+        id: field("id").and_then(text),
+        manufacturer: field("manufacturer").and_then(text),
+        product_line: None,
+        name: field("name").and_then(text),
+        material: field("material").and_then(text),
+        color_hex: first_color(filament),
+        diameter: field("diameter").and_then(number),
+        weight: field("weight").and_then(number),
+        spool_weight: field("spool_weight").and_then(number),
+        ean: field("ean").and_then(text),
+    })
+}
+
+// Every combination of colour, weight and diameter in a manufacturer file.
+// Names are templates such as `PolyLite PLA {color_name}`.
+fn source_records(file: &Value) -> Vec<Result<ProductRecord, String>> {
+    let manufacturer = file.get("manufacturer").and_then(text);
+    let mut records = Vec::new();
+
+    for filament in list(file.get("filaments")) {
+        let template = filament
+            .get("name")
+            .and_then(text)
+            .unwrap_or_else(|| "{color_name}".to_string());
+        let product_line = Some(template.replace("{color_name}", "").trim().to_string())
+            .filter(|line| !line.is_empty());
+        let material = filament.get("material").and_then(text);
+        let weights = list(filament.get("weights"));
+        let diameters: Vec<f32> = list(filament.get("diameters"))
+            .iter()
+            .filter_map(number)
+            .collect();
+        let colors = list(filament.get("colors"));
+
+        if weights.is_empty() || diameters.is_empty() || colors.is_empty() {
+            records.push(Err(format!(
+                "Filament '{}' needs at least one weight, diameter and colour",
+                template
+            )));
+            continue;
+        }
+
+        for color in &colors {
+            let color_name = color.get("name").and_then(text).unwrap_or_default();
+            let name = template
+                .replace("{color_name}", &color_name)
+                .trim()
+                .to_string();
+            for weight in &weights {
+                let net_weight = weight.get("weight").and_then(number);
+                for &diameter in &diameters {
+                    let id = [
+                        manufacturer.clone().unwrap_or_default(),
+                        material.clone().unwrap_or_default(),
+                        name.clone(),
+                        format!("{}", net_weight.unwrap_or_default().round()),
+                        format!("{}", (diameter * 100.0).round()),
+                    ]
+                    .iter()
+                    .map(|part| slug(part))
+                    .collect::<Vec<_>>()
+                    .join("_");
+
+                    records.push(Ok(ProductRecord {
+                        id: Some(id),
+                        manufacturer: manufacturer.clone(),
+                        product_line: product_line.clone(),
+                        name: Some(name.clone()),
+                        material: material.clone(),
+                        color_hex: first_color(color),
+                        diameter: Some(diameter),
+                        weight: net_weight,
+                        spool_weight: weight.get("spool_weight").and_then(number),
+                        ean: color
+                            .get("ean")
+                            .or_else(|| weight.get("ean"))
+                            .and_then(text),
+                    }));
+                }
+            }
+        }
+    }
+
+    records
+}
+
+// `hex`, `color_hex`, or the first of `hexes` / `color_hexes` for multicolour
+// filaments
+fn first_color(value: &Value) -> Option<String> {
+    ["hex", "color_hex"]
+        .iter()
+        .find_map(|key| value.get(*key).and_then(text))
+        .or_else(|| {
+            ["hexes", "color_hexes"]
+                .iter()
+                .find_map(|key| list(value.get(*key)).first().and_then(text))
+        })
+}
+
+// Lower-case letters and digits only
+fn slug(part: &str) -> String {
+    part.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
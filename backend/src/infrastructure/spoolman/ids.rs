@@ -0,0 +1,194 @@
+use crate::domain::error::FilamentError;
+use crate::domain::filament::FilamentRoll;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+// Spoolman addresses spools, filaments and vendors by integer id while rolls
+// use string ids, and filaments/vendors only exist here as roll attributes.
+// This registry hands out stable integers the first time each is seen.
+#[derive(Clone, Default)]
+pub struct SpoolmanIds {
+    inner: Arc<Mutex<Registry>>,
+    // Where the registry is saved; None keeps it in memory only
+    path: Option<Arc<PathBuf>>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Registry {
+    spools: Table,
+    filaments: Table,
+    vendors: Table,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Table {
+    entries: BTreeMap<u32, Entry>,
+    // Reverse of `entries`, rebuilt after loading
+    #[serde(skip)]
+    ids: HashMap<String, u32>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    key: String,
+    registered: DateTime<Utc>,
+}
+
+impl Table {
+    // The id for `key`, and whether it was assigned just now
+    fn id_for(&mut self, key: &str) -> (Registered, bool) {
+        if let Some(id) = self.ids.get(key) {
+            return (self.registered(*id), false);
+        }
+
+        // Never reuse an id, even if entries were removed from the file by hand
+        let id = self.entries.keys().next_back().map_or(1, |last| last + 1);
+        self.ids.insert(key.to_string(), id);
+        self.entries.insert(
+            id,
+            Entry {
+                key: key.to_string(),
+                registered: Utc::now(),
+            },
+        );
+        (self.registered(id), true)
+    }
+
+    fn registered(&self, id: u32) -> Registered {
+        Registered {
+            id,
+            registered: self.entries[&id].registered,
+        }
+    }
+
+    fn index(&mut self) {
+        self.ids = self
+            .entries
+            .iter()
+            .map(|(id, entry)| (entry.key.clone(), *id))
+            .collect();
+    }
+}
+
+// A Spoolman integer id and when the registry first saw it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Registered {
+    pub id: u32,
+    pub registered: DateTime<Utc>,
+}
+
+impl SpoolmanIds {
+    // Ids last only as long as the process
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Keeps ids in a JSON file. Moonraker and OctoPrint remember spool ids
+    // across restarts, so the same id has to keep naming the same roll.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, FilamentError> {
+        let path = path.into();
+        let mut registry: Registry = if path.exists() {
+            let json = fs::read(&path).map_err(io_error)?;
+            serde_json::from_slice(&json).map_err(|e| {
+                FilamentError::RepositoryError(format!("Failed to decode Spoolman ids: {}", e))
+            })?
+        } else {
+            Registry::default()
+        };
+        registry.spools.index();
+        registry.filaments.index();
+        registry.vendors.index();
+
+        Ok(SpoolmanIds {
+            inner: Arc::new(Mutex::new(registry)),
+            path: Some(Arc::new(path)),
+        })
+    }
+
+    pub fn spool(&self, roll: &FilamentRoll) -> Result<Registered, FilamentError> {
+        let mut registry = self.lock()?;
+        let (registered, assigned) = registry.spools.id_for(roll.id());
+        self.save_if(assigned, &registry)?;
+        Ok(registered)
+    }
+
+    pub fn filament(&self, roll: &FilamentRoll) -> Result<Registered, FilamentError> {
+        let mut registry = self.lock()?;
+        let (registered, assigned) = registry.filaments.id_for(&filament_key(roll));
+        self.save_if(assigned, &registry)?;
+        Ok(registered)
+    }
+
+    pub fn vendor(&self, manufacturer: &str) -> Result<Registered, FilamentError> {
+        let mut registry = self.lock()?;
+        let (registered, assigned) = registry.vendors.id_for(manufacturer);
+        self.save_if(assigned, &registry)?;
+        Ok(registered)
+    }
+
+    // Roll id behind a Spoolman spool id
+    pub fn roll_id(&self, spool_id: u32) -> Result<Option<String>, FilamentError> {
+        Ok(self
+            .lock()?
+            .spools
+            .entries
+            .get(&spool_id)
+            .map(|entry| entry.key.clone()))
+    }
+
+    // Assigns ids to every roll in a stable order so listings are deterministic
+    pub fn register_all(&self, rolls: &mut [FilamentRoll]) -> Result<(), FilamentError> {
+        rolls.sort_by(|a, b| a.id().cmp(b.id()));
+
+        let mut registry = self.lock()?;
+        let mut assigned = false;
+        for roll in rolls.iter() {
+            assigned |= registry.vendors.id_for(roll.manufacturer()).1;
+            assigned |= registry.filaments.id_for(&filament_key(roll)).1;
+            assigned |= registry.spools.id_for(roll.id()).1;
+        }
+        self.save_if(assigned, &registry)
+    }
+
+    // Saves after new ids are handed out, while still holding the lock
+    fn save_if(&self, assigned: bool, registry: &Registry) -> Result<(), FilamentError> {
+        let Some(path) = self.path.as_ref().filter(|_| assigned) else {
+            return Ok(());
+        };
+
+        let json = serde_json::to_vec(registry).map_err(|e| {
+            FilamentError::RepositoryError(format!("Failed to encode Spoolman ids: {}", e))
+        })?;
+        // Write then rename so a crash never leaves a truncated file
+        let temp_path = path.with_extension("tmp");
+        fs::write(&temp_path, json).map_err(io_error)?;
+        fs::rename(&temp_path, path.as_ref()).map_err(io_error)
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, Registry>, FilamentError> {
+        self.inner
+            .lock()
+            .map_err(|e| FilamentError::RepositoryError(format!("Failed to acquire lock: {}", e)))
+    }
+}
+
+fn io_error(e: std::io::Error) -> FilamentError {
+    FilamentError::RepositoryError(format!("I/O error: {}", e))
+}
+
+// Rolls sharing these attributes are the same Spoolman filament
+fn filament_key(roll: &FilamentRoll) -> String {
+    format!(
+        "{}\u{1f}{}\u{1f}{}\u{1f}{}\u{1f}{}\u{1f}{}",
+        roll.manufacturer(),
+        roll.name(),
+        roll.material(),
+        roll.color(),
+        roll.diameter(),
+        roll.weight()
+    )
+}
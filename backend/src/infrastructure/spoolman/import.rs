@@ -0,0 +1,332 @@
+use crate::domain::custom_field::CustomValue;
+use crate::domain::error::FilamentError;
+use crate::domain::filament::{FilamentRepository, FilamentRoll, FilamentRollBuilder};
+use crate::infrastructure::import::{ImportMode, ImportReport};
+use crate::infrastructure::spoolman::models::{DEFECTIVE_LOT_EXTRA, TAGS_EXTRA};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+
+// Imported rolls get this prefix on the Spoolman spool id, so running the same
+// export twice does not create duplicates
+pub const ROLL_ID_PREFIX: &str = "spoolman-";
+
+// One spool from a Spoolman export with its filament and vendor flattened in
+#[derive(Debug, Default, Clone, PartialEq)]
+struct SpoolRecord {
+    spool_id: Option<String>,
+    name: Option<String>,
+    material: Option<String>,
+    color_hex: Option<String>,
+    vendor: Option<String>,
+    diameter: Option<f32>,
+    filament_weight: Option<f32>,
+    initial_weight: Option<f32>,
+    remaining_weight: Option<f32>,
+    used_weight: Option<f32>,
+    location: Option<String>,
+    lot_nr: Option<String>,
+    // Spool extra fields by name, still JSON-encoded
+    extra: Vec<(String, String)>,
+    archived: bool,
+}
+
+// Accepts either a list of spools with nested filament and vendor objects (the
+// shape of `GET /api/v1/spool`), or an object with `vendors`, `filaments` and
+// `spools` lists that reference each other by `filament_id` / `vendor_id`.
+pub fn import_json(
+    repository: &dyn FilamentRepository,
+    json: &str,
+    mode: ImportMode,
+) -> Result<ImportReport, FilamentError> {
+    let document: Value = serde_json::from_str(json)
+        .map_err(|e| FilamentError::InvalidData(format!("Invalid Spoolman JSON: {}", e)))?;
+
+    let (spools, filaments, vendors) = match &document {
+        Value::Array(spools) => (spools.clone(), Vec::new(), Vec::new()),
+        Value::Object(export) => (
+            list(export.get("spools")),
+            list(export.get("filaments")),
+            list(export.get("vendors")),
+        ),
+        _ => {
+            return Err(FilamentError::InvalidData(
+                "Spoolman JSON must be a list of spools or an export object".to_string(),
+            ))
+        }
+    };
+
+    let filaments_by_id = index_by_id(&filaments);
+    let vendors_by_id = index_by_id(&vendors);
+
+    let records = spools
+        .iter()
+        .map(|spool| Ok(record_from_json(spool, &filaments_by_id, &vendors_by_id)))
+        .collect();
+
+    import_records(repository, records, mode)
+}
+
+// Reads Spoolman's CSV export, where nested fields use dotted column names
+// such as `filament.material` and `filament.vendor.name`
+pub fn import_csv<R: Read>(
+    repository: &dyn FilamentRepository,
+    reader: R,
+    mode: ImportMode,
+) -> Result<ImportReport, FilamentError> {
+    let mut reader = csv::Reader::from_reader(reader);
+    let headers = reader
+        .headers()
+        .map_err(|e| FilamentError::InvalidData(format!("Invalid Spoolman CSV header: {}", e)))?
+        .clone();
+
+    let records = reader
+        .records()
+        .map(|row| {
+            let row = row.map_err(|e| e.to_string())?;
+            let columns: HashMap<&str, &str> = headers.iter().zip(row.iter()).collect();
+            Ok(record_from_columns(&columns))
+        })
+        .collect();
+
+    import_records(repository, records, mode)
+}
+
+fn import_records(
+    repository: &dyn FilamentRepository,
+    records: Vec<Result<SpoolRecord, String>>,
+    mode: ImportMode,
+) -> Result<ImportReport, FilamentError> {
+    let mut report = ImportReport::new(mode);
+    let mut seen = HashSet::new();
+
+    for (index, record) in records.into_iter().enumerate() {
+        let row = index + 1;
+
+        let record = match record {
+            Ok(record) => record,
+            Err(message) => {
+                report.fail(row, message);
+                continue;
+            }
+        };
+
+        if record.archived {
+            report.skip(row, "Spool is archived".to_string());
+            continue;
+        }
+
+        let roll = match roll_from_record(&record) {
+            Ok(roll) => roll,
+            Err(e) => {
+                report.fail(row, e.to_string());
+                continue;
+            }
+        };
+
+        if !seen.insert(roll.id().to_string()) || exists(repository, roll.id())? {
+            report.skip(row, format!("Already imported as roll '{}'", roll.id()));
+            continue;
+        }
+
+        if mode == ImportMode::Apply {
+            repository.save(&roll)?;
+        }
+        report.imported.push(roll);
+    }
+
+    Ok(report)
+}
+
+fn roll_from_record(record: &SpoolRecord) -> Result<FilamentRoll, FilamentError> {
+    let material = record.material.clone().unwrap_or_default();
+    let manufacturer = record.vendor.clone().unwrap_or_default();
+    let name = record
+        .name
+        .clone()
+        .unwrap_or_else(|| format!("{} {}", manufacturer, material).trim().to_string());
+    let color = record
+        .color_hex
+        .as_ref()
+        .map(|hex| format!("#{}", hex.trim_start_matches('#').to_uppercase()))
+        .unwrap_or_default();
+
+    let weight = record
+        .initial_weight
+        .or(record.filament_weight)
+        .ok_or_else(|| {
+            FilamentError::InvalidData("Spool has no initial or filament weight".to_string())
+        })?;
+    let remaining_weight = record
+        .remaining_weight
+        .or(record.used_weight.map(|used| weight - used))
+        .unwrap_or(weight);
+
+    let mut builder = FilamentRollBuilder::new(
+        name,
+        material,
+        color,
+        record.diameter.unwrap_or(0.0),
+        weight,
+        manufacturer,
+    )
+    .with_remaining_weight(remaining_weight);
+
+    if let Some(spool_id) = &record.spool_id {
+        builder = builder.with_id(&format!("{}{}", ROLL_ID_PREFIX, spool_id));
+    }
+    if let Some(location) = &record.location {
+        builder = builder.with_storage_location(location);
+    }
+    if let Some(lot_nr) = &record.lot_nr {
+        builder = builder.with_lot_number(lot_nr);
+    }
+    for (name, encoded) in &record.extra {
+        let decoded = serde_json::from_str(encoded).unwrap_or_else(|_| json!(encoded));
+        match (name.as_str(), decoded) {
+            // Set from our own lot flags, not the spool
+            (DEFECTIVE_LOT_EXTRA, _) => {}
+            (TAGS_EXTRA, Value::Array(tags)) => {
+                for tag in tags.iter().filter_map(text) {
+                    builder = builder.with_tag(&tag);
+                }
+            }
+            (_, Value::Bool(flag)) => {
+                builder = builder.with_custom_field(name, CustomValue::Bool(flag));
+            }
+            (_, Value::Number(number)) => {
+                if let Some(number) = number.as_f64() {
+                    builder = builder.with_custom_field(name, CustomValue::Number(number));
+                }
+            }
+            (_, Value::String(value)) => {
+                builder = builder.with_custom_field(name, CustomValue::String(value));
+            }
+            _ => {}
+        }
+    }
+
+    builder.build()
+}
+
+fn exists(repository: &dyn FilamentRepository, id: &str) -> Result<bool, FilamentError> {
+    match repository.find_by_id(id) {
+        Ok(_) => Ok(true),
+        Err(FilamentError::NotFound(_)) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+fn record_from_json(
+    spool: &Value,
+    filaments_by_id: &HashMap<String, &Value>,
+    vendors_by_id: &HashMap<String, &Value>,
+) -> SpoolRecord {
+    let filament = match spool.get("filament") {
+        Some(filament @ Value::Object(_)) => Some(filament),
+        Some(id) => text(id).and_then(|id| filaments_by_id.get(&id).copied()),
+        None => spool
+            .get("filament_id")
+            .and_then(text)
+            .and_then(|id| filaments_by_id.get(&id).copied()),
+    };
+
+    let field = |name: &str| filament.and_then(|f| f.get(name));
+    let vendor = match field("vendor") {
+        Some(vendor @ Value::Object(_)) => Some(vendor),
+        _ => field("vendor_id")
+            .and_then(text)
+            .and_then(|id| vendors_by_id.get(&id).copied()),
+    };
+
+    SpoolRecord {
+        spool_id: spool.get("id").and_then(text),
+        name: field("name").and_then(text),
+        material: field("material").and_then(text),
+        color_hex: field("color_hex").and_then(text),
+        vendor: vendor.and_then(|v| v.get("name")).and_then(text),
+        diameter: field("diameter").and_then(number),
+        filament_weight: field("weight").and_then(number),
+        initial_weight: spool.get("initial_weight").and_then(number),
+        remaining_weight: spool.get("remaining_weight").and_then(number),
+        used_weight: spool.get("used_weight").and_then(number),
+        location: spool.get("location").and_then(text),
+        lot_nr: spool.get("lot_nr").and_then(text),
+        extra: spool
+            .get("extra")
+            .and_then(Value::as_object)
+            .map(|extra| {
+                extra
+                    .iter()
+                    .filter_map(|(name, value)| Some((name.clone(), value.as_str()?.to_string())))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        archived: spool
+            .get("archived")
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+    }
+}
+
+fn record_from_columns(columns: &HashMap<&str, &str>) -> SpoolRecord {
+    let text = |name: &str| {
+        columns
+            .get(name)
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    };
+    let number = |name: &str| text(name).and_then(|v| v.parse::<f32>().ok());
+
+    SpoolRecord {
+        spool_id: text("id"),
+        name: text("filament.name"),
+        material: text("filament.material"),
+        color_hex: text("filament.color_hex"),
+        vendor: text("filament.vendor.name"),
+        diameter: number("filament.diameter"),
+        filament_weight: number("filament.weight"),
+        initial_weight: number("initial_weight"),
+        remaining_weight: number("remaining_weight"),
+        used_weight: number("used_weight"),
+        location: text("location"),
+        lot_nr: text("lot_nr"),
+        extra: columns
+            .iter()
+            .filter_map(|(header, value)| {
+                let name = header.strip_prefix("extra.")?;
+                Some((name.to_string(), value.trim().to_string()))
+            })
+            .filter(|(_, value)| !value.is_empty())
+            .collect(),
+        archived: text("archived").is_some_and(|v| v.eq_ignore_ascii_case("true")),
+    }
+}
+
+pub(super) fn list(value: Option<&Value>) -> Vec<Value> {
+    value.and_then(Value::as_array).cloned().unwrap_or_default()
+}
+
+fn index_by_id(items: &[Value]) -> HashMap<String, &Value> {
+    items
+        .iter()
+        .filter_map(|item| item.get("id").and_then(text).map(|id| (id, item)))
+        .collect()
+}
+
+pub(super) fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+pub(super) fn number(value: &Value) -> Option<f32> {
+    match value {
+        Value::Number(n) => n.as_f64().map(|n| n as f32),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
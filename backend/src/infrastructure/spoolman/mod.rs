@@ -0,0 +1,4 @@
+pub mod catalogue;
+pub mod ids;
+pub mod import;
+pub mod models;
@@ -0,0 +1,193 @@
+use crate::domain::custom_field::CustomValue;
+use crate::domain::error::FilamentError;
+use crate::domain::filament::FilamentRoll;
+use crate::domain::lot::DefectiveLot;
+use crate::domain::material::{density_for, grams_to_length};
+use crate::infrastructure::spoolman::ids::SpoolmanIds;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+
+// Key in a spool's `extra` fields holding why its lot was flagged
+pub const DEFECTIVE_LOT_EXTRA: &str = "defective_lot";
+
+// Key in a spool's `extra` fields holding the roll's tags as a JSON list;
+// custom fields use their own names
+pub const TAGS_EXTRA: &str = "tags";
+
+// Wire format of the Spoolman v1 REST API. Field names follow Spoolman so
+// existing Moonraker and OctoPrint integrations can talk to this backend.
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct SpoolmanVendor {
+    pub id: u32,
+    #[serde(default = "Utc::now")]
+    pub registered: DateTime<Utc>,
+    pub name: String,
+    #[serde(default)]
+    pub extra: HashMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct SpoolmanFilament {
+    pub id: u32,
+    #[serde(default = "Utc::now")]
+    pub registered: DateTime<Utc>,
+    pub name: Option<String>,
+    pub vendor: Option<SpoolmanVendor>,
+    pub material: Option<String>,
+    pub density: f32,
+    pub diameter: f32,
+    // Net weight of filament on a full spool, in grams
+    pub weight: Option<f32>,
+    pub spool_weight: Option<f32>,
+    // Six or eight hex digits without a leading '#'
+    pub color_hex: Option<String>,
+    #[serde(default)]
+    pub extra: HashMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct SpoolmanSpool {
+    pub id: u32,
+    #[serde(default = "Utc::now")]
+    pub registered: DateTime<Utc>,
+    pub filament: SpoolmanFilament,
+    pub remaining_weight: Option<f32>,
+    pub initial_weight: Option<f32>,
+    #[serde(default)]
+    pub used_weight: f32,
+    pub remaining_length: Option<f32>,
+    #[serde(default)]
+    pub used_length: f32,
+    pub location: Option<String>,
+    pub lot_nr: Option<String>,
+    pub comment: Option<String>,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub extra: HashMap<String, String>,
+}
+
+// Body of `PUT /api/v1/spool/{id}/use`; exactly one field is expected
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct UseSpoolRequest {
+    pub use_weight: Option<f32>,
+    // Millimetres of filament, converted to grams using material density
+    pub use_length: Option<f32>,
+}
+
+// Body of `PATCH /api/v1/spool/{id}`
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct PatchSpoolRequest {
+    pub remaining_weight: Option<f32>,
+    pub used_weight: Option<f32>,
+    pub location: Option<String>,
+}
+
+impl SpoolmanVendor {
+    pub fn from_manufacturer(manufacturer: &str, ids: &SpoolmanIds) -> Result<Self, FilamentError> {
+        let registered = ids.vendor(manufacturer)?;
+        Ok(SpoolmanVendor {
+            id: registered.id,
+            registered: registered.registered,
+            name: manufacturer.to_string(),
+            extra: HashMap::new(),
+        })
+    }
+}
+
+impl SpoolmanFilament {
+    pub fn from_roll(roll: &FilamentRoll, ids: &SpoolmanIds) -> Result<Self, FilamentError> {
+        let registered = ids.filament(roll)?;
+        Ok(SpoolmanFilament {
+            id: registered.id,
+            registered: registered.registered,
+            name: Some(roll.name().to_string()),
+            vendor: Some(SpoolmanVendor::from_manufacturer(roll.manufacturer(), ids)?),
+            material: Some(roll.material().to_string()),
+            density: density_for(roll.material()),
+            diameter: roll.diameter(),
+            weight: Some(roll.weight()),
+            spool_weight: roll.spool_weight(),
+            color_hex: color_hex(roll.color()),
+            extra: HashMap::new(),
+        })
+    }
+}
+
+impl SpoolmanSpool {
+    pub fn from_roll(roll: &FilamentRoll, ids: &SpoolmanIds) -> Result<Self, FilamentError> {
+        let registered = ids.spool(roll)?;
+        let filament = SpoolmanFilament::from_roll(roll, ids)?;
+        let used_weight = roll.weight() - roll.remaining_weight();
+        let location = match roll.storage_location() {
+            "" => None,
+            location => Some(location.to_string()),
+        };
+        let lot_nr = match roll.lot_number() {
+            "" => None,
+            lot_nr => Some(lot_nr.to_string()),
+        };
+
+        // Spoolman stores extra fields as JSON-encoded strings
+        let mut extra: HashMap<String, String> = roll
+            .custom_fields()
+            .iter()
+            .map(|(name, value)| (name.clone(), extra_value(value)))
+            .collect();
+        if !roll.tags().is_empty() {
+            extra.insert(TAGS_EXTRA.to_string(), json!(roll.tags()).to_string());
+        }
+
+        Ok(SpoolmanSpool {
+            id: registered.id,
+            registered: registered.registered,
+            remaining_weight: Some(roll.remaining_weight()),
+            initial_weight: Some(roll.weight()),
+            used_weight,
+            remaining_length: Some(grams_to_length(
+                roll.remaining_weight(),
+                roll.diameter(),
+                filament.density,
+            )),
+            used_length: grams_to_length(used_weight, roll.diameter(), filament.density),
+            location,
+            lot_nr,
+            comment: None,
+            archived: false,
+            extra,
+            filament,
+        })
+    }
+}
+
+impl SpoolmanSpool {
+    pub fn flag_defective_lot(&mut self, lot: &DefectiveLot) {
+        self.extra.insert(
+            DEFECTIVE_LOT_EXTRA.to_string(),
+            json!(lot.reason()).to_string(),
+        );
+    }
+}
+
+// Numbers and booleans as JSON literals, strings and dates as JSON strings
+fn extra_value(value: &CustomValue) -> String {
+    match value {
+        CustomValue::Number(number) => json!(number).to_string(),
+        CustomValue::Bool(flag) => json!(flag).to_string(),
+        CustomValue::String(_) | CustomValue::Date(_) => json!(value.to_string()).to_string(),
+    }
+}
+
+// Spoolman only understands hex colours; named colours are left out
+pub fn color_hex(color: &str) -> Option<String> {
+    let hex = color.trim().trim_start_matches('#');
+    let is_hex = (hex.len() == 6 || hex.len() == 8) && hex.chars().all(|c| c.is_ascii_hexdigit());
+    if is_hex {
+        Some(hex.to_uppercase())
+    } else {
+        None
+    }
+}
@@ -1,5 +1,260 @@
-use backend::domain;
+use actix_web::{web, App, HttpServer};
+use backend::api;
+use backend::domain::assignment::RollAssignments;
+use backend::domain::audit::{AuditContext, AuditLog, ChangeSource};
+use backend::domain::filament::FilamentRepository;
+use backend::domain::product::ProductCatalogue;
+use backend::domain::services::filament_service::FilamentService;
+use backend::domain::services::print_job_service::PrintJobService;
+use backend::domain::services::printer_service::PrinterService;
+use backend::domain::services::product_service::ProductService;
+use backend::domain::services::scan_service::ScanService;
+use backend::infrastructure::attachments::LocalAttachmentStorage;
+use backend::infrastructure::bambu::mqtt::{BambuAmsConnector, BambuMqttConfig, BAMBU_MQTT_PORT};
+use backend::infrastructure::bambu::reconcile::AmsReconciler;
+use backend::infrastructure::bambu::tags::JsonFileTrayTags;
+use backend::infrastructure::labels::LabelSettings;
+use backend::infrastructure::moonraker::client::MoonrakerClient;
+use backend::infrastructure::moonraker::ledger::JsonFileProcessedJobs;
+use backend::infrastructure::moonraker::sync::MoonrakerSync;
+use backend::infrastructure::nfc::tigertag::TigerTagIds;
+use backend::infrastructure::repositories::audited::AuditedFilamentRepository;
+use backend::infrastructure::repositories::memory::{
+    InMemoryAuditLog, InMemoryDefectiveLotRepository, InMemoryFilamentRepository,
+    InMemoryPrintJobRepository, InMemoryPrinterRepository, InMemoryProductCatalogue,
+    InMemoryReservationRepository,
+};
+use backend::infrastructure::spoolman::ids::SpoolmanIds;
+use std::sync::Arc;
+use std::time::Duration;
 
-fn main() {
-    println!("Filament Tracker API starting...");
-}
\ No newline at end of file
+const MOONRAKER_POLL_INTERVAL: Duration = Duration::from_secs(30);
+const BAMBU_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    let bind_address =
+        std::env::var("FILAMENT_TRACKER_BIND").unwrap_or_else(|_| "127.0.0.1:8080".to_string());
+
+    // Every roll change is recorded field by field
+    let audit_log: Arc<dyn AuditLog> = Arc::new(InMemoryAuditLog::new());
+    let repository = Arc::new(AuditedFilamentRepository::new(
+        InMemoryFilamentRepository::new(),
+        audit_log.clone(),
+        AuditContext::new("api", ChangeSource::Api),
+    ));
+    // Photos and their thumbnails, one directory per roll
+    let attachments_dir =
+        std::env::var("FILAMENT_TRACKER_ATTACHMENTS").unwrap_or_else(|_| "attachments".to_string());
+    let printer_repository = Arc::new(InMemoryPrinterRepository::new());
+    let printers = Arc::new(PrinterService::new(
+        printer_repository.clone(),
+        repository.clone(),
+    ));
+    let service = web::Data::new(
+        FilamentService::transactional(repository.clone())
+            .with_reservations(Arc::new(InMemoryReservationRepository::new()))
+            .with_defective_lots(Arc::new(InMemoryDefectiveLotRepository::new()))
+            .with_attachments(Arc::new(LocalAttachmentStorage::new(attachments_dir)))
+            .with_assignments(printers.clone()),
+    );
+    // Spoolman clients keep spool ids across restarts, so save them when a
+    // file is given
+    let spoolman_ids = match std::env::var("FILAMENT_TRACKER_SPOOLMAN_IDS") {
+        Ok(path) => SpoolmanIds::open(path).map_err(|e| std::io::Error::other(e.to_string()))?,
+        Err(_) => SpoolmanIds::new(),
+    };
+    let spoolman_ids = web::Data::new(spoolman_ids);
+    // Label QR codes link here when set, otherwise they carry the roll id.
+    // Labels can only be printed on the printers listed here.
+    let label_printers = match std::env::var("FILAMENT_TRACKER_LABEL_PRINTERS") {
+        Ok(spec) => LabelSettings::parse_printers(&spec)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string()))?,
+        Err(_) => Default::default(),
+    };
+    let labels = web::Data::new(LabelSettings {
+        base_url: std::env::var("FILAMENT_TRACKER_PUBLIC_URL").ok(),
+        printers: label_printers,
+    });
+    // TigerTag's published id lists; TigerTag tags cannot be read or written
+    // without them
+    let tigertag_ids = match std::env::var("FILAMENT_TRACKER_TIGERTAG_IDS") {
+        Ok(path) => TigerTagIds::from_json(&std::fs::read_to_string(path)?)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))?,
+        Err(_) => TigerTagIds::default(),
+    };
+    let tigertag_ids = web::Data::new(tigertag_ids);
+    let jobs = web::Data::new(PrintJobService::new(
+        Arc::new(InMemoryPrintJobRepository::new()),
+        repository.clone(),
+        printer_repository,
+    ));
+    let catalogue: Arc<dyn ProductCatalogue> = Arc::new(InMemoryProductCatalogue::new());
+    let scan = web::Data::new(ScanService::new(
+        service.get_ref().clone(),
+        catalogue.clone(),
+    ));
+    let products = web::Data::new(ProductService::new(
+        catalogue.clone(),
+        service.get_ref().clone(),
+    ));
+    let catalogue: web::Data<dyn ProductCatalogue> = web::Data::from(catalogue);
+    // Printer-aware assignments, so OctoPrint selections get diameter and
+    // double-loading checks
+    let assignments: web::Data<dyn RollAssignments> =
+        web::Data::from(printers.clone() as Arc<dyn RollAssignments>);
+    // Deduct finished Moonraker jobs from the roll loaded on that printer.
+    // Only jobs started after launch count, unless an earlier Unix time is
+    // given in FILAMENT_TRACKER_MOONRAKER_SINCE.
+    if let Ok(url) = std::env::var("FILAMENT_TRACKER_MOONRAKER_URL") {
+        let printer = std::env::var("FILAMENT_TRACKER_MOONRAKER_PRINTER")
+            .unwrap_or_else(|_| "moonraker".to_string());
+        let ledger = std::env::var("FILAMENT_TRACKER_MOONRAKER_LEDGER")
+            .unwrap_or_else(|_| "moonraker-jobs.json".to_string());
+        let ledger = JsonFileProcessedJobs::open(ledger)
+            .map_err(|e| std::io::Error::other(e.to_string()))?;
+        let mut sync = MoonrakerSync::new(
+            &printer,
+            MoonrakerClient::new(&url),
+            service.get_ref().clone(),
+            printers.clone(),
+            Arc::new(ledger),
+        );
+        if let Ok(since) = std::env::var("FILAMENT_TRACKER_MOONRAKER_SINCE") {
+            let since = since
+                .parse()
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+            sync = sync.since(since);
+        }
+        println!(
+            "Syncing Moonraker jobs from {} as printer '{}'",
+            url, printer
+        );
+        actix_web::rt::spawn(sync.run(MOONRAKER_POLL_INTERVAL, move |poll| match poll {
+            Ok(report) => {
+                for deduction in &report.deducted {
+                    println!(
+                        "Moonraker job {} used {:.1} g of roll {}",
+                        deduction.job_id, deduction.grams, deduction.roll_id
+                    );
+                }
+                for failed in &report.failed {
+                    eprintln!(
+                        "Moonraker job {} not deducted: {}",
+                        failed.job_id, failed.message
+                    );
+                }
+            }
+            Err(e) => eprintln!("Moonraker sync failed: {}", e),
+        }));
+    }
+    // Keep the rolls in a Bambu printer's AMS in step with its reports. The
+    // printer is reached over TLS unless FILAMENT_TRACKER_BAMBU_TLS=false,
+    // e.g. for a local broker bridging it.
+    if let Ok(host) = std::env::var("FILAMENT_TRACKER_BAMBU_HOST") {
+        let serial = std::env::var("FILAMENT_TRACKER_BAMBU_SERIAL").map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "FILAMENT_TRACKER_BAMBU_SERIAL is not set",
+            )
+        })?;
+        let port = match std::env::var("FILAMENT_TRACKER_BAMBU_PORT") {
+            Ok(port) => port
+                .parse()
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?,
+            Err(_) => BAMBU_MQTT_PORT,
+        };
+        let config = BambuMqttConfig {
+            host,
+            port,
+            serial,
+            access_code: std::env::var("FILAMENT_TRACKER_BAMBU_ACCESS_CODE").ok(),
+            tls: std::env::var("FILAMENT_TRACKER_BAMBU_TLS").as_deref() != Ok("false"),
+        };
+        let printer =
+            std::env::var("FILAMENT_TRACKER_BAMBU_PRINTER").unwrap_or_else(|_| "bambu".to_string());
+        let tags = std::env::var("FILAMENT_TRACKER_BAMBU_TAGS")
+            .unwrap_or_else(|_| "bambu-tags.json".to_string());
+        let tags =
+            JsonFileTrayTags::open(tags).map_err(|e| std::io::Error::other(e.to_string()))?;
+        let reconciler = AmsReconciler::new(&printer, service.get_ref().clone(), printers.clone())
+            .with_tag_store(Arc::new(tags));
+        println!(
+            "Reconciling Bambu AMS {} at {}:{} as printer '{}'",
+            config.serial, config.host, config.port, printer
+        );
+        actix_web::rt::spawn(async move {
+            loop {
+                let result = match BambuAmsConnector::connect(config.clone()).await {
+                    Ok(connector) => {
+                        connector
+                            .run(reconciler.clone(), |report| match report {
+                                Ok(report) => {
+                                    for update in &report.updated {
+                                        println!(
+                                            "AMS tray {} set roll {} from {:.1} g to {:.1} g",
+                                            update.tool, update.roll_id, update.from, update.to
+                                        );
+                                    }
+                                    for discrepancy in &report.discrepancies {
+                                        eprintln!(
+                                            "AMS tray {} does not match roll {}: {:?}",
+                                            discrepancy.tool, discrepancy.roll_id, discrepancy.kind
+                                        );
+                                    }
+                                }
+                                Err(e) => eprintln!("AMS report not reconciled: {}", e),
+                            })
+                            .await
+                    }
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = result {
+                    eprintln!("Bambu MQTT connection failed: {}", e);
+                }
+                actix_web::rt::time::sleep(BAMBU_RECONNECT_DELAY).await;
+            }
+        });
+    }
+    let printers = web::Data::from(printers);
+    let audit_log: web::Data<dyn AuditLog> = web::Data::from(audit_log);
+    // Bulk imports write rolls straight to the repository
+    let rolls: web::Data<dyn FilamentRepository> =
+        web::Data::from(repository.clone() as Arc<dyn FilamentRepository>);
+
+    println!("Filament Tracker API starting on {}...", bind_address);
+
+    HttpServer::new(move || {
+        App::new()
+            .app_data(service.clone())
+            .app_data(spoolman_ids.clone())
+            .app_data(assignments.clone())
+            .app_data(printers.clone())
+            .app_data(jobs.clone())
+            .app_data(labels.clone())
+            .app_data(scan.clone())
+            .app_data(products.clone())
+            .app_data(catalogue.clone())
+            .app_data(tigertag_ids.clone())
+            .app_data(audit_log.clone())
+            .app_data(rolls.clone())
+            .configure(api::spoolman::configure)
+            .configure(api::octoprint::configure)
+            .configure(api::printers::configure)
+            .configure(api::jobs::configure)
+            .configure(api::reservations::configure)
+            .configure(api::lots::configure)
+            .configure(api::rolls::configure)
+            .configure(api::attachments::configure)
+            .configure(api::feasibility::configure)
+            .configure(api::labels::configure)
+            .configure(api::scan::configure)
+            .configure(api::products::configure)
+            .configure(api::tags::configure)
+            .configure(api::audit::configure)
+            .configure(api::inventory::configure)
+    })
+    .bind(bind_address)?
+    .run()
+    .await
+}
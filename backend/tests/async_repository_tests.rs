@@ -0,0 +1,69 @@
+use backend::domain::async_repository::AsyncFilamentRepository;
+use backend::domain::error::FilamentError;
+use backend::domain::filament::FilamentRoll;
+use backend::infrastructure::repositories::blocking::BlockingRepositoryAdapter;
+use backend::infrastructure::repositories::event_sourced::EventSourcedFilamentRepository;
+use backend::infrastructure::repositories::memory::{
+    InMemoryEventStore, InMemoryFilamentRepository, InMemorySnapshotStore,
+};
+
+// Helper function to create a test filament with ID
+fn create_test_filament(id: &str, remaining_weight: f32) -> FilamentRoll {
+    FilamentRoll::with_id(
+        id,
+        "Test Filament",
+        "PLA",
+        "#000000",
+        1.75,
+        1000.0,
+        remaining_weight,
+        "Test Brand",
+        "Bin 1",
+    )
+    .expect("Failed to create test filament")
+}
+
+#[actix_web::test]
+async fn test_in_memory_repository_is_async() {
+    // Arrange
+    let repository = InMemoryFilamentRepository::new();
+
+    // Act
+    repository
+        .save(&create_test_filament("async-1", 1000.0))
+        .await
+        .expect("Failed to save filament");
+    let updated = repository
+        .update_remaining_weight("async-1", 250.0)
+        .await
+        .expect("Failed to update weight");
+
+    // Assert
+    assert_eq!(updated.remaining_weight(), 250.0);
+}
+
+#[actix_web::test]
+async fn test_blocking_adapter_wraps_sync_repository() {
+    // Arrange
+    let sync_repository = EventSourcedFilamentRepository::open(
+        InMemoryEventStore::new(),
+        InMemorySnapshotStore::new(),
+    )
+    .expect("Failed to open repository");
+    let repository = BlockingRepositoryAdapter::new(sync_repository);
+
+    // Act
+    repository
+        .save(&create_test_filament("async-2", 1000.0))
+        .await
+        .expect("Failed to save filament");
+    let missing = repository.find_by_id("missing").await;
+    let found = repository
+        .find_by_material("PLA")
+        .await
+        .expect("Failed to find by material");
+
+    // Assert
+    assert!(matches!(missing, Err(FilamentError::NotFound(_))));
+    assert_eq!(found.len(), 1);
+}
@@ -0,0 +1,510 @@
+use actix_web::http::header::{CONTENT_DISPOSITION, CONTENT_TYPE};
+use actix_web::http::StatusCode;
+use actix_web::test::{call_service, init_service, read_body, read_body_json, TestRequest};
+use actix_web::{web, App};
+use backend::api;
+use backend::domain::assignment::RollAssignments;
+use backend::domain::attachment::{AttachmentStorage, RollNote};
+use backend::domain::audit::{AuditContext, AuditLog, ChangeSource};
+use backend::domain::error::FilamentError;
+use backend::domain::filament::{FilamentRepository, FilamentRoll};
+use backend::domain::services::filament_service::FilamentService;
+use backend::infrastructure::attachments::{
+    image_content_type, thumbnail, LocalAttachmentStorage, THUMBNAIL_SIZE,
+};
+use backend::infrastructure::import::ImportMode;
+use backend::infrastructure::inventory_csv::{import_csv, ColumnMapping};
+use backend::infrastructure::repositories::audited::AuditedFilamentRepository;
+use backend::infrastructure::repositories::event_sourced::EventSourcedFilamentRepository;
+use backend::infrastructure::repositories::file::{JsonLinesEventStore, JsonSnapshotStore};
+use backend::infrastructure::repositories::memory::{
+    InMemoryAuditLog, InMemoryFilamentRepository, InMemoryReservationRepository,
+    InMemoryRollAssignments,
+};
+use chrono::{Duration, Utc};
+use image::{ImageFormat, RgbImage};
+use serde_json::{json, Value};
+use std::io::Cursor;
+use std::path::Path;
+use std::sync::Arc;
+use std::thread;
+
+// Helper function to create a test filament with ID
+fn create_test_filament(id: &str) -> FilamentRoll {
+    FilamentRoll::with_id(
+        id,
+        "Galaxy Black",
+        "PLA",
+        "#101010",
+        1.75,
+        1000.0,
+        800.0,
+        "Test Brand",
+        "Shelf A",
+    )
+    .expect("Failed to create test filament")
+}
+
+// Noise of the given size, encoded as `format`; noise barely compresses, so
+// file sizes are close to the raw pixel data
+fn test_image(width: u32, height: u32, format: ImageFormat) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    RgbImage::from_fn(width, height, |x, y| {
+        let v = (x.wrapping_mul(7919) ^ y.wrapping_mul(104_729)).wrapping_mul(2_654_435_761);
+        image::Rgb([v as u8, (v >> 8) as u8, (v >> 16) as u8])
+    })
+    .write_to(&mut Cursor::new(&mut bytes), format)
+    .expect("Failed to encode test image");
+    bytes
+}
+
+fn service_with_storage(root: &Path) -> (Arc<InMemoryFilamentRepository>, FilamentService) {
+    let repository = Arc::new(InMemoryFilamentRepository::new());
+    repository.save(&create_test_filament("roll-1")).unwrap();
+    let service = FilamentService::new(repository.clone())
+        .with_attachments(Arc::new(LocalAttachmentStorage::new(root)));
+    (repository, service)
+}
+
+#[test]
+fn test_notes() {
+    // Arrange
+    let repository = Arc::new(InMemoryFilamentRepository::new());
+    repository.save(&create_test_filament("roll-1")).unwrap();
+    let service = FilamentService::new(repository.clone());
+
+    // Act
+    let first = service
+        .add_note("roll-1", "  Stringy above 230 °C ")
+        .unwrap();
+    let second = service.add_note("roll-1", "Dried 4h at 50 °C").unwrap();
+    let after_removal = service.remove_note("roll-1", first.id()).unwrap();
+
+    // Assert
+    assert_eq!(first.text(), "Stringy above 230 °C");
+    assert!(second.created_at() >= first.created_at());
+    assert_eq!(after_removal.notes(), [second]);
+    assert_eq!(repository.find_by_id("roll-1").unwrap(), after_removal);
+    assert!(matches!(
+        service.remove_note("roll-1", first.id()),
+        Err(FilamentError::Missing(_))
+    ));
+    assert!(matches!(
+        service.add_note("roll-1", " "),
+        Err(FilamentError::InvalidData(_))
+    ));
+    // Notes need no storage, but attachments do
+    assert!(matches!(
+        service.add_attachment("roll-1", "a.png", "image/png", b"png", b"png"),
+        Err(FilamentError::RepositoryError(_))
+    ));
+}
+
+#[test]
+fn test_local_storage() {
+    // Arrange
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let root = dir.path().join("attachments");
+    let storage = LocalAttachmentStorage::new(&root);
+
+    // Act
+    storage.put("roll-1", "photo", b"first").unwrap();
+    storage.put("roll-1", "photo", b"second").unwrap();
+    storage
+        .put("../escape", "../../photo", b"kept inside")
+        .unwrap();
+    storage.put("roll-2", "photo", b"other roll").unwrap();
+
+    // Assert
+    assert_eq!(storage.get("roll-1", "photo").unwrap(), b"second");
+    assert_eq!(
+        storage.get("../escape", "../../photo").unwrap(),
+        b"kept inside"
+    );
+    assert!(!dir.path().join("photo").exists());
+    assert_eq!(std::fs::read_dir(&root).unwrap().count(), 3);
+
+    storage.delete("roll-1", "photo").unwrap();
+    storage.delete("roll-1", "photo").unwrap();
+    assert!(matches!(
+        storage.get("roll-1", "photo"),
+        Err(FilamentError::Missing(_))
+    ));
+    storage.delete_roll("roll-2").unwrap();
+    storage.delete_roll("roll-2").unwrap();
+    assert!(matches!(
+        storage.get("roll-2", "photo"),
+        Err(FilamentError::Missing(_))
+    ));
+    assert!(matches!(
+        storage.delete_roll(""),
+        Err(FilamentError::InvalidData(_))
+    ));
+    assert!(root.exists());
+}
+
+#[test]
+fn test_thumbnails() {
+    // Arrange
+    let photo = test_image(1024, 512, ImageFormat::Jpeg);
+    let icon = test_image(32, 48, ImageFormat::Png);
+
+    // Act
+    let photo_thumbnail = image::load_from_memory(&thumbnail(&photo).unwrap()).unwrap();
+    let icon_thumbnail = thumbnail(&icon).unwrap();
+
+    // Assert
+    assert_eq!(
+        (photo_thumbnail.width(), photo_thumbnail.height()),
+        (THUMBNAIL_SIZE, THUMBNAIL_SIZE / 2)
+    );
+    assert_eq!(
+        image::guess_format(&icon_thumbnail).unwrap(),
+        ImageFormat::Png
+    );
+    let icon_thumbnail = image::load_from_memory(&icon_thumbnail).unwrap();
+    assert_eq!((icon_thumbnail.width(), icon_thumbnail.height()), (32, 48));
+    assert_eq!(image_content_type(&photo).unwrap(), "image/jpeg");
+    assert_eq!(image_content_type(&icon).unwrap(), "image/png");
+    assert!(matches!(
+        image_content_type(b"%PDF-1.7"),
+        Err(FilamentError::InvalidData(_))
+    ));
+    assert!(matches!(
+        thumbnail(&icon[..icon.len() / 2]),
+        Err(FilamentError::InvalidData(_))
+    ));
+}
+
+#[test]
+fn test_attachments_are_stored_and_removed() {
+    // Arrange
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let (repository, service) = service_with_storage(dir.path());
+    let photo = test_image(600, 600, ImageFormat::Png);
+    let preview = thumbnail(&photo).unwrap();
+
+    // Act
+    let attachment = service
+        .add_attachment("roll-1", "label.png", "image/png", &photo, &preview)
+        .unwrap();
+    let (stored, content) = service
+        .attachment_content("roll-1", attachment.id())
+        .unwrap();
+    let stored_thumbnail = service
+        .attachment_thumbnail("roll-1", attachment.id())
+        .unwrap();
+
+    // Assert
+    assert_eq!(stored, attachment);
+    assert_eq!(attachment.file_name(), "label.png");
+    assert_eq!(attachment.size(), photo.len() as u64);
+    assert_eq!(content, photo);
+    assert_eq!(stored_thumbnail, preview);
+    assert_eq!(
+        repository.find_by_id("roll-1").unwrap().attachments(),
+        std::slice::from_ref(&attachment)
+    );
+
+    let roll = service
+        .remove_attachment("roll-1", attachment.id())
+        .unwrap();
+    assert!(roll.attachments().is_empty());
+    assert!(matches!(
+        service.attachment_content("roll-1", attachment.id()),
+        Err(FilamentError::Missing(_))
+    ));
+    assert_eq!(
+        std::fs::read_dir(dir.path().join("roll-1"))
+            .unwrap()
+            .count(),
+        0
+    );
+    assert!(matches!(
+        service.add_attachment("missing", "label.png", "image/png", &photo, &preview),
+        Err(FilamentError::NotFound(_))
+    ));
+    assert!(!dir.path().join("missing").exists());
+}
+
+#[test]
+fn test_deleting_a_roll_removes_its_attachments() {
+    // Arrange
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let (repository, service) = service_with_storage(dir.path());
+    repository.save(&create_test_filament("roll-2")).unwrap();
+    let photo = test_image(64, 64, ImageFormat::Png);
+    service
+        .add_attachment("roll-1", "sample.png", "image/png", &photo, &photo)
+        .unwrap();
+    let kept = service
+        .add_attachment("roll-2", "sample.png", "image/png", &photo, &photo)
+        .unwrap();
+
+    // Act
+    service.delete_roll("roll-1").unwrap();
+
+    // Assert
+    assert!(matches!(
+        repository.find_by_id("roll-1"),
+        Err(FilamentError::NotFound(_))
+    ));
+    assert!(!dir.path().join("roll-1").exists());
+    assert_eq!(
+        service.attachment_content("roll-2", kept.id()).unwrap().1,
+        photo
+    );
+    assert!(matches!(
+        service.delete_roll("roll-1"),
+        Err(FilamentError::NotFound(_))
+    ));
+}
+
+#[test]
+fn test_loaded_or_reserved_rolls_are_not_deleted() {
+    // Arrange
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let repository = Arc::new(InMemoryFilamentRepository::new());
+    repository.save(&create_test_filament("roll-1")).unwrap();
+    repository.save(&create_test_filament("roll-2")).unwrap();
+    let assignments = Arc::new(InMemoryRollAssignments::new());
+    let service = FilamentService::new(repository.clone())
+        .with_attachments(Arc::new(LocalAttachmentStorage::new(dir.path())))
+        .with_reservations(Arc::new(InMemoryReservationRepository::new()))
+        .with_assignments(assignments.clone());
+    assignments.load_roll("printer-1", 0, "roll-1").unwrap();
+    let reservation = service
+        .reserve("roll-2", 50.0, "Benchy", Utc::now() + Duration::hours(1))
+        .unwrap();
+
+    // Act
+    let loaded = service.delete_roll("roll-1");
+    let reserved = service.delete_roll("roll-2");
+
+    // Assert
+    assert!(matches!(loaded, Err(FilamentError::InvalidData(_))));
+    assert!(matches!(reserved, Err(FilamentError::InvalidData(_))));
+    assert!(repository.find_by_id("roll-1").is_ok());
+    assert!(repository.find_by_id("roll-2").is_ok());
+
+    assignments.unload("printer-1", 0).unwrap();
+    service.release_reservation(reservation.id()).unwrap();
+    service.delete_roll("roll-1").unwrap();
+    service.delete_roll("roll-2").unwrap();
+    assert!(repository.find_all().unwrap().is_empty());
+}
+
+#[test]
+fn test_event_sourced_repository_keeps_notes_and_archives_deleted_rolls() {
+    // Arrange
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let events_path = dir.path().join("events.jsonl");
+    let snapshot_path = dir.path().join("snapshot.json");
+    let open = || {
+        EventSourcedFilamentRepository::open(
+            JsonLinesEventStore::open(&events_path).unwrap(),
+            JsonSnapshotStore::new(&snapshot_path),
+        )
+        .expect("Failed to open repository")
+    };
+    let mut kept = create_test_filament("kept");
+    kept.add_note(RollNote::new("Customer-supplied, return empties").unwrap());
+
+    // Act
+    {
+        let repository = open();
+        repository.save(&kept).unwrap();
+        repository.save(&create_test_filament("deleted")).unwrap();
+        repository.delete("deleted").unwrap();
+    }
+    let reopened = open();
+
+    // Assert
+    assert_eq!(reopened.find_by_id("kept").unwrap(), kept);
+    assert!(matches!(
+        reopened.find_by_id("deleted"),
+        Err(FilamentError::NotFound(_))
+    ));
+    assert_eq!(reopened.find_archived().unwrap()[0].id(), "deleted");
+}
+
+#[test]
+fn test_audited_delete_and_note_changes() {
+    // Arrange
+    let repository = AuditedFilamentRepository::new(
+        InMemoryFilamentRepository::new(),
+        InMemoryAuditLog::new(),
+        AuditContext::new("api-user", ChangeSource::Api),
+    );
+    let mut roll = create_test_filament("audit-1");
+    repository.save(&roll).unwrap();
+    let note = RollNote::new("Printed a benchy, looks fine").unwrap();
+
+    // Act
+    roll.add_note(note.clone());
+    repository.save(&roll).unwrap();
+    repository.delete("audit-1").unwrap();
+
+    // Assert
+    let entries = repository.audit_log().find_by_roll("audit-1").unwrap();
+    assert_eq!(entries.len(), 3);
+    let added = &entries[1].changes()[0];
+    assert_eq!(added.field, format!("notes.{}", note.id()));
+    assert_eq!(added.after.as_deref(), Some("Printed a benchy, looks fine"));
+    let deleted = entries[2].changes();
+    assert!(deleted.iter().all(|c| c.after.is_none()));
+    assert!(deleted
+        .iter()
+        .any(|c| c.field == "name" && c.before.as_deref() == Some("Galaxy Black")));
+    assert!(matches!(
+        repository.delete("audit-1"),
+        Err(FilamentError::NotFound(_))
+    ));
+}
+
+#[test]
+fn test_csv_update_keeps_notes() {
+    // Arrange
+    let repository = InMemoryFilamentRepository::new();
+    let mut roll = create_test_filament("roll-1");
+    roll.add_note(RollNote::new("Keep away from the window").unwrap());
+    repository.save(&roll).unwrap();
+
+    // Act
+    import_csv(
+        &repository,
+        "id,remaining_weight\nroll-1,500\n".as_bytes(),
+        &ColumnMapping::new(),
+        ImportMode::Apply,
+    )
+    .unwrap();
+
+    // Assert
+    let updated = repository.find_by_id("roll-1").unwrap();
+    assert_eq!(updated.remaining_weight(), 500.0);
+    assert_eq!(updated.notes(), roll.notes());
+}
+
+#[actix_web::test]
+async fn test_attachments_api() {
+    // Arrange
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let (_, service) = service_with_storage(dir.path());
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(service))
+            .configure(api::rolls::configure)
+            .configure(api::attachments::configure),
+    )
+    .await;
+    // Bigger than actix-web's default payload limit
+    let photo = test_image(1200, 900, ImageFormat::Png);
+    assert!(photo.len() > 256 * 1024);
+
+    // Act
+    let response = call_service(
+        &app,
+        TestRequest::post()
+            .uri("/api/rolls/roll-1/attachments?file_name=spool%20label.png")
+            .set_payload(photo.clone())
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let attachment: Value = read_body_json(response).await;
+    let uri = format!(
+        "/api/rolls/roll-1/attachments/{}",
+        attachment["id"].as_str().unwrap()
+    );
+
+    let download = call_service(&app, TestRequest::get().uri(&uri).to_request()).await;
+    let thumbnail = call_service(
+        &app,
+        TestRequest::get()
+            .uri(&format!("{}/thumbnail", uri))
+            .to_request(),
+    )
+    .await;
+    let note = call_service(
+        &app,
+        TestRequest::post()
+            .uri("/api/rolls/roll-1/notes")
+            .set_json(json!({ "text": "Label photo taken on arrival" }))
+            .to_request(),
+    )
+    .await;
+    let not_an_image = call_service(
+        &app,
+        TestRequest::post()
+            .uri("/api/rolls/roll-1/attachments?file_name=notes.txt")
+            .set_payload("just text")
+            .to_request(),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(attachment["file_name"], "spool label.png");
+    assert_eq!(attachment["content_type"], "image/png");
+    assert_eq!(download.status(), StatusCode::OK);
+    assert_eq!(download.headers().get(CONTENT_TYPE).unwrap(), "image/png");
+    assert!(download
+        .headers()
+        .get(CONTENT_DISPOSITION)
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .contains("spool label.png"));
+    assert_eq!(read_body(download).await, photo);
+    assert_eq!(thumbnail.status(), StatusCode::OK);
+    let thumbnail = image::load_from_memory(&read_body(thumbnail).await).unwrap();
+    assert_eq!(thumbnail.width(), THUMBNAIL_SIZE);
+    assert_eq!(note.status(), StatusCode::CREATED);
+    let note: Value = read_body_json(note).await;
+    assert_eq!(note["text"], "Label photo taken on arrival");
+    assert_eq!(not_an_image.status(), StatusCode::BAD_REQUEST);
+
+    let deleted = call_service(
+        &app,
+        TestRequest::delete().uri("/api/rolls/roll-1").to_request(),
+    )
+    .await;
+    assert_eq!(deleted.status(), StatusCode::NO_CONTENT);
+    let gone = call_service(&app, TestRequest::get().uri(&uri).to_request()).await;
+    assert_eq!(gone.status(), StatusCode::NOT_FOUND);
+    assert!(!dir.path().join("roll-1").exists());
+}
+
+#[test]
+fn test_note_and_attachment_edits_never_undo_concurrent_consumption() {
+    // Arrange
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let (_, service) = service_with_storage(dir.path());
+
+    // Act
+    let workers: Vec<_> = (0..40)
+        .map(|i| {
+            let worker = service.clone();
+            thread::spawn(move || match i % 4 {
+                0 | 2 => worker.consume("roll-1", 10.0).map(|_| ()),
+                1 => worker
+                    .add_note("roll-1", &format!("Note {}", i))
+                    .map(|_| ()),
+                _ => worker
+                    .add_attachment("roll-1", "a.png", "image/png", b"png", b"png")
+                    .map(|_| ()),
+            })
+        })
+        .collect();
+    for worker in workers {
+        worker
+            .join()
+            .expect("Worker thread panicked")
+            .expect("Update failed");
+    }
+
+    // Assert
+    let roll = service.get_roll("roll-1").unwrap();
+    assert_eq!(roll.remaining_weight(), 600.0);
+    assert_eq!(roll.notes().len(), 10);
+    assert_eq!(roll.attachments().len(), 10);
+}
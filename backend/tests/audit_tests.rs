@@ -0,0 +1,253 @@
+use actix_web::{web, App};
+use backend::api;
+use backend::domain::audit::{AuditContext, AuditLog, ChangeSource};
+use backend::domain::filament::{FilamentRepository, FilamentRoll};
+use backend::infrastructure::repositories::audited::AuditedFilamentRepository;
+use backend::infrastructure::repositories::memory::{InMemoryAuditLog, InMemoryFilamentRepository};
+use serde_json::Value;
+use std::sync::Arc;
+use std::thread;
+
+// Helper function to create a test filament with ID
+fn create_test_filament(id: &str, remaining_weight: f32) -> FilamentRoll {
+    FilamentRoll::with_id(
+        id,
+        "Test Filament",
+        "PLA",
+        "#000000",
+        1.75,
+        1000.0,
+        remaining_weight,
+        "Test Brand",
+        "Bin 1",
+    )
+    .expect("Failed to create test filament")
+}
+
+fn create_audited_repository(
+) -> AuditedFilamentRepository<InMemoryFilamentRepository, InMemoryAuditLog> {
+    AuditedFilamentRepository::new(
+        InMemoryFilamentRepository::new(),
+        InMemoryAuditLog::new(),
+        AuditContext::new("api-user", ChangeSource::Api),
+    )
+}
+
+#[test]
+fn test_registering_roll_records_all_fields() {
+    // Arrange
+    let repository = create_audited_repository();
+    let filament = create_test_filament("audit-1", 1000.0);
+
+    // Act
+    repository.save(&filament).expect("Failed to save filament");
+
+    // Assert
+    let entries = repository
+        .audit_log()
+        .find_by_roll("audit-1")
+        .expect("Failed to read audit log");
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].actor(), "api-user");
+    assert_eq!(entries[0].source(), ChangeSource::Api);
+    assert!(entries[0].changes().iter().all(|c| c.before.is_none()));
+    assert!(entries[0]
+        .changes()
+        .iter()
+        .any(|c| c.field == "remaining_weight" && c.after.as_deref() == Some("1000")));
+}
+
+#[test]
+fn test_weight_update_records_before_and_after() {
+    // Arrange
+    let repository = create_audited_repository();
+    repository
+        .save(&create_test_filament("audit-2", 1000.0))
+        .expect("Failed to save filament");
+    let cli = AuditContext::new("alice", ChangeSource::Cli);
+
+    // Act
+    repository
+        .update_remaining_weight_as("audit-2", 640.0, &cli)
+        .expect("Failed to update weight");
+
+    // Assert
+    let entries = repository
+        .audit_log()
+        .find_by_roll("audit-2")
+        .expect("Failed to read audit log");
+    assert_eq!(entries.len(), 2);
+    let update = &entries[1];
+    assert_eq!(update.actor(), "alice");
+    assert_eq!(update.source(), ChangeSource::Cli);
+    assert_eq!(update.changes().len(), 1);
+    assert_eq!(update.changes()[0].field, "remaining_weight");
+    assert_eq!(update.changes()[0].before.as_deref(), Some("1000"));
+    assert_eq!(update.changes()[0].after.as_deref(), Some("640"));
+}
+
+#[test]
+fn test_failed_update_is_not_audited() {
+    // Arrange
+    let repository = create_audited_repository();
+    repository
+        .save(&create_test_filament("audit-3", 500.0))
+        .expect("Failed to save filament");
+
+    // Act
+    let result = repository.update_remaining_weight("audit-3", -5.0);
+
+    // Assert
+    assert!(result.is_err());
+    let entries = repository
+        .audit_log()
+        .find_by_roll("audit-3")
+        .expect("Failed to read audit log");
+    assert_eq!(entries.len(), 1);
+}
+
+#[test]
+fn test_unchanged_save_is_not_audited() {
+    // Arrange
+    let repository = create_audited_repository();
+    let filament = create_test_filament("audit-4", 500.0);
+    repository.save(&filament).expect("Failed to save filament");
+
+    // Act
+    repository.save(&filament).expect("Failed to save filament");
+
+    // Assert
+    let entries = repository
+        .audit_log()
+        .find_by_roll("audit-4")
+        .expect("Failed to read audit log");
+    assert_eq!(entries.len(), 1);
+}
+
+#[test]
+fn test_global_audit_query_spans_rolls() {
+    // Arrange
+    let repository = create_audited_repository();
+    repository
+        .save(&create_test_filament("audit-5", 1000.0))
+        .expect("Failed to save filament");
+    repository
+        .save(&create_test_filament("audit-6", 1000.0))
+        .expect("Failed to save filament");
+
+    // Act
+    repository
+        .update_remaining_weight("audit-6", 900.0)
+        .expect("Failed to update weight");
+    let entries = repository
+        .audit_log()
+        .find_all()
+        .expect("Failed to read audit log");
+
+    // Assert
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[2].roll_id(), "audit-6");
+    assert!(entries[0].timestamp() <= entries[2].timestamp());
+}
+
+#[test]
+fn test_concurrent_writes_are_audited_in_order() {
+    // Arrange
+    let repository = Arc::new(create_audited_repository());
+    repository
+        .save(&create_test_filament("audit-7", 1000.0))
+        .expect("Failed to save filament");
+
+    // Act
+    let writers: Vec<_> = (0..20)
+        .map(|_| {
+            let repository = repository.clone();
+            thread::spawn(move || {
+                repository
+                    .update("audit-7", &mut |roll| {
+                        roll.update_remaining_weight(roll.remaining_weight() - 10.0)
+                    })
+                    .expect("Failed to consume");
+            })
+        })
+        .collect();
+    for writer in writers {
+        writer.join().expect("Writer panicked");
+    }
+
+    // Assert
+    // Each entry starts from the weight the entry before it left behind
+    let weights: Vec<_> = repository
+        .audit_log()
+        .find_by_roll("audit-7")
+        .expect("Failed to read audit log")
+        .iter()
+        .skip(1)
+        .map(|entry| {
+            let change = entry
+                .changes()
+                .iter()
+                .find(|c| c.field == "remaining_weight")
+                .expect("Weight change missing");
+            (
+                change.before.clone().unwrap(),
+                change.after.clone().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(weights.len(), 20);
+    assert!(weights.windows(2).all(|pair| pair[0].1 == pair[1].0));
+    assert_eq!(weights[19].1, "800");
+}
+
+#[actix_web::test]
+async fn test_audit_log_is_served_per_roll_and_globally() {
+    // Arrange
+    let audit_log: Arc<dyn AuditLog> = Arc::new(InMemoryAuditLog::new());
+    let repository = AuditedFilamentRepository::new(
+        InMemoryFilamentRepository::new(),
+        audit_log.clone(),
+        AuditContext::new("api-user", ChangeSource::Api),
+    );
+    repository
+        .save(&create_test_filament("audit-8", 1000.0))
+        .expect("Failed to save filament");
+    repository
+        .save(&create_test_filament("audit-9", 1000.0))
+        .expect("Failed to save filament");
+    repository
+        .update_remaining_weight("audit-9", 750.0)
+        .expect("Failed to update weight");
+    let app = actix_web::test::init_service(
+        App::new()
+            .app_data(web::Data::from(audit_log))
+            .configure(api::audit::configure),
+    )
+    .await;
+
+    // Act
+    let roll: Vec<Value> = actix_web::test::call_and_read_body_json(
+        &app,
+        actix_web::test::TestRequest::get()
+            .uri("/api/rolls/audit-9/audit")
+            .to_request(),
+    )
+    .await;
+    let all: Vec<Value> = actix_web::test::call_and_read_body_json(
+        &app,
+        actix_web::test::TestRequest::get()
+            .uri("/api/audit")
+            .to_request(),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(roll.len(), 2);
+    assert_eq!(roll[1]["actor"], "api-user");
+    assert!(roll[1]["changes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|c| c["field"] == "remaining_weight" && c["after"] == "750"));
+    assert_eq!(all.len(), 3);
+}
@@ -0,0 +1,248 @@
+use actix_web::http::StatusCode;
+use actix_web::test::{call_service, init_service, read_body_json, TestRequest};
+use actix_web::{web, App};
+use backend::api;
+use backend::domain::error::FilamentError;
+use backend::domain::filament::{FilamentRepository, FilamentRoll};
+use backend::domain::scan::RollDraft;
+use backend::domain::services::filament_service::FilamentService;
+use backend::domain::services::scan_service::ScanService;
+use backend::infrastructure::bambu::rfid::{is_dump, BambuTag};
+use backend::infrastructure::nfc::tigertag::TigerTagIds;
+use backend::infrastructure::nfc::TagFormat;
+use backend::infrastructure::repositories::memory::{
+    InMemoryFilamentRepository, InMemoryProductCatalogue,
+};
+use chrono::NaiveDate;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+// The same spool dumped three ways; the Flipper export is missing the
+// signature sectors, as it is without Bambu's keys for them
+const RAW_DUMP: &[u8] = include_bytes!("fixtures/bambu_pla_basic.bin");
+const FLIPPER_DUMP: &str = include_str!("fixtures/bambu_pla_basic.nfc");
+const PROXMARK_DUMP: &str = include_str!("fixtures/bambu_pla_basic.json");
+
+#[test]
+fn test_decode_raw_dump() {
+    // Act
+    let tag = BambuTag::parse(RAW_DUMP).unwrap();
+
+    // Assert
+    assert_eq!(tag.uid, "75886D0F");
+    assert_eq!(tag.variant_id, "A00-G1");
+    assert_eq!(tag.material_id, "GFA00");
+    assert_eq!(tag.filament_type, "PLA");
+    assert_eq!(tag.detailed_type, "PLA Basic");
+    assert_eq!(tag.color, "#00AE42");
+    assert_eq!(tag.weight, 1000.0);
+    assert_eq!(tag.diameter, 1.75);
+    assert_eq!(tag.drying_temperature, 55);
+    assert_eq!(tag.drying_hours, 8);
+    assert_eq!(tag.bed_temperature, 35);
+    assert_eq!(tag.min_nozzle_temperature, 190);
+    assert_eq!(tag.max_nozzle_temperature, 230);
+    assert_eq!(tag.tray_uuid, "A1B2C3D4E5F60718293A4B5C6D7E8F90");
+    assert_eq!(
+        tag.production_date,
+        NaiveDate::from_ymd_opt(2024, 3, 21).and_then(|date| date.and_hms_opt(14, 35, 0))
+    );
+}
+
+#[test]
+fn test_export_formats_agree() {
+    // Arrange
+    let raw = BambuTag::parse(RAW_DUMP).unwrap();
+    let eml: String = RAW_DUMP
+        .chunks(16)
+        .map(|block| {
+            let line: String = block.iter().map(|b| format!("{:02X}", b)).collect();
+            line + "\n"
+        })
+        .collect();
+
+    // Act
+    let flipper = BambuTag::parse(FLIPPER_DUMP.as_bytes()).unwrap();
+    let proxmark = BambuTag::parse(PROXMARK_DUMP.as_bytes()).unwrap();
+    let eml = BambuTag::parse(eml.as_bytes()).unwrap();
+
+    // Assert
+    assert_eq!(flipper, raw);
+    assert_eq!(proxmark, raw);
+    assert_eq!(eml, raw);
+}
+
+#[test]
+fn test_draft_and_roll() {
+    // Arrange
+    let tag = BambuTag::parse(RAW_DUMP).unwrap();
+
+    // Act
+    let draft = tag.draft();
+    let roll = tag
+        .to_builder()
+        .with_storage_location("AMS 1")
+        .build()
+        .unwrap();
+
+    // Assert
+    assert_eq!(
+        draft,
+        RollDraft {
+            name: "PLA Basic".to_string(),
+            material: "PLA".to_string(),
+            color: "#00AE42".to_string(),
+            diameter: 1.75,
+            weight: 1000.0,
+            manufacturer: "Bambu Lab".to_string(),
+            id: Some("bambu-A1B2C3D4E5F60718293A4B5C6D7E8F90".to_string()),
+            spool_weight: None,
+        }
+    );
+    assert_eq!(roll.id(), "bambu-A1B2C3D4E5F60718293A4B5C6D7E8F90");
+    assert_eq!(roll.remaining_weight(), 1000.0);
+    assert_eq!(roll.manufacturer(), "Bambu Lab");
+}
+
+#[test]
+fn test_unread_blocks_are_reported() {
+    // Arrange
+    let flipper = FLIPPER_DUMP.replace("Block 5: 00 AE 42 FF", "Block 5: ?? ?? ?? ??");
+    let mut blank = RAW_DUMP.to_vec();
+    blank[32..48].fill(0);
+
+    // Act
+    let missing = BambuTag::parse(flipper.as_bytes());
+    let truncated = BambuTag::parse(&RAW_DUMP[..1000]);
+    let untyped = BambuTag::parse(&blank);
+
+    // Assert
+    match missing {
+        Err(FilamentError::InvalidData(message)) => assert!(message.contains("Block 5")),
+        other => panic!("Expected a missing block error, got {:?}", other),
+    }
+    assert!(matches!(truncated, Err(FilamentError::InvalidData(_))));
+    assert!(matches!(untyped, Err(FilamentError::InvalidData(_))));
+}
+
+#[test]
+fn test_block_numbers_beyond_a_4k_tag_are_rejected() {
+    // Arrange
+    let proxmark = [
+        br#"{"blocks":{"18446744073709551615":"00"}}"#.as_slice(),
+        br#"{"blocks":{"4000000000":"00"}}"#,
+        br#"{"blocks":{"256":"00"}}"#,
+    ];
+    let flipper = FLIPPER_DUMP.replace("Block 63:", "Block 4000000000:");
+
+    // Act & Assert
+    for dump in proxmark {
+        assert!(matches!(
+            BambuTag::parse(dump),
+            Err(FilamentError::InvalidData(_))
+        ));
+    }
+    match BambuTag::parse(flipper.as_bytes()) {
+        Err(FilamentError::InvalidData(message)) => assert!(message.contains("4000000000")),
+        other => panic!("Expected an out of range block error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_detects_mifare_dumps() {
+    assert!(is_dump(RAW_DUMP));
+    assert!(is_dump(FLIPPER_DUMP.as_bytes()));
+    assert!(is_dump(PROXMARK_DUMP.as_bytes()));
+    assert!(!is_dump(br#"{"protocol":"openspool","type":"PLA"}"#));
+    assert_eq!(TagFormat::detect(RAW_DUMP), TagFormat::Bambu);
+    assert_eq!(
+        TagFormat::detect(PROXMARK_DUMP.as_bytes()),
+        TagFormat::Bambu
+    );
+    assert_eq!(
+        TagFormat::detect(br#"{"protocol":"openspool"}"#),
+        TagFormat::OpenSpool
+    );
+}
+
+#[test]
+fn test_raw_dump_starting_with_a_brace_is_still_raw() {
+    // Arrange
+    // The first UID byte can be anything, including `{`
+    let mut dump = RAW_DUMP.to_vec();
+    dump[0] = b'{';
+
+    // Act
+    let tag = BambuTag::parse(&dump).expect("Failed to parse raw dump");
+
+    // Assert
+    assert!(is_dump(&dump));
+    assert_eq!(TagFormat::detect(&dump), TagFormat::Bambu);
+    assert_eq!(tag.draft(), BambuTag::parse(RAW_DUMP).unwrap().draft());
+}
+
+#[actix_web::test]
+async fn test_read_bambu_tag_api() {
+    // Arrange
+    let repository = Arc::new(InMemoryFilamentRepository::new());
+    let scan = ScanService::new(
+        FilamentService::new(repository.clone()),
+        Arc::new(InMemoryProductCatalogue::new()),
+    );
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(FilamentService::new(repository.clone())))
+            .app_data(web::Data::new(scan))
+            .app_data(web::Data::new(TigerTagIds::default()))
+            .configure(api::tags::configure)
+            .configure(api::scan::configure),
+    )
+    .await;
+
+    // Act
+    let response = call_service(
+        &app,
+        TestRequest::post()
+            .uri("/api/tags/read")
+            .set_payload(FLIPPER_DUMP)
+            .to_request(),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let draft: Value = read_body_json(response).await;
+    assert_eq!(draft["name"], "PLA Basic");
+    assert_eq!(draft["color"], "#00AE42");
+
+    // Registering the same spool again finds the roll made the first time
+    let mut confirm = draft.clone();
+    confirm["storage_location"] = json!("AMS 1");
+    let mut registered = Vec::new();
+    for expected in [StatusCode::CREATED, StatusCode::OK] {
+        let response = call_service(
+            &app,
+            TestRequest::post()
+                .uri("/api/scan/register")
+                .set_json(&confirm)
+                .to_request(),
+        )
+        .await;
+        assert_eq!(response.status(), expected);
+        let roll: FilamentRoll = read_body_json(response).await;
+        registered.push(roll);
+    }
+    assert_eq!(registered[0].id(), "bambu-A1B2C3D4E5F60718293A4B5C6D7E8F90");
+    assert_eq!(registered[0], registered[1]);
+    assert_eq!(repository.find_all().unwrap().len(), 1);
+
+    let response = call_service(
+        &app,
+        TestRequest::post()
+            .uri("/api/tags/read?format=bambu")
+            .set_payload(&RAW_DUMP[..100])
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
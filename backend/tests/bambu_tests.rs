@@ -0,0 +1,485 @@
+use backend::domain::assignment::RollAssignments;
+use backend::domain::error::FilamentError;
+use backend::domain::filament::{FilamentRepository, FilamentRoll};
+use backend::domain::services::filament_service::FilamentService;
+use backend::infrastructure::bambu::ams::parse_report;
+use backend::infrastructure::bambu::mqtt::{BambuAmsConnector, BambuMqttConfig};
+use backend::infrastructure::bambu::reconcile::{AmsReconciler, DiscrepancyKind};
+use backend::infrastructure::bambu::tags::JsonFileTrayTags;
+use backend::infrastructure::repositories::memory::{
+    InMemoryFilamentRepository, InMemoryRollAssignments,
+};
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+const REPORT: &str = include_str!("fixtures/bambu_ams_report.json");
+
+// Helper function to create a test filament with ID
+fn create_test_filament(id: &str, material: &str, weight: f32, remaining: f32) -> FilamentRoll {
+    FilamentRoll::with_id(
+        id,
+        "Test Filament",
+        material,
+        "#000000",
+        1.75,
+        weight,
+        remaining,
+        "Bambu Lab",
+        "AMS",
+    )
+    .expect("Failed to create test filament")
+}
+
+fn setup(rolls: &[(u32, FilamentRoll)]) -> (Arc<InMemoryFilamentRepository>, AmsReconciler) {
+    let repository = Arc::new(InMemoryFilamentRepository::new());
+    let assignments = Arc::new(InMemoryRollAssignments::new());
+    for (tool, roll) in rolls {
+        repository.save(roll).expect("Failed to save filament");
+        assignments
+            .load_roll("x1c", *tool, roll.id())
+            .expect("Failed to load roll");
+    }
+
+    let reconciler =
+        AmsReconciler::new("x1c", FilamentService::new(repository.clone()), assignments);
+    (repository, reconciler)
+}
+
+#[test]
+fn test_parse_report_reads_trays() {
+    // Act
+    let trays = parse_report(REPORT.as_bytes()).unwrap().unwrap();
+
+    // Assert
+    assert_eq!(trays.len(), 4);
+    assert_eq!(trays[0].tool(), 0);
+    assert_eq!(trays[0].tray_type.as_deref(), Some("PLA"));
+    assert_eq!(trays[0].color.as_deref(), Some("#000000"));
+    assert_eq!(trays[0].remain, Some(78.0));
+    assert_eq!(trays[0].tag_uid.as_deref(), Some("A3F10B2C00000000"));
+    // Non-Bambu spools report no remaining percentage and a blank tag
+    assert_eq!(trays[1].remain, None);
+    assert_eq!(trays[1].tag_uid, None);
+    assert!(trays[2].is_empty());
+}
+
+#[test]
+fn test_parse_report_without_ams_state() {
+    // Act
+    let result = parse_report(br#"{"print":{"command":"push_status","mc_percent":42}}"#);
+
+    // Assert
+    assert_eq!(result.unwrap(), None);
+}
+
+#[test]
+fn test_small_drop_is_applied() {
+    // Arrange
+    let (repository, reconciler) = setup(&[(0, create_test_filament("pla", "PLA", 1000.0, 820.0))]);
+    let trays = parse_report(REPORT.as_bytes()).unwrap().unwrap();
+
+    // Act
+    let report = reconciler.reconcile(&trays).unwrap();
+
+    // Assert
+    assert_eq!(report.updated.len(), 1);
+    assert_eq!(report.updated[0].to, 780.0);
+    assert!(report.discrepancies.is_empty());
+    assert_eq!(
+        repository.find_by_id("pla").unwrap().remaining_weight(),
+        780.0
+    );
+    // Trays 1 and 3 are loaded but have no roll assigned
+    let unassigned: Vec<u32> = report.unassigned.iter().map(|t| t.tool()).collect();
+    assert_eq!(unassigned, vec![1, 3]);
+}
+
+#[test]
+fn test_large_difference_is_flagged_not_written() {
+    // Arrange
+    let (repository, reconciler) = setup(&[(0, create_test_filament("pla", "PLA", 1000.0, 500.0))]);
+    let trays = parse_report(REPORT.as_bytes()).unwrap().unwrap();
+
+    // Act
+    let report = reconciler.reconcile(&trays).unwrap();
+
+    // Assert
+    assert!(report.updated.is_empty());
+    assert_eq!(
+        report.discrepancies[0].kind,
+        DiscrepancyKind::WeightMismatch {
+            tracked: 500.0,
+            reported: 780.0
+        }
+    );
+    assert_eq!(
+        repository.find_by_id("pla").unwrap().remaining_weight(),
+        500.0
+    );
+}
+
+#[test]
+fn test_material_mismatch_is_flagged() {
+    // Arrange
+    let (_, reconciler) = setup(&[
+        (0, create_test_filament("abs", "ABS", 1000.0, 780.0)),
+        (3, create_test_filament("silk", "PLA", 500.0, 200.0)),
+    ]);
+    let trays = parse_report(REPORT.as_bytes()).unwrap().unwrap();
+
+    // Act
+    let report = reconciler.reconcile(&trays).unwrap();
+
+    // Assert
+    assert_eq!(report.discrepancies.len(), 1);
+    assert_eq!(report.discrepancies[0].roll_id, "abs");
+    assert!(matches!(
+        report.discrepancies[0].kind,
+        DiscrepancyKind::MaterialMismatch { .. }
+    ));
+}
+
+#[test]
+fn test_changed_tag_is_flagged() {
+    // Arrange
+    let (_, reconciler) = setup(&[(0, create_test_filament("pla", "PLA", 1000.0, 780.0))]);
+    let mut trays = parse_report(REPORT.as_bytes()).unwrap().unwrap();
+    reconciler.reconcile(&trays).unwrap();
+
+    // Act
+    trays[0].tag_uid = Some("FFFFFFFF00000000".to_string());
+    let report = reconciler.reconcile(&trays).unwrap();
+
+    // Assert
+    assert_eq!(
+        report.discrepancies[0].kind,
+        DiscrepancyKind::TagChanged {
+            previous: "A3F10B2C00000000".to_string(),
+            current: "FFFFFFFF00000000".to_string()
+        }
+    );
+}
+
+#[test]
+fn test_swapped_tray_skips_weight_sync_until_resolved() {
+    // Arrange
+    let (repository, reconciler) = setup(&[(0, create_test_filament("pla", "PLA", 1000.0, 780.0))]);
+    let mut trays = parse_report(REPORT.as_bytes()).unwrap().unwrap();
+    reconciler.reconcile(&trays).unwrap();
+    trays[0].tag_uid = Some("FFFFFFFF00000000".to_string());
+    trays[0].remain = Some(75.0);
+
+    // Act
+    reconciler.reconcile(&trays).unwrap();
+    let later = reconciler.reconcile(&trays).unwrap();
+
+    // Assert
+    assert!(later.updated.is_empty());
+    assert!(matches!(
+        later.discrepancies[0].kind,
+        DiscrepancyKind::TagChanged { .. }
+    ));
+    assert_eq!(
+        repository.find_by_id("pla").unwrap().remaining_weight(),
+        780.0
+    );
+
+    assert!(reconciler.confirm_swap(0).unwrap());
+    assert!(!reconciler.confirm_swap(0).unwrap());
+    let confirmed = reconciler.reconcile(&trays).unwrap();
+    assert!(confirmed.discrepancies.is_empty());
+    assert_eq!(confirmed.updated[0].to, 750.0);
+}
+
+#[test]
+fn test_new_assignment_accepts_the_loaded_tag() {
+    // Arrange
+    let repository = Arc::new(InMemoryFilamentRepository::new());
+    let assignments = Arc::new(InMemoryRollAssignments::new());
+    for roll in [
+        create_test_filament("pla", "PLA", 1000.0, 780.0),
+        create_test_filament("pla-2", "PLA", 1000.0, 780.0),
+    ] {
+        repository.save(&roll).unwrap();
+    }
+    assignments.load_roll("x1c", 0, "pla").unwrap();
+    let reconciler = AmsReconciler::new(
+        "x1c",
+        FilamentService::new(repository.clone()),
+        assignments.clone(),
+    );
+    let mut trays = parse_report(REPORT.as_bytes()).unwrap().unwrap();
+    reconciler.reconcile(&trays).unwrap();
+    trays[0].tag_uid = Some("FFFFFFFF00000000".to_string());
+    assert!(!reconciler
+        .reconcile(&trays)
+        .unwrap()
+        .discrepancies
+        .is_empty());
+
+    // Act
+    assignments.unload("x1c", 0).unwrap();
+    assignments.load_roll("x1c", 0, "pla-2").unwrap();
+    let report = reconciler.reconcile(&trays).unwrap();
+
+    // Assert
+    assert!(report.discrepancies.is_empty());
+}
+
+// Uses 50 g of the roll just before any write to it, as a print finishing
+// meanwhile would
+struct ConsumedBeforeWrite(InMemoryFilamentRepository);
+
+impl ConsumedBeforeWrite {
+    fn finish_print(&self, id: &str) -> Result<FilamentRoll, FilamentError> {
+        self.0.update(id, &mut |roll| {
+            roll.update_remaining_weight(roll.remaining_weight() - 50.0)
+        })
+    }
+}
+
+impl FilamentRepository for ConsumedBeforeWrite {
+    fn save(&self, filament: &FilamentRoll) -> Result<(), FilamentError> {
+        self.0.save(filament)
+    }
+
+    fn find_by_id(&self, id: &str) -> Result<FilamentRoll, FilamentError> {
+        self.0.find_by_id(id)
+    }
+
+    fn update_remaining_weight(
+        &self,
+        id: &str,
+        remaining_weight: f32,
+    ) -> Result<FilamentRoll, FilamentError> {
+        self.finish_print(id)?;
+        self.0.update_remaining_weight(id, remaining_weight)
+    }
+
+    fn find_all(&self) -> Result<Vec<FilamentRoll>, FilamentError> {
+        self.0.find_all()
+    }
+
+    fn find_by_material(&self, material: &str) -> Result<Vec<FilamentRoll>, FilamentError> {
+        self.0.find_by_material(material)
+    }
+
+    fn delete(&self, id: &str) -> Result<(), FilamentError> {
+        self.0.delete(id)
+    }
+
+    fn update(
+        &self,
+        id: &str,
+        change: &mut dyn FnMut(&mut FilamentRoll) -> Result<(), FilamentError>,
+    ) -> Result<FilamentRoll, FilamentError> {
+        self.finish_print(id)?;
+        self.0.update(id, change)
+    }
+}
+
+#[test]
+fn test_estimate_does_not_overwrite_a_concurrent_deduction() {
+    // Arrange
+    let repository = Arc::new(ConsumedBeforeWrite(InMemoryFilamentRepository::new()));
+    let assignments = Arc::new(InMemoryRollAssignments::new());
+    repository
+        .save(&create_test_filament("pla", "PLA", 1000.0, 820.0))
+        .unwrap();
+    assignments.load_roll("x1c", 0, "pla").unwrap();
+    let reconciler =
+        AmsReconciler::new("x1c", FilamentService::new(repository.clone()), assignments);
+    let trays = parse_report(REPORT.as_bytes()).unwrap().unwrap();
+
+    // Act
+    let report = reconciler.reconcile(&trays).unwrap();
+
+    // Assert
+    // The AMS estimate of 780 g is above what the deduction left
+    assert!(report.updated.is_empty());
+    assert_eq!(
+        repository.0.find_by_id("pla").unwrap().remaining_weight(),
+        770.0
+    );
+}
+
+#[test]
+fn test_flagged_swap_survives_a_restart() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("bambu-tags.json");
+    let repository = Arc::new(InMemoryFilamentRepository::new());
+    let assignments = Arc::new(InMemoryRollAssignments::new());
+    repository
+        .save(&create_test_filament("pla", "PLA", 1000.0, 780.0))
+        .unwrap();
+    assignments.load_roll("x1c", 0, "pla").unwrap();
+    let reconciler = |path: &std::path::Path| {
+        AmsReconciler::new(
+            "x1c",
+            FilamentService::new(repository.clone()),
+            assignments.clone(),
+        )
+        .with_tag_store(Arc::new(JsonFileTrayTags::open(path).unwrap()))
+    };
+    let mut trays = parse_report(REPORT.as_bytes()).unwrap().unwrap();
+    reconciler(&path).reconcile(&trays).unwrap();
+    trays[0].tag_uid = Some("FFFFFFFF00000000".to_string());
+
+    // Act
+    let restarted = reconciler(&path);
+    let report = restarted.reconcile(&trays).unwrap();
+    assert!(restarted.confirm_swap(0).unwrap());
+    let confirmed = reconciler(&path).reconcile(&trays).unwrap();
+
+    // Assert
+    assert!(matches!(
+        report.discrepancies[0].kind,
+        DiscrepancyKind::TagChanged { .. }
+    ));
+    assert!(confirmed.discrepancies.is_empty());
+}
+
+// Reads one MQTT control packet, returning its type nibble and body
+fn read_packet(stream: &mut TcpStream) -> Option<(u8, Vec<u8>)> {
+    let mut header = [0u8; 1];
+    stream.read_exact(&mut header).ok()?;
+
+    let (mut length, mut shift) = (0usize, 0);
+    loop {
+        let mut byte = [0u8; 1];
+        stream.read_exact(&mut byte).ok()?;
+        length |= ((byte[0] & 0x7F) as usize) << shift;
+        if byte[0] & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
+    }
+
+    let mut body = vec![0u8; length];
+    stream.read_exact(&mut body).ok()?;
+    Some((header[0] >> 4, body))
+}
+
+fn publish_packet(topic: &str, payload: &[u8]) -> Vec<u8> {
+    let mut body = (topic.len() as u16).to_be_bytes().to_vec();
+    body.extend_from_slice(topic.as_bytes());
+    body.extend_from_slice(payload);
+
+    let mut packet = vec![0x30];
+    let mut length = body.len();
+    loop {
+        let mut byte = (length % 128) as u8;
+        length /= 128;
+        if length > 0 {
+            byte |= 0x80;
+        }
+        packet.push(byte);
+        if length == 0 {
+            break;
+        }
+    }
+    packet.extend(body);
+    packet
+}
+
+// A minimal MQTT 3.1.1 broker for one client: acknowledges the connection and
+// subscription, then publishes `messages`. Reports topics the client publishes to.
+fn start_broker(messages: Vec<(String, Vec<u8>)>) -> (u16, mpsc::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind broker");
+    let port = listener.local_addr().unwrap().port();
+    let (published, received) = mpsc::channel();
+
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut messages = Some(messages);
+
+        while let Some((kind, body)) = read_packet(&mut stream) {
+            match kind {
+                // CONNECT
+                1 => stream.write_all(&[0x20, 0x02, 0x00, 0x00]).unwrap(),
+                // PUBLISH
+                3 => {
+                    let topic_length = u16::from_be_bytes([body[0], body[1]]) as usize;
+                    let topic = String::from_utf8_lossy(&body[2..2 + topic_length]);
+                    let _ = published.send(topic.to_string());
+                }
+                // SUBSCRIBE
+                8 => {
+                    stream
+                        .write_all(&[0x90, 0x03, body[0], body[1], 0x00])
+                        .unwrap();
+                    for (topic, payload) in messages.take().unwrap_or_default() {
+                        stream.write_all(&publish_packet(&topic, &payload)).unwrap();
+                    }
+                }
+                // PINGREQ
+                12 => stream.write_all(&[0xD0, 0x00]).unwrap(),
+                _ => {}
+            }
+        }
+    });
+
+    (port, received)
+}
+
+#[actix_web::test]
+async fn test_connector_receives_ams_trays_over_mqtt() {
+    // Arrange
+    let report_topic = "device/01S00A000000000/report".to_string();
+    let (port, published) = start_broker(vec![
+        (
+            report_topic.clone(),
+            br#"{"print":{"command":"push_status","mc_percent":42}}"#.to_vec(),
+        ),
+        (
+            "device/other/report".to_string(),
+            REPORT.as_bytes().to_vec(),
+        ),
+        (report_topic, REPORT.as_bytes().to_vec()),
+    ]);
+    let config = BambuMqttConfig {
+        host: "127.0.0.1".to_string(),
+        port,
+        serial: "01S00A000000000".to_string(),
+        access_code: None,
+        tls: false,
+    };
+
+    // Act
+    let mut connector = BambuAmsConnector::connect(config).await.unwrap();
+    let trays = connector.next_trays().await.unwrap();
+
+    // Assert
+    assert_eq!(trays.len(), 4);
+    assert_eq!(trays[3].tray_type.as_deref(), Some("PLA-S"));
+    assert_eq!(
+        published.recv_timeout(Duration::from_secs(5)).unwrap(),
+        "device/01S00A000000000/request"
+    );
+}
+
+#[actix_web::test]
+async fn test_access_code_is_not_sent_without_tls() {
+    // Arrange
+    let config = BambuMqttConfig {
+        host: "127.0.0.1".to_string(),
+        port: 1883,
+        serial: "01S00A000000000".to_string(),
+        access_code: Some("12345678".to_string()),
+        tls: false,
+    };
+
+    // Act
+    let result = BambuAmsConnector::connect(config).await;
+
+    // Assert
+    assert!(matches!(result, Err(FilamentError::InvalidData(_))));
+}
@@ -0,0 +1,475 @@
+use actix_web::http::StatusCode;
+use actix_web::test::{call_service, init_service, read_body_json, TestRequest};
+use actix_web::{web, App};
+use backend::api;
+use backend::domain::audit::{AuditContext, AuditLog, ChangeSource};
+use backend::domain::custom_field::CustomValue;
+use backend::domain::error::FilamentError;
+use backend::domain::filament::{FilamentRepository, FilamentRoll, FilamentRollBuilder};
+use backend::domain::roll_filter::RollFilter;
+use backend::domain::services::filament_service::FilamentService;
+use backend::infrastructure::import::ImportMode;
+use backend::infrastructure::inventory_csv::{export_csv, import_csv, ColumnMapping};
+use backend::infrastructure::repositories::audited::AuditedFilamentRepository;
+use backend::infrastructure::repositories::event_sourced::EventSourcedFilamentRepository;
+use backend::infrastructure::repositories::file::{JsonLinesEventStore, JsonSnapshotStore};
+use backend::infrastructure::repositories::memory::{InMemoryAuditLog, InMemoryFilamentRepository};
+use backend::infrastructure::spoolman::ids::SpoolmanIds;
+use backend::infrastructure::spoolman::import::import_json;
+use backend::infrastructure::spoolman::models::SpoolmanSpool;
+use chrono::NaiveDate;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::thread;
+
+// Helper function to create a test filament with ID
+fn create_test_filament(id: &str) -> FilamentRoll {
+    FilamentRollBuilder::new(
+        id.to_string(),
+        "PA-CF".to_string(),
+        "#202020".to_string(),
+        1.75,
+        500.0,
+        "Test Brand".to_string(),
+    )
+    .with_id(id)
+    .with_tag("carbon-fibre — hardened nozzle")
+    .with_tag("customer-supplied")
+    .with_custom_field("customer", CustomValue::String("Acme".to_string()))
+    .with_custom_field("nozzle", CustomValue::Number(0.6))
+    .with_custom_field("dried", CustomValue::Bool(true))
+    .with_custom_field(
+        "opened",
+        CustomValue::Date(NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()),
+    )
+    .build()
+    .expect("Failed to create test filament")
+}
+
+fn plain_filament(id: &str) -> FilamentRoll {
+    FilamentRoll::with_id(
+        id,
+        "Black",
+        "PLA",
+        "#000000",
+        1.75,
+        1000.0,
+        1000.0,
+        "Test Brand",
+        "",
+    )
+    .expect("Failed to create test filament")
+}
+
+#[test]
+fn test_tags_and_fields_on_roll() {
+    // Arrange
+    let mut roll = create_test_filament("roll-1");
+
+    // Act
+    roll.set_tags(&[
+        " For prototypes only ".to_string(),
+        "for PROTOTYPES only".to_string(),
+        "customer-supplied".to_string(),
+    ])
+    .unwrap();
+    roll.set_custom_field(" nozzle ", CustomValue::Number(0.4))
+        .unwrap();
+
+    // Assert
+    assert_eq!(roll.tags(), ["For prototypes only", "customer-supplied"]);
+    assert!(roll.has_tag("Customer-Supplied"));
+    assert_eq!(roll.custom_field("nozzle"), Some(&CustomValue::Number(0.4)));
+    assert_eq!(
+        roll.remove_custom_field("customer"),
+        Some(CustomValue::String("Acme".to_string()))
+    );
+    assert!(matches!(
+        roll.set_tags(&["a; b".to_string()]),
+        Err(FilamentError::InvalidData(_))
+    ));
+    assert!(matches!(
+        roll.set_tags(&[" ".to_string()]),
+        Err(FilamentError::InvalidData(_))
+    ));
+    assert!(matches!(
+        roll.set_custom_field("", CustomValue::Bool(false)),
+        Err(FilamentError::InvalidData(_))
+    ));
+}
+
+#[test]
+fn test_custom_values() {
+    // Assert
+    assert_eq!(
+        serde_json::to_value(CustomValue::Number(0.6)).unwrap(),
+        json!({ "type": "number", "value": 0.6 })
+    );
+    assert_eq!(
+        CustomValue::parse("date", "2024-05-01")
+            .unwrap()
+            .to_string(),
+        "2024-05-01"
+    );
+    assert_eq!(
+        CustomValue::parse("bool", "Yes").unwrap(),
+        CustomValue::Bool(true)
+    );
+    assert!(
+        CustomValue::String("Acme ".to_string()).matches(&CustomValue::String("acme".to_string()))
+    );
+    assert!(!CustomValue::String("1".to_string()).matches(&CustomValue::Number(1.0)));
+    for (kind, text) in [("number", "0,6"), ("bool", "maybe"), ("date", "01/05/2024")] {
+        assert!(matches!(
+            CustomValue::parse(kind, text),
+            Err(FilamentError::InvalidData(_))
+        ));
+    }
+    assert!(matches!(
+        CustomValue::parse("colour", "red"),
+        Err(FilamentError::InvalidData(_))
+    ));
+}
+
+#[test]
+fn test_filter_by_tags_and_fields() {
+    // Arrange
+    let repository = Arc::new(InMemoryFilamentRepository::new());
+    repository.save(&create_test_filament("tagged")).unwrap();
+    repository.save(&plain_filament("plain")).unwrap();
+    let service = FilamentService::new(repository);
+    let by_tag = RollFilter {
+        tags: vec!["CUSTOMER-SUPPLIED".to_string()],
+        ..RollFilter::default()
+    };
+    let by_field = RollFilter {
+        custom_fields: BTreeMap::from([(
+            "customer".to_string(),
+            CustomValue::String("acme".to_string()),
+        )]),
+        ..RollFilter::default()
+    };
+    let wrong_type = RollFilter {
+        custom_fields: BTreeMap::from([(
+            "dried".to_string(),
+            CustomValue::String("true".to_string()),
+        )]),
+        ..RollFilter::default()
+    };
+
+    // Act
+    let tagged = service.find_rolls(&by_tag).unwrap();
+    let for_customer = service.find_rolls(&by_field).unwrap();
+    let mismatched = service.find_rolls(&wrong_type).unwrap();
+
+    // Assert
+    assert_eq!(tagged.len(), 1);
+    assert_eq!(tagged[0].id(), "tagged");
+    assert_eq!(for_customer, tagged);
+    assert!(mismatched.is_empty());
+    assert_eq!(service.find_rolls(&RollFilter::default()).unwrap().len(), 2);
+}
+
+#[test]
+fn test_persisted_by_event_sourced_repository() {
+    // Arrange
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let events_path = dir.path().join("events.jsonl");
+    let snapshot_path = dir.path().join("snapshot.json");
+    let open = || {
+        EventSourcedFilamentRepository::open(
+            JsonLinesEventStore::open(&events_path).unwrap(),
+            JsonSnapshotStore::new(&snapshot_path),
+        )
+        .expect("Failed to open repository")
+    };
+    let mut roll = create_test_filament("es-1");
+
+    // Act
+    {
+        let repository = open();
+        repository.save(&roll).unwrap();
+        roll.remove_custom_field("dried");
+        repository.save(&roll).unwrap();
+    }
+    let reopened = open();
+
+    // Assert
+    assert_eq!(reopened.find_by_id("es-1").unwrap(), roll);
+}
+
+#[test]
+fn test_audit_lists_custom_field_changes() {
+    // Arrange
+    let repository = AuditedFilamentRepository::new(
+        InMemoryFilamentRepository::new(),
+        InMemoryAuditLog::new(),
+        AuditContext::new("api-user", ChangeSource::Api),
+    );
+    let mut roll = create_test_filament("audit-1");
+    repository.save(&roll).unwrap();
+
+    // Act
+    roll.remove_custom_field("dried");
+    roll.set_custom_field("nozzle", CustomValue::String("0.6".to_string()))
+        .unwrap();
+    roll.set_tags(&["customer-supplied".to_string()]).unwrap();
+    repository.save(&roll).unwrap();
+
+    // Assert
+    let entries = repository.audit_log().find_by_roll("audit-1").unwrap();
+    let changes: Vec<(&str, Option<&str>, Option<&str>)> = entries[1]
+        .changes()
+        .iter()
+        .map(|c| (c.field.as_str(), c.before.as_deref(), c.after.as_deref()))
+        .collect();
+    assert_eq!(
+        changes,
+        vec![
+            (
+                "tags",
+                Some("carbon-fibre — hardened nozzle; customer-supplied"),
+                Some("customer-supplied")
+            ),
+            (
+                "custom_fields.nozzle",
+                Some("0.6 (number)"),
+                Some("0.6 (string)")
+            ),
+            ("custom_fields.dried", Some("true (bool)"), None),
+        ]
+    );
+}
+
+#[test]
+fn test_csv_round_trip_keeps_types() {
+    // Arrange
+    let source = InMemoryFilamentRepository::new();
+    source.save(&create_test_filament("csv-1")).unwrap();
+    source.save(&plain_filament("csv-2")).unwrap();
+    let mut output = Vec::new();
+    export_csv(&source, &mut output).unwrap();
+    let target = InMemoryFilamentRepository::new();
+
+    // Act
+    let report = import_csv(
+        &target,
+        output.as_slice(),
+        &ColumnMapping::new(),
+        ImportMode::Apply,
+    )
+    .unwrap();
+
+    // Assert
+    let csv = String::from_utf8(output).unwrap();
+    let header = csv.lines().next().unwrap();
+    assert!(header.ends_with(
+        ",tags,percentage_remaining,custom.customer:string,custom.dried:bool,custom.nozzle:number,custom.opened:date"
+    ));
+    assert!(csv
+        .lines()
+        .nth(1)
+        .unwrap()
+        .contains("carbon-fibre — hardened nozzle; customer-supplied"));
+    assert_eq!(report.imported.len(), 2);
+    assert_eq!(
+        target.find_by_id("csv-1").unwrap(),
+        source.find_by_id("csv-1").unwrap()
+    );
+    let plain = target.find_by_id("csv-2").unwrap();
+    assert!(plain.tags().is_empty());
+    assert!(plain.custom_fields().is_empty());
+}
+
+#[test]
+fn test_csv_custom_columns_update_existing_rolls() {
+    // Arrange
+    let repository = InMemoryFilamentRepository::new();
+    repository.save(&create_test_filament("csv-1")).unwrap();
+    let csv = "id,tags,custom.nozzle:number,custom.bin,custom.dried:bool\n\
+               csv-1,,0.8,Top shelf,\n\
+               csv-1-bad,,wide,,\n";
+
+    // Act
+    let report = import_csv(
+        &repository,
+        csv.as_bytes(),
+        &ColumnMapping::new(),
+        ImportMode::Apply,
+    )
+    .unwrap();
+
+    // Assert
+    let roll = repository.find_by_id("csv-1").unwrap();
+    assert_eq!(roll.custom_field("nozzle"), Some(&CustomValue::Number(0.8)));
+    assert_eq!(
+        roll.custom_field("bin"),
+        Some(&CustomValue::String("Top shelf".to_string()))
+    );
+    // Blank cells keep what the roll had
+    assert_eq!(roll.custom_field("dried"), Some(&CustomValue::Bool(true)));
+    assert_eq!(roll.tags().len(), 2);
+    assert_eq!(report.failed.len(), 1);
+    assert!(report.failed[0].message.contains("custom.nozzle:number"));
+}
+
+#[test]
+fn test_spoolman_extra_fields() {
+    // Arrange
+    let roll = create_test_filament("roll-1");
+    let ids = SpoolmanIds::new();
+
+    // Act
+    let spool = SpoolmanSpool::from_roll(&roll, &ids).unwrap();
+    let mut exported = serde_json::to_value(&spool).unwrap();
+    exported["id"] = json!(12);
+    let repository = InMemoryFilamentRepository::new();
+    let report = import_json(
+        &repository,
+        &json!([exported]).to_string(),
+        ImportMode::Apply,
+    )
+    .unwrap();
+
+    // Assert
+    assert_eq!(
+        spool.extra["tags"],
+        r#"["carbon-fibre — hardened nozzle","customer-supplied"]"#
+    );
+    assert_eq!(spool.extra["nozzle"], "0.6");
+    assert_eq!(spool.extra["customer"], "\"Acme\"");
+    assert_eq!(spool.extra["opened"], "\"2024-05-01\"");
+    assert_eq!(report.imported.len(), 1);
+    let imported = repository.find_by_id("spoolman-12").unwrap();
+    assert_eq!(imported.tags(), roll.tags());
+    assert_eq!(
+        imported.custom_field("dried"),
+        Some(&CustomValue::Bool(true))
+    );
+    // Spoolman has no date type, so dates come back as text
+    assert_eq!(
+        imported.custom_field("opened"),
+        Some(&CustomValue::String("2024-05-01".to_string()))
+    );
+}
+
+#[actix_web::test]
+async fn test_rolls_api() {
+    // Arrange
+    let repository = Arc::new(InMemoryFilamentRepository::new());
+    repository.save(&plain_filament("roll-1")).unwrap();
+    repository.save(&plain_filament("roll-2")).unwrap();
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(FilamentService::new(repository)))
+            .configure(api::rolls::configure),
+    )
+    .await;
+
+    // Act
+    let response = call_service(
+        &app,
+        TestRequest::put()
+            .uri("/api/rolls/roll-1/tags")
+            .set_json(json!(["for prototypes only"]))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = call_service(
+        &app,
+        TestRequest::put()
+            .uri("/api/rolls/roll-1/fields/spool%20cost")
+            .set_json(json!({ "type": "number", "value": 21.5 }))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let roll: Value = read_body_json(response).await;
+
+    let response = call_service(
+        &app,
+        TestRequest::post()
+            .uri("/api/rolls/search")
+            .set_json(json!({ "tags": ["For Prototypes Only"] }))
+            .to_request(),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let found: Vec<Value> = read_body_json(response).await;
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0]["id"], "roll-1");
+    assert_eq!(roll["tags"], json!(["for prototypes only"]));
+    assert_eq!(
+        roll["custom_fields"]["spool cost"],
+        json!({ "type": "number", "value": 21.5 })
+    );
+
+    let response = call_service(
+        &app,
+        TestRequest::delete()
+            .uri("/api/rolls/roll-1/fields/spool%20cost")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = call_service(
+        &app,
+        TestRequest::delete()
+            .uri("/api/rolls/roll-1/fields/spool%20cost")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let response = call_service(
+        &app,
+        TestRequest::put()
+            .uri("/api/rolls/roll-2/fields/dried")
+            .set_json(json!({ "type": "bool", "value": "yes" }))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn test_tag_and_field_edits_never_undo_concurrent_consumption() {
+    // Arrange
+    let repository = Arc::new(InMemoryFilamentRepository::new());
+    repository.save(&plain_filament("busy")).unwrap();
+    let service = FilamentService::new(repository);
+
+    // Act
+    let workers: Vec<_> = (0..60)
+        .map(|i| {
+            let worker = service.clone();
+            thread::spawn(move || match i % 4 {
+                0 | 2 => worker.consume("busy", 10.0).map(|_| ()),
+                1 => worker
+                    .set_tags("busy", &[format!("batch-{}", i)])
+                    .map(|_| ()),
+                _ => worker
+                    .set_custom_field("busy", "shelf", CustomValue::Number(i as f64))
+                    .map(|_| ()),
+            })
+        })
+        .collect();
+    for worker in workers {
+        worker
+            .join()
+            .expect("Worker thread panicked")
+            .expect("Update failed");
+    }
+    service
+        .remove_custom_field("busy", "shelf")
+        .expect("Failed to remove field");
+
+    // Assert
+    let roll = service.get_roll("busy").unwrap();
+    assert_eq!(roll.remaining_weight(), 700.0);
+    assert!(roll.custom_fields().is_empty());
+    assert_eq!(roll.tags().len(), 1);
+}
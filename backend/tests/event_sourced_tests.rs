@@ -0,0 +1,298 @@
+use backend::domain::error::FilamentError;
+use backend::domain::events::{EventStore, FilamentEvent, SnapshotStore};
+use backend::domain::filament::{FilamentRepository, FilamentRoll};
+use backend::domain::projections::LowInventoryProjection;
+use backend::infrastructure::repositories::event_sourced::EventSourcedFilamentRepository;
+use backend::infrastructure::repositories::file::{JsonLinesEventStore, JsonSnapshotStore};
+use backend::infrastructure::repositories::memory::{InMemoryEventStore, InMemorySnapshotStore};
+
+// Helper function to create a test filament with ID
+fn create_test_filament(id: &str, remaining_weight: f32) -> FilamentRoll {
+    FilamentRoll::with_id(
+        id,
+        "Test Filament",
+        "PLA",
+        "#000000",
+        1.75,
+        1000.0,
+        remaining_weight,
+        "Test Brand",
+        "Bin 1",
+    )
+    .expect("Failed to create test filament")
+}
+
+fn open_in_memory(
+    events: &InMemoryEventStore,
+    snapshots: &InMemorySnapshotStore,
+) -> EventSourcedFilamentRepository<InMemoryEventStore, InMemorySnapshotStore> {
+    EventSourcedFilamentRepository::open(events.clone(), snapshots.clone())
+        .expect("Failed to open repository")
+}
+
+#[test]
+fn test_changes_are_recorded_as_domain_events() {
+    // Arrange
+    let events = InMemoryEventStore::new();
+    let repository = open_in_memory(&events, &InMemorySnapshotStore::new());
+    let mut filament = create_test_filament("es-1", 1000.0);
+    repository.save(&filament).expect("Failed to save filament");
+
+    // Act
+    repository
+        .update_remaining_weight("es-1", 800.0)
+        .expect("Failed to update weight");
+    filament = repository.find_by_id("es-1").unwrap();
+    filament.move_to("Dry Box");
+    repository.save(&filament).expect("Failed to save filament");
+    repository.archive("es-1").expect("Failed to archive roll");
+
+    // Assert
+    let recorded = events.load_since(0).expect("Failed to load events");
+    assert_eq!(recorded.len(), 4);
+    assert!(matches!(
+        recorded[0].event(),
+        FilamentEvent::RollRegistered { .. }
+    ));
+    assert!(matches!(
+        recorded[1].event(),
+        FilamentEvent::FilamentConsumed { grams, .. } if *grams == 200.0
+    ));
+    assert!(matches!(
+        recorded[2].event(),
+        FilamentEvent::RollMoved { storage_location, .. } if storage_location == "Dry Box"
+    ));
+    assert!(matches!(
+        recorded[3].event(),
+        FilamentEvent::RollArchived { .. }
+    ));
+}
+
+#[test]
+fn test_state_is_rebuilt_from_events_on_open() {
+    // Arrange
+    let events = InMemoryEventStore::new();
+    let snapshots = InMemorySnapshotStore::new();
+    {
+        let repository = open_in_memory(&events, &snapshots);
+        repository
+            .save(&create_test_filament("es-2", 1000.0))
+            .expect("Failed to save filament");
+        repository
+            .update_remaining_weight("es-2", 420.0)
+            .expect("Failed to update weight");
+    }
+
+    // Act
+    let reopened = open_in_memory(&events, &snapshots);
+
+    // Assert
+    let filament = reopened
+        .find_by_id("es-2")
+        .expect("Failed to find filament");
+    assert_eq!(filament.remaining_weight(), 420.0);
+}
+
+#[test]
+fn test_archived_rolls_are_hidden_from_queries() {
+    // Arrange
+    let repository = open_in_memory(&InMemoryEventStore::new(), &InMemorySnapshotStore::new());
+    repository
+        .save(&create_test_filament("es-3", 1000.0))
+        .expect("Failed to save filament");
+
+    // Act
+    repository.archive("es-3").expect("Failed to archive roll");
+
+    // Assert
+    assert!(matches!(
+        repository.find_by_id("es-3"),
+        Err(FilamentError::NotFound(_))
+    ));
+    assert!(repository.find_all().unwrap().is_empty());
+    assert_eq!(repository.find_archived().unwrap().len(), 1);
+}
+
+#[test]
+fn test_invalid_update_records_no_event() {
+    // Arrange
+    let events = InMemoryEventStore::new();
+    let repository = open_in_memory(&events, &InMemorySnapshotStore::new());
+    repository
+        .save(&create_test_filament("es-4", 100.0))
+        .expect("Failed to save filament");
+
+    // Act
+    let result = repository.update_remaining_weight("es-4", 1500.0);
+
+    // Assert
+    assert!(matches!(result, Err(FilamentError::InvalidData(_))));
+    assert_eq!(events.load_since(0).unwrap().len(), 1);
+}
+
+#[test]
+fn test_snapshot_interval_writes_snapshots() {
+    // Arrange
+    let snapshots = InMemorySnapshotStore::new();
+    let repository =
+        open_in_memory(&InMemoryEventStore::new(), &snapshots).with_snapshot_interval(2);
+
+    // Act
+    repository
+        .save(&create_test_filament("es-5", 1000.0))
+        .expect("Failed to save filament");
+    repository
+        .update_remaining_weight("es-5", 900.0)
+        .expect("Failed to update weight");
+
+    // Assert
+    let snapshot = snapshots
+        .load_latest()
+        .expect("Failed to load snapshot")
+        .expect("Expected a snapshot");
+    assert_eq!(snapshot.version, 2);
+    assert_eq!(snapshot.rolls[0].remaining_weight(), 900.0);
+}
+
+#[test]
+fn test_rebuild_low_inventory_projection() {
+    // Arrange
+    let repository = open_in_memory(&InMemoryEventStore::new(), &InMemorySnapshotStore::new());
+    repository
+        .save(&create_test_filament("es-6", 1000.0))
+        .expect("Failed to save filament");
+    repository
+        .save(&create_test_filament("es-7", 1000.0))
+        .expect("Failed to save filament");
+    repository
+        .save(&create_test_filament("es-8", 100.0))
+        .expect("Failed to save filament");
+    repository
+        .update_remaining_weight("es-6", 50.0)
+        .expect("Failed to update weight");
+    repository.archive("es-8").expect("Failed to archive roll");
+
+    // Act
+    let mut projection = LowInventoryProjection::new();
+    repository
+        .rebuild(&mut projection)
+        .expect("Failed to rebuild projection");
+
+    // Assert
+    assert_eq!(projection.roll_ids(), vec!["es-6".to_string()]);
+}
+
+#[test]
+fn test_file_stores_survive_restart_from_snapshot() {
+    // Arrange
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let events_path = dir.path().join("events.jsonl");
+    let snapshot_path = dir.path().join("snapshot.json");
+    {
+        let repository = EventSourcedFilamentRepository::open(
+            JsonLinesEventStore::open(&events_path).unwrap(),
+            JsonSnapshotStore::new(&snapshot_path),
+        )
+        .expect("Failed to open repository");
+        repository
+            .save(&create_test_filament("es-9", 1000.0))
+            .expect("Failed to save filament");
+        repository.snapshot().expect("Failed to snapshot");
+        repository
+            .update_remaining_weight("es-9", 700.0)
+            .expect("Failed to update weight");
+    }
+
+    // Act
+    let reopened = EventSourcedFilamentRepository::open(
+        JsonLinesEventStore::open(&events_path).unwrap(),
+        JsonSnapshotStore::new(&snapshot_path),
+    )
+    .expect("Failed to reopen repository");
+
+    // Assert
+    let filament = reopened
+        .find_by_id("es-9")
+        .expect("Failed to find filament");
+    assert_eq!(filament.remaining_weight(), 700.0);
+    reopened
+        .update_remaining_weight("es-9", 600.0)
+        .expect("Failed to update weight");
+    let events = JsonLinesEventStore::open(&events_path).unwrap();
+    assert_eq!(events.load_since(0).unwrap().last().unwrap().sequence(), 3);
+}
+
+#[test]
+fn test_torn_last_line_is_dropped_on_open() {
+    // Arrange
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let events_path = dir.path().join("events.jsonl");
+    {
+        let repository = EventSourcedFilamentRepository::open(
+            JsonLinesEventStore::open(&events_path).unwrap(),
+            InMemorySnapshotStore::new(),
+        )
+        .expect("Failed to open repository");
+        repository
+            .save(&create_test_filament("es-10", 1000.0))
+            .expect("Failed to save filament");
+    }
+    // A crash part-way through the next append
+    let mut log = std::fs::read_to_string(&events_path).unwrap();
+    log.push_str("{\"sequence\":2,\"event\":{\"type\":\"filament_con");
+    std::fs::write(&events_path, log).unwrap();
+
+    // Act
+    let events = JsonLinesEventStore::open(&events_path).expect("Failed to open torn log");
+    let recorded = events
+        .append(&[FilamentEvent::RollArchived {
+            roll_id: "es-10".to_string(),
+        }])
+        .expect("Failed to append after recovery");
+
+    // Assert
+    assert_eq!(recorded[0].sequence(), 2);
+    assert_eq!(events.load_since(0).unwrap().len(), 2);
+}
+
+#[test]
+fn test_registering_archived_id_restores_the_roll() {
+    // Arrange
+    let repository = open_in_memory(&InMemoryEventStore::new(), &InMemorySnapshotStore::new());
+    repository
+        .save(&create_test_filament("es-11", 1000.0))
+        .expect("Failed to save filament");
+    repository.archive("es-11").expect("Failed to archive roll");
+
+    // Act
+    repository
+        .save(&create_test_filament("es-11", 500.0))
+        .expect("Failed to save filament");
+
+    // Assert
+    assert_eq!(
+        repository.find_by_id("es-11").unwrap().remaining_weight(),
+        500.0
+    );
+    assert!(repository.find_archived().unwrap().is_empty());
+}
+
+#[test]
+fn test_failed_snapshot_does_not_fail_a_recorded_write() {
+    // Arrange
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let events = InMemoryEventStore::new();
+    // Nowhere to write the snapshot
+    let snapshots = JsonSnapshotStore::new(dir.path().join("missing").join("snapshot.json"));
+    let repository = EventSourcedFilamentRepository::open(events.clone(), snapshots)
+        .expect("Failed to open repository")
+        .with_snapshot_interval(1);
+
+    // Act
+    let result = repository.save(&create_test_filament("es-12", 1000.0));
+
+    // Assert
+    assert!(result.is_ok());
+    assert_eq!(events.load_since(0).unwrap().len(), 1);
+    assert!(repository.find_by_id("es-12").is_ok());
+}
@@ -0,0 +1,388 @@
+use actix_web::test::{call_service, init_service, read_body_json, TestRequest};
+use actix_web::{web, App};
+use backend::api;
+use backend::domain::error::FilamentError;
+use backend::domain::feasibility::{Allocation, FilamentRequirement, Fulfilment};
+use backend::domain::filament::{FilamentRepository, FilamentRoll};
+use backend::domain::roll_filter::RollFilter;
+use backend::domain::services::filament_service::FilamentService;
+use backend::infrastructure::repositories::memory::{
+    InMemoryFilamentRepository, InMemoryReservationRepository,
+};
+use backend::infrastructure::slicer::{parse_3mf, parse_gcode};
+use chrono::{Duration, Utc};
+use serde_json::{json, Value};
+use std::io::{Cursor, Write};
+use std::sync::Arc;
+use zip::write::SimpleFileOptions;
+
+const GCODE: &str = include_str!("fixtures/two_colour.gcode");
+
+// Helper function to create a test filament with ID
+fn create_test_filament(id: &str, material: &str, color: &str, remaining: f32) -> FilamentRoll {
+    FilamentRoll::with_id(
+        id,
+        id,
+        material,
+        color,
+        1.75,
+        1000.0,
+        remaining,
+        "Test Brand",
+        "Bin 1",
+    )
+    .expect("Failed to create test filament")
+}
+
+fn setup() -> FilamentService {
+    let rolls = Arc::new(InMemoryFilamentRepository::new());
+    for roll in [
+        create_test_filament("orange-big", "PLA", "#FF8000", 800.0),
+        create_test_filament("orange-small", "PLA", "#ff8000", 150.0),
+        create_test_filament("black-1", "PLA", "#000000", 60.0),
+        create_test_filament("black-2", "PLA", "#000000", 50.0),
+        create_test_filament("petg", "PETG", "#FFFFFF", 40.0),
+    ] {
+        rolls.save(&roll).expect("Failed to save filament");
+    }
+    FilamentService::new(rolls).with_reservations(Arc::new(InMemoryReservationRepository::new()))
+}
+
+fn requirement(extruder: u32, material: &str, color: &str, grams: f32) -> FilamentRequirement {
+    FilamentRequirement {
+        extruder,
+        material: material.to_string(),
+        color: Some(color.to_string()),
+        diameter: Some(1.75),
+        grams,
+    }
+}
+
+fn allocation(roll_id: &str, grams: f32) -> Allocation {
+    Allocation {
+        roll_id: roll_id.to_string(),
+        grams,
+    }
+}
+
+#[test]
+fn test_single_roll_prefers_smallest_sufficient_roll() {
+    // Arrange
+    let service = setup();
+
+    // Act
+    let report = service
+        .check_feasibility(&[requirement(0, "pla", "#FF8000", 100.0)])
+        .unwrap();
+
+    // Assert
+    assert!(report.is_feasible());
+    let check = &report.checks[0];
+    let candidates: Vec<&str> = check
+        .candidates
+        .iter()
+        .map(|c| c.roll_id.as_str())
+        .collect();
+    assert_eq!(candidates, vec!["orange-big", "orange-small"]);
+    assert_eq!(
+        check.fulfilment,
+        Fulfilment::SingleRoll {
+            allocation: allocation("orange-small", 100.0)
+        }
+    );
+}
+
+#[test]
+fn test_swap_when_no_single_roll_suffices() {
+    // Arrange
+    let service = setup();
+
+    // Act
+    let report = service
+        .check_feasibility(&[requirement(0, "PLA", "#000000", 100.0)])
+        .unwrap();
+
+    // Assert
+    assert!(report.is_feasible());
+    assert_eq!(
+        report.checks[0].fulfilment,
+        Fulfilment::Swap {
+            allocations: vec![allocation("black-1", 60.0), allocation("black-2", 40.0)]
+        }
+    );
+}
+
+#[test]
+fn test_shortfall_reports_missing_grams() {
+    // Arrange
+    let service = setup();
+
+    // Act
+    let report = service
+        .check_feasibility(&[
+            requirement(0, "PETG", "#FFFFFF", 65.0),
+            requirement(1, "ABS", "#FFFFFF", 20.0),
+        ])
+        .unwrap();
+
+    // Assert
+    assert!(!report.is_feasible());
+    assert_eq!(report.shortfall(), 45.0);
+    assert_eq!(
+        report.checks[0].fulfilment,
+        Fulfilment::Shortfall {
+            missing_grams: 25.0,
+            allocations: vec![allocation("petg", 40.0)]
+        }
+    );
+    assert!(report.checks[1].candidates.is_empty());
+}
+
+#[test]
+fn test_extruders_do_not_share_the_same_grams() {
+    // Arrange
+    let service = setup();
+
+    // Act
+    let report = service
+        .check_feasibility(&[
+            requirement(0, "PLA", "#000000", 60.0),
+            requirement(1, "PLA", "#000000", 60.0),
+        ])
+        .unwrap();
+
+    // Assert
+    assert_eq!(report.shortfall(), 10.0);
+    assert_eq!(report.checks[1].candidates[0].available_weight, 50.0);
+}
+
+#[test]
+fn test_reserved_filament_is_not_available() {
+    // Arrange
+    let service = setup();
+    service
+        .reserve(
+            "orange-small",
+            100.0,
+            "queued.gcode",
+            Utc::now() + Duration::hours(4),
+        )
+        .unwrap();
+
+    // Act
+    let report = service
+        .check_feasibility(&[requirement(0, "PLA", "#FF8000", 100.0)])
+        .unwrap();
+
+    // Assert
+    assert_eq!(
+        report.checks[0].fulfilment,
+        Fulfilment::SingleRoll {
+            allocation: allocation("orange-big", 100.0)
+        }
+    );
+}
+
+#[test]
+fn test_negative_grams_are_rejected() {
+    // Arrange
+    let service = setup();
+
+    // Act
+    let result = service.check_feasibility(&[requirement(2, "PLA", "#000000", -1.0)]);
+
+    // Assert
+    assert!(matches!(result, Err(FilamentError::InvalidData(_))));
+}
+
+#[test]
+fn test_non_ascii_colours_match_nothing() {
+    // Arrange
+    let service = setup();
+    let filter = RollFilter {
+        color: Some("#aaaaaéa".to_string()),
+        ..RollFilter::default()
+    };
+    let with_alpha = RollFilter {
+        color: Some("#ff8000FF".to_string()),
+        ..RollFilter::default()
+    };
+
+    // Act
+    let matched = service.find_rolls(&filter).unwrap();
+    let report = service
+        .check_feasibility(&[requirement(0, "PLA", "#aaaaaéa", 10.0)])
+        .unwrap();
+
+    // Assert
+    assert!(matched.is_empty());
+    assert!(matches!(
+        report.checks[0].fulfilment,
+        Fulfilment::Shortfall { .. }
+    ));
+    assert_eq!(service.find_rolls(&with_alpha).unwrap().len(), 2);
+}
+
+#[test]
+fn test_parse_gcode_reads_used_extruders() {
+    // Act
+    let requirements = parse_gcode(GCODE.as_bytes()).unwrap();
+
+    // Assert
+    assert_eq!(
+        requirements,
+        vec![
+            requirement(0, "PLA", "#FF8000", 12.0),
+            requirement(1, "PLA", "#000000", 3.55),
+        ]
+    );
+}
+
+#[test]
+fn test_parse_gcode_converts_length_without_weight() {
+    // Arrange
+    let gcode = "; filament used [mm] = 1000\n; filament_type = PLA\n";
+
+    // Act
+    let requirements = parse_gcode(gcode.as_bytes()).unwrap();
+
+    // Assert
+    assert_eq!(requirements.len(), 1);
+    assert!((requirements[0].grams - 2.98).abs() < 0.01);
+    assert_eq!(requirements[0].color, None);
+}
+
+#[test]
+fn test_parse_gcode_without_summary_fails() {
+    // Act
+    let result = parse_gcode("G28\nG1 X10\n".as_bytes());
+
+    // Assert
+    assert!(matches!(result, Err(FilamentError::InvalidData(_))));
+}
+
+fn build_3mf(files: &[(&str, &str)]) -> Vec<u8> {
+    let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, contents) in files {
+        writer
+            .start_file(*name, SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(contents.as_bytes()).unwrap();
+    }
+    writer.finish().unwrap().into_inner()
+}
+
+#[test]
+fn test_parse_3mf_adds_up_plates() {
+    // Arrange
+    let slice_info = r##"<?xml version="1.0" encoding="UTF-8"?>
+<config>
+  <plate>
+    <metadata key="index" value="1"/>
+    <filament id="1" tray_info_idx="GFA00" type="PLA" color="#FF8000" used_m="3.37" used_g="10.05" />
+    <filament id="3" tray_info_idx="GFG99" type="PETG" color="#FFFFFF" used_m="0.50" used_g="1.50" />
+  </plate>
+  <plate>
+    <metadata key="index" value="2"/>
+    <filament id="1" tray_info_idx="GFA00" type="PLA" color="#FF8000" used_m="2.00" used_g="5.95" />
+  </plate>
+</config>"##;
+    let archive = build_3mf(&[
+        ("3D/3dmodel.model", "<model/>"),
+        ("Metadata/slice_info.config", slice_info),
+    ]);
+
+    // Act
+    let requirements = parse_3mf(Cursor::new(archive)).unwrap();
+
+    // Assert
+    let summary: Vec<(u32, &str, f32)> = requirements
+        .iter()
+        .map(|r| (r.extruder, r.material.as_str(), r.grams))
+        .collect();
+    assert_eq!(summary, vec![(0, "PLA", 16.0), (2, "PETG", 1.5)]);
+}
+
+#[test]
+fn test_parse_3mf_rejects_oversized_slice_info() {
+    // Arrange
+    // Compresses to a few kilobytes but expands to 2 MiB
+    let slice_info = format!("<config>{}</config>", " ".repeat(2 * 1024 * 1024));
+    let archive = build_3mf(&[("Metadata/slice_info.config", &slice_info)]);
+
+    // Act
+    let result = parse_3mf(Cursor::new(archive));
+
+    // Assert
+    match result {
+        Err(FilamentError::InvalidData(message)) => assert!(message.contains("slice_info")),
+        other => panic!("Expected an oversized entry error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_3mf_falls_back_to_embedded_gcode() {
+    // Arrange
+    let archive = build_3mf(&[("Metadata/plate_1.gcode", GCODE)]);
+
+    // Act
+    let requirements = parse_3mf(Cursor::new(archive)).unwrap();
+
+    // Assert
+    assert_eq!(requirements.len(), 2);
+}
+
+#[actix_web::test]
+async fn test_upload_endpoint_accepts_gcode_and_3mf() {
+    // Arrange
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(setup()))
+            .configure(api::feasibility::configure),
+    )
+    .await;
+    let archive = build_3mf(&[("Metadata/plate_1.gcode", GCODE)]);
+
+    for body in [GCODE.as_bytes().to_vec(), archive] {
+        // Act
+        let request = TestRequest::post()
+            .uri("/api/feasibility/upload")
+            .set_payload(body)
+            .to_request();
+        let response = call_service(&app, request).await;
+
+        // Assert
+        assert!(response.status().is_success());
+        let report: Value = read_body_json(response).await;
+        assert_eq!(report["checks"].as_array().unwrap().len(), 2);
+        assert_eq!(report["checks"][0]["fulfilment"]["kind"], "single_roll");
+    }
+}
+
+#[actix_web::test]
+async fn test_check_endpoint_reports_shortfall() {
+    // Arrange
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(setup()))
+            .configure(api::feasibility::configure),
+    )
+    .await;
+
+    // Act
+    let request = TestRequest::post()
+        .uri("/api/feasibility")
+        .set_json(json!({
+            "requirements": [
+                {"extruder": 0, "material": "PETG", "color": null, "diameter": null, "grams": 100.0}
+            ]
+        }))
+        .to_request();
+    let response = call_service(&app, request).await;
+
+    // Assert
+    assert!(response.status().is_success());
+    let report: Value = read_body_json(response).await;
+    assert_eq!(report["checks"][0]["fulfilment"]["kind"], "shortfall");
+    assert_eq!(report["checks"][0]["fulfilment"]["missing_grams"], 60.0);
+}
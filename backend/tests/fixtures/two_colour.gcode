@@ -0,0 +1,18 @@
+; generated by PrusaSlicer 2.7.1+linux-x64-GTK3 on 2026-10-12 at 18:04:11 UTC
+M73 P0 R42
+G28 ; home all
+G1 X10 Y10 Z0.2 E0.5 F1200
+M107
+; filament used [mm] = 4021.33, 1187.90, 0.00
+; filament used [cm3] = 9.67, 2.86, 0.00
+; filament used [g] = 12.00, 3.55, 0.00
+; filament cost = 0.30, 0.09, 0.00
+; total filament used [g] = 15.55
+; estimated printing time (normal mode) = 42m 13s
+
+; prusaslicer_config = begin
+; filament_colour = #FF8000;#000000;#FFFFFF
+; filament_density = 1.24;1.24;1.27
+; filament_diameter = 1.75,1.75,1.75
+; filament_type = PLA;PLA;PETG
+; prusaslicer_config = end
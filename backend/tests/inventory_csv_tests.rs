@@ -0,0 +1,329 @@
+use actix_web::http::StatusCode;
+use actix_web::test::{call_service, init_service, read_body, read_body_json, TestRequest};
+use actix_web::{web, App};
+use backend::api;
+use backend::domain::error::FilamentError;
+use backend::domain::filament::{FilamentRepository, FilamentRoll};
+use backend::infrastructure::import::ImportMode;
+use backend::infrastructure::inventory_csv::{
+    export_csv, import_csv, ColumnMapping, InventoryField,
+};
+use backend::infrastructure::repositories::memory::InMemoryFilamentRepository;
+use serde_json::Value;
+use std::sync::Arc;
+
+// Helper function to create a test filament with ID
+fn create_test_filament(id: &str, remaining_weight: f32) -> FilamentRoll {
+    FilamentRoll::with_id(
+        id,
+        "Basic Black",
+        "PLA",
+        "#000000",
+        1.75,
+        1000.0,
+        remaining_weight,
+        "Test Brand",
+        "Bin 1",
+    )
+    .expect("Failed to create test filament")
+}
+
+#[test]
+fn test_export_includes_every_field_and_percentage() {
+    // Arrange
+    let repository = InMemoryFilamentRepository::new();
+    repository
+        .save(&create_test_filament("csv-2", 250.0))
+        .expect("Failed to save filament");
+    repository
+        .save(&create_test_filament("csv-1", 1000.0))
+        .expect("Failed to save filament");
+
+    // Act
+    let mut output = Vec::new();
+    export_csv(&repository, &mut output).expect("Failed to export");
+    let csv = String::from_utf8(output).unwrap();
+
+    // Assert
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        lines[0],
+        "id,name,material,color,diameter,weight,remaining_weight,manufacturer,storage_location,lot_number,manufactured_on,received_on,tags,percentage_remaining"
+    );
+    assert_eq!(
+        lines[1],
+        "csv-1,Basic Black,PLA,#000000,1.75,1000,1000,Test Brand,Bin 1,,,,,100"
+    );
+    assert_eq!(
+        lines[2],
+        "csv-2,Basic Black,PLA,#000000,1.75,1000,250,Test Brand,Bin 1,,,,,25"
+    );
+}
+
+#[test]
+fn test_export_then_import_round_trips() {
+    // Arrange
+    let source = InMemoryFilamentRepository::new();
+    source
+        .save(&create_test_filament("csv-3", 420.0))
+        .expect("Failed to save filament");
+    let mut output = Vec::new();
+    export_csv(&source, &mut output).expect("Failed to export");
+    let target = InMemoryFilamentRepository::new();
+
+    // Act
+    let report = import_csv(
+        &target,
+        output.as_slice(),
+        &ColumnMapping::new(),
+        ImportMode::Apply,
+    )
+    .expect("Failed to import");
+
+    // Assert
+    assert_eq!(report.imported.len(), 1);
+    assert_eq!(
+        target.find_by_id("csv-3").unwrap(),
+        source.find_by_id("csv-3").unwrap()
+    );
+}
+
+#[test]
+fn test_import_with_column_mapping() {
+    // Arrange
+    let repository = InMemoryFilamentRepository::new();
+    let csv = "SKU,Product,Type,Colour,Dia (mm),Net g,Left g,Brand,Shelf\n\
+               P-100,Matte Grey,PLA,#808080,1.75,1000,900,Acme,Rack 3\n";
+    let mapping = ColumnMapping::new()
+        .map(InventoryField::Id, "SKU")
+        .map(InventoryField::Name, "Product")
+        .map(InventoryField::Material, "Type")
+        .map(InventoryField::Color, "Colour")
+        .map(InventoryField::Diameter, "Dia (mm)")
+        .map(InventoryField::Weight, "Net g")
+        .map(InventoryField::RemainingWeight, "Left g")
+        .map(InventoryField::Manufacturer, "Brand")
+        .map(InventoryField::StorageLocation, "Shelf");
+
+    // Act
+    let report = import_csv(&repository, csv.as_bytes(), &mapping, ImportMode::Apply)
+        .expect("Failed to import");
+
+    // Assert
+    assert_eq!(report.imported.len(), 1);
+    let roll = repository.find_by_id("P-100").unwrap();
+    assert_eq!(roll.name(), "Matte Grey");
+    assert_eq!(roll.remaining_weight(), 900.0);
+    assert_eq!(roll.storage_location(), "Rack 3");
+}
+
+#[test]
+fn test_import_upserts_by_id_keeping_missing_fields() {
+    // Arrange
+    let repository = InMemoryFilamentRepository::new();
+    repository
+        .save(&create_test_filament("csv-4", 1000.0))
+        .expect("Failed to save filament");
+    let csv = "id,remaining_weight,storage_location\ncsv-4,555,Dry Box\n";
+
+    // Act
+    let report = import_csv(
+        &repository,
+        csv.as_bytes(),
+        &ColumnMapping::new(),
+        ImportMode::Apply,
+    )
+    .expect("Failed to import");
+
+    // Assert
+    assert_eq!(report.updated.len(), 1);
+    assert!(report.imported.is_empty());
+    let roll = repository.find_by_id("csv-4").unwrap();
+    assert_eq!(roll.remaining_weight(), 555.0);
+    assert_eq!(roll.storage_location(), "Dry Box");
+    assert_eq!(roll.name(), "Basic Black");
+}
+
+// Tags the roll as someone else would, just after the import has read it
+struct TaggedAfterRead(InMemoryFilamentRepository);
+
+impl FilamentRepository for TaggedAfterRead {
+    fn save(&self, filament: &FilamentRoll) -> Result<(), FilamentError> {
+        self.0.save(filament)
+    }
+
+    fn find_by_id(&self, id: &str) -> Result<FilamentRoll, FilamentError> {
+        let roll = self.0.find_by_id(id)?;
+        self.0
+            .update(id, &mut |roll| roll.set_tags(&["Opened".to_string()]))?;
+        Ok(roll)
+    }
+
+    fn update_remaining_weight(
+        &self,
+        id: &str,
+        remaining_weight: f32,
+    ) -> Result<FilamentRoll, FilamentError> {
+        self.0.update_remaining_weight(id, remaining_weight)
+    }
+
+    fn find_all(&self) -> Result<Vec<FilamentRoll>, FilamentError> {
+        self.0.find_all()
+    }
+
+    fn find_by_material(&self, material: &str) -> Result<Vec<FilamentRoll>, FilamentError> {
+        self.0.find_by_material(material)
+    }
+
+    fn delete(&self, id: &str) -> Result<(), FilamentError> {
+        self.0.delete(id)
+    }
+
+    fn update(
+        &self,
+        id: &str,
+        change: &mut dyn FnMut(&mut FilamentRoll) -> Result<(), FilamentError>,
+    ) -> Result<FilamentRoll, FilamentError> {
+        self.0.update(id, change)
+    }
+}
+
+#[test]
+fn test_import_upsert_keeps_changes_made_after_the_read() {
+    // Arrange
+    let repository = TaggedAfterRead(InMemoryFilamentRepository::new());
+    repository
+        .save(&create_test_filament("csv-5", 1000.0))
+        .expect("Failed to save filament");
+    let csv = "id,remaining_weight\ncsv-5,555\n";
+
+    // Act
+    let report = import_csv(
+        &repository,
+        csv.as_bytes(),
+        &ColumnMapping::new(),
+        ImportMode::Apply,
+    )
+    .expect("Failed to import");
+
+    // Assert
+    let roll = repository.find_all().unwrap().remove(0);
+    assert_eq!(roll.remaining_weight(), 555.0);
+    assert_eq!(roll.tags(), ["Opened".to_string()]);
+    assert_eq!(report.updated, vec![roll]);
+}
+
+#[test]
+fn test_import_reports_row_errors() {
+    // Arrange
+    let repository = InMemoryFilamentRepository::new();
+    let csv = "id,name,material,color,diameter,weight,remaining_weight,manufacturer\n\
+               ok-1,Good,PLA,#FFFFFF,1.75,1000,800,Acme\n\
+               bad-1,Heavy,PLA,#FFFFFF,1.75,1000,1200,Acme\n\
+               bad-2,Typo,PLA,#FFFFFF,one,1000,800,Acme\n\
+               ok-1,Again,PLA,#FFFFFF,1.75,1000,800,Acme\n";
+
+    // Act
+    let report = import_csv(
+        &repository,
+        csv.as_bytes(),
+        &ColumnMapping::new(),
+        ImportMode::Apply,
+    )
+    .expect("Failed to import");
+
+    // Assert
+    assert_eq!(report.imported.len(), 1);
+    assert_eq!(report.failed.len(), 3);
+    assert_eq!(report.failed[0].row, 2);
+    assert!(report.failed[0]
+        .message
+        .contains("cannot exceed total weight"));
+    assert!(report.failed[1].message.contains("Invalid number 'one'"));
+    assert!(report.failed[2].message.contains("Duplicate id"));
+}
+
+#[test]
+fn test_import_dry_run_writes_nothing() {
+    // Arrange
+    let repository = InMemoryFilamentRepository::new();
+    let csv = "name,material,color,diameter,weight,manufacturer\n\
+               New Roll,PETG,#00FF00,1.75,750,Acme\n";
+
+    // Act
+    let report = import_csv(
+        &repository,
+        csv.as_bytes(),
+        &ColumnMapping::new(),
+        ImportMode::DryRun,
+    )
+    .expect("Failed to import");
+
+    // Assert
+    assert!(report.dry_run);
+    assert_eq!(report.imported.len(), 1);
+    assert!(repository.find_all().unwrap().is_empty());
+}
+
+#[actix_web::test]
+async fn test_export_and_import_over_http() {
+    // Arrange
+    let repository = Arc::new(InMemoryFilamentRepository::new());
+    repository
+        .save(&create_test_filament("csv-6", 800.0))
+        .expect("Failed to save filament");
+    let rolls: web::Data<dyn FilamentRepository> =
+        web::Data::from(repository.clone() as Arc<dyn FilamentRepository>);
+    let app = init_service(
+        App::new()
+            .app_data(rolls)
+            .configure(api::inventory::configure),
+    )
+    .await;
+
+    // Act
+    let export = call_service(
+        &app,
+        TestRequest::get().uri("/api/inventory/export").to_request(),
+    )
+    .await;
+    assert_eq!(export.status(), StatusCode::OK);
+    let csv = String::from_utf8(read_body(export).await.to_vec()).unwrap();
+
+    let dry_run = call_service(
+        &app,
+        TestRequest::post()
+            .uri("/api/inventory/import?dry_run=true&remaining_weight=Grams%20left")
+            .set_payload("id,Grams left\ncsv-6,120\n")
+            .to_request(),
+    )
+    .await;
+    let preview: Value = read_body_json(dry_run).await;
+    let applied = call_service(
+        &app,
+        TestRequest::post()
+            .uri("/api/inventory/import?remaining_weight=Grams%20left")
+            .set_payload("id,Grams left\ncsv-6,120\n")
+            .to_request(),
+    )
+    .await;
+    let unknown = call_service(
+        &app,
+        TestRequest::post()
+            .uri("/api/inventory/import?weight_left=Grams")
+            .set_payload("id\n")
+            .to_request(),
+    )
+    .await;
+
+    // Assert
+    assert!(csv.starts_with("id,name,material"));
+    assert!(csv.contains("csv-6,Basic Black"));
+    assert_eq!(preview["updated"][0]["remaining_weight"], 120.0);
+    assert_eq!(applied.status(), StatusCode::OK);
+    assert_eq!(
+        repository.find_by_id("csv-6").unwrap().remaining_weight(),
+        120.0
+    );
+    assert_eq!(unknown.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,225 @@
+use actix_web::http::StatusCode;
+use actix_web::test::{call_service, init_service, read_body, TestRequest};
+use actix_web::{web, App};
+use backend::api;
+use backend::domain::error::FilamentError;
+use backend::domain::filament::{FilamentRepository, FilamentRoll};
+use backend::domain::roll_filter::RollFilter;
+use backend::domain::services::filament_service::FilamentService;
+use backend::infrastructure::labels::sheet::{render_sheets, SheetTemplate};
+use backend::infrastructure::labels::{LabelLayout, LabelSettings};
+use backend::infrastructure::repositories::memory::InMemoryFilamentRepository;
+use serde_json::json;
+use std::sync::Arc;
+
+// Helper function to create a test filament with ID
+fn create_test_filament(id: &str, material: &str, location: &str) -> FilamentRoll {
+    FilamentRoll::with_id(
+        id,
+        id,
+        material,
+        "#FF8000",
+        1.75,
+        1000.0,
+        1000.0,
+        "Test Brand",
+        location,
+    )
+    .expect("Failed to create test filament")
+}
+
+fn layouts(count: usize, template: &SheetTemplate) -> Vec<LabelLayout> {
+    let size = template.label_size().unwrap();
+    (0..count)
+        .map(|i| {
+            let roll = create_test_filament(&format!("roll-{}", i), "PLA", "Shelf A");
+            LabelLayout::for_roll(&roll, size, None).unwrap()
+        })
+        .collect()
+}
+
+fn page_count(pdf: &[u8]) -> usize {
+    let pdf = String::from_utf8_lossy(pdf);
+    let start = pdf.find("/Type/Pages/Count ").unwrap() + "/Type/Pages/Count ".len();
+    pdf[start..]
+        .split(|c: char| !c.is_ascii_digit())
+        .next()
+        .unwrap()
+        .parse()
+        .unwrap()
+}
+
+fn seeded_service() -> FilamentService {
+    let repository = Arc::new(InMemoryFilamentRepository::new());
+    for i in 0..30 {
+        let material = if i % 3 == 0 { "PETG" } else { "PLA" };
+        repository
+            .save(&create_test_filament(
+                &format!("roll-{:02}", i),
+                material,
+                "Shipment 42",
+            ))
+            .unwrap();
+    }
+    repository
+        .save(&create_test_filament("old", "PETG", "Shelf A"))
+        .unwrap();
+    FilamentService::new(repository)
+}
+
+#[test]
+fn test_avery_templates_fit_their_pages() {
+    for code in ["L7160", "l7163", "L7651", "5160"] {
+        // Act
+        let template = SheetTemplate::avery(code).unwrap();
+
+        // Assert
+        let size = template.label_size().unwrap();
+        assert_eq!(size.width_mm, template.label_width_mm);
+    }
+    assert_eq!(
+        SheetTemplate::avery("L7160").unwrap().labels_per_sheet(),
+        21
+    );
+    assert!(matches!(
+        SheetTemplate::avery("L9999"),
+        Err(FilamentError::Missing(_))
+    ));
+}
+
+#[test]
+fn test_grid_larger_than_page_is_rejected() {
+    // Arrange
+    let mut template = SheetTemplate::avery("L7160").unwrap();
+    template.rows = 8;
+
+    // Act
+    let result = template.label_size();
+
+    // Assert
+    assert!(matches!(result, Err(FilamentError::InvalidData(_))));
+}
+
+#[test]
+fn test_labels_flow_onto_further_sheets() {
+    // Arrange
+    let template = SheetTemplate::avery("L7160").unwrap();
+    let labels = layouts(25, &template);
+
+    // Act
+    let fresh = render_sheets(&labels, &template, 0).unwrap();
+    let part_used = render_sheets(&labels, &template, 20).unwrap();
+
+    // Assert
+    assert!(fresh.starts_with(b"%PDF"));
+    assert_eq!(page_count(&fresh), 2);
+    assert_eq!(page_count(&part_used), 3);
+}
+
+#[test]
+fn test_labels_must_match_template_size() {
+    // Arrange
+    let template = SheetTemplate::avery("L7160").unwrap();
+    let labels = layouts(1, &SheetTemplate::avery("L7651").unwrap());
+
+    // Act
+    let result = render_sheets(&labels, &template, 0);
+
+    // Assert
+    assert!(matches!(result, Err(FilamentError::InvalidData(_))));
+}
+
+#[test]
+fn test_find_rolls_by_filter() {
+    // Arrange
+    let service = seeded_service();
+    let filter = RollFilter {
+        material: Some("petg".to_string()),
+        location: Some("Shipment 42".to_string()),
+        ..RollFilter::default()
+    };
+
+    // Act
+    let rolls = service.find_rolls(&filter).unwrap();
+
+    // Assert
+    let ids: Vec<&str> = rolls.iter().map(|r| r.id()).collect();
+    assert_eq!(
+        ids,
+        vec![
+            "roll-00", "roll-03", "roll-06", "roll-09", "roll-12", "roll-15", "roll-18", "roll-21",
+            "roll-24", "roll-27"
+        ]
+    );
+}
+
+#[actix_web::test]
+async fn test_sheet_endpoint() {
+    // Arrange
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(seeded_service()))
+            .app_data(web::Data::new(LabelSettings::default()))
+            .configure(api::labels::configure),
+    )
+    .await;
+    let custom = json!({
+        "name": "Two by two",
+        "page_width_mm": 100.0,
+        "page_height_mm": 100.0,
+        "columns": 2,
+        "rows": 2,
+        "label_width_mm": 45.0,
+        "label_height_mm": 45.0,
+        "left_mm": 4.0,
+        "top_mm": 4.0,
+        "column_gap_mm": 2.0,
+        "row_gap_mm": 2.0
+    });
+
+    // Act
+    let shipment = call_service(
+        &app,
+        TestRequest::post()
+            .uri("/api/labels/sheet")
+            .set_json(json!({"filter": {"location": "Shipment 42"}, "template": "L7160"}))
+            .to_request(),
+    )
+    .await;
+    let custom = call_service(
+        &app,
+        TestRequest::post()
+            .uri("/api/labels/sheet")
+            .set_json(json!({"roll_ids": ["old", "roll-01"], "template": custom, "skip": 3}))
+            .to_request(),
+    )
+    .await;
+    let ambiguous = call_service(
+        &app,
+        TestRequest::post()
+            .uri("/api/labels/sheet")
+            .set_json(json!({"roll_ids": ["old"], "filter": {}, "template": "L7160"}))
+            .to_request(),
+    )
+    .await;
+    let roll_ids = vec!["old"; api::labels::MAX_LABELS + 1];
+    let too_many = call_service(
+        &app,
+        TestRequest::post()
+            .uri("/api/labels/sheet")
+            .set_json(json!({"roll_ids": roll_ids, "template": "L7160"}))
+            .to_request(),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(
+        shipment.headers().get("content-type").unwrap(),
+        "application/pdf"
+    );
+    assert_eq!(page_count(&read_body(shipment).await), 2);
+    assert_eq!(custom.status(), StatusCode::OK);
+    assert_eq!(page_count(&read_body(custom).await), 2);
+    assert_eq!(ambiguous.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(too_many.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,221 @@
+use actix_web::http::StatusCode;
+use actix_web::test::{call_service, init_service, read_body, TestRequest};
+use actix_web::{web, App};
+use backend::api;
+use backend::domain::error::FilamentError;
+use backend::domain::filament::{FilamentRepository, FilamentRoll};
+use backend::domain::services::filament_service::FilamentService;
+use backend::infrastructure::labels::raster::render_bitmap;
+use backend::infrastructure::labels::svg::render_svg;
+use backend::infrastructure::labels::{
+    roll_payload, LabelLayout, LabelSettings, LabelSize, ROLL_URI_PREFIX,
+};
+use backend::infrastructure::repositories::memory::InMemoryFilamentRepository;
+use std::sync::Arc;
+
+// Helper function to create a test filament with ID
+fn create_test_filament(id: &str, name: &str) -> FilamentRoll {
+    FilamentRoll::with_id(
+        id,
+        name,
+        "PETG",
+        "#FF8000",
+        1.75,
+        1000.0,
+        800.0,
+        "Test Brand",
+        "Shelf A",
+    )
+    .expect("Failed to create test filament")
+}
+
+#[test]
+fn test_payload_uses_public_url_when_configured() {
+    // Act
+    let with_url = roll_payload("roll-1", Some("http://printfarm.local:8080/"));
+    let without_url = roll_payload("roll-1", None);
+
+    // Assert
+    assert_eq!(with_url, "http://printfarm.local:8080/rolls/roll-1");
+    assert_eq!(without_url, format!("{}roll-1", ROLL_URI_PREFIX));
+}
+
+#[test]
+fn test_parse_label_size() {
+    // Act & Assert
+    assert_eq!(LabelSize::parse("62x29").unwrap(), LabelSize::BROTHER_62);
+    assert_eq!(LabelSize::parse("40X30").unwrap(), LabelSize::SMALL);
+    assert!(matches!(
+        LabelSize::parse("62"),
+        Err(FilamentError::InvalidData(_))
+    ));
+    assert!(matches!(
+        LabelSize::parse("5x5"),
+        Err(FilamentError::InvalidData(_))
+    ));
+}
+
+#[test]
+fn test_layout_keeps_text_beside_qr_code() {
+    // Arrange
+    let roll = create_test_filament("roll-1", "Galaxy Black");
+
+    // Act
+    let layout = LabelLayout::for_roll(&roll, LabelSize::SMALL, None).unwrap();
+
+    // Assert
+    let texts: Vec<&str> = layout.lines.iter().map(|l| l.text.as_str()).collect();
+    assert_eq!(
+        texts,
+        vec!["Galaxy Black", "PETG 1.75 mm", "Shelf A", "roll-1"]
+    );
+    for line in &layout.lines {
+        assert!(line.x >= layout.qr.x + layout.qr.side);
+        assert!(line.y >= 0.0 && line.y + line.size <= LabelSize::SMALL.height_mm);
+    }
+    assert_eq!(layout.swatch.as_ref().unwrap().rgb, [0xFF, 0x80, 0x00]);
+}
+
+#[test]
+fn test_long_names_are_shortened() {
+    // Arrange
+    let roll = create_test_filament("roll-1", &"Very Long Filament Name ".repeat(4));
+
+    // Act
+    let layout = LabelLayout::for_roll(&roll, LabelSize::SMALL, None).unwrap();
+
+    // Assert
+    assert!(layout.lines[0].text.ends_with("..."));
+}
+
+#[test]
+fn test_svg_is_sized_in_millimetres() {
+    // Arrange
+    let roll = create_test_filament("roll-1", "Black & <White>");
+    let layout = LabelLayout::for_roll(&roll, LabelSize::BROTHER_62, None).unwrap();
+
+    // Act
+    let svg = render_svg(&layout);
+
+    // Assert
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.contains(r#"width="62mm" height="29mm" viewBox="0 0 62 29""#));
+    assert!(svg.contains("Black &amp; &lt;White&gt;"));
+    assert!(svg.contains("fill=\"#FF8000\""));
+}
+
+#[test]
+fn test_bitmap_matches_label_size_and_layout() {
+    // Arrange
+    let roll = create_test_filament("roll-1", "Galaxy Black");
+    let layout = LabelLayout::for_roll(&roll, LabelSize::BROTHER_62, None).unwrap();
+
+    // Act
+    let bitmap = render_bitmap(&layout, 300).unwrap();
+
+    // Assert
+    assert_eq!((bitmap.width(), bitmap.height()), (732, 343));
+    // Quiet zone is white, the centre of the top-left finder pattern black
+    let dots = |mm: f32| (mm * 300.0 / 25.4).round() as u32;
+    let modules = layout.qr.modules as u32;
+    let module = dots(layout.qr.side) / modules;
+    let inset = (dots(layout.qr.side) - module * modules) / 2;
+    let at_module = |x: u32, y: u32| {
+        bitmap.pixel(
+            dots(layout.qr.x) + inset + x * module + module / 2,
+            dots(layout.qr.y) + inset + y * module + module / 2,
+        )
+    };
+    assert_eq!(at_module(1, 1), [0xFF, 0xFF, 0xFF]);
+    assert_eq!(at_module(7, 7), [0, 0, 0]);
+    // Middle of the colour swatch
+    let swatch = layout.swatch.unwrap();
+    let centre = |mm: f32| ((mm + swatch.side / 2.0) * 300.0 / 25.4) as u32;
+    assert_eq!(
+        bitmap.pixel(centre(swatch.x), centre(swatch.y)),
+        [0xFF, 0x80, 0x00]
+    );
+}
+
+#[test]
+fn test_png_records_resolution() {
+    // Arrange
+    let roll = create_test_filament("roll-1", "Galaxy Black");
+    let layout = LabelLayout::for_roll(&roll, LabelSize::SMALL, None).unwrap();
+
+    // Act
+    let png = render_bitmap(&layout, 203).unwrap().to_png().unwrap();
+
+    // Assert
+    let reader = png::Decoder::new(png.as_slice()).read_info().unwrap();
+    let info = reader.info();
+    assert_eq!((info.width, info.height), (320, 240));
+    assert_eq!(info.pixel_dims.unwrap().xppu, 7992);
+}
+
+#[test]
+fn test_out_of_range_dpi_is_rejected() {
+    // Arrange
+    let roll = create_test_filament("roll-1", "Galaxy Black");
+    let layout = LabelLayout::for_roll(&roll, LabelSize::SMALL, None).unwrap();
+
+    // Act
+    let result = render_bitmap(&layout, 4800);
+
+    // Assert
+    assert!(matches!(result, Err(FilamentError::InvalidData(_))));
+}
+
+#[actix_web::test]
+async fn test_label_endpoints() {
+    // Arrange
+    let repository = Arc::new(InMemoryFilamentRepository::new());
+    repository
+        .save(&create_test_filament("roll-1", "Galaxy Black"))
+        .unwrap();
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(FilamentService::new(repository)))
+            .app_data(web::Data::new(LabelSettings::default()))
+            .configure(api::labels::configure),
+    )
+    .await;
+
+    // Act
+    let svg = call_service(
+        &app,
+        TestRequest::get()
+            .uri("/api/rolls/roll-1/label.svg?size=40x30")
+            .to_request(),
+    )
+    .await;
+    let png = call_service(
+        &app,
+        TestRequest::get()
+            .uri("/api/rolls/roll-1/label.png?dpi=203")
+            .to_request(),
+    )
+    .await;
+    let missing = call_service(
+        &app,
+        TestRequest::get()
+            .uri("/api/rolls/nope/label.svg")
+            .to_request(),
+    )
+    .await;
+    let bad_size = call_service(
+        &app,
+        TestRequest::get()
+            .uri("/api/rolls/roll-1/label.png?size=huge")
+            .to_request(),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(svg.headers().get("content-type").unwrap(), "image/svg+xml");
+    assert!(read_body(svg).await.starts_with(b"<svg"));
+    assert_eq!(png.headers().get("content-type").unwrap(), "image/png");
+    assert!(read_body(png).await.starts_with(b"\x89PNG"));
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    assert_eq!(bad_size.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,432 @@
+use actix_web::http::StatusCode;
+use actix_web::test::{call_service, init_service, read_body_json, TestRequest};
+use actix_web::{web, App};
+use backend::api;
+use backend::api::lots::LotRoll;
+use backend::domain::audit::diff_rolls;
+use backend::domain::error::FilamentError;
+use backend::domain::feasibility::{FilamentRequirement, Fulfilment};
+use backend::domain::filament::{FilamentRepository, FilamentRoll, FilamentRollBuilder};
+use backend::domain::services::filament_service::FilamentService;
+use backend::infrastructure::import::ImportMode;
+use backend::infrastructure::inventory_csv::{export_csv, import_csv, ColumnMapping};
+use backend::infrastructure::repositories::memory::{
+    InMemoryDefectiveLotRepository, InMemoryFilamentRepository,
+};
+use backend::infrastructure::spoolman::ids::SpoolmanIds;
+use backend::infrastructure::spoolman::import::import_json;
+use backend::infrastructure::spoolman::models::SpoolmanSpool;
+use chrono::NaiveDate;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::thread;
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).expect("Invalid test date")
+}
+
+// Helper function to create a test filament with ID
+fn create_test_filament(id: &str, manufacturer: &str, lot_number: &str) -> FilamentRoll {
+    FilamentRollBuilder::new(
+        id.to_string(),
+        "PLA".to_string(),
+        "#FF8000".to_string(),
+        1.75,
+        1000.0,
+        manufacturer.to_string(),
+    )
+    .with_id(id)
+    .with_storage_location("Bin 1")
+    .with_lot_number(lot_number)
+    .with_manufactured_on(date(2024, 1, 15))
+    .with_received_on(date(2024, 3, 2))
+    .build()
+    .expect("Failed to create test filament")
+}
+
+fn setup() -> (Arc<InMemoryFilamentRepository>, FilamentService) {
+    let rolls = Arc::new(InMemoryFilamentRepository::new());
+    for roll in [
+        create_test_filament("bad-1", "Test Brand", "L2401"),
+        create_test_filament("bad-2", "Test Brand", "L2401"),
+        create_test_filament("other-brand", "Other Brand", "L2401"),
+        create_test_filament("good", "Test Brand", "L2402"),
+    ] {
+        rolls.save(&roll).expect("Failed to save filament");
+    }
+    let service = FilamentService::new(rolls.clone())
+        .with_defective_lots(Arc::new(InMemoryDefectiveLotRepository::new()));
+    (rolls, service)
+}
+
+#[test]
+fn test_lot_details_on_roll() {
+    // Arrange
+    let roll = create_test_filament("roll-1", "Test Brand", " L2401 ");
+
+    // Assert
+    assert_eq!(roll.lot_number(), "L2401");
+    assert_eq!(roll.manufactured_on(), Some(date(2024, 1, 15)));
+    assert_eq!(roll.received_on(), Some(date(2024, 3, 2)));
+
+    // Manufactured after it arrived
+    let backwards = FilamentRollBuilder::new(
+        "Backwards".to_string(),
+        "PLA".to_string(),
+        "#000000".to_string(),
+        1.75,
+        1000.0,
+        "Test Brand".to_string(),
+    )
+    .with_manufactured_on(date(2024, 5, 1))
+    .with_received_on(date(2024, 4, 1))
+    .build();
+    assert!(matches!(backwards, Err(FilamentError::InvalidData(_))));
+
+    // Rolls saved before lots existed still load
+    let mut saved = serde_json::to_value(&roll).unwrap();
+    for field in ["lot_number", "manufactured_on", "received_on"] {
+        saved.as_object_mut().unwrap().remove(field);
+    }
+    let old: FilamentRoll = serde_json::from_value(saved).unwrap();
+    assert_eq!(old.lot_number(), "");
+    assert_eq!(old.received_on(), None);
+}
+
+#[test]
+fn test_find_by_lot() {
+    // Arrange
+    let (rolls, service) = setup();
+
+    // Act
+    let in_lot = service.rolls_in_lot("L2401").unwrap();
+
+    // Assert
+    let ids: Vec<&str> = in_lot.iter().map(|r| r.id()).collect();
+    assert_eq!(ids, vec!["bad-1", "bad-2", "other-brand"]);
+    assert!(rolls.find_by_lot("").unwrap().is_empty());
+    assert!(rolls.find_by_lot("l2401").unwrap().is_empty());
+}
+
+#[test]
+fn test_set_lot_on_existing_roll() {
+    // Arrange
+    let (_, service) = setup();
+
+    // Act
+    let updated = service
+        .set_lot("good", "L2403", None, Some(date(2024, 6, 1)))
+        .unwrap();
+
+    // Assert
+    assert_eq!(updated.lot_number(), "L2403");
+    assert_eq!(updated.manufactured_on(), None);
+    assert_eq!(service.get_roll("good").unwrap(), updated);
+    assert_eq!(
+        diff_rolls(
+            Some(&create_test_filament("good", "Test Brand", "L2402")),
+            &updated
+        )
+        .iter()
+        .map(|change| change.field.as_str())
+        .collect::<Vec<_>>(),
+        vec!["lot_number", "manufactured_on", "received_on"]
+    );
+    assert!(matches!(
+        service.set_lot(
+            "good",
+            "L2403",
+            Some(date(2024, 7, 1)),
+            Some(date(2024, 6, 1))
+        ),
+        Err(FilamentError::InvalidData(_))
+    ));
+}
+
+#[test]
+fn test_defective_lot_is_excluded_from_feasibility() {
+    // Arrange
+    let (_, service) = setup();
+    let requirements = [FilamentRequirement {
+        extruder: 0,
+        material: "PLA".to_string(),
+        color: Some("#FF8000".to_string()),
+        diameter: Some(1.75),
+        grams: 2500.0,
+    }];
+    let before = service.check_feasibility(&requirements).unwrap();
+
+    // Act
+    let lot = service
+        .mark_lot_defective("test brand", "L2401", "Colour off")
+        .unwrap();
+    let after = service.check_feasibility(&requirements).unwrap();
+
+    // Assert
+    assert!(before.is_feasible());
+    let candidates: Vec<&str> = after.checks[0]
+        .candidates
+        .iter()
+        .map(|c| c.roll_id.as_str())
+        .collect();
+    assert_eq!(candidates, vec!["good", "other-brand"]);
+    assert!(matches!(
+        after.checks[0].fulfilment,
+        Fulfilment::Shortfall { missing_grams, .. } if missing_grams == 500.0
+    ));
+    assert_eq!(
+        service
+            .defective_lot_of(&service.get_roll("bad-1").unwrap())
+            .unwrap(),
+        Some(lot)
+    );
+    assert_eq!(
+        service
+            .defective_lot_of(&service.get_roll("other-brand").unwrap())
+            .unwrap(),
+        None
+    );
+
+    // Clearing the flag makes the rolls available again
+    service.clear_defective_lot("Test Brand", "L2401").unwrap();
+    assert!(service
+        .check_feasibility(&requirements)
+        .unwrap()
+        .is_feasible());
+    assert!(matches!(
+        service.clear_defective_lot("Test Brand", "L2401"),
+        Err(FilamentError::Missing(_))
+    ));
+}
+
+#[test]
+fn test_flagging_needs_a_lot_repository() {
+    // Arrange
+    let service = FilamentService::new(Arc::new(InMemoryFilamentRepository::new()));
+
+    // Assert
+    assert!(service.defective_lots().unwrap().is_empty());
+    assert!(matches!(
+        service.mark_lot_defective("Test Brand", "L2401", "Brittle"),
+        Err(FilamentError::RepositoryError(_))
+    ));
+    assert!(matches!(
+        FilamentService::new(Arc::new(InMemoryFilamentRepository::new()))
+            .with_defective_lots(Arc::new(InMemoryDefectiveLotRepository::new()))
+            .mark_lot_defective("Test Brand", " ", "Brittle"),
+        Err(FilamentError::InvalidData(_))
+    ));
+}
+
+#[test]
+fn test_lots_in_csv() {
+    // Arrange
+    let source = InMemoryFilamentRepository::new();
+    source
+        .save(&create_test_filament("csv-1", "Test Brand", "L2401"))
+        .unwrap();
+    let mut output = Vec::new();
+    export_csv(&source, &mut output).unwrap();
+    let target = InMemoryFilamentRepository::new();
+    let bad_date = "id,name,material,color,diameter,weight,manufacturer,received_on\n\
+                    csv-2,Black,PLA,#000000,1.75,1000,Test Brand,02/03/2024\n";
+
+    // Act
+    let report = import_csv(
+        &target,
+        output.as_slice(),
+        &ColumnMapping::new(),
+        ImportMode::Apply,
+    )
+    .unwrap();
+    let rejected = import_csv(
+        &target,
+        bad_date.as_bytes(),
+        &ColumnMapping::new(),
+        ImportMode::Apply,
+    )
+    .unwrap();
+
+    // Assert
+    let csv = String::from_utf8(output).unwrap();
+    assert!(csv
+        .lines()
+        .nth(1)
+        .unwrap()
+        .contains(",L2401,2024-01-15,2024-03-02,"));
+    assert_eq!(report.imported.len(), 1);
+    assert_eq!(
+        target.find_by_id("csv-1").unwrap(),
+        source.find_by_id("csv-1").unwrap()
+    );
+    assert_eq!(rejected.failed.len(), 1);
+    assert!(rejected.failed[0].message.contains("received_on"));
+}
+
+#[test]
+fn test_lots_in_spoolman() {
+    // Arrange
+    let repository = InMemoryFilamentRepository::new();
+    let export = json!([{
+        "id": 7,
+        "filament": {"name": "Orange", "material": "PLA", "color_hex": "FF8000",
+                     "diameter": 1.75, "weight": 1000,
+                     "vendor": {"name": "Test Brand"}},
+        "initial_weight": 1000,
+        "lot_nr": "L2401"
+    }]);
+
+    // Act
+    import_json(&repository, &export.to_string(), ImportMode::Apply).unwrap();
+    let roll = repository.find_by_id("spoolman-7").unwrap();
+    let spool = SpoolmanSpool::from_roll(&roll, &SpoolmanIds::new()).unwrap();
+
+    // Assert
+    assert_eq!(roll.lot_number(), "L2401");
+    assert_eq!(spool.lot_nr.as_deref(), Some("L2401"));
+}
+
+#[actix_web::test]
+async fn test_lots_api() {
+    // Arrange
+    let (_, service) = setup();
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(service))
+            .app_data(web::Data::new(SpoolmanIds::new()))
+            .configure(api::lots::configure)
+            .configure(api::rolls::configure)
+            .configure(api::spoolman::configure),
+    )
+    .await;
+
+    // Act
+    let response = call_service(
+        &app,
+        TestRequest::put()
+            .uri("/api/lots/L2401/defective")
+            .set_json(json!({ "manufacturer": "Test Brand", "reason": "Brittle" }))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = call_service(
+        &app,
+        TestRequest::get().uri("/api/lots/L2401/rolls").to_request(),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let rolls: Vec<LotRoll> = read_body_json(response).await;
+    let flagged: Vec<(&str, bool)> = rolls
+        .iter()
+        .map(|r| (r.roll.id(), r.defective_lot.is_some()))
+        .collect();
+    assert_eq!(
+        flagged,
+        vec![("bad-1", true), ("bad-2", true), ("other-brand", false)]
+    );
+
+    // Searches flag them too
+    let response = call_service(
+        &app,
+        TestRequest::post()
+            .uri("/api/rolls/search")
+            .set_json(json!({}))
+            .to_request(),
+    )
+    .await;
+    let found: Vec<LotRoll> = read_body_json(response).await;
+    let bad_1 = found.iter().find(|r| r.roll.id() == "bad-1").unwrap();
+    assert_eq!(
+        bad_1.defective_lot.as_ref().map(|lot| lot.reason()),
+        Some("Brittle")
+    );
+    assert!(found
+        .iter()
+        .find(|r| r.roll.id() == "other-brand")
+        .unwrap()
+        .defective_lot
+        .is_none());
+
+    // Spoolman clients see the lot and the flag
+    let response = call_service(
+        &app,
+        TestRequest::get()
+            .uri("/api/v1/spool?lot_nr=L2401")
+            .to_request(),
+    )
+    .await;
+    let spools: Vec<Value> = read_body_json(response).await;
+    assert_eq!(spools.len(), 3);
+    assert!(spools.iter().all(|spool| spool["lot_nr"] == "L2401"));
+    assert_eq!(
+        spools
+            .iter()
+            .filter(|spool| spool["extra"]["defective_lot"] == "\"Brittle\"")
+            .count(),
+        2
+    );
+
+    let response = call_service(
+        &app,
+        TestRequest::put()
+            .uri("/api/rolls/good/lot")
+            .set_json(json!({ "lot_number": "L2401", "received_on": "2024-04-01" }))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let roll: Value = read_body_json(response).await;
+    assert_eq!(roll["lot_number"], "L2401");
+    assert_eq!(roll["received_on"], "2024-04-01");
+
+    let response = call_service(
+        &app,
+        TestRequest::delete()
+            .uri("/api/lots/L2401/defective?manufacturer=Test%20Brand")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let response = call_service(
+        &app,
+        TestRequest::get().uri("/api/lots/defective").to_request(),
+    )
+    .await;
+    let lots: Vec<Value> = read_body_json(response).await;
+    assert!(lots.is_empty());
+}
+
+#[test]
+fn test_setting_lot_never_undoes_concurrent_consumption() {
+    // Arrange
+    let (_, service) = setup();
+
+    // Act
+    let workers: Vec<_> = (0..40)
+        .map(|i| {
+            let worker = service.clone();
+            thread::spawn(move || {
+                if i % 2 == 0 {
+                    worker.consume("good", 5.0).map(|_| ())
+                } else {
+                    worker
+                        .set_lot("good", &format!("L{}", i), None, None)
+                        .map(|_| ())
+                }
+            })
+        })
+        .collect();
+    for worker in workers {
+        worker
+            .join()
+            .expect("Worker thread panicked")
+            .expect("Update failed");
+    }
+
+    // Assert
+    assert_eq!(service.get_roll("good").unwrap().remaining_weight(), 900.0);
+}
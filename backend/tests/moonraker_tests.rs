@@ -0,0 +1,320 @@
+use actix_web::{web, App, HttpResponse, HttpServer};
+use backend::domain::assignment::RollAssignments;
+use backend::domain::filament::{FilamentRepository, FilamentRoll};
+use backend::domain::services::filament_service::FilamentService;
+use backend::infrastructure::moonraker::client::MoonrakerClient;
+use backend::infrastructure::moonraker::ledger::{InMemoryProcessedJobs, JsonFileProcessedJobs};
+use backend::infrastructure::moonraker::sync::MoonrakerSync;
+use backend::infrastructure::repositories::memory::{
+    InMemoryFilamentRepository, InMemoryRollAssignments,
+};
+use serde_json::{json, Value};
+use std::net::TcpListener;
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+
+type Jobs = Arc<Mutex<Vec<Value>>>;
+
+// Serves `/server/history/list` from a shared job list, honouring `since` and
+// paging
+async fn history(jobs: web::Data<Jobs>, query: web::Query<Vec<(String, String)>>) -> HttpResponse {
+    let param = |name: &str| query.iter().find(|(k, _)| k == name).map(|(_, v)| v);
+    let start = param("start").and_then(|v| v.parse().ok()).unwrap_or(0);
+    let limit = param("limit").and_then(|v| v.parse().ok()).unwrap_or(50);
+    let since = param("since").and_then(|v| v.parse().ok()).unwrap_or(0.0);
+    let jobs: Vec<Value> = jobs
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|job| job["start_time"].as_f64().unwrap_or(0.0) >= since)
+        .cloned()
+        .collect();
+    let page: Vec<Value> = jobs.iter().skip(start).take(limit).cloned().collect();
+    HttpResponse::Ok().json(json!({ "result": { "count": jobs.len(), "jobs": page } }))
+}
+
+// Starts a mock Moonraker on a random local port and returns its base URL
+fn start_mock_moonraker(jobs: Jobs) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind mock server");
+    let address = listener.local_addr().unwrap();
+    let server = HttpServer::new(move || {
+        App::new()
+            .app_data(web::Data::new(jobs.clone()))
+            .route("/server/history/list", web::get().to(history))
+    })
+    .listen(listener)
+    .expect("Failed to start mock server")
+    .workers(1)
+    .run();
+    actix_web::rt::spawn(server);
+    format!("http://{}", address)
+}
+
+fn job(job_id: &str, status: &str, filament_used: f32) -> Value {
+    json!({
+        "job_id": job_id,
+        "filename": format!("{}.gcode", job_id),
+        "status": status,
+        "start_time": 1700000000.0,
+        "end_time": 1700003600.0,
+        "filament_used": filament_used,
+    })
+}
+
+fn setup() -> (FilamentService, Arc<InMemoryRollAssignments>) {
+    let repository = Arc::new(InMemoryFilamentRepository::new());
+    let filament = FilamentRoll::with_id(
+        "moon-roll",
+        "Galaxy Black",
+        "PLA",
+        "#000000",
+        1.75,
+        1000.0,
+        1000.0,
+        "Prusament",
+        "Voron",
+    )
+    .expect("Failed to create test filament");
+    repository.save(&filament).expect("Failed to save filament");
+
+    let assignments = Arc::new(InMemoryRollAssignments::new());
+    assignments
+        .load_roll("voron", 0, "moon-roll")
+        .expect("Failed to load roll");
+
+    (FilamentService::new(repository), assignments)
+}
+
+#[actix_web::test]
+async fn test_finished_jobs_are_deducted_once() {
+    // Arrange
+    let jobs: Jobs = Arc::new(Mutex::new(vec![
+        job("000001", "completed", 10_000.0),
+        job("000002", "in_progress", 500.0),
+    ]));
+    let base_url = start_mock_moonraker(jobs.clone());
+    let (service, assignments) = setup();
+    let sync = MoonrakerSync::new(
+        "voron",
+        MoonrakerClient::new(&base_url),
+        service.clone(),
+        assignments,
+        Arc::new(InMemoryProcessedJobs::new()),
+    )
+    .since(0.0);
+
+    // Act
+    let first = sync.poll_once().await.expect("First poll failed");
+    let second = sync.poll_once().await.expect("Second poll failed");
+
+    // Assert
+    // Ten metres of 1.75 mm PLA is roughly 29.8 g
+    assert_eq!(first.deducted.len(), 1);
+    assert!((first.deducted[0].grams - 29.82).abs() < 0.05);
+    assert!(second.deducted.is_empty());
+    let remaining = service.get_roll("moon-roll").unwrap().remaining_weight();
+    assert!((1000.0 - remaining - 29.82).abs() < 0.05);
+}
+
+#[actix_web::test]
+async fn test_in_progress_job_is_deducted_when_it_finishes() {
+    // Arrange
+    let jobs: Jobs = Arc::new(Mutex::new(vec![job("000003", "in_progress", 0.0)]));
+    let base_url = start_mock_moonraker(jobs.clone());
+    let (service, assignments) = setup();
+    let sync = MoonrakerSync::new(
+        "voron",
+        MoonrakerClient::new(&base_url),
+        service,
+        assignments,
+        Arc::new(InMemoryProcessedJobs::new()),
+    )
+    .since(0.0);
+    sync.poll_once().await.expect("First poll failed");
+
+    // Act
+    *jobs.lock().unwrap() = vec![job("000003", "cancelled", 3_000.0)];
+    let report = sync.poll_once().await.expect("Second poll failed");
+
+    // Assert
+    assert_eq!(report.deducted.len(), 1);
+    assert_eq!(report.deducted[0].job_id, "000003");
+}
+
+#[actix_web::test]
+async fn test_restart_does_not_deduct_twice() {
+    // Arrange
+    let jobs: Jobs = Arc::new(Mutex::new(vec![job("000004", "completed", 5_000.0)]));
+    let base_url = start_mock_moonraker(jobs);
+    let (service, assignments) = setup();
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let ledger_path = dir.path().join("moonraker-jobs.json");
+    {
+        let sync = MoonrakerSync::new(
+            "voron",
+            MoonrakerClient::new(&base_url),
+            service.clone(),
+            assignments.clone(),
+            Arc::new(JsonFileProcessedJobs::open(&ledger_path).unwrap()),
+        )
+        .since(0.0);
+        sync.poll_once().await.expect("Poll before restart failed");
+    }
+    let after_first_run = service.get_roll("moon-roll").unwrap().remaining_weight();
+
+    // Act
+    let restarted = MoonrakerSync::new(
+        "voron",
+        MoonrakerClient::new(&base_url),
+        service.clone(),
+        assignments,
+        Arc::new(JsonFileProcessedJobs::open(&ledger_path).unwrap()),
+    )
+    .since(0.0);
+    let report = restarted
+        .poll_once()
+        .await
+        .expect("Poll after restart failed");
+
+    // Assert
+    assert!(report.deducted.is_empty());
+    assert_eq!(
+        service.get_roll("moon-roll").unwrap().remaining_weight(),
+        after_first_run
+    );
+}
+
+#[actix_web::test]
+async fn test_history_is_paged() {
+    // Arrange
+    let many_jobs = (0..120)
+        .map(|i| job(&format!("{:06}", i), "completed", 100.0))
+        .collect();
+    let base_url = start_mock_moonraker(Arc::new(Mutex::new(many_jobs)));
+    let client = MoonrakerClient::new(&base_url);
+
+    // Act
+    let jobs = client
+        .job_history(None)
+        .await
+        .expect("Failed to read history");
+
+    // Assert
+    assert_eq!(jobs.len(), 120);
+    assert_eq!(jobs[119].job_id, "000119");
+}
+
+#[actix_web::test]
+async fn test_job_without_loaded_roll_is_reported() {
+    // Arrange
+    let jobs: Jobs = Arc::new(Mutex::new(vec![job("000005", "completed", 1_000.0)]));
+    let base_url = start_mock_moonraker(jobs);
+    let (service, _) = setup();
+    let sync = MoonrakerSync::new(
+        "prusa-mk4",
+        MoonrakerClient::new(&base_url),
+        service,
+        Arc::new(InMemoryRollAssignments::new()),
+        Arc::new(InMemoryProcessedJobs::new()),
+    )
+    .since(0.0);
+
+    // Act
+    let report = sync.poll_once().await.expect("Poll failed");
+
+    // Assert
+    assert!(report.deducted.is_empty());
+    assert_eq!(report.failed.len(), 1);
+    assert!(report.failed[0].message.contains("No roll loaded"));
+}
+
+#[actix_web::test]
+async fn test_run_hands_every_poll_to_the_caller() {
+    // Arrange
+    let jobs: Jobs = Arc::new(Mutex::new(vec![job("000006", "completed", 1_000.0)]));
+    let base_url = start_mock_moonraker(jobs);
+    let (service, _) = setup();
+    let sync = MoonrakerSync::new(
+        "prusa-mk4",
+        MoonrakerClient::new(&base_url),
+        service,
+        Arc::new(InMemoryRollAssignments::new()),
+        Arc::new(InMemoryProcessedJobs::new()),
+    )
+    .since(0.0);
+    let (sender, receiver) = mpsc::channel();
+
+    // Act
+    let running = actix_web::rt::spawn(sync.run(Duration::from_millis(10), move |poll| {
+        let _ = sender.send(poll);
+    }));
+    let mut polls = Vec::new();
+    while polls.len() < 2 {
+        match receiver.try_recv() {
+            Ok(poll) => polls.push(poll.expect("Poll failed")),
+            Err(_) => actix_web::rt::time::sleep(Duration::from_millis(5)).await,
+        }
+    }
+    running.abort();
+
+    // Assert
+    // The job is retried, and reported again, until a roll is loaded
+    assert_eq!(polls[0].failed.len(), 1);
+    assert_eq!(polls[0].failed[0].job_id, "000006");
+    assert_eq!(polls[1], polls[0]);
+}
+
+#[actix_web::test]
+async fn test_failed_job_is_deducted_once_a_roll_is_loaded() {
+    // Arrange
+    let jobs: Jobs = Arc::new(Mutex::new(vec![job("000007", "completed", 1_000.0)]));
+    let base_url = start_mock_moonraker(jobs);
+    let (service, _) = setup();
+    let assignments = Arc::new(InMemoryRollAssignments::new());
+    let sync = MoonrakerSync::new(
+        "prusa-mk4",
+        MoonrakerClient::new(&base_url),
+        service.clone(),
+        assignments.clone(),
+        Arc::new(InMemoryProcessedJobs::new()),
+    )
+    .since(0.0);
+    sync.poll_once().await.expect("Poll without roll failed");
+
+    // Act
+    assignments
+        .load_roll("prusa-mk4", 0, "moon-roll")
+        .expect("Failed to load roll");
+    let report = sync.poll_once().await.expect("Poll with roll failed");
+
+    // Assert
+    assert!(report.failed.is_empty());
+    assert_eq!(report.deducted.len(), 1);
+    assert_eq!(report.deducted[0].job_id, "000007");
+    assert!(service.get_roll("moon-roll").unwrap().remaining_weight() < 1000.0);
+}
+
+#[actix_web::test]
+async fn test_history_before_the_sync_started_is_ignored() {
+    // Arrange
+    let jobs: Jobs = Arc::new(Mutex::new(vec![job("000008", "completed", 10_000.0)]));
+    let base_url = start_mock_moonraker(jobs);
+    let (service, assignments) = setup();
+    let sync = MoonrakerSync::new(
+        "voron",
+        MoonrakerClient::new(&base_url),
+        service.clone(),
+        assignments,
+        Arc::new(InMemoryProcessedJobs::new()),
+    );
+
+    // Act
+    let report = sync.poll_once().await.expect("Poll failed");
+
+    // Assert
+    assert_eq!(report, Default::default());
+    assert_eq!(
+        service.get_roll("moon-roll").unwrap().remaining_weight(),
+        1000.0
+    );
+}
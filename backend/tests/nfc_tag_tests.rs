@@ -0,0 +1,309 @@
+use actix_web::http::StatusCode;
+use actix_web::test::{call_service, init_service, read_body, read_body_json, TestRequest};
+use actix_web::{web, App};
+use backend::api;
+use backend::domain::error::FilamentError;
+use backend::domain::filament::{FilamentRepository, FilamentRoll};
+use backend::domain::scan::RollDraft;
+use backend::domain::services::filament_service::FilamentService;
+use backend::infrastructure::nfc::openspool::{from_json, from_ndef, to_json, to_ndef};
+use backend::infrastructure::nfc::tigertag::{decode, encode, TigerTagIds, TAG_BYTES};
+use backend::infrastructure::nfc::{mime_message, mime_records, tlv_wrap, TagFormat};
+use backend::infrastructure::repositories::memory::InMemoryFilamentRepository;
+use serde_json::Value;
+use std::sync::Arc;
+
+const TIGERTAG_IDS: &str = include_str!("fixtures/tigertag_ids.json");
+
+// Helper function to create a test filament with ID
+fn create_test_filament(id: &str) -> FilamentRoll {
+    FilamentRoll::with_id(
+        id,
+        "PolyTerra Charcoal",
+        "PLA",
+        "#2B2B2B",
+        1.75,
+        1000.0,
+        800.0,
+        "Polymaker",
+        "Shelf A",
+    )
+    .expect("Failed to create test filament")
+}
+
+// Ids in the shape of TigerTag's lists, chosen for the tests
+fn ids() -> TigerTagIds {
+    TigerTagIds::from_json(TIGERTAG_IDS).expect("Failed to load TigerTag ids")
+}
+
+#[test]
+fn test_openspool_payload() {
+    // Arrange
+    let roll = create_test_filament("roll-1");
+
+    // Act
+    let json = to_json(&roll).unwrap();
+
+    // Assert
+    let payload: Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(payload["protocol"], "openspool");
+    assert_eq!(payload["version"], "1.0");
+    assert_eq!(payload["type"], "PLA");
+    assert_eq!(payload["color_hex"], "2B2B2B");
+    assert_eq!(payload["brand"], "Polymaker");
+    assert_eq!(payload["min_temp"], "190");
+    assert_eq!(payload["max_temp"], "220");
+    assert_eq!(payload["diameter"], 1.75);
+    assert_eq!(payload["weight"], 1000.0);
+}
+
+#[test]
+fn test_openspool_round_trip() {
+    // Arrange
+    let roll = create_test_filament("roll-1");
+
+    // Act
+    let draft = from_json(&to_json(&roll).unwrap()).unwrap();
+
+    // Assert
+    assert_eq!(
+        draft,
+        RollDraft {
+            name: "Polymaker PLA".to_string(),
+            material: "PLA".to_string(),
+            color: "#2B2B2B".to_string(),
+            diameter: 1.75,
+            weight: 1000.0,
+            manufacturer: "Polymaker".to_string(),
+            id: None,
+            spool_weight: None,
+        }
+    );
+}
+
+#[test]
+fn test_openspool_basic_tag_uses_defaults() {
+    // Arrange
+    let json = r#"{"protocol":"openspool","version":"1.0","type":"PETG","color_hex":"ffaabb",
+        "brand":"Generic","min_temp":"220","max_temp":"240","subtype":"Matte"}"#;
+
+    // Act
+    let draft = from_json(json).unwrap();
+
+    // Assert
+    assert_eq!(draft.name, "Generic PETG Matte");
+    assert_eq!(draft.color, "#FFAABB");
+    assert_eq!(draft.diameter, 1.75);
+    assert_eq!(draft.weight, 1000.0);
+    assert!(matches!(
+        from_json(r#"{"type":"PLA","color_hex":"000000"}"#),
+        Err(FilamentError::InvalidData(_))
+    ));
+}
+
+#[test]
+fn test_openspool_rejects_named_colours() {
+    let roll = FilamentRoll::new(
+        "Black".to_string(),
+        "PLA".to_string(),
+        "Black".to_string(),
+        1.75,
+        1000.0,
+        "Generic".to_string(),
+    )
+    .unwrap();
+
+    assert!(matches!(to_json(&roll), Err(FilamentError::InvalidData(_))));
+}
+
+#[test]
+fn test_ndef_message_layout() {
+    // Act
+    let message = mime_message("application/json", b"{}");
+
+    // Assert
+    // MB, ME and SR set with a MIME type name format
+    assert_eq!(message[0], 0xD2);
+    assert_eq!(message[1], 16);
+    assert_eq!(message[2], 2);
+    assert_eq!(&message[3..19], b"application/json");
+    assert_eq!(&message[19..], b"{}");
+
+    // Long payloads need a four-byte length
+    let long = mime_message("application/json", &[b' '; 300]);
+    assert_eq!(long[0], 0xC2);
+    assert_eq!(&long[2..6], &300u32.to_be_bytes());
+    assert_eq!(
+        mime_records(&long).unwrap(),
+        vec![("application/json".to_string(), vec![b' '; 300])]
+    );
+}
+
+#[test]
+fn test_openspool_ndef_from_tag_dump() {
+    // Arrange
+    let roll = create_test_filament("roll-1");
+    let message = to_ndef(&roll).unwrap();
+    // As read from NTAG user memory: a lock control TLV, padding, the message
+    // and unused pages after the terminator
+    let mut dump = vec![0x01, 0x03, 0xA0, 0x0C, 0x34, 0x00];
+    dump.extend(tlv_wrap(&message));
+    dump.extend([0x00; 16]);
+
+    // Act
+    let from_message = from_ndef(&message).unwrap();
+    let from_dump = from_ndef(&dump).unwrap();
+
+    // Assert
+    assert_eq!(from_message.material, "PLA");
+    assert_eq!(from_dump, from_message);
+    assert_eq!(TagFormat::detect(&dump), TagFormat::Ndef);
+    assert!(matches!(
+        from_ndef(&message[..message.len() - 4]),
+        Err(FilamentError::InvalidData(_))
+    ));
+}
+
+#[test]
+fn test_tigertag_layout() {
+    // Arrange
+    let roll = create_test_filament("roll-1");
+
+    // Act
+    let tag = encode(&roll, &ids()).unwrap();
+
+    // Assert
+    assert_eq!(tag.len(), TAG_BYTES);
+    assert_eq!(&tag[0..4], &1542820452u32.to_be_bytes());
+    assert_eq!(&tag[4..8], &[0xFF; 4]);
+    assert_eq!(&tag[8..10], &38219u16.to_be_bytes());
+    assert_eq!(tag[13], 56);
+    assert_eq!(&tag[14..16], &33812u16.to_be_bytes());
+    assert_eq!(&tag[16..20], &[0x2B, 0x2B, 0x2B, 0xFF]);
+    assert_eq!(&tag[20..23], &[0x00, 0x03, 0xE8]);
+    assert_eq!(tag[23], 21);
+    assert_eq!(&tag[24..26], &190u16.to_be_bytes());
+    assert_eq!(&tag[26..28], &220u16.to_be_bytes());
+}
+
+#[test]
+fn test_tigertag_round_trip() {
+    // Arrange
+    let roll = FilamentRoll::with_id(
+        "roll-2",
+        "Clear",
+        "PETG",
+        "#E0F0FF80",
+        2.85,
+        750.0,
+        750.0,
+        "Some Small Brand",
+        "",
+    )
+    .unwrap();
+
+    // Act
+    let draft = decode(&encode(&roll, &ids()).unwrap(), &ids()).unwrap();
+
+    // Assert
+    // Unlisted brands are written as generic
+    assert_eq!(
+        draft,
+        RollDraft {
+            name: "Generic PETG".to_string(),
+            material: "PETG".to_string(),
+            color: "#E0F0FF80".to_string(),
+            diameter: 2.85,
+            weight: 750.0,
+            manufacturer: "Generic".to_string(),
+            id: None,
+            spool_weight: None,
+        }
+    );
+}
+
+#[test]
+fn test_tigertag_needs_known_ids() {
+    // Arrange
+    let roll = create_test_filament("roll-1");
+    let mut tag = encode(&roll, &ids()).unwrap();
+
+    // Assert
+    assert!(matches!(
+        encode(&roll, &TigerTagIds::default()),
+        Err(FilamentError::InvalidData(_))
+    ));
+    assert!(matches!(
+        decode(&tag[..20], &ids()),
+        Err(FilamentError::InvalidData(_))
+    ));
+    // Kilograms are converted, lengths are refused
+    tag[20..23].copy_from_slice(&[0, 0, 2]);
+    tag[23] = 35;
+    assert_eq!(decode(&tag, &ids()).unwrap().weight, 2000.0);
+    tag[23] = 79;
+    assert!(matches!(
+        decode(&tag, &ids()),
+        Err(FilamentError::InvalidData(_))
+    ));
+}
+
+#[actix_web::test]
+async fn test_tags_api() {
+    // Arrange
+    let repository = Arc::new(InMemoryFilamentRepository::new());
+    repository.save(&create_test_filament("roll-1")).unwrap();
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(FilamentService::new(repository)))
+            .app_data(web::Data::new(ids()))
+            .configure(api::tags::configure),
+    )
+    .await;
+
+    // Act
+    let response = call_service(
+        &app,
+        TestRequest::get()
+            .uri("/api/rolls/roll-1/tag.tigertag")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let tag = read_body(response).await;
+
+    let response = call_service(
+        &app,
+        TestRequest::post()
+            .uri("/api/tags/read")
+            .set_payload(tag)
+            .to_request(),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let draft: RollDraft = read_body_json(response).await;
+    assert_eq!(draft.manufacturer, "Polymaker");
+    assert_eq!(draft.weight, 1000.0);
+
+    let response = call_service(
+        &app,
+        TestRequest::get()
+            .uri("/api/rolls/roll-1/tag.openspool")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "application/json"
+    );
+    let response = call_service(
+        &app,
+        TestRequest::get()
+            .uri("/api/rolls/roll-1/tag.mifare")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
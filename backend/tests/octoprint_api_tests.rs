@@ -0,0 +1,313 @@
+use actix_web::{test, web, App};
+use backend::api;
+use backend::domain::assignment::RollAssignments;
+use backend::domain::filament::{FilamentRepository, FilamentRoll};
+use backend::domain::printer::{Printer, Toolhead};
+use backend::domain::services::filament_service::FilamentService;
+use backend::domain::services::printer_service::PrinterService;
+use backend::infrastructure::repositories::memory::{
+    InMemoryFilamentRepository, InMemoryPrinterRepository, InMemoryRollAssignments,
+};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+// Helper function to create a test filament with ID
+fn create_test_filament(
+    id: &str,
+    material: &str,
+    diameter: f32,
+    remaining_weight: f32,
+) -> FilamentRoll {
+    FilamentRoll::with_id(
+        id,
+        id,
+        material,
+        "#000000",
+        diameter,
+        1000.0,
+        remaining_weight,
+        "Test Brand",
+        "Bin 1",
+    )
+    .expect("Failed to create test filament")
+}
+
+fn seeded_repository() -> Arc<InMemoryFilamentRepository> {
+    let repository = Arc::new(InMemoryFilamentRepository::new());
+    for roll in [
+        create_test_filament("pla-175", "PLA", 1.75, 800.0),
+        create_test_filament("petg-175", "PETG", 1.75, 500.0),
+        create_test_filament("pla-285", "PLA", 2.85, 900.0),
+        create_test_filament("empty-175", "PLA", 1.75, 0.0),
+    ] {
+        repository.save(&roll).expect("Failed to save filament");
+    }
+    repository
+}
+
+macro_rules! octoprint_app {
+    ($repository:expr) => {
+        test::init_service(
+            App::new()
+                .app_data(web::Data::new(FilamentService::transactional(
+                    $repository.clone(),
+                )))
+                .app_data(web::Data::from(
+                    Arc::new(InMemoryRollAssignments::new()) as Arc<dyn RollAssignments>
+                ))
+                .configure(api::octoprint::configure),
+        )
+        .await
+    };
+}
+
+macro_rules! select {
+    ($app:expr, $tool:expr, $roll_id:expr) => {
+        test::call_service(
+            &$app,
+            test::TestRequest::put()
+                .uri(&format!("/api/octoprint/printers/mk4/tools/{}", $tool))
+                .set_json(json!({ "roll_id": $roll_id }))
+                .to_request(),
+        )
+        .await
+    };
+}
+
+#[actix_web::test]
+async fn test_compatible_rolls_match_profile_diameter_and_material() {
+    // Arrange
+    let repository = seeded_repository();
+    let app = octoprint_app!(repository);
+
+    // Act
+    let by_diameter: Vec<Value> = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get()
+            .uri("/api/octoprint/rolls?diameter=1.75")
+            .to_request(),
+    )
+    .await;
+    let by_material: Vec<Value> = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get()
+            .uri("/api/octoprint/rolls?diameter=1.75&material=pla")
+            .to_request(),
+    )
+    .await;
+
+    // Assert
+    let ids: Vec<&str> = by_diameter
+        .iter()
+        .map(|r| r["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, vec!["petg-175", "pla-175"]);
+    assert_eq!(by_material.len(), 1);
+    assert_eq!(by_material[0]["id"], "pla-175");
+    assert_eq!(by_material[0]["percentage_remaining"], 80.0);
+}
+
+#[actix_web::test]
+async fn test_select_active_roll_per_tool() {
+    // Arrange
+    let repository = seeded_repository();
+    let app = octoprint_app!(repository);
+
+    // Act
+    let response = select!(app, 1, "petg-175");
+    let tools: Vec<Value> = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get()
+            .uri("/api/octoprint/printers/mk4/tools?tools=2")
+            .to_request(),
+    )
+    .await;
+
+    // Assert
+    assert!(response.status().is_success());
+    assert_eq!(tools.len(), 2);
+    assert_eq!(tools[0]["roll"], Value::Null);
+    assert_eq!(tools[1]["tool"], 1);
+    assert_eq!(tools[1]["roll"]["id"], "petg-175");
+}
+
+#[actix_web::test]
+async fn test_tool_list_is_capped_at_the_printers_tools() {
+    // Arrange
+    let repository = seeded_repository();
+    let printers = Arc::new(PrinterService::new(
+        Arc::new(InMemoryPrinterRepository::new()),
+        repository.clone(),
+    ));
+    printers
+        .register_printer(
+            &Printer::with_id(
+                "mk4",
+                "Prusa MK4",
+                vec![
+                    Toolhead::new("Left", 1.75).unwrap(),
+                    Toolhead::new("Right", 1.75).unwrap(),
+                ],
+            )
+            .unwrap(),
+        )
+        .expect("Failed to register printer");
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(FilamentService::transactional(
+                repository.clone(),
+            )))
+            .app_data(web::Data::from(printers as Arc<dyn RollAssignments>))
+            .configure(api::octoprint::configure),
+    )
+    .await;
+
+    // Act
+    // Other query parameters, numeric or not, are ignored
+    let tools: Vec<Value> = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get()
+            .uri("/api/octoprint/printers/mk4/tools?tools=4000000000&plugin=octoprint")
+            .to_request(),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(tools.len(), 2);
+    assert_eq!(tools[1]["tool"], 1);
+}
+
+#[actix_web::test]
+async fn test_select_unknown_roll_is_not_found() {
+    // Arrange
+    let repository = seeded_repository();
+    let app = octoprint_app!(repository);
+
+    // Act
+    let response = select!(app, 0, "missing");
+
+    // Assert
+    assert_eq!(response.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_clear_tool_selection() {
+    // Arrange
+    let repository = seeded_repository();
+    let app = octoprint_app!(repository);
+    select!(app, 0, "pla-175");
+
+    // Act
+    let response = test::call_service(
+        &app,
+        test::TestRequest::delete()
+            .uri("/api/octoprint/printers/mk4/tools/0")
+            .to_request(),
+    )
+    .await;
+    let tools: Vec<Value> = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get()
+            .uri("/api/octoprint/printers/mk4/tools")
+            .to_request(),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(response.status(), 204);
+    assert_eq!(tools[0]["roll"], Value::Null);
+}
+
+#[actix_web::test]
+async fn test_usage_report_deducts_from_selected_rolls() {
+    // Arrange
+    let repository = seeded_repository();
+    let app = octoprint_app!(repository);
+    select!(app, 0, "pla-175");
+    select!(app, 1, "petg-175");
+
+    // Act
+    let response = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/api/octoprint/printers/mk4/usage")
+            .set_json(json!({
+                "tools": [
+                    { "tool": 0, "grams": 50.0 },
+                    { "tool": 1, "length_mm": 1000.0 }
+                ]
+            }))
+            .to_request(),
+    )
+    .await;
+
+    // Assert
+    assert!(response.status().is_success());
+    assert_eq!(
+        repository.find_by_id("pla-175").unwrap().remaining_weight(),
+        750.0
+    );
+    // 1 m of 1.75 mm PETG is roughly 3 g
+    let petg = repository
+        .find_by_id("petg-175")
+        .unwrap()
+        .remaining_weight();
+    assert!((496.0..497.5).contains(&petg));
+}
+
+#[actix_web::test]
+async fn test_over_consumption_rejects_whole_report() {
+    // Arrange
+    let repository = seeded_repository();
+    let app = octoprint_app!(repository);
+    select!(app, 0, "pla-175");
+    select!(app, 1, "petg-175");
+
+    // Act
+    let response = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/api/octoprint/printers/mk4/usage")
+            .set_json(json!({
+                "tools": [
+                    { "tool": 0, "grams": 50.0 },
+                    { "tool": 1, "grams": 600.0 }
+                ]
+            }))
+            .to_request(),
+    )
+    .await;
+    let status = response.status();
+    let body: Value = test::read_body_json(response).await;
+
+    // Assert
+    assert_eq!(status, 400);
+    assert_eq!(
+        body["message"],
+        "Invalid filament data: Cannot consume 600 g from roll 'petg-175': only 500 g remaining"
+    );
+    assert_eq!(
+        repository.find_by_id("pla-175").unwrap().remaining_weight(),
+        800.0
+    );
+}
+
+#[actix_web::test]
+async fn test_usage_for_tool_without_selection_is_rejected() {
+    // Arrange
+    let repository = seeded_repository();
+    let app = octoprint_app!(repository);
+
+    // Act
+    let response = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/api/octoprint/printers/mk4/usage")
+            .set_json(json!({ "tools": [{ "tool": 0, "grams": 5.0 }] }))
+            .to_request(),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(response.status(), 400);
+}
@@ -0,0 +1,267 @@
+use backend::domain::error::FilamentError;
+use backend::domain::filament::{FilamentRepository, FilamentRoll};
+use backend::domain::print_job::PrintStatus;
+use backend::domain::printer::{Printer, PrinterRepository, Toolhead};
+use backend::domain::services::print_job_service::{MaterialWaste, PrintJobService};
+use backend::infrastructure::repositories::memory::{
+    InMemoryFilamentRepository, InMemoryPrintJobRepository, InMemoryPrinterRepository,
+};
+use std::sync::Arc;
+
+// Helper function to create a test filament with ID
+fn create_test_filament(id: &str, material: &str, remaining: f32) -> FilamentRoll {
+    FilamentRoll::with_id(
+        id,
+        "Test Filament",
+        material,
+        "#000000",
+        1.75,
+        1000.0,
+        remaining,
+        "Test Brand",
+        "Bin 1",
+    )
+    .expect("Failed to create test filament")
+}
+
+fn setup() -> (Arc<InMemoryFilamentRepository>, PrintJobService) {
+    let rolls = Arc::new(InMemoryFilamentRepository::new());
+    for roll in [
+        create_test_filament("pla", "PLA", 800.0),
+        create_test_filament("petg", "PETG", 500.0),
+        create_test_filament("pla-2", "PLA", 30.0),
+    ] {
+        rolls.save(&roll).expect("Failed to save filament");
+    }
+
+    let printers = Arc::new(InMemoryPrinterRepository::new());
+    printers
+        .save(
+            &Printer::with_id(
+                "mk4",
+                "Prusa MK4",
+                vec![Toolhead::new("Extruder", 1.75).unwrap()],
+            )
+            .expect("Failed to create test printer"),
+        )
+        .expect("Failed to save printer");
+
+    let service = PrintJobService::new(
+        Arc::new(InMemoryPrintJobRepository::new()),
+        rolls.clone(),
+        printers,
+    );
+    (rolls, service)
+}
+
+fn grams(usage: &[(&str, f32)]) -> Vec<(String, f32)> {
+    usage.iter().map(|(id, g)| (id.to_string(), *g)).collect()
+}
+
+#[test]
+fn test_start_job_records_planned_usage() {
+    // Arrange
+    let (_, service) = setup();
+
+    // Act
+    let job = service
+        .start_job(
+            "benchy.gcode",
+            "mk4",
+            &grams(&[("pla", 12.5), ("petg", 3.0)]),
+        )
+        .unwrap();
+
+    // Assert
+    assert_eq!(job.status(), PrintStatus::InProgress);
+    assert_eq!(job.usage().len(), 2);
+    assert_eq!(job.usage()[1].material, "PETG");
+    assert_eq!(job.planned_grams(), 15.5);
+    assert_eq!(service.get_job(job.id()).unwrap(), job);
+}
+
+#[test]
+fn test_successful_job_deducts_actual_usage() {
+    // Arrange
+    let (rolls, service) = setup();
+    let job = service
+        .start_job(
+            "benchy.gcode",
+            "mk4",
+            &grams(&[("pla", 12.5), ("petg", 3.0)]),
+        )
+        .unwrap();
+
+    // Act
+    let finished = service
+        .finish_job(
+            job.id(),
+            PrintStatus::Success,
+            &grams(&[("pla", 13.0), ("petg", 2.0)]),
+        )
+        .unwrap();
+
+    // Assert
+    assert_eq!(finished.status(), PrintStatus::Success);
+    assert!(finished.ended_at().is_some());
+    assert_eq!(finished.usage()[0].actual_grams, Some(13.0));
+    assert_eq!(rolls.find_by_id("pla").unwrap().remaining_weight(), 787.0);
+    assert_eq!(rolls.find_by_id("petg").unwrap().remaining_weight(), 498.0);
+}
+
+#[test]
+fn test_failed_job_deducts_partial_usage_from_progress() {
+    // Arrange
+    let (rolls, service) = setup();
+    let job = service
+        .start_job("vase.gcode", "mk4", &grams(&[("pla", 100.0)]))
+        .unwrap();
+
+    // Act
+    let finished = service
+        .finish_job_at_progress(job.id(), PrintStatus::Failed, 40.0)
+        .unwrap();
+
+    // Assert
+    assert_eq!(finished.status(), PrintStatus::Failed);
+    assert_eq!(finished.actual_grams(), 40.0);
+    assert_eq!(rolls.find_by_id("pla").unwrap().remaining_weight(), 760.0);
+}
+
+#[test]
+fn test_job_cannot_finish_twice() {
+    // Arrange
+    let (rolls, service) = setup();
+    let job = service
+        .start_job("benchy.gcode", "mk4", &grams(&[("pla", 10.0)]))
+        .unwrap();
+    service
+        .finish_job(job.id(), PrintStatus::Success, &grams(&[("pla", 10.0)]))
+        .unwrap();
+
+    // Act
+    let result = service.finish_job(job.id(), PrintStatus::Success, &grams(&[("pla", 10.0)]));
+
+    // Assert
+    assert!(matches!(result, Err(FilamentError::InvalidData(_))));
+    assert_eq!(rolls.find_by_id("pla").unwrap().remaining_weight(), 790.0);
+}
+
+#[test]
+fn test_usage_beyond_what_is_left_empties_the_roll() {
+    // Arrange
+    let (rolls, service) = setup();
+    let job = service
+        .start_job(
+            "big.gcode",
+            "mk4",
+            &grams(&[("pla", 10.0), ("pla-2", 50.0)]),
+        )
+        .unwrap();
+
+    // Act
+    let finished = service
+        .finish_job(
+            job.id(),
+            PrintStatus::Success,
+            &grams(&[("pla", 10.0), ("pla-2", 50.0)]),
+        )
+        .unwrap();
+
+    // Assert
+    assert_eq!(finished.status(), PrintStatus::Success);
+    assert_eq!(finished.actual_grams(), 60.0);
+    assert_eq!(service.get_job(job.id()).unwrap(), finished);
+    assert_eq!(rolls.find_by_id("pla").unwrap().remaining_weight(), 790.0);
+    assert_eq!(rolls.find_by_id("pla-2").unwrap().remaining_weight(), 0.0);
+}
+
+#[test]
+fn test_rejected_deduction_leaves_job_and_rolls_unchanged() {
+    // Arrange
+    let (rolls, service) = setup();
+    let job = service
+        .start_job("big.gcode", "mk4", &grams(&[("pla", 10.0), ("petg", 5.0)]))
+        .unwrap();
+    rolls.delete("petg").unwrap();
+
+    // Act
+    let result = service.finish_job(
+        job.id(),
+        PrintStatus::Success,
+        &grams(&[("pla", 10.0), ("petg", 5.0)]),
+    );
+
+    // Assert
+    assert!(matches!(result, Err(FilamentError::NotFound(_))));
+    assert_eq!(
+        service.get_job(job.id()).unwrap().status(),
+        PrintStatus::InProgress
+    );
+    assert_eq!(rolls.find_by_id("pla").unwrap().remaining_weight(), 800.0);
+}
+
+#[test]
+fn test_job_on_unknown_printer_is_rejected() {
+    // Arrange
+    let (_, service) = setup();
+
+    // Act
+    let result = service.start_job("benchy.gcode", "ender", &grams(&[("pla", 10.0)]));
+
+    // Assert
+    assert!(matches!(result, Err(FilamentError::Missing(_))));
+    assert!(service.list_jobs().unwrap().is_empty());
+}
+
+#[test]
+fn test_waste_report_groups_failed_usage_by_material() {
+    // Arrange
+    let (_, service) = setup();
+    let failed = service
+        .start_job("a.gcode", "mk4", &grams(&[("pla", 50.0), ("petg", 20.0)]))
+        .unwrap();
+    service
+        .finish_job(
+            failed.id(),
+            PrintStatus::Failed,
+            &grams(&[("pla", 8.0), ("petg", 2.0)]),
+        )
+        .unwrap();
+    let cancelled = service
+        .start_job("b.gcode", "mk4", &grams(&[("pla-2", 20.0)]))
+        .unwrap();
+    service
+        .finish_job(
+            cancelled.id(),
+            PrintStatus::Cancelled,
+            &grams(&[("pla-2", 5.0)]),
+        )
+        .unwrap();
+    let success = service
+        .start_job("c.gcode", "mk4", &grams(&[("pla", 30.0)]))
+        .unwrap();
+    service
+        .finish_job(success.id(), PrintStatus::Success, &grams(&[("pla", 30.0)]))
+        .unwrap();
+
+    // Act
+    let waste = service.waste_by_material().unwrap();
+
+    // Assert
+    assert_eq!(
+        waste,
+        vec![
+            MaterialWaste {
+                material: "PETG".to_string(),
+                jobs: 1,
+                grams: 2.0
+            },
+            MaterialWaste {
+                material: "PLA".to_string(),
+                jobs: 2,
+                grams: 13.0
+            },
+        ]
+    );
+}
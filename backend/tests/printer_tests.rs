@@ -0,0 +1,248 @@
+use actix_web::test::{call_and_read_body_json, call_service, init_service, TestRequest};
+use actix_web::{web, App};
+use backend::api;
+use backend::domain::assignment::RollAssignments;
+use backend::domain::error::FilamentError;
+use backend::domain::filament::{FilamentRepository, FilamentRoll};
+use backend::domain::printer::{Printer, Toolhead};
+use backend::domain::services::printer_service::{PrinterService, RollLocation};
+use backend::infrastructure::repositories::memory::{
+    InMemoryFilamentRepository, InMemoryPrinterRepository,
+};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+// Helper function to create a test filament with ID
+fn create_test_filament(id: &str, diameter: f32) -> FilamentRoll {
+    FilamentRoll::with_id(
+        id,
+        "Test Filament",
+        "PLA",
+        "#000000",
+        diameter,
+        1000.0,
+        800.0,
+        "Test Brand",
+        "Bin 1",
+    )
+    .expect("Failed to create test filament")
+}
+
+fn create_test_printer(id: &str) -> Printer {
+    Printer::with_id(
+        id,
+        "Test Printer",
+        vec![
+            Toolhead::new("AMS slot 1", 1.75).unwrap(),
+            Toolhead::new("AMS slot 2", 1.75).unwrap(),
+        ],
+    )
+    .expect("Failed to create test printer")
+}
+
+fn create_test_service() -> PrinterService {
+    let rolls = Arc::new(InMemoryFilamentRepository::new());
+    for roll in [
+        create_test_filament("roll-1", 1.75),
+        create_test_filament("roll-2", 1.75),
+        create_test_filament("thick", 2.85),
+    ] {
+        rolls.save(&roll).expect("Failed to save filament");
+    }
+
+    let service = PrinterService::new(Arc::new(InMemoryPrinterRepository::new()), rolls);
+    service
+        .register_printer(&create_test_printer("x1c"))
+        .expect("Failed to register printer");
+    service
+        .register_printer(&create_test_printer("mk4"))
+        .expect("Failed to register printer");
+    service
+}
+
+#[test]
+fn test_printer_requires_a_toolhead() {
+    // Act
+    let result = Printer::with_id("empty", "Empty", Vec::new());
+
+    // Assert
+    assert!(matches!(result, Err(FilamentError::InvalidData(_))));
+}
+
+#[test]
+fn test_load_roll_on_toolhead() {
+    // Arrange
+    let service = create_test_service();
+
+    // Act
+    let printer = service.load_roll("x1c", 1, "roll-1").unwrap();
+
+    // Assert
+    assert_eq!(printer.toolhead(0).unwrap().loaded_roll(), None);
+    assert_eq!(printer.toolhead(1).unwrap().loaded_roll(), Some("roll-1"));
+    assert_eq!(
+        service.find_roll_location("roll-1").unwrap(),
+        Some(RollLocation {
+            printer_id: "x1c".to_string(),
+            tool: 1
+        })
+    );
+}
+
+#[test]
+fn test_load_rejects_mismatched_diameter() {
+    // Arrange
+    let service = create_test_service();
+
+    // Act
+    let result = service.load_roll("x1c", 0, "thick");
+
+    // Assert
+    assert!(matches!(result, Err(FilamentError::InvalidData(_))));
+    assert_eq!(
+        service
+            .get_printer("x1c")
+            .unwrap()
+            .toolhead(0)
+            .unwrap()
+            .loaded_roll(),
+        None
+    );
+}
+
+#[test]
+fn test_roll_cannot_be_loaded_in_two_places() {
+    // Arrange
+    let service = create_test_service();
+    service.load_roll("x1c", 0, "roll-1").unwrap();
+
+    // Act
+    let same_printer = service.load_roll("x1c", 1, "roll-1");
+    let other_printer = service.load_roll("mk4", 0, "roll-1");
+    let reload = service.load_roll("x1c", 0, "roll-1");
+
+    // Assert
+    assert!(matches!(same_printer, Err(FilamentError::InvalidData(_))));
+    assert!(matches!(other_printer, Err(FilamentError::InvalidData(_))));
+    assert!(reload.is_ok());
+}
+
+#[test]
+fn test_unloaded_roll_can_move_to_another_printer() {
+    // Arrange
+    let service = create_test_service();
+    service.load_roll("x1c", 0, "roll-1").unwrap();
+
+    // Act
+    service.unload_roll("x1c", 0).unwrap();
+    let result = service.load_roll("mk4", 0, "roll-1");
+
+    // Assert
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_unknown_tool_is_not_found() {
+    // Arrange
+    let service = create_test_service();
+
+    // Act
+    let result = service.load_roll("x1c", 5, "roll-1");
+
+    // Assert
+    assert!(matches!(result, Err(FilamentError::Missing(_))));
+}
+
+#[test]
+fn test_not_found_messages_name_what_is_missing() {
+    // Arrange
+    let service = create_test_service();
+
+    // Act
+    let printer = service.get_printer("prusa-xl").unwrap_err();
+    let tool = service.load_roll("x1c", 5, "roll-1").unwrap_err();
+    let roll = service.load_roll("x1c", 0, "missing").unwrap_err();
+
+    // Assert
+    assert_eq!(printer.to_string(), "Printer 'prusa-xl' not found");
+    assert_eq!(tool.to_string(), "Printer 'x1c' has no tool 5");
+    assert_eq!(roll.to_string(), "Filament with id 'missing' not found");
+}
+
+#[test]
+fn test_roll_assignments_apply_printer_checks() {
+    // Arrange
+    let service = create_test_service();
+    let assignments: Arc<dyn RollAssignments> = Arc::new(service.clone());
+
+    // Act
+    assignments.load_roll("x1c", 0, "roll-2").unwrap();
+    let mismatched = assignments.load_roll("x1c", 1, "thick");
+    let duplicate = assignments.load_roll("mk4", 1, "roll-2");
+
+    // Assert
+    assert_eq!(
+        assignments.loaded_roll("x1c", 0).unwrap(),
+        Some("roll-2".to_string())
+    );
+    assert!(matches!(mismatched, Err(FilamentError::InvalidData(_))));
+    assert!(matches!(duplicate, Err(FilamentError::InvalidData(_))));
+}
+
+#[test]
+fn test_register_duplicate_printer_fails() {
+    // Arrange
+    let service = create_test_service();
+
+    // Act
+    let result = service.register_printer(&create_test_printer("x1c"));
+
+    // Assert
+    assert!(matches!(result, Err(FilamentError::InvalidData(_))));
+}
+
+#[actix_web::test]
+async fn test_register_and_load_through_api() {
+    // Arrange
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(create_test_service()))
+            .configure(api::printers::configure),
+    )
+    .await;
+
+    // Act
+    let created = call_service(
+        &app,
+        TestRequest::post()
+            .uri("/api/printers")
+            .set_json(json!({
+                "id": "voron",
+                "name": "Voron 2.4",
+                "toolheads": [{ "name": "Extruder", "filament_diameter": 1.75 }]
+            }))
+            .to_request(),
+    )
+    .await;
+    let loaded: Value = call_and_read_body_json(
+        &app,
+        TestRequest::put()
+            .uri("/api/printers/voron/tools/0")
+            .set_json(json!({ "roll_id": "roll-1" }))
+            .to_request(),
+    )
+    .await;
+    let mismatched = call_service(
+        &app,
+        TestRequest::put()
+            .uri("/api/printers/x1c/tools/0")
+            .set_json(json!({ "roll_id": "thick" }))
+            .to_request(),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(created.status(), 201);
+    assert_eq!(loaded["toolheads"][0]["loaded_roll"], "roll-1");
+    assert_eq!(mismatched.status(), 400);
+}
@@ -0,0 +1,312 @@
+use actix_web::http::StatusCode;
+use actix_web::test::{call_service, init_service, read_body_json, TestRequest};
+use actix_web::{web, App};
+use backend::api;
+use backend::domain::error::FilamentError;
+use backend::domain::filament::{FilamentRepository, FilamentRoll};
+use backend::domain::product::{Product, ProductCatalogue};
+use backend::domain::services::filament_service::FilamentService;
+use backend::domain::services::product_service::ProductService;
+use backend::infrastructure::import::ImportMode;
+use backend::infrastructure::repositories::memory::{
+    InMemoryFilamentRepository, InMemoryProductCatalogue,
+};
+use backend::infrastructure::spoolman::catalogue::import_products;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::thread;
+
+const COMPILED_JSON: &str = include_str!("fixtures/spoolmandb_filaments.json");
+const MANUFACTURER_JSON: &str = include_str!("fixtures/spoolmandb_polymaker.json");
+
+fn setup() -> (
+    ProductService,
+    Arc<InMemoryProductCatalogue>,
+    Arc<InMemoryFilamentRepository>,
+) {
+    let products = Arc::new(InMemoryProductCatalogue::new());
+    let rolls = Arc::new(InMemoryFilamentRepository::new());
+    let service = ProductService::new(products.clone(), FilamentService::new(rolls.clone()));
+    (service, products, rolls)
+}
+
+#[test]
+fn test_import_compiled_spoolman_db() {
+    // Arrange
+    let catalogue = InMemoryProductCatalogue::new();
+
+    // Act
+    let report =
+        import_products(&catalogue, COMPILED_JSON, ImportMode::Apply).expect("Failed to import");
+
+    // Assert
+    assert_eq!(report.imported.len(), 3);
+    let product = catalogue
+        .find_by_id("prusament_petg_prusaorange_1000_175_n")
+        .expect("Failed to find imported product");
+    assert_eq!(product.manufacturer(), "Prusament");
+    assert_eq!(product.name(), "Prusa Orange");
+    assert_eq!(product.material(), "PETG");
+    assert_eq!(product.color(), "#FF8E22");
+    assert_eq!(product.net_weight(), 1000.0);
+    assert_eq!(product.spool_weight(), Some(201.0));
+    assert_eq!(product.ean(), Some("8594173675063"));
+
+    // Multicolour filaments take their first colour
+    let rainbow = catalogue
+        .find_by_id("esun_pla_silkrainbow_1000_175_n")
+        .unwrap();
+    assert_eq!(rainbow.color(), "#E63946");
+    assert_eq!(rainbow.spool_weight(), None);
+
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].row, 4);
+    assert!(report.failed[0].message.contains("diameter"));
+}
+
+#[test]
+fn test_import_manufacturer_file_expands_variants() {
+    // Arrange
+    let catalogue = InMemoryProductCatalogue::new();
+
+    // Act
+    let report = import_products(&catalogue, MANUFACTURER_JSON, ImportMode::Apply)
+        .expect("Failed to import");
+
+    // Assert
+    // Two colours, two spool sizes and two diameters, plus one single variant
+    assert_eq!(report.imported.len(), 9);
+    let black = catalogue
+        .find_by_id("polymaker_pla_polyterraplacharcoalblack_1000_175")
+        .expect("Failed to find expanded product");
+    assert_eq!(black.name(), "PolyTerra PLA Charcoal Black");
+    assert_eq!(black.product_line(), Some("PolyTerra PLA"));
+    assert_eq!(black.color(), "#2B2B2B");
+    let red = catalogue
+        .find_by_ean("6938936712305")
+        .expect("Failed to find product by barcode");
+    assert_eq!(red.name(), "Panchroma Matte PLA Muted Red");
+    assert_eq!(
+        catalogue
+            .find_by_id("polymaker_pla_polyterraplacottonwhite_3000_285")
+            .unwrap()
+            .spool_weight(),
+        Some(480.0)
+    );
+
+    // A filament without spool sizes cannot be expanded
+    assert_eq!(report.failed.len(), 1);
+    assert!(report.failed[0].message.contains("PolyLite PETG"));
+}
+
+#[test]
+fn test_import_again_updates_products() {
+    // Arrange
+    let catalogue = InMemoryProductCatalogue::new();
+    import_products(&catalogue, COMPILED_JSON, ImportMode::Apply).unwrap();
+
+    // Act
+    let report =
+        import_products(&catalogue, COMPILED_JSON, ImportMode::Apply).expect("Failed to import");
+
+    // Assert
+    assert!(report.imported.is_empty());
+    assert_eq!(report.updated.len(), 3);
+    assert_eq!(catalogue.find_all().unwrap().len(), 3);
+}
+
+#[test]
+fn test_import_dry_run_writes_nothing() {
+    // Arrange
+    let catalogue = InMemoryProductCatalogue::new();
+
+    // Act
+    let report =
+        import_products(&catalogue, COMPILED_JSON, ImportMode::DryRun).expect("Failed to import");
+
+    // Assert
+    assert!(report.dry_run);
+    assert_eq!(report.imported.len(), 3);
+    assert!(catalogue.find_all().unwrap().is_empty());
+}
+
+#[test]
+fn test_import_rejects_other_json() {
+    let catalogue = InMemoryProductCatalogue::new();
+
+    let result = import_products(&catalogue, r#"{"spools": []}"#, ImportMode::Apply);
+
+    assert!(matches!(result, Err(FilamentError::InvalidData(_))));
+}
+
+#[test]
+fn test_product_validation() {
+    assert!(Product::new("Prusament", "Galaxy Black", "PLA", "", 1.75, 1000.0).is_err());
+    assert!(Product::new("Prusament", "Galaxy Black", "PLA", "#3D3E3D", 1.75, 0.0).is_err());
+    let product =
+        Product::new("Prusament", "Galaxy Black", "PLA", "#3D3E3D", 1.75, 1000.0).unwrap();
+    assert!(product.clone().with_spool_weight(-1.0).is_err());
+    assert!(product.with_ean("4006381333932").is_err());
+}
+
+#[test]
+fn test_add_product_rejects_duplicate_barcode() {
+    // Arrange
+    let (service, _, _) = setup();
+    let product = Product::new("Prusament", "Galaxy Black", "PLA", "#3D3E3D", 1.75, 1000.0)
+        .unwrap()
+        .with_ean("4006381333931")
+        .unwrap();
+    service.add_product(&product).unwrap();
+    let other = Product::new("Prusament", "Jet Black", "PLA", "#000000", 1.75, 1000.0)
+        .unwrap()
+        .with_ean("4006381333931")
+        .unwrap();
+
+    // Act
+    let result = service.add_product(&other);
+
+    // Assert
+    assert!(matches!(result, Err(FilamentError::InvalidData(_))));
+    assert!(matches!(
+        service.add_product(&product),
+        Err(FilamentError::InvalidData(_))
+    ));
+}
+
+#[test]
+fn test_concurrent_adds_cannot_share_a_barcode() {
+    // Arrange
+    let (service, products, _) = setup();
+
+    // Act
+    let adders: Vec<_> = (0..20)
+        .map(|i| {
+            let service = service.clone();
+            thread::spawn(move || {
+                let product = Product::new(
+                    "Prusament",
+                    &format!("Colour {}", i),
+                    "PLA",
+                    "#3D3E3D",
+                    1.75,
+                    1000.0,
+                )
+                .unwrap()
+                .with_ean("4006381333931")
+                .unwrap();
+                service.add_product(&product)
+            })
+        })
+        .collect();
+    let added = adders
+        .into_iter()
+        .map(|adder| adder.join().expect("Adder panicked"))
+        .filter(Result::is_ok)
+        .count();
+
+    // Assert
+    assert_eq!(added, 1);
+    assert_eq!(products.find_all().unwrap().len(), 1);
+}
+
+#[test]
+fn test_create_roll_from_product() {
+    // Arrange
+    let (service, products, rolls) = setup();
+    import_products(products.as_ref(), COMPILED_JSON, ImportMode::Apply).unwrap();
+
+    // Act
+    let full = service
+        .create_roll("polymaker_pla_polyliteplablack_1000_175_n", "Shelf A", None)
+        .expect("Failed to create roll");
+    let opened = service
+        .create_roll("polymaker_pla_polyliteplablack_1000_175_n", "", Some(420.0))
+        .expect("Failed to create roll");
+
+    // Assert
+    assert_eq!(full.name(), "PolyLite PLA Black");
+    assert_eq!(full.material(), "PLA");
+    assert_eq!(full.color(), "#000000");
+    assert_eq!(full.diameter(), 1.75);
+    assert_eq!(full.weight(), 1000.0);
+    assert_eq!(full.remaining_weight(), 1000.0);
+    assert_eq!(full.manufacturer(), "Polymaker");
+    assert_eq!(full.storage_location(), "Shelf A");
+    assert_eq!(full.spool_weight(), Some(140.0));
+    assert_eq!(opened.remaining_weight(), 420.0);
+    assert_ne!(full.id(), opened.id());
+    assert_eq!(rolls.find_all().unwrap().len(), 2);
+    assert!(matches!(
+        service.create_roll("missing", "", None),
+        Err(FilamentError::Missing(_))
+    ));
+}
+
+#[actix_web::test]
+async fn test_products_api() {
+    // Arrange
+    let (service, products, _) = setup();
+    let catalogue: Arc<dyn ProductCatalogue> = products;
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(service))
+            .app_data(web::Data::from(catalogue))
+            .configure(api::products::configure),
+    )
+    .await;
+
+    // Act
+    let response = call_service(
+        &app,
+        TestRequest::post()
+            .uri("/api/products/import")
+            .set_payload(MANUFACTURER_JSON)
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let report: Value = read_body_json(response).await;
+    assert_eq!(report["imported"].as_array().unwrap().len(), 9);
+
+    let response = call_service(
+        &app,
+        TestRequest::post()
+            .uri("/api/products")
+            .set_json(json!({
+                "manufacturer": "Prusament",
+                "name": "Galaxy Black",
+                "material": "PLA",
+                "color": "#3D3E3D",
+                "diameter": 1.75,
+                "net_weight": 1000.0,
+                "spool_weight": 201.0,
+                "ean": "4006381333931"
+            }))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let product: Product = read_body_json(response).await;
+
+    let response = call_service(
+        &app,
+        TestRequest::post()
+            .uri(&format!("/api/products/{}/rolls", product.id()))
+            .set_json(json!({ "storage_location": "Dry box" }))
+            .to_request(),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let roll: FilamentRoll = read_body_json(response).await;
+    assert_eq!(roll.name(), "Galaxy Black");
+    assert_eq!(roll.storage_location(), "Dry box");
+
+    let response = call_service(&app, TestRequest::get().uri("/api/products").to_request()).await;
+    let listed: Vec<Product> = read_body_json(response).await;
+    assert_eq!(listed.len(), 10);
+    assert_eq!(listed[0].manufacturer(), "Polymaker");
+    assert_eq!(listed[9], product);
+}
@@ -0,0 +1,197 @@
+use backend::domain::error::FilamentError;
+use backend::domain::filament::{FilamentRepository, FilamentRoll};
+use backend::domain::reservation::{Reservation, ReservationRepository};
+use backend::domain::services::filament_service::FilamentService;
+use backend::infrastructure::repositories::memory::{
+    InMemoryFilamentRepository, InMemoryReservationRepository,
+};
+use chrono::{Duration, Utc};
+use serde_json::json;
+use std::sync::Arc;
+
+// Helper function to create a test filament with ID
+fn create_test_filament(id: &str, remaining: f32) -> FilamentRoll {
+    FilamentRoll::with_id(
+        id,
+        "Test Filament",
+        "PLA",
+        "#000000",
+        1.75,
+        1000.0,
+        remaining,
+        "Test Brand",
+        "Bin 1",
+    )
+    .expect("Failed to create test filament")
+}
+
+fn setup() -> (
+    Arc<InMemoryFilamentRepository>,
+    Arc<InMemoryReservationRepository>,
+    FilamentService,
+) {
+    let rolls = Arc::new(InMemoryFilamentRepository::new());
+    rolls
+        .save(&create_test_filament("roll-1", 300.0))
+        .expect("Failed to save filament");
+    rolls
+        .save(&create_test_filament("roll-2", 900.0))
+        .expect("Failed to save filament");
+    let reservations = Arc::new(InMemoryReservationRepository::new());
+    let service = FilamentService::new(rolls.clone()).with_reservations(reservations.clone());
+    (rolls, reservations, service)
+}
+
+fn in_hours(hours: i64) -> chrono::DateTime<Utc> {
+    Utc::now() + Duration::hours(hours)
+}
+
+#[test]
+fn test_reservation_reduces_available_weight() {
+    // Arrange
+    let (rolls, _, service) = setup();
+
+    // Act
+    service
+        .reserve("roll-1", 200.0, "helmet.gcode", in_hours(12))
+        .unwrap();
+
+    // Assert
+    assert_eq!(service.available_weight("roll-1").unwrap(), 100.0);
+    // The filament is still on the roll
+    assert_eq!(
+        rolls.find_by_id("roll-1").unwrap().remaining_weight(),
+        300.0
+    );
+}
+
+#[test]
+fn test_two_jobs_cannot_reserve_the_same_grams() {
+    // Arrange
+    let (_, _, service) = setup();
+    service
+        .reserve("roll-1", 200.0, "first.gcode", in_hours(12))
+        .unwrap();
+
+    // Act
+    let result = service.reserve("roll-1", 200.0, "second.gcode", in_hours(12));
+
+    // Assert
+    match result {
+        Err(FilamentError::InvalidData(message)) => {
+            assert_eq!(
+                message,
+                "Cannot reserve 200 g on roll 'roll-1': only 100 g available"
+            );
+        }
+        other => panic!("Expected InvalidData, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_expired_reservations_are_ignored() {
+    // Arrange
+    let (_, reservations, service) = setup();
+    let expired: Reservation = serde_json::from_value(json!({
+        "id": "old",
+        "roll_id": "roll-1",
+        "grams": 250.0,
+        "purpose": "stale.gcode",
+        "created_at": (Utc::now() - Duration::days(2)).to_rfc3339(),
+        "expires_at": (Utc::now() - Duration::days(1)).to_rfc3339(),
+    }))
+    .unwrap();
+    reservations.save(&expired).unwrap();
+
+    // Act
+    let available = service.available_weight("roll-1").unwrap();
+
+    // Assert
+    assert_eq!(available, 300.0);
+    assert!(service.reservations_for("roll-1").unwrap().is_empty());
+}
+
+#[test]
+fn test_reservation_must_expire_in_future() {
+    // Act
+    let result = Reservation::new("roll-1", 10.0, "late.gcode", in_hours(-1));
+
+    // Assert
+    assert!(matches!(result, Err(FilamentError::InvalidData(_))));
+}
+
+#[test]
+fn test_low_inventory_considers_reservations() {
+    // Arrange
+    let (_, _, service) = setup();
+    let before: Vec<String> = service
+        .get_low_inventory()
+        .unwrap()
+        .iter()
+        .map(|r| r.id().to_string())
+        .collect();
+
+    // Act
+    service
+        .reserve("roll-2", 750.0, "cosplay.gcode", in_hours(48))
+        .unwrap();
+    let mut after: Vec<String> = service
+        .get_low_inventory()
+        .unwrap()
+        .iter()
+        .map(|r| r.id().to_string())
+        .collect();
+    after.sort();
+
+    // Assert
+    assert!(before.is_empty());
+    assert_eq!(after, vec!["roll-2"]);
+}
+
+#[test]
+fn test_consume_reservation_deducts_and_releases() {
+    // Arrange
+    let (_, _, service) = setup();
+    let reservation = service
+        .reserve("roll-1", 200.0, "helmet.gcode", in_hours(12))
+        .unwrap();
+
+    // Act
+    let roll = service
+        .consume_reservation(reservation.id(), 180.0)
+        .unwrap();
+
+    // Assert
+    assert_eq!(roll.remaining_weight(), 120.0);
+    assert_eq!(service.available_weight("roll-1").unwrap(), 120.0);
+}
+
+#[test]
+fn test_release_reservation_frees_grams() {
+    // Arrange
+    let (_, _, service) = setup();
+    let reservation = service
+        .reserve("roll-1", 200.0, "helmet.gcode", in_hours(12))
+        .unwrap();
+
+    // Act
+    service.release_reservation(reservation.id()).unwrap();
+
+    // Assert
+    assert_eq!(service.available_weight("roll-1").unwrap(), 300.0);
+}
+
+#[test]
+fn test_reserve_without_reservation_store_fails() {
+    // Arrange
+    let rolls = Arc::new(InMemoryFilamentRepository::new());
+    rolls.save(&create_test_filament("roll-1", 300.0)).unwrap();
+    let service = FilamentService::new(rolls);
+
+    // Act
+    let result = service.reserve("roll-1", 10.0, "a.gcode", in_hours(1));
+
+    // Assert
+    assert!(matches!(result, Err(FilamentError::RepositoryError(_))));
+    assert_eq!(service.available_weight("roll-1").unwrap(), 300.0);
+}
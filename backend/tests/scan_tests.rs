@@ -0,0 +1,310 @@
+use actix_web::http::StatusCode;
+use actix_web::test::{call_service, init_service, read_body_json, TestRequest};
+use actix_web::{web, App};
+use backend::api;
+use backend::domain::error::FilamentError;
+use backend::domain::filament::{FilamentRepository, FilamentRoll};
+use backend::domain::product::{normalize_barcode, Product, ProductCatalogue};
+use backend::domain::scan::{roll_payload, RollDraft, ScanCode, ScanResult};
+use backend::domain::services::filament_service::{FilamentService, Registration};
+use backend::domain::services::scan_service::ScanService;
+use backend::infrastructure::repositories::memory::{
+    InMemoryFilamentRepository, InMemoryProductCatalogue,
+};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::thread;
+
+// Helper function to create a test filament with ID
+fn create_test_filament(id: &str) -> FilamentRoll {
+    FilamentRoll::with_id(
+        id,
+        "Galaxy Black",
+        "PLA",
+        "#000000",
+        1.75,
+        1000.0,
+        800.0,
+        "Test Brand",
+        "Shelf A",
+    )
+    .expect("Failed to create test filament")
+}
+
+fn create_test_product(ean: &str) -> Product {
+    Product::new("Prusament", "Galaxy Black", "PLA", "#3D3E3D", 1.75, 1000.0)
+        .unwrap()
+        .with_ean(ean)
+        .unwrap()
+}
+
+fn setup() -> (
+    ScanService,
+    Arc<InMemoryFilamentRepository>,
+    Arc<InMemoryProductCatalogue>,
+) {
+    let rolls = Arc::new(InMemoryFilamentRepository::new());
+    let products = Arc::new(InMemoryProductCatalogue::new());
+    let service = ScanService::new(FilamentService::new(rolls.clone()), products.clone());
+    (service, rolls, products)
+}
+
+#[test]
+fn test_normalize_barcode_checks_digits() {
+    // EAN-13
+    assert_eq!(
+        normalize_barcode("4006381333931"),
+        Some("4006381333931".to_string())
+    );
+    // UPC-A becomes the equivalent EAN-13
+    assert_eq!(
+        normalize_barcode("0 36000 29145 2"),
+        Some("0036000291452".to_string())
+    );
+    // EAN-8
+    assert_eq!(normalize_barcode("96385074"), Some("96385074".to_string()));
+    // Wrong check digit, wrong length, not digits
+    assert_eq!(normalize_barcode("4006381333932"), None);
+    assert_eq!(normalize_barcode("400638133393"), None);
+    assert_eq!(normalize_barcode("roll-1"), None);
+}
+
+#[test]
+fn test_scan_code_recognises_our_labels() {
+    assert_eq!(
+        ScanCode::parse(&roll_payload("roll-1", None)),
+        ScanCode::Roll("roll-1".to_string())
+    );
+    assert_eq!(
+        ScanCode::parse(&roll_payload(
+            "roll-1",
+            Some("http://printfarm.local:8080/")
+        )),
+        ScanCode::Roll("roll-1".to_string())
+    );
+    // Links keep working when the host changes or extra parts are added
+    assert_eq!(
+        ScanCode::parse("https://example.com/app/rolls/roll-1/?from=label#top"),
+        ScanCode::Roll("roll-1".to_string())
+    );
+    assert_eq!(
+        ScanCode::parse("036000291452"),
+        ScanCode::Barcode("0036000291452".to_string())
+    );
+    assert_eq!(
+        ScanCode::parse(" roll-1\n"),
+        ScanCode::Text("roll-1".to_string())
+    );
+}
+
+#[test]
+fn test_resolve_finds_rolls_by_label_and_id() {
+    // Arrange
+    let (service, rolls, _) = setup();
+    let roll = create_test_filament("roll-1");
+    rolls.save(&roll).unwrap();
+
+    // Act
+    let from_label = service.resolve(&roll_payload("roll-1", None)).unwrap();
+    let from_id = service.resolve("roll-1").unwrap();
+
+    // Assert
+    assert_eq!(from_label, ScanResult::Roll { roll: roll.clone() });
+    assert_eq!(from_id, ScanResult::Roll { roll });
+}
+
+#[test]
+fn test_resolve_unknown_roll_is_not_found() {
+    // Arrange
+    let (service, _, _) = setup();
+
+    // Act
+    let result = service.resolve(&roll_payload("missing", None));
+
+    // Assert
+    assert!(matches!(result, Err(FilamentError::NotFound(_))));
+    assert!(matches!(
+        service.resolve("  "),
+        Err(FilamentError::InvalidData(_))
+    ));
+}
+
+#[test]
+fn test_resolve_catalogued_barcode_drafts_a_roll() {
+    // Arrange
+    let (service, _, products) = setup();
+    let product = create_test_product("4006381333931");
+    products.save(&product).unwrap();
+
+    // Act
+    let result = service.resolve("4006381333931").unwrap();
+
+    // Assert
+    let ScanResult::Product {
+        product: found,
+        draft,
+    } = result
+    else {
+        panic!("Expected a product, got {:?}", result);
+    };
+    assert_eq!(found, product);
+    assert_eq!(
+        draft,
+        RollDraft {
+            name: "Galaxy Black".to_string(),
+            material: "PLA".to_string(),
+            color: "#3D3E3D".to_string(),
+            diameter: 1.75,
+            weight: 1000.0,
+            manufacturer: "Prusament".to_string(),
+            id: None,
+            spool_weight: None,
+        }
+    );
+}
+
+#[test]
+fn test_resolve_upc_matches_product_stored_as_ean() {
+    // Arrange
+    let (service, _, products) = setup();
+    products
+        .save(&create_test_product("0036000291452"))
+        .unwrap();
+
+    // Act
+    let result = service.resolve("036000291452").unwrap();
+
+    // Assert
+    assert!(matches!(result, ScanResult::Product { .. }));
+}
+
+#[test]
+fn test_resolve_uncatalogued_barcode() {
+    // Arrange
+    let (service, rolls, _) = setup();
+    // Numeric roll ids still resolve to their roll
+    rolls.save(&create_test_filament("96385074")).unwrap();
+
+    // Act
+    let unknown = service.resolve("4006381333931").unwrap();
+    let numeric_id = service.resolve("96385074").unwrap();
+
+    // Assert
+    assert_eq!(
+        unknown,
+        ScanResult::UnknownBarcode {
+            barcode: "4006381333931".to_string()
+        }
+    );
+    assert!(matches!(numeric_id, ScanResult::Roll { .. }));
+}
+
+#[test]
+fn test_register_saves_full_roll() {
+    // Arrange
+    let (service, rolls, _) = setup();
+    let product = create_test_product("4006381333931");
+
+    // Act
+    let registration = service
+        .register(RollDraft::from(&product), "Dry box 2")
+        .unwrap();
+
+    // Assert
+    let Registration::Created(roll) = registration else {
+        panic!("Expected a new roll");
+    };
+    assert_eq!(rolls.find_by_id(roll.id()).unwrap(), roll);
+    assert_eq!(roll.remaining_weight(), 1000.0);
+    assert_eq!(roll.storage_location(), "Dry box 2");
+    assert_eq!(roll.manufacturer(), "Prusament");
+}
+
+#[actix_web::test]
+async fn test_scan_then_register_over_http() {
+    // Arrange
+    let (service, rolls, products) = setup();
+    products
+        .save(&create_test_product("4006381333931"))
+        .unwrap();
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(service))
+            .configure(api::scan::configure),
+    )
+    .await;
+
+    // Act
+    let response = call_service(
+        &app,
+        TestRequest::post()
+            .uri("/api/scan")
+            .set_json(json!({ "payload": "4006381333931" }))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let scanned: Value = read_body_json(response).await;
+    assert_eq!(scanned["kind"], "product");
+
+    let mut confirm = scanned["draft"].clone();
+    confirm["color"] = json!("#000000");
+    confirm["storage_location"] = json!("Shelf B");
+    let response = call_service(
+        &app,
+        TestRequest::post()
+            .uri("/api/scan/register")
+            .set_json(&confirm)
+            .to_request(),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let roll: FilamentRoll = read_body_json(response).await;
+    assert_eq!(roll.color(), "#000000");
+    assert_eq!(roll.storage_location(), "Shelf B");
+    assert_eq!(rolls.find_by_id(roll.id()).unwrap(), roll);
+
+    let response = call_service(
+        &app,
+        TestRequest::post()
+            .uri("/api/scan")
+            .set_json(json!({ "payload": "filament-tracker:roll:unknown" }))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[test]
+fn test_concurrent_registrations_of_one_tag_create_one_roll() {
+    // Arrange
+    let (service, rolls, _) = setup();
+    let draft = RollDraft {
+        id: Some("tag-1".to_string()),
+        ..RollDraft::from(&create_test_product("4006381333931"))
+    };
+
+    // Act
+    let scanners: Vec<_> = (0..20)
+        .map(|_| {
+            let service = service.clone();
+            let draft = draft.clone();
+            thread::spawn(move || service.register(draft, "AMS 1"))
+        })
+        .collect();
+    let registrations: Vec<Registration> = scanners
+        .into_iter()
+        .map(|scanner| scanner.join().expect("Scanner panicked").unwrap())
+        .collect();
+
+    // Assert
+    let created = registrations
+        .iter()
+        .filter(|r| matches!(r, Registration::Created(_)))
+        .count();
+    assert_eq!(created, 1);
+    assert!(registrations.iter().all(|r| r.roll().id() == "tag-1"));
+    assert_eq!(rolls.find_all().unwrap().len(), 1);
+}
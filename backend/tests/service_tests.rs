@@ -1,12 +1,14 @@
 use backend::domain::filament::{FilamentRepository, FilamentRoll};
 use backend::domain::services::filament_service::FilamentService;
 use backend::infrastructure::repositories::memory::InMemoryFilamentRepository;
+use std::sync::Arc;
+use std::thread;
 
 #[test]
 fn test_get_low_inventory_filaments() {
     // Arrange
-    let repository = InMemoryFilamentRepository::new();
-    let service = FilamentService::new(&repository);
+    let repository = Arc::new(InMemoryFilamentRepository::new());
+    let service = FilamentService::new(repository.clone());
 
     // Create test filaments with different remaining percentages
     let filament1 = FilamentRoll::with_id(
@@ -69,3 +71,128 @@ fn test_get_low_inventory_filaments() {
     assert!(low_inventory.iter().any(|f| f.id() == "test-id-3"));
     assert!(!low_inventory.iter().any(|f| f.id() == "test-id-2"));
 }
+
+#[test]
+fn test_service_is_shareable_across_threads() {
+    // Arrange
+    let repository = Arc::new(InMemoryFilamentRepository::new());
+    let service = FilamentService::new(repository.clone());
+    let filament = FilamentRoll::with_id(
+        "test-id-shared",
+        "Low PLA",
+        "PLA",
+        "#FF0000",
+        1.75,
+        1000.0,
+        100.0, // 10% remaining
+        "Brand A",
+        "Bin 1",
+    )
+    .expect("Failed to create test filament");
+    repository.save(&filament).expect("Failed to save filament");
+
+    // Act
+    let worker = service.clone();
+    let low_inventory = thread::spawn(move || worker.get_low_inventory())
+        .join()
+        .expect("Worker thread panicked")
+        .expect("Failed to get low inventory");
+
+    // Assert
+    assert_eq!(low_inventory.len(), 1);
+    assert_eq!(low_inventory[0].id(), "test-id-shared");
+}
+
+#[test]
+fn test_concurrent_consumption_never_overdraws() {
+    // Arrange
+    let repository = Arc::new(InMemoryFilamentRepository::new());
+    let service = FilamentService::new(repository.clone());
+    let filament = FilamentRoll::with_id(
+        "test-id-race",
+        "Race PLA",
+        "PLA",
+        "#FF0000",
+        1.75,
+        1000.0,
+        100.0,
+        "Brand A",
+        "Bin 1",
+    )
+    .expect("Failed to create test filament");
+    repository.save(&filament).expect("Failed to save filament");
+
+    // Act
+    let workers: Vec<_> = (0..8)
+        .map(|_| {
+            let worker = service.clone();
+            thread::spawn(move || worker.consume("test-id-race", 30.0))
+        })
+        .collect();
+    let succeeded = workers
+        .into_iter()
+        .map(|w| w.join().expect("Worker thread panicked"))
+        .filter(Result::is_ok)
+        .count();
+
+    // Assert
+    assert_eq!(succeeded, 3);
+    assert_eq!(
+        repository
+            .find_by_id("test-id-race")
+            .unwrap()
+            .remaining_weight(),
+        10.0
+    );
+}
+
+#[test]
+fn test_moving_a_roll_never_undoes_concurrent_consumption() {
+    // Arrange
+    let repository = Arc::new(InMemoryFilamentRepository::new());
+    let service = FilamentService::new(repository.clone());
+    let filament = FilamentRoll::with_id(
+        "test-id-move",
+        "Moving PLA",
+        "PLA",
+        "#FF0000",
+        1.75,
+        1000.0,
+        1000.0,
+        "Brand A",
+        "Bin 1",
+    )
+    .expect("Failed to create test filament");
+    repository.save(&filament).expect("Failed to save filament");
+
+    // Act
+    let workers: Vec<_> = (0..50)
+        .map(|i| {
+            let worker = service.clone();
+            thread::spawn(move || {
+                if i % 2 == 0 {
+                    worker.consume("test-id-move", 10.0).map(|_| ())
+                } else {
+                    worker
+                        .move_roll("test-id-move", &format!("Bin {}", i))
+                        .map(|_| ())
+                }
+            })
+        })
+        .collect();
+    for worker in workers {
+        worker
+            .join()
+            .expect("Worker thread panicked")
+            .expect("Update failed");
+    }
+
+    // Assert
+    assert_eq!(
+        repository
+            .find_by_id("test-id-move")
+            .unwrap()
+            .remaining_weight(),
+        750.0
+    );
+}
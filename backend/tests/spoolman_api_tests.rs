@@ -0,0 +1,286 @@
+use actix_web::{test, web, App};
+use backend::api;
+use backend::domain::filament::{FilamentRepository, FilamentRoll};
+use backend::domain::services::filament_service::FilamentService;
+use backend::infrastructure::repositories::memory::{
+    InMemoryDefectiveLotRepository, InMemoryFilamentRepository,
+};
+use backend::infrastructure::spoolman::ids::SpoolmanIds;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+// Helper function to create a test filament with ID
+fn create_test_filament(id: &str, material: &str, manufacturer: &str) -> FilamentRoll {
+    FilamentRoll::with_id(
+        id,
+        "Galaxy Black",
+        material,
+        "#1A1A1A",
+        1.75,
+        1000.0,
+        800.0,
+        manufacturer,
+        "Shelf A",
+    )
+    .expect("Failed to create test filament")
+}
+
+fn seeded_service() -> FilamentService {
+    let repository = Arc::new(InMemoryFilamentRepository::new());
+    repository
+        .save(&create_test_filament("roll-a", "PLA", "Prusament"))
+        .expect("Failed to save filament");
+    repository
+        .save(&create_test_filament("roll-b", "PLA", "Prusament"))
+        .expect("Failed to save filament");
+    repository
+        .save(&create_test_filament("roll-c", "PETG", "Polymaker"))
+        .expect("Failed to save filament");
+    FilamentService::new(repository)
+}
+
+macro_rules! spoolman_app {
+    ($service:expr) => {
+        test::init_service(
+            App::new()
+                .app_data(web::Data::new($service))
+                .app_data(web::Data::new(SpoolmanIds::new()))
+                .configure(api::spoolman::configure),
+        )
+        .await
+    };
+}
+
+#[actix_web::test]
+async fn test_list_spools_maps_rolls() {
+    // Arrange
+    let app = spoolman_app!(seeded_service());
+
+    // Act
+    let request = test::TestRequest::get().uri("/api/v1/spool").to_request();
+    let spools: Vec<Value> = test::call_and_read_body_json(&app, request).await;
+
+    // Assert
+    assert_eq!(spools.len(), 3);
+    assert_eq!(spools[0]["id"], 1);
+    assert_eq!(spools[0]["remaining_weight"], 800.0);
+    assert_eq!(spools[0]["used_weight"], 200.0);
+    assert_eq!(spools[0]["location"], "Shelf A");
+    assert_eq!(spools[0]["filament"]["material"], "PLA");
+    assert_eq!(spools[0]["filament"]["color_hex"], "1A1A1A");
+    assert_eq!(spools[0]["filament"]["vendor"]["name"], "Prusament");
+}
+
+#[actix_web::test]
+async fn test_spools_from_defective_lots_are_flagged() {
+    // Arrange
+    let service =
+        seeded_service().with_defective_lots(Arc::new(InMemoryDefectiveLotRepository::new()));
+    service
+        .set_lot("roll-a", "L2401", None, None)
+        .expect("Failed to set lot");
+    service
+        .mark_lot_defective("Prusament", "L2401", "Brittle")
+        .expect("Failed to flag lot");
+    let app = spoolman_app!(service);
+
+    // Act
+    let spools: Vec<Value> = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri("/api/v1/spool").to_request(),
+    )
+    .await;
+    let used: Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::put()
+            .uri("/api/v1/spool/1/use")
+            .set_json(json!({ "use_weight": 10.0 }))
+            .to_request(),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(spools[0]["lot_nr"], "L2401");
+    assert_eq!(spools[0]["extra"]["defective_lot"], "\"Brittle\"");
+    assert!(spools[1]["extra"].get("defective_lot").is_none());
+    assert_eq!(used["extra"]["defective_lot"], "\"Brittle\"");
+}
+
+#[actix_web::test]
+async fn test_rolls_with_same_attributes_share_filament_and_vendor() {
+    // Arrange
+    let app = spoolman_app!(seeded_service());
+
+    // Act
+    let filaments: Vec<Value> = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get()
+            .uri("/api/v1/filament")
+            .to_request(),
+    )
+    .await;
+    let vendors: Vec<Value> = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri("/api/v1/vendor").to_request(),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(filaments.len(), 2);
+    assert_eq!(vendors.len(), 2);
+}
+
+#[actix_web::test]
+async fn test_filter_spools_by_material() {
+    // Arrange
+    let app = spoolman_app!(seeded_service());
+
+    // Act
+    let request = test::TestRequest::get()
+        .uri("/api/v1/spool?filament.material=petg")
+        .to_request();
+    let spools: Vec<Value> = test::call_and_read_body_json(&app, request).await;
+
+    // Assert
+    assert_eq!(spools.len(), 1);
+    assert_eq!(spools[0]["filament"]["vendor"]["name"], "Polymaker");
+}
+
+#[actix_web::test]
+async fn test_use_weight_deducts_from_roll() {
+    // Arrange
+    let service = seeded_service();
+    let app = spoolman_app!(service.clone());
+
+    // Act
+    let request = test::TestRequest::put()
+        .uri("/api/v1/spool/1/use")
+        .set_json(json!({ "use_weight": 25.5 }))
+        .to_request();
+    let spool: Value = test::call_and_read_body_json(&app, request).await;
+
+    // Assert
+    assert_eq!(spool["remaining_weight"], 774.5);
+    assert_eq!(
+        service.get_roll("roll-a").unwrap().remaining_weight(),
+        774.5
+    );
+}
+
+#[actix_web::test]
+async fn test_use_length_converts_to_grams() {
+    // Arrange
+    let service = seeded_service();
+    let app = spoolman_app!(service.clone());
+
+    // Act
+    // One metre of 1.75 mm PLA weighs roughly 2.98 g
+    let request = test::TestRequest::put()
+        .uri("/api/v1/spool/1/use")
+        .set_json(json!({ "use_length": 1000.0 }))
+        .to_request();
+    let response = test::call_service(&app, request).await;
+
+    // Assert
+    assert!(response.status().is_success());
+    let remaining = service.get_roll("roll-a").unwrap().remaining_weight();
+    assert!((800.0 - remaining - 2.98).abs() < 0.01);
+}
+
+#[actix_web::test]
+async fn test_over_consumption_is_rejected() {
+    // Arrange
+    let service = seeded_service();
+    let app = spoolman_app!(service.clone());
+
+    // Act
+    let request = test::TestRequest::put()
+        .uri("/api/v1/spool/1/use")
+        .set_json(json!({ "use_weight": 900.0 }))
+        .to_request();
+    let response = test::call_service(&app, request).await;
+
+    // Assert
+    assert_eq!(response.status(), 400);
+    let body: Value = test::read_body_json(response).await;
+    assert!(body["message"]
+        .as_str()
+        .unwrap()
+        .contains("only 800 g remaining"));
+    assert_eq!(
+        service.get_roll("roll-a").unwrap().remaining_weight(),
+        800.0
+    );
+}
+
+#[actix_web::test]
+async fn test_patch_spool_location_and_weight() {
+    // Arrange
+    let service = seeded_service();
+    let app = spoolman_app!(service.clone());
+
+    // Act
+    let request = test::TestRequest::patch()
+        .uri("/api/v1/spool/3")
+        .set_json(json!({ "used_weight": 600.0, "location": "Dry Box" }))
+        .to_request();
+    let spool: Value = test::call_and_read_body_json(&app, request).await;
+
+    // Assert
+    assert_eq!(spool["remaining_weight"], 400.0);
+    let roll = service.get_roll("roll-c").unwrap();
+    assert_eq!(roll.storage_location(), "Dry Box");
+    assert_eq!(roll.remaining_weight(), 400.0);
+}
+
+#[actix_web::test]
+async fn test_unknown_spool_is_not_found() {
+    // Arrange
+    let app = spoolman_app!(seeded_service());
+
+    // Act
+    let request = test::TestRequest::get()
+        .uri("/api/v1/spool/99")
+        .to_request();
+    let response = test::call_service(&app, request).await;
+
+    // Assert
+    assert_eq!(response.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_spool_ids_survive_a_restart() {
+    // Arrange
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let path = dir.path().join("spoolman_ids.json");
+    let mut first_run = vec![
+        create_test_filament("roll-b", "PLA", "Prusament"),
+        create_test_filament("roll-c", "PETG", "Polymaker"),
+    ];
+    SpoolmanIds::open(&path)
+        .unwrap()
+        .register_all(&mut first_run)
+        .unwrap();
+    let roll_b = SpoolmanIds::open(&path)
+        .unwrap()
+        .spool(&first_run[0])
+        .unwrap();
+
+    // Act: a roll that sorts first appears after the restart
+    let ids = SpoolmanIds::open(&path).unwrap();
+    let mut second_run = vec![
+        create_test_filament("roll-c", "PETG", "Polymaker"),
+        create_test_filament("roll-a", "PLA", "Prusament"),
+        create_test_filament("roll-b", "PLA", "Prusament"),
+    ];
+    ids.register_all(&mut second_run).unwrap();
+
+    // Assert
+    assert_eq!(ids.spool(&second_run[1]).unwrap(), roll_b);
+    assert_eq!(ids.roll_id(1).unwrap().as_deref(), Some("roll-b"));
+    assert_eq!(ids.roll_id(2).unwrap().as_deref(), Some("roll-c"));
+    assert_eq!(ids.spool(&second_run[0]).unwrap().id, 3);
+    let reopened = SpoolmanIds::open(&path).unwrap();
+    assert_eq!(reopened.roll_id(3).unwrap().as_deref(), Some("roll-a"));
+    assert_eq!(reopened.vendor("Polymaker").unwrap().id, 2);
+}
@@ -0,0 +1,195 @@
+use actix_web::http::StatusCode;
+use actix_web::test::{call_service, init_service, read_body_json, TestRequest};
+use actix_web::{web, App};
+use backend::api;
+use backend::domain::filament::FilamentRepository;
+use backend::infrastructure::import::ImportMode;
+use backend::infrastructure::repositories::memory::InMemoryFilamentRepository;
+use backend::infrastructure::spoolman::import::{import_csv, import_json};
+use serde_json::Value;
+use std::sync::Arc;
+
+const SPOOLS_JSON: &str = include_str!("fixtures/spoolman_spools.json");
+const SPOOLS_CSV: &str = include_str!("fixtures/spoolman_spools.csv");
+
+#[test]
+fn test_import_json_creates_rolls() {
+    // Arrange
+    let repository = InMemoryFilamentRepository::new();
+
+    // Act
+    let report =
+        import_json(&repository, SPOOLS_JSON, ImportMode::Apply).expect("Failed to import");
+
+    // Assert
+    assert_eq!(report.imported.len(), 2);
+    let roll = repository
+        .find_by_id("spoolman-1")
+        .expect("Failed to find imported roll");
+    assert_eq!(roll.name(), "Galaxy Black");
+    assert_eq!(roll.manufacturer(), "Prusament");
+    assert_eq!(roll.color(), "#1A1A1A");
+    assert_eq!(roll.remaining_weight(), 640.5);
+    assert_eq!(roll.storage_location(), "Shelf A");
+
+    // Remaining weight derived from used weight when not given
+    let roll = repository
+        .find_by_id("spoolman-2")
+        .expect("Failed to find imported roll");
+    assert_eq!(roll.weight(), 750.0);
+    assert_eq!(roll.remaining_weight(), 600.0);
+}
+
+#[test]
+fn test_import_reports_invalid_rows_without_aborting() {
+    // Arrange
+    let repository = InMemoryFilamentRepository::new();
+
+    // Act
+    let report =
+        import_json(&repository, SPOOLS_JSON, ImportMode::Apply).expect("Failed to import");
+
+    // Assert
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].row, 3);
+    assert!(report.failed[0].message.contains("Color cannot be empty"));
+    assert_eq!(report.skipped.len(), 1);
+    assert_eq!(report.skipped[0].row, 4);
+}
+
+#[test]
+fn test_dry_run_writes_nothing() {
+    // Arrange
+    let repository = InMemoryFilamentRepository::new();
+
+    // Act
+    let report =
+        import_json(&repository, SPOOLS_JSON, ImportMode::DryRun).expect("Failed to import");
+
+    // Assert
+    assert!(report.dry_run);
+    assert_eq!(report.imported.len(), 2);
+    assert!(repository.find_all().unwrap().is_empty());
+}
+
+#[test]
+fn test_reimport_skips_existing_rolls() {
+    // Arrange
+    let repository = InMemoryFilamentRepository::new();
+    import_json(&repository, SPOOLS_JSON, ImportMode::Apply).expect("Failed to import");
+
+    // Act
+    let report =
+        import_json(&repository, SPOOLS_JSON, ImportMode::Apply).expect("Failed to import");
+
+    // Assert
+    assert!(report.imported.is_empty());
+    assert_eq!(report.skipped.len(), 3);
+    assert_eq!(repository.find_all().unwrap().len(), 2);
+}
+
+#[test]
+fn test_import_json_export_with_references() {
+    // Arrange
+    let repository = InMemoryFilamentRepository::new();
+    let export = r#"{
+        "vendors": [{ "id": 9, "name": "eSun" }],
+        "filaments": [{ "id": 5, "name": "PLA+ Cold White", "vendor_id": 9,
+                        "material": "PLA", "diameter": 1.75, "weight": 1000,
+                        "color_hex": "F4F4F4" }],
+        "spools": [{ "id": 21, "filament_id": 5, "remaining_weight": 300,
+                     "location": "Drawer 2" }]
+    }"#;
+
+    // Act
+    let report = import_json(&repository, export, ImportMode::Apply).expect("Failed to import");
+
+    // Assert
+    assert_eq!(report.imported.len(), 1);
+    let roll = repository.find_by_id("spoolman-21").unwrap();
+    assert_eq!(roll.manufacturer(), "eSun");
+    assert_eq!(roll.remaining_weight(), 300.0);
+}
+
+#[test]
+fn test_import_csv_export() {
+    // Arrange
+    let repository = InMemoryFilamentRepository::new();
+
+    // Act
+    let report = import_csv(&repository, SPOOLS_CSV.as_bytes(), ImportMode::Apply)
+        .expect("Failed to import");
+
+    // Assert
+    assert_eq!(report.imported.len(), 1);
+    let roll = repository.find_by_id("spoolman-10").unwrap();
+    assert_eq!(roll.manufacturer(), "Bambu Lab");
+    assert_eq!(roll.remaining_weight(), 820.0);
+    assert_eq!(roll.storage_location(), "AMS 1");
+    assert_eq!(report.failed.len(), 1);
+    assert!(report.failed[0]
+        .message
+        .contains("Material cannot be empty"));
+}
+
+#[test]
+fn test_malformed_json_is_an_error() {
+    // Arrange
+    let repository = InMemoryFilamentRepository::new();
+
+    // Act
+    let result = import_json(&repository, "{ not json", ImportMode::Apply);
+
+    // Assert
+    assert!(result.is_err());
+}
+
+#[actix_web::test]
+async fn test_import_api_dry_run_then_apply() {
+    // Arrange
+    let repository = Arc::new(InMemoryFilamentRepository::new());
+    let rolls: web::Data<dyn FilamentRepository> =
+        web::Data::from(repository.clone() as Arc<dyn FilamentRepository>);
+    let app = init_service(
+        App::new()
+            .app_data(rolls)
+            .configure(api::inventory::configure),
+    )
+    .await;
+
+    // Act
+    let dry_run = call_service(
+        &app,
+        TestRequest::post()
+            .uri("/api/import/spoolman?dry_run=true")
+            .set_payload(SPOOLS_JSON)
+            .to_request(),
+    )
+    .await;
+    assert_eq!(dry_run.status(), StatusCode::OK);
+    let preview: Value = read_body_json(dry_run).await;
+    assert!(repository.find_all().unwrap().is_empty());
+
+    let applied = call_service(
+        &app,
+        TestRequest::post()
+            .uri("/api/import/spoolman?format=csv")
+            .set_payload(SPOOLS_CSV)
+            .to_request(),
+    )
+    .await;
+    let unknown = call_service(
+        &app,
+        TestRequest::post()
+            .uri("/api/import/spoolman?format=xml")
+            .set_payload(SPOOLS_JSON)
+            .to_request(),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(preview["imported"].as_array().unwrap().len(), 2);
+    assert_eq!(applied.status(), StatusCode::OK);
+    assert!(repository.find_by_id("spoolman-10").is_ok());
+    assert_eq!(unknown.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,328 @@
+use actix_web::http::StatusCode;
+use actix_web::test::{call_service, init_service, read_body, read_body_json, TestRequest};
+use actix_web::{web, App};
+use backend::api;
+use backend::domain::error::FilamentError;
+use backend::domain::filament::{FilamentRepository, FilamentRoll};
+use backend::domain::services::filament_service::FilamentService;
+use backend::infrastructure::labels::raster::{render_bitmap, Bitmap};
+use backend::infrastructure::labels::thermal::{send_raw, ThermalFormat};
+use backend::infrastructure::labels::{LabelLayout, LabelSettings, LabelSize};
+use backend::infrastructure::repositories::memory::InMemoryFilamentRepository;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::Read;
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+// Helper function to create a test filament with ID
+fn create_test_filament(id: &str) -> FilamentRoll {
+    FilamentRoll::with_id(
+        id,
+        "Galaxy Black",
+        "PLA",
+        "#000000",
+        1.75,
+        1000.0,
+        800.0,
+        "Test Brand",
+        "Shelf A",
+    )
+    .expect("Failed to create test filament")
+}
+
+fn label(id: &str, dpi: u32) -> Bitmap {
+    let layout = LabelLayout::for_roll(&create_test_filament(id), LabelSize::SMALL, None).unwrap();
+    render_bitmap(&layout, dpi).unwrap()
+}
+
+// Accepts one connection and returns everything sent on it
+fn listen() -> (String, JoinHandle<Vec<u8>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let handle = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut received = Vec::new();
+        stream.read_to_end(&mut received).unwrap();
+        received
+    });
+    (address, handle)
+}
+
+#[test]
+fn test_zpl_graphic_field_matches_bitmap() {
+    // Arrange
+    let bitmap = label("roll-1", 203);
+
+    // Act
+    let job = ThermalFormat::Zpl
+        .encode(std::slice::from_ref(&bitmap))
+        .unwrap();
+
+    // Assert
+    let job = String::from_utf8(job).unwrap();
+    let bytes_per_row = bitmap.width().div_ceil(8);
+    let total = bytes_per_row * bitmap.height();
+    let prefix = format!(
+        "^XA^PW{}^LL{}^FO0,0^GFA,{},{},{},",
+        bitmap.width(),
+        bitmap.height(),
+        total,
+        total,
+        bytes_per_row
+    );
+    assert!(job.starts_with(&prefix));
+    assert!(job.ends_with("^FS^XZ\n"));
+
+    let hex = &job[prefix.len()..job.len() - "^FS^XZ\n".len()];
+    assert_eq!(hex.len() as u32, total * 2);
+    for (y, x) in [(0, 0), (40, 40), (120, 60), (100, 250)] {
+        let index = (y * bytes_per_row + x / 8) as usize * 2;
+        let byte = u8::from_str_radix(&hex[index..index + 2], 16).unwrap();
+        assert_eq!(byte & (0x80 >> (x % 8)) != 0, bitmap.is_dark(x, y));
+    }
+}
+
+#[test]
+fn test_zpl_batch_has_one_format_per_label() {
+    // Act
+    let job = ThermalFormat::Zpl
+        .encode(&[label("roll-1", 203), label("roll-2", 203)])
+        .unwrap();
+
+    // Assert
+    let job = String::from_utf8(job).unwrap();
+    assert_eq!(job.matches("^XA").count(), 2);
+    assert_eq!(job.matches("^XZ").count(), 2);
+}
+
+#[test]
+fn test_escpos_raster_images_are_cut() {
+    // Arrange
+    let bitmap = label("roll-1", 203);
+    let bytes_per_row = bitmap.width().div_ceil(8) as usize;
+    let image_len = 8 + bytes_per_row * bitmap.height() as usize + 4;
+
+    // Act
+    let job = ThermalFormat::EscPos
+        .encode(&[bitmap.clone(), bitmap.clone()])
+        .unwrap();
+
+    // Assert
+    assert_eq!(&job[..2], &[0x1B, 0x40]);
+    assert_eq!(job.len(), 2 + 2 * image_len);
+    let header = &job[2..10];
+    assert_eq!(&header[..4], &[0x1D, 0x76, 0x30, 0x00]);
+    assert_eq!(
+        u16::from_le_bytes([header[4], header[5]]) as usize,
+        bytes_per_row
+    );
+    assert_eq!(
+        u16::from_le_bytes([header[6], header[7]]) as u32,
+        bitmap.height()
+    );
+    assert_eq!(
+        &job[2 + image_len - 4..2 + image_len],
+        &[0x1D, 0x56, 0x42, 0x00]
+    );
+}
+
+#[test]
+fn test_brother_raster_job_structure() {
+    // Arrange
+    let bitmap = label("roll-1", 300);
+
+    // Act
+    let job = ThermalFormat::Brother
+        .encode(std::slice::from_ref(&bitmap))
+        .unwrap();
+
+    // Assert
+    assert!(job[..200].iter().all(|b| *b == 0));
+    assert_eq!(&job[200..206], &[0x1B, 0x40, 0x1B, 0x69, 0x61, 0x01]);
+    // Print information: continuous 40 mm tape, one raster line per bitmap row
+    assert_eq!(&job[206..213], &[0x1B, 0x69, 0x7A, 0x86, 0x0A, 40, 0x00]);
+    assert_eq!(
+        u32::from_le_bytes(job[213..217].try_into().unwrap()),
+        bitmap.height()
+    );
+    // Mode settings, then a `g 0 90` raster line per row across the 720-dot head
+    let raster = 236;
+    assert_eq!(&job[raster..raster + 3], &[0x67, 0x00, 90]);
+    assert_eq!(job.len(), raster + bitmap.height() as usize * 93 + 1);
+    assert_eq!(*job.last().unwrap(), 0x1A);
+}
+
+#[test]
+fn test_brother_requires_300_dpi() {
+    // Act
+    let result = ThermalFormat::Brother.encode(&[label("roll-1", 203)]);
+
+    // Assert
+    assert!(matches!(result, Err(FilamentError::InvalidData(_))));
+}
+
+#[test]
+fn test_send_raw_delivers_whole_job() {
+    // Arrange
+    let (address, received) = listen();
+    let job = ThermalFormat::Zpl.encode(&[label("roll-1", 203)]).unwrap();
+
+    // Act
+    send_raw(&address, &job, Duration::from_secs(5)).unwrap();
+
+    // Assert
+    assert_eq!(received.join().unwrap(), job);
+}
+
+#[test]
+fn test_send_raw_reports_unreachable_printer() {
+    // Arrange
+    let address = {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().to_string()
+    };
+
+    // Act
+    let result = send_raw(&address, b"^XA^XZ", Duration::from_secs(1));
+
+    // Assert
+    assert!(matches!(result, Err(FilamentError::RepositoryError(_))));
+}
+
+#[actix_web::test]
+async fn test_thermal_label_endpoints() {
+    // Arrange
+    let repository = Arc::new(InMemoryFilamentRepository::new());
+    for id in ["roll-1", "roll-2"] {
+        repository.save(&create_test_filament(id)).unwrap();
+    }
+    let (address, received) = listen();
+    let settings = LabelSettings {
+        printers: HashMap::from([("desk".to_string(), address.clone())]),
+        ..LabelSettings::default()
+    };
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(FilamentService::new(repository)))
+            .app_data(web::Data::new(settings))
+            .configure(api::labels::configure),
+    )
+    .await;
+    let batch = json!({"roll_ids": ["roll-1", "roll-2"], "format": "esc_pos", "size": "40x30"});
+
+    // Act
+    let zpl = call_service(
+        &app,
+        TestRequest::get()
+            .uri("/api/rolls/roll-1/label.zpl")
+            .to_request(),
+    )
+    .await;
+    let unknown = call_service(
+        &app,
+        TestRequest::get()
+            .uri("/api/rolls/roll-1/label.pdf")
+            .to_request(),
+    )
+    .await;
+    let streamed = call_service(
+        &app,
+        TestRequest::post()
+            .uri("/api/labels")
+            .set_json(&batch)
+            .to_request(),
+    )
+    .await;
+    let mut print = batch.clone();
+    print["printer"] = json!("desk");
+    let printed = call_service(
+        &app,
+        TestRequest::post()
+            .uri("/api/labels/print")
+            .set_json(&print)
+            .to_request(),
+    )
+    .await;
+    // A raw address is not a configured printer id, so nothing is sent to it
+    print["printer"] = json!(address);
+    let free_form = call_service(
+        &app,
+        TestRequest::post()
+            .uri("/api/labels/print")
+            .set_json(&print)
+            .to_request(),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(
+        zpl.headers().get("content-type").unwrap(),
+        "application/vnd.zebra-zpl"
+    );
+    assert!(read_body(zpl).await.starts_with(b"^XA"));
+    assert_eq!(unknown.status(), StatusCode::BAD_REQUEST);
+    let streamed = read_body(streamed).await;
+    assert_eq!(printed.status(), StatusCode::OK);
+    let summary: Value = read_body_json(printed).await;
+    assert_eq!(summary, json!({"labels": 2, "bytes": streamed.len()}));
+    assert_eq!(received.join().unwrap(), streamed.to_vec());
+    assert_eq!(free_form.status(), StatusCode::NOT_FOUND);
+}
+
+#[test]
+fn test_label_printers_are_parsed_from_settings() {
+    // Act
+    let printers =
+        LabelSettings::parse_printers("desk=192.168.1.50, shop = labels.local:9100,").unwrap();
+    let invalid = LabelSettings::parse_printers("desk");
+
+    // Assert
+    assert_eq!(printers.len(), 2);
+    assert_eq!(printers["desk"], "192.168.1.50");
+    assert_eq!(printers["shop"], "labels.local:9100");
+    assert!(matches!(invalid, Err(FilamentError::InvalidData(_))));
+}
+
+#[actix_web::test]
+async fn test_batch_label_count_is_capped() {
+    // Arrange
+    let repository = Arc::new(InMemoryFilamentRepository::new());
+    repository.save(&create_test_filament("roll-1")).unwrap();
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(FilamentService::new(repository)))
+            .app_data(web::Data::new(LabelSettings {
+                // Nothing listens here; the batch is refused before sending
+                printers: HashMap::from([("desk".to_string(), "127.0.0.1:9".to_string())]),
+                ..LabelSettings::default()
+            }))
+            .configure(api::labels::configure),
+    )
+    .await;
+    let roll_ids = vec!["roll-1"; api::labels::MAX_LABELS + 1];
+
+    // Act
+    let response = call_service(
+        &app,
+        TestRequest::post()
+            .uri("/api/labels")
+            .set_json(json!({"roll_ids": roll_ids, "format": "zpl"}))
+            .to_request(),
+    )
+    .await;
+    let printed = call_service(
+        &app,
+        TestRequest::post()
+            .uri("/api/labels/print")
+            .set_json(json!({"roll_ids": roll_ids, "format": "zpl", "printer": "desk"}))
+            .to_request(),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(printed.status(), StatusCode::BAD_REQUEST);
+}
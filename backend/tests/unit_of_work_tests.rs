@@ -0,0 +1,165 @@
+use backend::domain::error::FilamentError;
+use backend::domain::filament::{FilamentRepository, FilamentRoll};
+use backend::domain::unit_of_work::{TransactionalFilamentRepository, UnitOfWork};
+use backend::infrastructure::repositories::event_sourced::EventSourcedFilamentRepository;
+use backend::infrastructure::repositories::memory::{
+    InMemoryEventStore, InMemoryFilamentRepository, InMemorySnapshotStore,
+};
+
+// Helper function to create a test filament with ID
+fn create_test_filament(id: &str, material: &str, remaining_weight: f32) -> FilamentRoll {
+    FilamentRoll::with_id(
+        id,
+        "Test Filament",
+        material,
+        "#000000",
+        1.75,
+        1000.0,
+        remaining_weight,
+        "Test Brand",
+        "Bin 1",
+    )
+    .expect("Failed to create test filament")
+}
+
+fn seed(repository: &dyn FilamentRepository) {
+    repository
+        .save(&create_test_filament("uow-pla", "PLA", 800.0))
+        .expect("Failed to save filament");
+    repository
+        .save(&create_test_filament("uow-petg", "PETG", 100.0))
+        .expect("Failed to save filament");
+}
+
+#[test]
+fn test_commit_applies_all_changes() {
+    // Arrange
+    let repository = InMemoryFilamentRepository::new();
+    seed(&repository);
+    let unit = UnitOfWork::new()
+        .consume("uow-pla", 120.0)
+        .consume("uow-petg", 40.0);
+
+    // Act
+    let updated = repository.commit(&unit).expect("Failed to commit");
+
+    // Assert
+    assert_eq!(updated.len(), 2);
+    assert_eq!(
+        repository.find_by_id("uow-pla").unwrap().remaining_weight(),
+        680.0
+    );
+    assert_eq!(
+        repository
+            .find_by_id("uow-petg")
+            .unwrap()
+            .remaining_weight(),
+        60.0
+    );
+}
+
+#[test]
+fn test_commit_is_all_or_nothing() {
+    // Arrange
+    let repository = InMemoryFilamentRepository::new();
+    seed(&repository);
+    let unit = UnitOfWork::new()
+        .consume("uow-pla", 120.0)
+        .consume("uow-petg", 150.0);
+
+    // Act
+    let result = repository.commit(&unit);
+
+    // Assert
+    match result {
+        Err(FilamentError::InvalidData(msg)) => assert!(msg.contains("uow-petg")),
+        _ => panic!("Expected InvalidData error"),
+    }
+    assert_eq!(
+        repository.find_by_id("uow-pla").unwrap().remaining_weight(),
+        800.0
+    );
+    assert_eq!(
+        repository
+            .find_by_id("uow-petg")
+            .unwrap()
+            .remaining_weight(),
+        100.0
+    );
+}
+
+#[test]
+fn test_commit_with_unknown_roll_changes_nothing() {
+    // Arrange
+    let repository = InMemoryFilamentRepository::new();
+    seed(&repository);
+    let unit = UnitOfWork::new()
+        .update_remaining_weight("uow-pla", 500.0)
+        .consume("missing", 10.0);
+
+    // Act
+    let result = repository.commit(&unit);
+
+    // Assert
+    assert!(matches!(result, Err(FilamentError::NotFound(id)) if id == "missing"));
+    assert_eq!(
+        repository.find_by_id("uow-pla").unwrap().remaining_weight(),
+        800.0
+    );
+}
+
+#[test]
+fn test_repeated_changes_to_one_roll_accumulate() {
+    // Arrange
+    let repository = InMemoryFilamentRepository::new();
+    seed(&repository);
+    let unit = UnitOfWork::new()
+        .consume("uow-pla", 300.0)
+        .consume("uow-pla", 300.0)
+        .consume("uow-pla", 300.0);
+
+    // Act
+    let result = repository.commit(&unit);
+
+    // Assert
+    assert!(matches!(result, Err(FilamentError::InvalidData(_))));
+    assert_eq!(
+        repository.find_by_id("uow-pla").unwrap().remaining_weight(),
+        800.0
+    );
+}
+
+#[test]
+fn test_event_sourced_commit_is_all_or_nothing() {
+    // Arrange
+    let events = InMemoryEventStore::new();
+    let repository =
+        EventSourcedFilamentRepository::open(events.clone(), InMemorySnapshotStore::new())
+            .expect("Failed to open repository");
+    seed(&repository);
+    let failing = UnitOfWork::new()
+        .consume("uow-pla", 120.0)
+        .consume("uow-petg", 150.0);
+    let passing = UnitOfWork::new()
+        .consume("uow-pla", 120.0)
+        .consume("uow-petg", 50.0);
+
+    // Act
+    let failed = repository.commit(&failing);
+    let committed = repository.commit(&passing);
+
+    // Assert
+    assert!(failed.is_err());
+    assert!(committed.is_ok());
+    assert_eq!(
+        repository.find_by_id("uow-pla").unwrap().remaining_weight(),
+        680.0
+    );
+    assert_eq!(
+        repository
+            .find_by_id("uow-petg")
+            .unwrap()
+            .remaining_weight(),
+        50.0
+    );
+}